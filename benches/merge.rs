@@ -0,0 +1,71 @@
+//! Times `pend merge` on a large finished log.
+//!
+//! ```sh
+//! cargo bench --bench merge                     # logs memory-mapped
+//! cargo bench --bench merge --no-default-features   # logs read
+//! ```
+//!
+//! Prints the best throughput of a few runs; set `PEND_BENCH_MB` to change
+//! the log size (default 512 MiB).
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+const RUNS: u32 = 5;
+
+fn pend(dir: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_pend"));
+    cmd.env("PEND_DIR", dir).stdin(Stdio::null());
+    cmd
+}
+
+fn main() {
+    if !cfg!(unix) {
+        eprintln!("the merge benchmark needs a Unix shell");
+        return;
+    }
+    let mb: u64 = std::env::var("PEND_BENCH_MB")
+        .ok()
+        .and_then(|mb| mb.parse().ok())
+        .unwrap_or(512);
+    let dir = std::env::temp_dir().join(format!("pend-bench-merge-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Lines of 100 bytes, with one on stderr halfway.
+    let script = format!(
+        "line=$(printf '%099d' 0); i=0; \
+         while [ $i -lt 100 ]; do echo $line; i=$((i+1)); done > chunk; \
+         yes \"$(cat chunk)\" | head -c {} ; echo oops >&2; \
+         yes \"$(cat chunk)\" | head -c {}",
+        mb * 1024 * 1024 / 2,
+        mb * 1024 * 1024 / 2,
+    );
+    let status = pend(&dir)
+        .current_dir(&dir)
+        .args(["do", "big", "sh", "-c", &script])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let status = pend(&dir)
+        .args(["wait", "big"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let mut best = f64::MAX;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        let status = pend(&dir)
+            .args(["merge", "big", "--output", "/dev/null"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        best = best.min(started.elapsed().as_secs_f64());
+    }
+    println!(
+        "merge {mb} MiB: best of {RUNS} {best:.3}s ({:.0} MiB/s)",
+        mb as f64 / best
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
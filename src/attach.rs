@@ -0,0 +1,66 @@
+//! `pend attach`: follow a running job's output as its worker writes it.
+//!
+//! Where `pend wait` replays the logs from the start and polls them for
+//! more, `pend attach` shows only what the job writes from now on, pushed by
+//! its worker over the control socket (see `control.rs`): stdout to stdout,
+//! stderr to stderr. It ends with the job and exits with the job's exit
+//! code; Ctrl-C detaches and leaves the job running.
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::control::Frame;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+use crate::status::State;
+
+/// How long to wait for a freshly started worker to open its socket.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_POLL: Duration = Duration::from_millis(50);
+
+/// Entry point for `pend attach <job>`. Returns the job's exit code.
+pub(crate) fn attach(config: &Config, job_name: &str) -> Result<i32> {
+    let status = crate::status::job_status(config, job_name)?;
+    if status.state != State::Running {
+        return Err(PendError::Usage(format!(
+            "job '{job_name}' is not running; `pend wait {job_name}` replays its output"
+        )));
+    }
+    let paths = JobPaths::new(config, job_name)?;
+    // A job submitted a moment ago counts as running before its worker
+    // listens on the control socket, so keep knocking for a while.
+    let give_up = Instant::now() + CONNECT_TIMEOUT;
+    let frames = loop {
+        if let Some(frames) = crate::control::attach(&paths) {
+            break frames;
+        }
+        if Instant::now() >= give_up {
+            return Err(PendError::Usage(format!(
+                "cannot reach the worker of job '{job_name}'; `pend wait {job_name}` follows its logs instead"
+            )));
+        }
+        std::thread::sleep(CONNECT_POLL);
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    let mut stderr = std::io::stderr().lock();
+    for frame in frames {
+        match frame {
+            Frame::Out(bytes) => {
+                stdout.write_all(&bytes)?;
+                stdout.flush()?;
+            }
+            Frame::Err(bytes) => stderr.write_all(&bytes)?,
+            Frame::Exit(code) => return Ok(code),
+        }
+    }
+
+    // The worker let go of us: it fell over, or we fell behind its output.
+    match crate::validate::read_exit_code(&paths.exit)? {
+        Some(code) => Ok(code),
+        None => Err(PendError::Io(std::io::Error::other(format!(
+            "lost the connection to the worker of job '{job_name}'; \
+             `pend wait {job_name}` replays all of its output"
+        )))),
+    }
+}
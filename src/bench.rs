@@ -0,0 +1,128 @@
+//! `pend bench`: run a command several times and report its wall time.
+//!
+//! Each run is an ordinary job – `<name>-1`, `<name>-2`, … – started once
+//! the previous one finished, so every run has its own log and metadata to
+//! inspect afterwards. The time of a run is that of its last attempt as
+//! recorded in `<job>.json`, from the command's start to its exit, which
+//! leaves out the worker's own start-up. A failing run ends the benchmark
+//! with its exit code.
+use std::ffi::OsString;
+use std::fs;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+use crate::status::State;
+use crate::worker::JobOptions;
+
+/// How often the running job is checked for completion.
+const POLL: Duration = Duration::from_millis(20);
+/// How many polls pass between checks whether the worker died.
+const POLLS_PER_LIVENESS_CHECK: u32 = 50;
+
+/// Entry point for `pend bench <name> --runs N -- <cmd …>`. Returns the
+/// exit code of the first failing run, else 0.
+pub(crate) fn bench(
+    config: &Config,
+    name: &str,
+    runs: u32,
+    cmd: &[OsString],
+    options: &JobOptions,
+) -> Result<i32> {
+    let width = format!("{name}-{runs}").chars().count();
+    let mut times = Vec::new();
+    for run in 1..=runs {
+        let job = format!("{name}-{run}");
+        crate::job::do_job(config, &job, cmd, options)?;
+        let paths = JobPaths::new(config, &job)?;
+        let code = wait_for(config, &job, &paths)?;
+        if code != 0 {
+            eprintln!(
+                "pend: run {run} ('{job}') failed with exit code {}",
+                crate::exit_status::describe(code)
+            );
+            return Ok(code);
+        }
+        let time = last_attempt(&paths).ok_or_else(|| PendError::Corrupt {
+            path: paths.meta.clone(),
+            reason: "no timing of the last attempt".into(),
+        })?;
+        println!("{job:<width$}  {}", seconds(time));
+        times.push(time);
+    }
+
+    let stats = Stats::of(&times);
+    println!();
+    println!("{:<7} {runs}", "runs");
+    println!("{:<7} {}", "min", seconds(stats.min));
+    println!("{:<7} {}", "mean", seconds(stats.mean));
+    println!("{:<7} {}", "median", seconds(stats.median));
+    println!("{:<7} {}", "stddev", seconds(stats.stddev));
+    println!("{:<7} {}", "max", seconds(stats.max));
+    Ok(0)
+}
+
+/// Block until the job finished and return its exit code.
+fn wait_for(config: &Config, job: &str, paths: &JobPaths) -> Result<i32> {
+    let mut polls = 0u32;
+    loop {
+        if let Some(code) = crate::validate::read_exit_code(&paths.exit)? {
+            return Ok(code);
+        }
+        polls += 1;
+        if polls.is_multiple_of(POLLS_PER_LIVENESS_CHECK)
+            && crate::status::job_status(config, job)?.state == State::Died
+        {
+            return Err(PendError::WorkerDied(job.to_string()));
+        }
+        std::thread::sleep(POLL);
+    }
+}
+
+/// Wall time of the job's last attempt, in seconds.
+fn last_attempt(paths: &JobPaths) -> Option<f64> {
+    let meta = crate::version::parse_meta(&fs::read(&paths.meta).ok()?).ok()?;
+    let attempt = meta.get("attempts")?.as_array()?.last()?;
+    let time = |key: &str| chrono::DateTime::parse_from_rfc3339(attempt.get(key)?.as_str()?).ok();
+    let elapsed = time("ended")? - time("started")?;
+    Some(elapsed.num_microseconds()? as f64 / 1e6)
+}
+
+fn seconds(secs: f64) -> String {
+    format!("{secs:.3}s")
+}
+
+/// Summary statistics of a non-empty sample.
+struct Stats {
+    min: f64,
+    max: f64,
+    mean: f64,
+    median: f64,
+    /// Sample standard deviation; 0 for a single run.
+    stddev: f64,
+}
+
+impl Stats {
+    fn of(times: &[f64]) -> Self {
+        let mut sorted = times.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let median = match n % 2 {
+            0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+            _ => sorted[n / 2],
+        };
+        let variance = match n {
+            1 => 0.0,
+            _ => sorted.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1) as f64,
+        };
+        Stats {
+            min: sorted[0],
+            max: sorted[n - 1],
+            mean,
+            median,
+            stddev: variance.sqrt(),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Result caching for `pend do --cache`.
+//!
+//! A cached job records a *cache key* in its metadata: a SHA-256 over the
+//! command, the working directory, the values of the environment variables
+//! named with `--cache-env` and the remaining job options. When the same job
+//! is submitted again with an identical key, and its previous run succeeded
+//! no longer than the TTL ago, `pend do` leaves the existing artifacts in
+//! place instead of running the command again; `pend wait` then replays the
+//! cached output and exit code as usual.
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+
+use crate::paths::JobPaths;
+use crate::worker::JobOptions;
+
+/// Cache key of running `cmd` with `options` from the current directory.
+pub(crate) fn cache_key(cmd: &[OsString], options: &JobOptions) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    // NUL separators keep `["a b"]` and `["a", "b"]` apart.
+    for arg in cmd {
+        hasher.update(arg.as_encoded_bytes());
+        hasher.update([0]);
+    }
+    hasher.update([0]);
+    hasher.update(std::env::current_dir()?.to_string_lossy().as_bytes());
+    hasher.update([0]);
+    for name in &options.cache_env {
+        hasher.update(name.as_bytes());
+        hasher.update([b'=']);
+        if let Some(value) = std::env::var_os(name) {
+            hasher.update(value.to_string_lossy().as_bytes());
+        }
+        hasher.update([0]);
+    }
+    // Everything else that shapes the run (container image, user, …), but
+    // not the cache settings, the note, the `--meta` pairs or what happens
+    // to the artifacts.
+    let shaping = JobOptions {
+        cache: None,
+        cache_env: Vec::new(),
+        note: None,
+        user_meta: BTreeMap::new(),
+        ephemeral: false,
+        expires_in: None,
+        checksum_log: false,
+        log_to: None,
+        mirror_fifo: None,
+        mirror_policy: None,
+        slots: Vec::new(),
+        priority: None,
+        max_jobs: None,
+        queue: false,
+        ..options.clone()
+    };
+    hasher.update(serde_json::to_vec(&shaping)?);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Seconds since the previous run of `paths` finished successfully with
+/// cache key `key`, if it did so within `ttl_secs`.
+pub(crate) fn cached_run_age(paths: &JobPaths, key: &str, ttl_secs: u64) -> Option<i64> {
+    let code = fs::read_to_string(&paths.exit).ok()?;
+    if code.trim() != "0" {
+        return None;
+    }
+    let meta = crate::version::parse_meta(&fs::read(&paths.meta).ok()?).ok()?;
+    // A run whose output was truncated cannot be replayed.
+    if meta.get("cache_key")?.as_str()? != key || crate::truncate::was_truncated(&meta) {
+        return None;
+    }
+    let ended = chrono::DateTime::parse_from_rfc3339(meta.get("ended")?.as_str()?).ok()?;
+    let age = chrono::Utc::now()
+        .signed_duration_since(ended)
+        .num_seconds()
+        .max(0);
+    (age as u64 <= ttl_secs).then_some(age)
+}
@@ -0,0 +1,65 @@
+//! `pend do --capture` and `--max-out-size` / `--max-err-size`: keeping
+//! only the output worth keeping.
+//!
+//! Some commands write a flood of stdout nobody reads while their stderr
+//! tells what went wrong. `--capture stderr` connects the command's stdout
+//! to the null device – it is never read, so it costs nothing – and
+//! `--capture stdout` does the same for stderr. The stall watchdog and
+//! progress reports only see the captured streams.
+//!
+//! `--max-out-size 1M` keeps the first megabyte each attempt writes to
+//! stdout, in `.out` and in `.log`, and drains the rest of the pipe so the
+//! command never blocks on it; `--max-err-size` does the same for stderr.
+//! The bytes left out, over all attempts, are recorded in `.json` as
+//! `out_dropped` / `err_dropped`.
+use serde::{Deserialize, Serialize};
+
+/// Which of the command's streams the worker reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Capture {
+    #[default]
+    Both,
+    Stdout,
+    Stderr,
+}
+
+impl Capture {
+    pub(crate) fn stdout(self) -> bool {
+        self != Capture::Stderr
+    }
+
+    pub(crate) fn stderr(self) -> bool {
+        self != Capture::Stdout
+    }
+}
+
+/// What one attempt may still keep of a stream.
+pub(crate) struct Budget {
+    /// Bytes left to keep; `None` keeps everything.
+    left: Option<u64>,
+    /// Bytes read but not kept.
+    pub(crate) dropped: u64,
+}
+
+impl Budget {
+    pub(crate) fn new(limit: Option<u64>) -> Self {
+        Self {
+            left: limit,
+            dropped: 0,
+        }
+    }
+
+    /// The part of `chunk` to keep; the rest counts as dropped.
+    pub(crate) fn take<'a>(&mut self, chunk: &'a [u8]) -> &'a [u8] {
+        let Some(left) = &mut self.left else {
+            return chunk;
+        };
+        let keep = chunk
+            .len()
+            .min(usize::try_from(*left).unwrap_or(usize::MAX));
+        *left -= keep as u64;
+        self.dropped += (chunk.len() - keep) as u64;
+        &chunk[..keep]
+    }
+}
@@ -1,16 +1,136 @@
 //! Color utilities shared by `pend wait` when displaying interleaved output
 //! from multiple jobs.
 //!
-//! The actual colour palette lives in `src/wait.rs`;  this helper merely
-//! determines – at runtime – whether ANSI escape sequences should be emitted
-//! at all. We honour the de-facto standard `NO_COLOR` environment variable so
-//! that users can globally disable colourized CLI output.
+//! Besides the palette this helper determines – at runtime – whether ANSI
+//! escape sequences should be emitted at all. `--color auto|always|never` (or `--no-color`) decides explicitly;
+//! in `auto` mode we honour the de-facto standard `NO_COLOR` and
+//! `CLICOLOR_FORCE` environment variables and otherwise colour only a
+//! terminal. Legacy Windows consoles only render escapes once virtual
+//! terminal processing is switched on; when that fails colours stay off.
+//! Builds without the `color` feature never colour.
 //!
 //! Because the binary has no public API the module is `pub(crate)` by default;
 //! these docs exist purely to guide future maintainers.
-/// Decide at runtime whether color escapes should be emitted. Honors the
-/// de-facto standard `NO_COLOR` environment variable so users can globally
-/// disable ANSI sequences.
-pub(crate) fn colors_enabled() -> bool {
-    std::env::var_os("NO_COLOR").is_none()
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// The `--color` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ColorChoice {
+    /// Colour a terminal unless `NO_COLOR` is set (or `CLICOLOR_FORCE`
+    /// forces colour).
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[cfg(feature = "color")]
+pub(crate) use anstyle::Style;
+
+/// Stand-in for `anstyle::Style` when built without the `color` feature;
+/// renders as nothing.
+#[cfg(not(feature = "color"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Style;
+
+#[cfg(not(feature = "color"))]
+impl Style {
+    pub(crate) const fn new() -> Self {
+        Style
+    }
+
+    pub(crate) fn render(&self) -> &'static str {
+        ""
+    }
+
+    pub(crate) fn render_reset(&self) -> &'static str {
+        ""
+    }
+}
+
+/// Colour of the output of the `idx`-th job in a multi-job wait.
+#[cfg(feature = "color")]
+pub(crate) fn job_style(idx: usize) -> Style {
+    use anstyle::{AnsiColor, Color};
+
+    let color = match idx % 6 {
+        0 => AnsiColor::Red,
+        1 => AnsiColor::Green,
+        2 => AnsiColor::Yellow,
+        3 => AnsiColor::Blue,
+        4 => AnsiColor::Magenta,
+        _ => AnsiColor::Cyan,
+    };
+    Style::new().fg_color(Some(Color::Ansi(color)))
+}
+
+#[cfg(not(feature = "color"))]
+pub(crate) fn job_style(_idx: usize) -> Style {
+    Style
+}
+
+static PAGED: AtomicBool = AtomicBool::new(false);
+
+/// Note that stdout now feeds a pager (`pend wait --pager`), which passes
+/// colours on to the terminal.
+pub(crate) fn set_paged() {
+    PAGED.store(true, Ordering::Relaxed);
+}
+
+/// Decide at runtime whether color escapes should be emitted for the
+/// `--color` setting `choice`. In `auto` mode `NO_COLOR` wins over
+/// `CLICOLOR_FORCE`, and output that is redirected to a file or pipe is never
+/// coloured – replayed bytes must then match the captured log exactly.
+pub(crate) fn colors_enabled(choice: ColorChoice) -> bool {
+    use std::io::IsTerminal;
+
+    if cfg!(not(feature = "color")) {
+        return false;
+    }
+    let wanted = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+                false
+            } else if std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0")
+            {
+                true
+            } else {
+                std::io::stdout().is_terminal() || PAGED.load(Ordering::Relaxed)
+            }
+        }
+    };
+    wanted && (!std::io::stdout().is_terminal() || console_renders_ansi())
+}
+
+/// Switch on virtual terminal processing for the console behind stdout
+/// (once). Terminals that are not consoles, such as mintty, report failure
+/// but understand escapes as long as `TERM` says so.
+#[cfg(windows)]
+fn console_renders_ansi() -> bool {
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        STD_OUTPUT_HANDLE,
+    };
+
+    static RENDERS: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *RENDERS.get_or_init(|| {
+        let enabled = unsafe {
+            let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+            let mut mode = 0;
+            GetConsoleMode(handle, &mut mode) != 0
+                && (mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                    || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0)
+        };
+        enabled || std::env::var("TERM").is_ok_and(|term| term != "dumb")
+    })
+}
+
+#[cfg(not(windows))]
+fn console_renders_ansi() -> bool {
+    true
 }
@@ -0,0 +1,557 @@
+//! pend's configuration file.
+//!
+//! Read from `$PEND_CONFIG` when set, otherwise from `config.toml` in the
+//! user's configuration directory (`$XDG_CONFIG_HOME/pend`, default
+//! `~/.config/pend`; on Windows `%APPDATA%\pend`). A missing file is an
+//! empty configuration.
+//!
+//! Templates name commands a team runs over and over:
+//!
+//! ```toml
+//! [templates]
+//! bench = "cargo bench --bench {target}"
+//!
+//! [templates.test]
+//! command = "cargo test {filter}"
+//! defaults = { filter = "" }
+//! ```
+//!
+//! `pend do mybench --template bench target=io` fills in the `{target}`
+//! placeholder and runs the result like `--shell` does (`sh -c`,
+//! `cmd /c`); `${…}` is left for the shell to expand.
+//!
+//! Profiles bundle defaults for one kind of machine, selected with
+//! `--profile <name>` or `$PEND_PROFILE`:
+//!
+//! ```toml
+//! [profiles.ci]
+//! dir = "/var/tmp/pend-ci"
+//! color = "never"
+//! timeout = 3600
+//! max_log_size = "10M"
+//! max_jobs = 8
+//! ```
+//!
+//! Command-line options win over the profile, and an explicit `PEND_DIR`
+//! over its `dir`/`persistent`. `pend config show` prints every effective
+//! setting with the place it came from; `pend config set` edits the file,
+//! keeping its comments and layout.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::color::ColorChoice;
+use crate::error::{PendError, Result};
+use crate::worker::JobOptions;
+
+/// Environment variable naming the configuration file.
+pub(crate) const ENV: &str = "PEND_CONFIG";
+
+/// Environment variable selecting a profile when `--profile` is not given.
+pub(crate) const PROFILE_ENV: &str = "PEND_PROFILE";
+
+/// The contents of the configuration file.
+#[derive(Deserialize, Default)]
+pub(crate) struct File {
+    #[serde(default)]
+    templates: BTreeMap<String, Template>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+    /// Where the worker looks for hook scripts (see `hooks.rs`).
+    hooks_dir: Option<PathBuf>,
+}
+
+/// Defaults for one environment, e.g. a laptop or a build server.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Profile {
+    /// The name the profile was selected by.
+    #[serde(skip)]
+    name: String,
+    /// Jobs root, like `--dir`.
+    dir: Option<PathBuf>,
+    /// Like `--persistent`.
+    persistent: bool,
+    color: Option<ColorChoice>,
+    timeout: Option<u64>,
+    retries: Option<u32>,
+    #[serde(deserialize_with = "size")]
+    max_log_size: Option<u64>,
+    max_log_files: Option<u32>,
+    compress_logs: bool,
+    /// Like `--max-jobs`.
+    max_jobs: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Template {
+    Command(String),
+    Full {
+        command: String,
+        #[serde(default)]
+        defaults: BTreeMap<String, String>,
+    },
+}
+
+/// Where the configuration is read from; `None` when neither [`ENV`] nor
+/// the variables locating the configuration directory are set.
+pub(crate) fn path() -> Option<PathBuf> {
+    let non_empty = |var: &str| {
+        env::var_os(var)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    if let Some(path) = non_empty(ENV) {
+        return Some(path);
+    }
+    #[cfg(windows)]
+    let base = non_empty("APPDATA")?;
+    #[cfg(unix)]
+    let base = match non_empty("XDG_CONFIG_HOME") {
+        Some(dir) => dir,
+        None => non_empty("HOME")?.join(".config"),
+    };
+    Some(base.join("pend").join("config.toml"))
+}
+
+/// The profile named by `--profile` or [`PROFILE_ENV`]; the empty profile
+/// when neither is set.
+pub(crate) fn profile(name: Option<&str>) -> Result<Profile> {
+    let from_env = env::var(PROFILE_ENV).ok().filter(|v| !v.is_empty());
+    let Some(name) = name.map(str::to_string).or(from_env) else {
+        return Ok(Profile::default());
+    };
+    let mut profile = load()?
+        .profiles
+        .remove(&name)
+        .ok_or_else(|| PendError::Usage(format!("unknown profile '{name}'")))?;
+    profile.name = name;
+    Ok(profile)
+}
+
+/// Load the configuration. Only a file named explicitly by [`ENV`] has to
+/// exist.
+pub(crate) fn load() -> Result<File> {
+    let Some(path) = path() else {
+        return Ok(File::default());
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound && env::var_os(ENV).is_none() => {
+            return Ok(File::default())
+        }
+        Err(err) => {
+            return Err(PendError::Usage(format!(
+                "cannot read config {}: {err}",
+                path.display()
+            )))
+        }
+    };
+    toml::from_str(&text)
+        .map_err(|err| PendError::Usage(format!("invalid config {}: {err}", path.display())))
+}
+
+impl File {
+    pub(crate) fn hooks_dir(self) -> Option<PathBuf> {
+        self.hooks_dir
+    }
+
+    /// The shell command line of `pend do --template <name> key=value …`.
+    pub(crate) fn expand_template(
+        &self,
+        name: &str,
+        params: &[String],
+        ascii: bool,
+    ) -> Result<String> {
+        let (command, defaults) = match self.templates.get(name) {
+            Some(Template::Command(command)) => (command, None),
+            Some(Template::Full { command, defaults }) => (command, Some(defaults)),
+            None => return Err(PendError::Usage(format!("unknown template '{name}'"))),
+        };
+        let slots = placeholders(command);
+
+        let mut values: BTreeMap<&str, &str> = defaults
+            .into_iter()
+            .flatten()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        for param in params {
+            let Some((key, value)) = param.split_once('=').filter(|(k, _)| !k.is_empty()) else {
+                return Err(PendError::Usage(format!(
+                    "template parameters are key=value, got '{param}'"
+                )));
+            };
+            if !slots.iter().any(|(_, slot)| *slot == key) {
+                return Err(PendError::Usage(format!(
+                    "template '{name}' has no parameter '{key}'"
+                )));
+            }
+            values.insert(key, value);
+        }
+
+        let mut line = String::new();
+        let mut copied = 0;
+        for (range, slot) in slots {
+            let value = values.get(slot).ok_or_else(|| {
+                PendError::Usage(format!(
+                    "template '{name}' needs a value for {{{slot}}} (pass {slot}={})",
+                    crate::glyphs::ellipsis(ascii)
+                ))
+            })?;
+            line.push_str(&command[copied..range.start]);
+            line.push_str(value);
+            copied = range.end;
+        }
+        line.push_str(&command[copied..]);
+        Ok(line)
+    }
+}
+
+impl Profile {
+    /// The jobs root the profile selects, if any.
+    pub(crate) fn jobs_root(&self) -> io::Result<Option<PathBuf>> {
+        match &self.dir {
+            Some(dir) => Ok(Some(dir.clone())),
+            None if self.persistent => crate::paths::persistent_root().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Fill in the job settings not given on the command line.
+    pub(crate) fn apply(&self, options: &mut JobOptions) {
+        options.timeout = options.timeout.or(self.timeout);
+        options.retries = options.retries.or(self.retries);
+        options.max_log_size = options.max_log_size.or(self.max_log_size);
+        options.max_log_files = options.max_log_files.or(self.max_log_files);
+        options.compress_logs |= self.compress_logs;
+        options.max_jobs = options.max_jobs.or(self.max_jobs);
+    }
+}
+
+/// The global options given on the command line, which win over the
+/// environment and the configuration file.
+#[derive(Default)]
+pub(crate) struct Flags {
+    pub(crate) dir: Option<PathBuf>,
+    pub(crate) persistent: bool,
+    pub(crate) color: Option<ColorChoice>,
+    pub(crate) no_color: bool,
+    pub(crate) ascii: bool,
+    pub(crate) profile: Option<String>,
+}
+
+/// The settings every command runs with: where the jobs live and how output
+/// looks. `main` resolves them once from the command line, the profile and
+/// the environment and passes them down; a worker runs with those of the
+/// `pend do` that started it (see `worker.rs`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Config {
+    /// The jobs root shared by all users (see [`Flags::jobs_root`]).
+    pub(crate) dir: PathBuf,
+    /// Whether each user's jobs live in `<dir>/<user>`
+    /// ([`crate::paths::PER_USER_ENV`]).
+    pub(crate) per_user: bool,
+    pub(crate) color: ColorChoice,
+    /// Whether output is limited to ASCII (see `glyphs.rs`).
+    pub(crate) ascii: bool,
+}
+
+impl Config {
+    pub(crate) fn resolve(flags: &Flags, profile: &Profile) -> io::Result<Self> {
+        Ok(Self {
+            dir: match flags.jobs_root(profile)?.0 {
+                Some(dir) => dir,
+                None => crate::paths::default_root()?,
+            },
+            per_user: crate::paths::env_flag(crate::paths::PER_USER_ENV),
+            color: flags.color(profile).0,
+            ascii: flags.ascii || !crate::glyphs::locale_is_utf8(),
+        })
+    }
+
+    /// Point `command`, another pend process, at the same jobs root.
+    pub(crate) fn export(&self, command: &mut std::process::Command) {
+        command.env("PEND_DIR", &self.dir);
+    }
+}
+
+/// Where an effective setting came from.
+pub(crate) enum Source {
+    Default,
+    File,
+    Profile(String),
+    Env(&'static str),
+    Flag(&'static str),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => f.write_str("default"),
+            Source::File => f.write_str("config file"),
+            Source::Profile(name) => write!(f, "profile {name}"),
+            Source::Env(var) => write!(f, "env {var}"),
+            Source::Flag(flag) => write!(f, "flag {flag}"),
+        }
+    }
+}
+
+impl Flags {
+    /// The jobs root picked by the command line or the profile; `None`
+    /// leaves it to `PEND_DIR`, `PEND_PERSISTENT` or the temporary directory.
+    pub(crate) fn jobs_root(&self, profile: &Profile) -> io::Result<(Option<PathBuf>, Source)> {
+        if self.persistent {
+            return Ok((
+                Some(crate::paths::persistent_root()?),
+                Source::Flag("--persistent"),
+            ));
+        }
+        if let Some(dir) = &self.dir {
+            return Ok((Some(dir.clone()), Source::Flag("--dir")));
+        }
+        if env::var_os("PEND_DIR").is_some() {
+            return Ok((None, Source::Env("PEND_DIR")));
+        }
+        if let Some(dir) = profile.jobs_root()? {
+            return Ok((Some(dir), Source::Profile(profile.name.clone())));
+        }
+        if crate::paths::env_flag(crate::paths::PERSISTENT_ENV) {
+            return Ok((None, Source::Env(crate::paths::PERSISTENT_ENV)));
+        }
+        Ok((None, Source::Default))
+    }
+
+    /// The colour setting: `--no-color`, `--color`, the profile, `auto`.
+    pub(crate) fn color(&self, profile: &Profile) -> (ColorChoice, Source) {
+        if self.no_color {
+            (ColorChoice::Never, Source::Flag("--no-color"))
+        } else if let Some(choice) = self.color {
+            (choice, Source::Flag("--color"))
+        } else if let Some(choice) = profile.color {
+            (choice, Source::Profile(profile.name.clone()))
+        } else {
+            (ColorChoice::default(), Source::Default)
+        }
+    }
+
+    /// The profile name given by `--profile` or [`PROFILE_ENV`].
+    fn profile_name(&self) -> Option<(String, Source)> {
+        if let Some(name) = &self.profile {
+            return Some((name.clone(), Source::Flag("--profile")));
+        }
+        env::var(PROFILE_ENV)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|name| (name, Source::Env(PROFILE_ENV)))
+    }
+}
+
+/// `pend config show`: every effective setting, its value and its source.
+pub(crate) fn show(flags: &Flags) -> Result<()> {
+    let profile = profile(flags.profile.as_deref())?;
+    let config = load()?;
+    let none = || "none".to_string();
+    let from_profile = |set: bool| {
+        if set {
+            Source::Profile(profile.name.clone())
+        } else {
+            Source::Default
+        }
+    };
+
+    let mut rows = vec![(
+        "config",
+        path().map_or_else(none, |path| path.display().to_string()),
+        if env::var_os(ENV).is_some_and(|v| !v.is_empty()) {
+            Source::Env(ENV)
+        } else {
+            Source::Default
+        },
+    )];
+    let (name, source) = flags
+        .profile_name()
+        .unwrap_or_else(|| (none(), Source::Default));
+    rows.push(("profile", name, source));
+
+    let resolved = Config::resolve(flags, &profile)?;
+    let root = crate::paths::jobs_root_path(&resolved)?;
+    rows.push((
+        "dir",
+        root.display().to_string(),
+        flags.jobs_root(&profile)?.1,
+    ));
+    let per_user = resolved.per_user;
+    rows.push((
+        "per_user",
+        per_user.to_string(),
+        if per_user {
+            Source::Env(crate::paths::PER_USER_ENV)
+        } else {
+            Source::Default
+        },
+    ));
+
+    let (color, source) = flags.color(&profile);
+    let color = clap::ValueEnum::to_possible_value(&color)
+        .map_or_else(none, |value| value.get_name().to_string());
+    rows.push(("color", color, source));
+    rows.push((
+        "timeout",
+        profile.timeout.map_or_else(none, |secs| format!("{secs}s")),
+        from_profile(profile.timeout.is_some()),
+    ));
+    rows.push((
+        "retries",
+        profile.retries.unwrap_or(0).to_string(),
+        from_profile(profile.retries.is_some()),
+    ));
+    rows.push((
+        "max_log_size",
+        profile
+            .max_log_size
+            .map_or_else(none, |bytes| bytes.to_string()),
+        from_profile(profile.max_log_size.is_some()),
+    ));
+    rows.push((
+        "max_log_files",
+        profile.max_log_files.map_or_else(none, |n| n.to_string()),
+        from_profile(profile.max_log_files.is_some()),
+    ));
+    rows.push((
+        "compress_logs",
+        profile.compress_logs.to_string(),
+        from_profile(profile.compress_logs),
+    ));
+    rows.push((
+        "max_jobs",
+        profile.max_jobs.map_or_else(none, |n| n.to_string()),
+        from_profile(profile.max_jobs.is_some()),
+    ));
+    let (hooks, source) = match config.hooks_dir {
+        Some(dir) => (dir, Source::File),
+        None => (root.join("hooks"), Source::Default),
+    };
+    rows.push(("hooks_dir", hooks.display().to_string(), source));
+
+    let mut out = io::stdout().lock();
+    for (key, value, source) in rows {
+        writeln!(out, "{key:<15}{value}  ({source})")?;
+    }
+    Ok(())
+}
+
+/// `pend config set <key> <value>`: change one setting in the file. Profile
+/// settings go to the profile selected by `--profile` or [`PROFILE_ENV`].
+pub(crate) fn set(flags: &Flags, key: &str, value: &str) -> Result<()> {
+    let path = path()
+        .ok_or_else(|| PendError::Usage(format!("cannot locate the config file; set {ENV}")))?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(PendError::Usage(format!(
+                "cannot read config {}: {err}",
+                path.display()
+            )))
+        }
+    };
+    let mut doc: toml_edit::DocumentMut = text
+        .parse()
+        .map_err(|err| PendError::Usage(format!("invalid config {}: {err}", path.display())))?;
+
+    let item = setting(key, value)?;
+    if key == "hooks_dir" {
+        doc[key] = item;
+    } else {
+        let Some((name, _)) = flags.profile_name() else {
+            return Err(PendError::Usage(format!(
+                "'{key}' is a profile setting; select the profile with --profile"
+            )));
+        };
+        let profiles = doc["profiles"].or_insert(toml_edit::table());
+        if let Some(profiles) = profiles.as_table_mut() {
+            profiles.set_implicit(true);
+        }
+        profiles[&name].or_insert(toml_edit::table())[key] = item;
+    }
+
+    let text = doc.to_string();
+    toml::from_str::<File>(&text)
+        .map_err(|err| PendError::Usage(format!("invalid value for {key}: {}", err.message())))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    crate::paths::replace_file(&path, text.as_bytes())?;
+    Ok(())
+}
+
+/// The TOML value `pend config set` writes for `key`.
+fn setting(key: &str, value: &str) -> Result<toml_edit::Item> {
+    let expected = |what: &str| PendError::Usage(format!("{key} takes {what}, got '{value}'"));
+    let value: toml_edit::Value = match key {
+        "dir" | "hooks_dir" | "color" => value.into(),
+        "timeout" | "retries" | "max_log_files" | "max_jobs" => value
+            .parse::<i64>()
+            .map_err(|_| expected("a number"))?
+            .into(),
+        "max_log_size" => value
+            .parse::<i64>()
+            .map_or_else(|_| value.into(), Into::into),
+        "persistent" | "compress_logs" => value
+            .parse::<bool>()
+            .map_err(|_| expected("true or false"))?
+            .into(),
+        _ => return Err(PendError::Usage(format!("unknown setting '{key}'"))),
+    };
+    Ok(toml_edit::value(value))
+}
+
+/// A size given as a number of bytes or a string such as `"10M"`.
+fn size<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+    match Option::<Size>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Size::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(Size::Text(text)) => crate::parse_size(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// The `{name}` placeholders in `command` with their byte ranges.
+fn placeholders(command: &str) -> Vec<(Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = command[from..].find('{').map(|i| from + i) {
+        from = open + 1;
+        if command[..open].ends_with('$') {
+            continue;
+        }
+        let Some(len) = command[from..].find('}') else {
+            break;
+        };
+        let slot = &command[from..from + len];
+        if !slot.is_empty()
+            && slot
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            found.push((open..from + len + 1, slot));
+            from += len + 1;
+        }
+    }
+    found
+}
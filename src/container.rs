@@ -0,0 +1,107 @@
+//! Container execution backend for `pend do --docker/--podman IMAGE`.
+//!
+//! The worker still owns everything around the command – naming, logs, exit
+//! codes, timeouts and retries – and merely wraps the user command in
+//! `<engine> run`. The current directory is mounted into the container and
+//! used as its working directory so relative paths keep working.
+//!
+//! Each attempt gets a unique container name. Killing the engine's client
+//! process does not necessarily stop the container, so timeouts additionally
+//! issue `<engine> kill <name>`.
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Container engine CLI to drive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Engine {
+    Docker,
+    Podman,
+}
+
+impl Engine {
+    pub(crate) fn program(self) -> &'static str {
+        match self {
+            Engine::Docker => "docker",
+            Engine::Podman => "podman",
+        }
+    }
+}
+
+/// Where and in which image to run a job's command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Container {
+    pub(crate) engine: Engine,
+    pub(crate) image: String,
+}
+
+impl Container {
+    /// Build the `<engine> run …` invocation executing `cmd` in a container
+    /// named `name`.
+    pub(crate) fn wrap(&self, name: &str, cmd: &[OsString]) -> std::io::Result<Vec<OsString>> {
+        let host_dir = std::env::current_dir()?.into_os_string();
+        // Windows paths mean nothing inside a Linux container.
+        let guest_dir = if cfg!(windows) {
+            OsString::from("/work")
+        } else {
+            host_dir.clone()
+        };
+        let mut volume = host_dir;
+        volume.push(":");
+        volume.push(&guest_dir);
+
+        let mut argv: Vec<OsString> = vec![
+            self.engine.program().into(),
+            "run".into(),
+            "--rm".into(),
+            // Forward signals properly and reap zombies inside the container.
+            "--init".into(),
+            "--name".into(),
+            name.into(),
+            "-v".into(),
+            volume,
+            "-w".into(),
+            guest_dir,
+            self.image.clone().into(),
+        ];
+        argv.extend(cmd.iter().cloned());
+        Ok(argv)
+    }
+
+    /// Best-effort `<engine> kill <name>`.
+    pub(crate) fn kill(&self, name: &str) {
+        let _ = Command::new(self.engine.program())
+            .args(["kill", name])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+/// A container name unique to this attempt, e.g. `pend-build-4242-1`.
+///
+/// Job names may contain characters engines reject in names; those are
+/// replaced with `_`.
+pub(crate) fn container_name(job_name: &str) -> String {
+    static ATTEMPT: AtomicU32 = AtomicU32::new(0);
+
+    let job: String = job_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "pend-{}-{}-{}",
+        job,
+        std::process::id(),
+        ATTEMPT.fetch_add(1, Ordering::Relaxed) + 1
+    )
+}
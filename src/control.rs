@@ -0,0 +1,428 @@
+//! Local control socket exposed by running workers.
+//!
+//! Every worker listens for as long as it runs – on a Unix domain socket
+//! `<job>.sock` inside the jobs directory, on a named pipe
+//! `\\.\pipe\pend-<hash of that path>` on Windows. Front-end commands talk to
+//! it instead of guessing PIDs or polling the file system:
+//!
+//! | Request  | Reply                                                        |
+//! |----------|--------------------------------------------------------------|
+//! | `status` | One JSON line with the live state (PID, attempt, …).         |
+//! | `kill`   | `ok` once the running attempt has been signalled.            |
+//! | `wait`   | Nothing until the job ends, then `exit <code>`.              |
+//! | `attach` | Every chunk of output from now on, then `exit <code>`.       |
+//!
+//! `attach` frames each chunk as `out <len>\n` or `err <len>\n` followed by
+//! the bytes. A client that falls more than [`ATTACH_BACKLOG`] chunks
+//! behind is disconnected rather than slowing the job down.
+//!
+//! There is one endpoint per job rather than one per jobs root: workers are
+//! independent processes, and a shared endpoint would need a process that
+//! outlives them all to own it. A job's endpoint lives and dies with its
+//! worker, next to its other artifacts, and reaching it is a single connect.
+//!
+//! The socket is purely an optimisation: when it is missing (worker gone,
+//! path too long for `sun_path`) callers fall back to the artifact files,
+//! which remain the source of truth.
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::paths::JobPaths;
+
+#[cfg(unix)]
+use unix as sys;
+#[cfg(windows)]
+use windows as sys;
+
+/// Chunks queued for an attached client before it counts as too slow.
+const ATTACH_BACKLOG: usize = 256;
+
+/// How long a finishing worker waits for attached clients to read the rest.
+const FINISH_DRAIN: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+struct Shared {
+    pid: Option<u32>,
+    attempt: u32,
+    exit_code: Option<i32>,
+    subscribers: Vec<sys::Conn>,
+    /// Per attached client, the queue its connection's thread writes from.
+    attached: Vec<SyncSender<Vec<u8>>>,
+    /// Connection threads still writing to attached clients.
+    writers: usize,
+}
+
+/// Worker-side end of the control socket.
+pub(crate) struct ControlServer {
+    shared: Arc<Mutex<Shared>>,
+    paths: JobPaths,
+}
+
+impl ControlServer {
+    /// Listen on the job's socket and serve requests on background threads.
+    /// Returns `None` when the socket cannot be created.
+    pub(crate) fn start(job_name: &str, paths: &JobPaths) -> Option<Self> {
+        let mut listener = sys::Listener::bind(paths).ok()?;
+
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let thread_shared = Arc::clone(&shared);
+        let job = job_name.to_string();
+        let thread_paths = paths.clone();
+        std::thread::spawn(move || loop {
+            let conn = match listener.accept() {
+                Ok(conn) => conn,
+                Err(_) => {
+                    std::thread::sleep(Duration::from_millis(100));
+                    continue;
+                }
+            };
+            // `wait` and `attach` keep their connection for the whole run.
+            let job = job.clone();
+            let paths = thread_paths.clone();
+            let shared = Arc::clone(&thread_shared);
+            std::thread::spawn(move || {
+                let _ = handle(conn, &job, &paths, &shared);
+            });
+        });
+
+        Some(Self {
+            shared,
+            paths: paths.clone(),
+        })
+    }
+
+    /// Record the PID of a freshly spawned attempt.
+    pub(crate) fn set_attempt(&self, attempt: u32, pid: u32) {
+        let mut s = self.shared.lock().unwrap();
+        s.attempt = attempt;
+        s.pid = Some(pid);
+    }
+
+    /// Where the job's output goes for attached clients.
+    pub(crate) fn tap(&self) -> Tap {
+        Tap(Arc::clone(&self.shared))
+    }
+
+    /// Notify all `wait` and `attach` clients and remove the socket. Must be
+    /// called *after* the `.exit` marker has been written.
+    pub(crate) fn finish(self, exit_code: i32) {
+        sys::remove(&self.paths);
+        {
+            let mut s = self.shared.lock().unwrap();
+            s.exit_code = Some(exit_code);
+            for mut sub in s.subscribers.drain(..) {
+                let _ = writeln!(sub, "exit {exit_code}");
+            }
+            for client in s.attached.drain(..) {
+                let _ = client.try_send(format!("exit {exit_code}\n").into_bytes());
+            }
+        }
+        // The worker exits next; give attached clients their last chunks.
+        let deadline = std::time::Instant::now() + FINISH_DRAIN;
+        while self.shared.lock().unwrap().writers > 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Hands the job's output to attached clients; kept by the log writer.
+#[derive(Clone)]
+pub(crate) struct Tap(Arc<Mutex<Shared>>);
+
+impl Tap {
+    pub(crate) fn send(&self, is_stderr: bool, chunk: &[u8]) {
+        let mut s = self.0.lock().unwrap();
+        if s.attached.is_empty() {
+            return;
+        }
+        let stream = if is_stderr { "err" } else { "out" };
+        let mut frame = format!("{stream} {}\n", chunk.len()).into_bytes();
+        frame.extend_from_slice(chunk);
+        s.attached
+            .retain(|client| client.try_send(frame.clone()).is_ok());
+    }
+}
+
+fn handle(conn: sys::Conn, job: &str, paths: &JobPaths, shared: &Mutex<Shared>) -> io::Result<()> {
+    let mut line = String::new();
+    BufReader::new(&conn).read_line(&mut line)?;
+    let mut conn = conn;
+
+    match line.trim() {
+        "status" => {
+            let s = shared.lock().unwrap();
+            let reply = json!({
+                "job": job,
+                "state": if s.exit_code.is_some() { "finished" } else { "running" },
+                "pid": s.pid,
+                "worker_pid": std::process::id(),
+                "attempt": s.attempt,
+                "exit_code": s.exit_code,
+            });
+            writeln!(conn, "{reply}")
+        }
+        "kill" => {
+            std::fs::write(&paths.cancel, b"")?;
+            // The attempt's Job Object reaches the whole process tree.
+            #[cfg(windows)]
+            if crate::process::JobObject::open(paths).is_some_and(|job| job.terminate(1)) {
+                return writeln!(conn, "ok");
+            }
+            let pid = shared.lock().unwrap().pid;
+            if let Some(pid) = pid {
+                crate::process::terminate_process_tree(pid);
+            }
+            writeln!(conn, "ok")
+        }
+        "wait" => {
+            let mut s = shared.lock().unwrap();
+            match s.exit_code {
+                Some(code) => writeln!(conn, "exit {code}"),
+                None => {
+                    s.subscribers.push(conn);
+                    Ok(())
+                }
+            }
+        }
+        "attach" => {
+            let (client, frames) = sync_channel(ATTACH_BACKLOG);
+            {
+                let mut s = shared.lock().unwrap();
+                if let Some(code) = s.exit_code {
+                    return writeln!(conn, "exit {code}");
+                }
+                s.attached.push(client);
+                s.writers += 1;
+            }
+            // Ends after the exit frame, or when the client goes away or
+            // falls behind.
+            let written = frames
+                .into_iter()
+                .try_for_each(|frame| conn.write_all(&frame));
+            shared.lock().unwrap().writers -= 1;
+            written
+        }
+        other => writeln!(conn, "error unknown request '{other}'"),
+    }
+}
+
+/// Send a one-shot request to the worker of the job and return the
+/// (trimmed) reply line, or `None` when no worker is listening.
+pub(crate) fn request(paths: &JobPaths, command: &str) -> Option<String> {
+    let mut conn = sys::connect(paths, Some(Duration::from_secs(2))).ok()?;
+    writeln!(conn, "{command}").ok()?;
+    let mut line = String::new();
+    BufReader::new(conn).read_line(&mut line).ok()?;
+    Some(line.trim().to_string()).filter(|l| !l.is_empty())
+}
+
+/// Block until the worker reports the job's exit code. Returns `None`
+/// when no worker is listening or the connection drops.
+pub(crate) fn subscribe_exit(paths: &JobPaths) -> Option<i32> {
+    let mut conn = sys::connect(paths, None).ok()?;
+    writeln!(conn, "wait").ok()?;
+    let mut line = String::new();
+    BufReader::new(conn).read_line(&mut line).ok()?;
+    line.trim().strip_prefix("exit ")?.parse().ok()
+}
+
+/// Ask the worker of a job to terminate the running attempt through the
+/// control socket. Returns `false` when no worker is listening.
+pub(crate) fn request_kill(paths: &JobPaths) -> bool {
+    request(paths, "kill").as_deref() == Some("ok")
+}
+
+/// One message of an `attach` stream.
+pub(crate) enum Frame {
+    Out(Vec<u8>),
+    Err(Vec<u8>),
+    Exit(i32),
+}
+
+/// The job's output as its worker pushes it, see [`attach`].
+pub(crate) struct Attached(BufReader<sys::Conn>);
+
+impl Iterator for Attached {
+    type Item = Frame;
+
+    /// The next frame; `None` once the connection ends without one.
+    fn next(&mut self) -> Option<Frame> {
+        let mut header = String::new();
+        self.0.read_line(&mut header).ok()?;
+        let (kind, value) = header.trim().split_once(' ')?;
+        let chunk = |reader: &mut BufReader<sys::Conn>| {
+            let mut bytes = vec![0; value.parse().ok()?];
+            reader.read_exact(&mut bytes).ok()?;
+            Some(bytes)
+        };
+        match kind {
+            "out" => chunk(&mut self.0).map(Frame::Out),
+            "err" => chunk(&mut self.0).map(Frame::Err),
+            "exit" => value.parse().ok().map(Frame::Exit),
+            _ => None,
+        }
+    }
+}
+
+/// Follow the output of the job from now on. Returns `None` when no
+/// worker is listening.
+pub(crate) fn attach(paths: &JobPaths) -> Option<Attached> {
+    let mut conn = sys::connect(paths, None).ok()?;
+    writeln!(conn, "attach").ok()?;
+    Some(Attached(BufReader::new(conn)))
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    pub(super) type Conn = UnixStream;
+
+    pub(super) struct Listener(UnixListener);
+
+    impl Listener {
+        pub(super) fn bind(paths: &JobPaths) -> io::Result<Self> {
+            // A stale socket left behind by a crashed worker would make
+            // `bind` fail – we own the job lock, so it is safe to remove.
+            let _ = std::fs::remove_file(&paths.sock);
+            UnixListener::bind(&paths.sock).map(Self)
+        }
+
+        pub(super) fn accept(&mut self) -> io::Result<Conn> {
+            let (conn, _) = self.0.accept()?;
+            // Only for the request line: a client that never sends one must
+            // not hold a thread forever.
+            conn.set_read_timeout(Some(Duration::from_secs(1)))?;
+            Ok(conn)
+        }
+    }
+
+    pub(super) fn connect(paths: &JobPaths, timeout: Option<Duration>) -> io::Result<Conn> {
+        let conn = UnixStream::connect(&paths.sock)?;
+        conn.set_read_timeout(timeout)?;
+        Ok(conn)
+    }
+
+    pub(super) fn remove(paths: &JobPaths) {
+        let _ = std::fs::remove_file(&paths.sock);
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use std::fs::File;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle};
+    use windows_sys::Win32::Foundation::{
+        ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX,
+    };
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, WaitNamedPipeW, PIPE_READMODE_BYTE,
+        PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    pub(super) type Conn = File;
+
+    /// The job's pipe, named after its `.sock` path so that jobs of
+    /// different jobs roots never meet.
+    fn pipe_name(paths: &JobPaths) -> String {
+        let path = std::path::absolute(&paths.sock).unwrap_or_else(|_| paths.sock.clone());
+        let digest = Sha256::digest(path.to_string_lossy().to_lowercase().as_bytes());
+        format!(r"\\.\pipe\pend-{:x}", digest)
+    }
+
+    fn wide(name: &str) -> Vec<u16> {
+        name.encode_utf16().chain(Some(0)).collect()
+    }
+
+    /// A new instance of the pipe, waiting for a client.
+    fn create(name: &[u16], first: bool) -> io::Result<File> {
+        let flags = if first {
+            FILE_FLAG_FIRST_PIPE_INSTANCE
+        } else {
+            0
+        };
+        // Safety: `name` is a valid, NUL-terminated wide string; no security
+        // attributes means the default descriptor.
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_DUPLEX | flags,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                64 * 1024,
+                4 * 1024,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        // Safety: the handle was just created and is owned by nobody else.
+        Ok(unsafe { File::from_raw_handle(handle as _) })
+    }
+
+    pub(super) struct Listener {
+        name: Vec<u16>,
+        /// The instance created but not yet connected.
+        next: Option<File>,
+    }
+
+    impl Listener {
+        pub(super) fn bind(paths: &JobPaths) -> io::Result<Self> {
+            let name = wide(&pipe_name(paths));
+            // Owning the first instance keeps anybody else from serving
+            // this job's pipe.
+            let next = Some(create(&name, true)?);
+            Ok(Self { name, next })
+        }
+
+        pub(super) fn accept(&mut self) -> io::Result<Conn> {
+            let pipe = match self.next.take() {
+                Some(pipe) => pipe,
+                None => create(&self.name, false)?,
+            };
+            // Safety: `pipe` is a valid pipe handle; no overlapped I/O.
+            if unsafe { ConnectNamedPipe(pipe.as_raw_handle() as _, std::ptr::null_mut()) } == 0 {
+                let err = io::Error::last_os_error();
+                // The client connected between creation and this call.
+                if err.raw_os_error() != Some(ERROR_PIPE_CONNECTED as i32) {
+                    return Err(err);
+                }
+            }
+            Ok(pipe)
+        }
+    }
+
+    /// Named pipes have no read timeout; the worker answers requests at once.
+    pub(super) fn connect(paths: &JobPaths, _timeout: Option<Duration>) -> io::Result<Conn> {
+        let name = pipe_name(paths);
+        let open = || {
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&name)
+        };
+        match open() {
+            // Every instance is taken until the worker creates the next one.
+            Err(err) if err.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) => {
+                // Safety: the name is a valid, NUL-terminated wide string.
+                unsafe { WaitNamedPipeW(wide(&name).as_ptr(), 2000) };
+                open()
+            }
+            result => result,
+        }
+    }
+
+    /// The pipe goes away with the worker's handles.
+    pub(super) fn remove(_paths: &JobPaths) {}
+}
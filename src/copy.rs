@@ -0,0 +1,135 @@
+//! `pend copy <job> <dest-dir>`: export a job's raw artifacts.
+//!
+//! Every artifact of the job – rotated log generations included, the lock
+//! and control socket left out – is copied into the destination directory
+//! under its own name with its modification time kept. `--link` hard-links
+//! instead, falling back to a copy where the destination is on another
+//! filesystem. Next to the files, `<job>.manifest.json` lists each one with
+//! its size, SHA-256 and modification time, plus the job's state when it was
+//! copied, so a CI step can move the results into its artifacts folder and
+//! tell later what it got.
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+use crate::status::{job_status, State};
+
+#[derive(Serialize)]
+struct Manifest {
+    job: String,
+    copied: String,
+    state: State,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    files: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    name: String,
+    bytes: u64,
+    sha256: String,
+    modified: String,
+    /// Hard-linked rather than copied.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    linked: bool,
+}
+
+/// Entry point for `pend copy <job> <dest-dir> [--link]`; returns the path
+/// of the manifest.
+pub(crate) fn copy_job(
+    config: &Config,
+    job_name: &str,
+    dest: &Path,
+    link: bool,
+) -> Result<PathBuf> {
+    let paths = JobPaths::new(config, job_name)?;
+    let status = job_status(config, job_name)?;
+    if status.state == State::Running {
+        eprintln!("pend: job '{job_name}' is still running; copying its artifacts so far");
+    }
+    fs::create_dir_all(dest)?;
+
+    let mut sources: Vec<&PathBuf> = paths
+        .all()
+        .into_iter()
+        .filter(|path| *path != &paths.lock && *path != &paths.sock)
+        .collect();
+    let rotated = paths.rotated();
+    sources.extend(&rotated);
+
+    let mut files = Vec::new();
+    for source in sources {
+        let Some(name) = source.file_name() else {
+            continue;
+        };
+        let metadata = match fs::metadata(source) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let target = dest.join(name);
+        if target.exists() && same_file(source, &target)? {
+            return Err(PendError::Usage(format!(
+                "{} is the job's own directory",
+                dest.display()
+            )));
+        }
+        let linked = link && hard_link(source, &target);
+        if !linked {
+            fs::copy(source, &target)?;
+            File::options()
+                .write(true)
+                .open(&target)?
+                .set_modified(metadata.modified()?)?;
+        }
+        files.push(Entry {
+            name: name.to_string_lossy().into_owned(),
+            bytes: metadata.len(),
+            sha256: crate::validate::sha256_file(&target)?,
+            modified: timestamp(metadata.modified()?),
+            linked,
+        });
+    }
+
+    let manifest = Manifest {
+        job: job_name.to_string(),
+        copied: Utc::now().to_rfc3339(),
+        state: status.state,
+        exit_code: status.exit_code,
+        files,
+    };
+    let path = dest.join(format!("{job_name}.manifest.json"));
+    let mut json = serde_json::to_vec_pretty(&manifest).map_err(io::Error::other)?;
+    json.push(b'\n');
+    crate::paths::replace_file(&path, &json)?;
+    Ok(path)
+}
+
+/// Hard-link `source` to `target`, replacing it; `false` when linking is
+/// impossible (another filesystem, no support) and a copy has to do.
+fn hard_link(source: &Path, target: &Path) -> bool {
+    let _ = fs::remove_file(target);
+    match fs::hard_link(source, target) {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::debug!(%err, path = %source.display(), "cannot hard-link, copying");
+            false
+        }
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> io::Result<bool> {
+    Ok(fs::canonicalize(a)? == fs::canonicalize(b)?)
+}
+
+fn timestamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339()
+}
@@ -0,0 +1,116 @@
+//! Storing `.out` and `.log` once when they are the same.
+//!
+//! A job that writes nothing to stderr leaves `.log` byte for byte equal to
+//! `.out`. Once the job has finished, the worker replaces `.log` with a
+//! reflink of `.out` – a copy-on-write clone, on filesystems that support
+//! it (Btrfs, XFS) – or else a hard link, so the bytes are stored once.
+//! Where neither works both files simply stay as they are.
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::paths::JobPaths;
+
+/// How `.log` came to share the storage of `.out`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Shared {
+    Reflink,
+    HardLink,
+}
+
+/// Make `.log` share the storage of `.out` when the two are identical.
+/// `None` when they differ, are empty, or cannot be linked.
+pub(crate) fn share_identical(paths: &JobPaths) -> io::Result<Option<Shared>> {
+    let len = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let out_len = len(&paths.out);
+    if out_len == 0 || len(&paths.err) != 0 || len(&paths.log) != out_len {
+        return Ok(None);
+    }
+    if same_file(&paths.out, &paths.log) || !same_contents(&paths.out, &paths.log)? {
+        return Ok(None);
+    }
+
+    // Build the replacement next to `.log`, then swap it in, so readers
+    // always find a complete file.
+    let mut staged = paths.log.clone().into_os_string();
+    staged.push(".dedup");
+    let staged = std::path::PathBuf::from(staged);
+    let _ = fs::remove_file(&staged);
+    let shared = if reflink(&paths.out, &staged).is_ok() {
+        Shared::Reflink
+    } else {
+        let _ = fs::remove_file(&staged);
+        if let Err(err) = fs::hard_link(&paths.out, &staged) {
+            tracing::debug!(%err, "cannot link .log to .out, keeping both");
+            return Ok(None);
+        }
+        Shared::HardLink
+    };
+    if let Err(err) = fs::rename(&staged, &paths.log) {
+        let _ = fs::remove_file(&staged);
+        return Err(err);
+    }
+    tracing::debug!(?shared, bytes = out_len, "stored .log once with .out");
+    Ok(Some(shared))
+}
+
+/// Whether both paths name the same file.
+#[cfg(unix)]
+pub(crate) fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn same_file(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let (mut a, mut b) = (File::open(a)?, File::open(b)?);
+    let mut buf_a = vec![0u8; 64 * 1024];
+    let mut buf_b = vec![0u8; 64 * 1024];
+    loop {
+        let n = read_full(&mut a, &mut buf_a)?;
+        if read_full(&mut b, &mut buf_b)? != n || buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Fill `buf` as far as the file allows; fewer bytes only at its end.
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Clone `src` into a new file `dst` sharing its blocks.
+#[cfg(target_os = "linux")]
+fn reflink(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let src = File::open(src)?;
+    let dst = File::create_new(dst)?;
+    // Safety: both descriptors are open for the duration of the call.
+    if unsafe { libc::ioctl(dst.as_raw_fd(), libc::FICLONE, src.as_raw_fd()) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
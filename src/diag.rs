@@ -0,0 +1,66 @@
+//! Internal diagnostics logging.
+//!
+//! pend's own decisions – watcher fallbacks, lock acquisition, log rotation,
+//! retries and restarts – are traced with [`tracing`] so odd behaviour on a
+//! user's machine can be reconstructed. Nothing is recorded unless asked for:
+//!
+//! * `-v` / `-vv` on any command logs at debug / trace level to stderr.
+//! * `PEND_LOG` takes an explicit filter (e.g. `debug`, `pend::lock=trace`)
+//!   and overrides `-v`.
+//!
+//! The detached worker has no terminal, so it inherits the filter through
+//! `PEND_LOG` and writes to `<job>.pend-debug.log` instead.
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::EnvFilter;
+
+use crate::paths::JobPaths;
+
+/// Environment variable holding the filter directive.
+pub(crate) const ENV: &str = "PEND_LOG";
+
+/// The filter in effect for this process, handed on to spawned workers.
+static FILTER: OnceLock<String> = OnceLock::new();
+
+/// Set up logging to stderr for a front-end command.
+pub(crate) fn init(verbose: u8) {
+    let filter = match std::env::var(ENV) {
+        Ok(filter) => filter,
+        Err(_) => match verbose {
+            0 => return,
+            1 => "pend=debug".to_string(),
+            _ => "pend=trace".to_string(),
+        },
+    };
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&filter))
+        .with_writer(std::io::stderr)
+        .try_init();
+    let _ = FILTER.set(filter);
+}
+
+/// Set up logging to `<job>.pend-debug.log` inside a worker.
+pub(crate) fn init_worker(paths: &JobPaths) {
+    let Ok(filter) = std::env::var(ENV) else {
+        return;
+    };
+    let Ok(file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&paths.debug)
+    else {
+        return;
+    };
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&filter))
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .try_init();
+    let _ = FILTER.set(filter);
+}
+
+/// Pass the active filter (if any) on to a spawned worker.
+pub(crate) fn export(command: &mut std::process::Command) {
+    if let Some(filter) = FILTER.get() {
+        command.env(ENV, filter);
+    }
+}
@@ -0,0 +1,80 @@
+//! The command's environment: `pend do --env` / `--clean-env`.
+//!
+//! By default the command inherits pend's environment. `--clean-env` starts
+//! it from the few variables programs need to work at all ([`BASE`]) so a
+//! run does not depend on whatever the submitting shell had exported;
+//! `--env NAME=VALUE` (or a bare `NAME`, taking its value at submission)
+//! adds to either.
+//!
+//! The metadata records the resulting environment, restricted to [`BASE`],
+//! the locale (`LC_*`) and the `--env` variables, so secrets elsewhere in the
+//! environment never end up in `<job>.json`.
+use std::collections::BTreeMap;
+use std::env;
+use std::process::Command;
+
+use crate::worker::JobOptions;
+
+/// Variables a clean environment keeps.
+#[cfg(unix)]
+const BASE: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "TERM", "TZ", "TMPDIR",
+];
+#[cfg(windows)]
+const BASE: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "TEMP",
+    "TMP",
+    "USERNAME",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+];
+
+/// Resolve `--env` arguments at submission: a bare `NAME` takes its current
+/// value and is dropped when unset.
+pub(crate) fn resolve(args: &[String]) -> Vec<(String, String)> {
+    args.iter()
+        .filter_map(|arg| match arg.split_once('=') {
+            Some((name, value)) => Some((name.to_string(), value.to_string())),
+            None => Some((arg.clone(), env::var(arg).ok()?)),
+        })
+        .collect()
+}
+
+/// Set up the environment of `command` as `options` ask.
+pub(crate) fn apply(command: &mut Command, options: &JobOptions) {
+    if options.clean_env {
+        command.env_clear();
+        for name in BASE {
+            if let Some(value) = env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+    }
+    command.envs(options.env.iter().map(|(name, value)| (name, value)));
+}
+
+/// The command's environment as recorded in the metadata.
+pub(crate) fn recorded(options: &JobOptions) -> BTreeMap<String, String> {
+    let mut recorded: BTreeMap<String, String> = env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(name, _)| in_base(name) || (!options.clean_env && name.starts_with("LC_")))
+        .collect();
+    recorded.extend(options.env.iter().cloned());
+    recorded
+}
+
+/// Whether `name` is one of [`BASE`]; Windows names ignore case.
+fn in_base(name: &str) -> bool {
+    BASE.iter().any(|base| match cfg!(windows) {
+        true => base.eq_ignore_ascii_case(name),
+        false => *base == name,
+    })
+}
@@ -0,0 +1,185 @@
+//! pend's error type and the exit codes of its own failures.
+//!
+//! `pend wait` exits with the code of the job it waited for, so a failing
+//! `pend` must not simply exit 1 – a script could not tell "the build
+//! failed" from "there is no job called build". Failures of pend itself are
+//! therefore mapped to a reserved set of codes:
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 2    | Usage error: invalid arguments or job name, unsupported option. |
+//! | 3    | Job not found. |
+//! | 4    | Job already running, or lock held (`pend lock --no-wait`). |
+//! | 5    | Corrupt artifact (see `validate.rs`). |
+//! | 6    | Too many jobs running (`--max-jobs`, see `max_jobs.rs`). |
+//! | 141  | Stdout was closed early (`pend wait job \| head`); nothing is printed. |
+//! | 125  | Internal error (I/O failure, worker died, …). |
+//!
+//! Modules that deal with job semantics return [`PendError`] so callers can
+//! match on the cause; low-level helpers keep returning `io::Error`, which
+//! converts into [`PendError::Io`]. Where a `PendError` has to travel
+//! through an `io::Result` it is wrapped with a matching [`io::ErrorKind`]
+//! and recovered unchanged on the way back.
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Exit code of `pend wait` / `pend validate` when an artifact is corrupt.
+pub(crate) const CORRUPT_EXIT: i32 = 5;
+
+/// Exit code when stdout was closed under us: `128 + SIGPIPE`, as shells
+/// report a filter the signal killed.
+pub(crate) const BROKEN_PIPE_EXIT: i32 = 141;
+
+/// Make `println!` into a closed stdout end the process quietly with
+/// [`BROKEN_PIPE_EXIT`] instead of panicking with a backtrace. Every other
+/// panic is reported as usual.
+pub(crate) fn exit_quietly_on_closed_stdout() {
+    #[cfg(unix)]
+    let codes = [libc::EPIPE];
+    #[cfg(windows)]
+    let codes = [
+        windows_sys::Win32::Foundation::ERROR_BROKEN_PIPE as i32,
+        windows_sys::Win32::Foundation::ERROR_NO_DATA as i32,
+    ];
+    // What `println!` panics with; see `std::io::stdio::print_to`.
+    let messages: Vec<String> = codes
+        .iter()
+        .map(|&code| {
+            format!(
+                "failed printing to stdout: {}",
+                io::Error::from_raw_os_error(code)
+            )
+        })
+        .collect();
+    let report = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| payload.downcast_ref::<&str>().copied());
+        if message.is_some_and(|message| messages.iter().any(|m| m == message)) {
+            std::process::exit(BROKEN_PIPE_EXIT);
+        }
+        report(info)
+    }));
+}
+
+pub(crate) type Result<T> = std::result::Result<T, PendError>;
+
+#[derive(Debug)]
+pub(crate) enum PendError {
+    /// No artifacts exist for the job.
+    NotFound(String),
+    /// Another worker holds the job's lock.
+    AlreadyRunning(String),
+    /// Somebody else holds the named lock (see `mutex.rs`).
+    LockHeld(String),
+    /// `--max-jobs` jobs are running already.
+    TooManyJobs {
+        running: usize,
+        limit: u32,
+    },
+    /// The job name breaks the naming rules; carries the rule.
+    InvalidName(&'static str),
+    /// Invalid arguments or an option the platform does not support.
+    Usage(String),
+    /// The job's worker stopped without recording an exit code.
+    WorkerDied(String),
+    /// An artifact exists but cannot be parsed.
+    Corrupt {
+        path: PathBuf,
+        reason: String,
+    },
+    Io(io::Error),
+}
+
+impl PendError {
+    /// The process exit code reported for this failure.
+    pub(crate) fn exit_code(&self) -> i32 {
+        match self {
+            Self::Usage(_) | Self::InvalidName(_) => 2,
+            Self::NotFound(_) => 3,
+            Self::AlreadyRunning(_) | Self::LockHeld(_) => 4,
+            Self::Corrupt { .. } => CORRUPT_EXIT,
+            Self::TooManyJobs { .. } => 6,
+            Self::Io(err) => match err.kind() {
+                io::ErrorKind::InvalidInput | io::ErrorKind::Unsupported => 2,
+                _ => 125,
+            },
+            Self::WorkerDied(_) => 125,
+        }
+    }
+
+    /// Whether the failure is a write to a reader that went away.
+    pub(crate) fn is_broken_pipe(&self) -> bool {
+        matches!(self, Self::Io(err) if err.kind() == io::ErrorKind::BrokenPipe)
+    }
+
+    fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::NotFound(_) => io::ErrorKind::NotFound,
+            Self::AlreadyRunning(_) => io::ErrorKind::AlreadyExists,
+            Self::LockHeld(_) => io::ErrorKind::WouldBlock,
+            Self::TooManyJobs { .. } => io::ErrorKind::ResourceBusy,
+            Self::InvalidName(_) | Self::Usage(_) => io::ErrorKind::InvalidInput,
+            Self::Corrupt { .. } => io::ErrorKind::InvalidData,
+            Self::WorkerDied(_) => io::ErrorKind::Other,
+            Self::Io(err) => err.kind(),
+        }
+    }
+}
+
+impl fmt::Display for PendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(job) => write!(f, "job '{job}' not found"),
+            Self::AlreadyRunning(job) => write!(f, "job '{job}' is already running"),
+            Self::LockHeld(name) => write!(f, "lock '{name}' is held elsewhere"),
+            Self::TooManyJobs { running, limit } => write!(
+                f,
+                "{running} jobs are running, the limit is {limit} (--max-jobs); \
+                 pass --queue to wait for one to finish"
+            ),
+            Self::InvalidName(rule) => f.write_str(rule),
+            Self::Usage(msg) => f.write_str(msg),
+            Self::WorkerDied(job) => write!(
+                f,
+                "the worker of job '{job}' died without recording an exit code"
+            ),
+            Self::Corrupt { path, reason } => {
+                write!(f, "corrupt artifact {}: {reason}", path.display())
+            }
+            Self::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for PendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PendError {
+    fn from(err: io::Error) -> Self {
+        if err.get_ref().is_some_and(|inner| inner.is::<PendError>()) {
+            let inner = err.into_inner().expect("checked above");
+            return *inner.downcast::<PendError>().expect("checked above");
+        }
+        Self::Io(err)
+    }
+}
+
+impl From<PendError> for io::Error {
+    fn from(err: PendError) -> Self {
+        match err {
+            PendError::Io(err) => err,
+            err => io::Error::new(err.kind(), err),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Rendering and mapping of recorded exit codes.
+//!
+//! `<job>.exit` stores the code exactly as reported by the OS, as a signed
+//! 32-bit integer. On Unix that is a value in `0..=255` (`128 + n` for
+//! signals). Windows exit codes are full 32-bit `DWORD`s though, and crashes
+//! surface as `NTSTATUS` values such as `0xC0000005` which come out negative
+//! when read as `i32`. The helpers below keep those values intact in the
+//! artifacts while presenting them in a recognisable form and mapping them to
+//! something the current platform can actually exit with.
+
+/// Well-known `NTSTATUS` failure codes worth naming in summaries.
+const NTSTATUS_NAMES: &[(u32, &str)] = &[
+    (0x8000_0003, "STATUS_BREAKPOINT"),
+    (0xC000_0005, "STATUS_ACCESS_VIOLATION"),
+    (0xC000_0017, "STATUS_NO_MEMORY"),
+    (0xC000_001D, "STATUS_ILLEGAL_INSTRUCTION"),
+    (0xC000_0094, "STATUS_INTEGER_DIVIDE_BY_ZERO"),
+    (0xC000_00FD, "STATUS_STACK_OVERFLOW"),
+    (0xC000_0135, "STATUS_DLL_NOT_FOUND"),
+    (0xC000_013A, "STATUS_CONTROL_C_EXIT"),
+    (0xC000_0142, "STATUS_DLL_INIT_FAILED"),
+    (0xC000_0409, "STATUS_STACK_BUFFER_OVERRUN"),
+];
+
+/// Human-readable form of `code` for summaries: plain decimal for ordinary
+/// codes, hexadecimal (plus the symbolic name when known) for values with the
+/// `NTSTATUS` severity bits set.
+pub(crate) fn describe(code: i32) -> String {
+    if code >= 0 {
+        return code.to_string();
+    }
+    let raw = code as u32;
+    match NTSTATUS_NAMES.iter().find(|(c, _)| *c == raw) {
+        Some((_, name)) => format!("0x{raw:08X} ({name})"),
+        None => format!("0x{raw:08X}"),
+    }
+}
+
+/// Exit code for the `pend` process itself when reporting `code`.
+///
+/// Windows passes the full 32-bit value through. Unix only keeps the low
+/// eight bits, which could turn a failure like `0xC0000100` into a "success";
+/// codes outside `0..=255` (e.g. recorded by a Windows worker on a shared
+/// jobs directory) therefore collapse to a generic `1`.
+pub(crate) fn host_exit_code(code: i32) -> i32 {
+    if cfg!(windows) || (0..=255).contains(&code) {
+        code
+    } else {
+        1
+    }
+}
@@ -0,0 +1,65 @@
+//! `pend do --expires-in`: jobs that clean up after themselves.
+//!
+//! A throwaway job can declare how long its artifacts are worth keeping:
+//! with `--expires-in 2h` the worker records in `.json`, once the job has
+//! finished, when that time is up:
+//!
+//! ```json
+//! "expires_in": 7200, "expires": "2024-05-01T14:00:00+00:00"
+//! ```
+//!
+//! Nothing is deleted behind the user's back while pend is idle; expired
+//! jobs go when `pend clean --expired` runs – from cron, a CI teardown or by
+//! hand – and `pend serve` sweeps them every minute while it runs. Running
+//! jobs never expire, and a job resubmitted under the same name starts over.
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+use crate::paths::JobPaths;
+
+/// When a job that ended at `ended` expires.
+pub(crate) fn expiry(ended: DateTime<Utc>, expires_in: u64) -> String {
+    let ttl = chrono::Duration::seconds(i64::try_from(expires_in).unwrap_or(i64::MAX));
+    ended
+        .checked_add_signed(ttl)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+        .to_rfc3339()
+}
+
+/// Whether the finished job at `paths` expired by `now`.
+fn expired(paths: &JobPaths, now: DateTime<Utc>) -> bool {
+    if !paths.exit.exists() {
+        return false;
+    }
+    let Some(meta) = std::fs::read(&paths.meta)
+        .ok()
+        .and_then(|bytes| crate::version::parse_meta(&bytes).ok())
+    else {
+        return false;
+    };
+    meta.get("expires")
+        .and_then(|expires| expires.as_str())
+        .and_then(|expires| DateTime::parse_from_rfc3339(expires).ok())
+        .is_some_and(|expires| expires <= now)
+}
+
+/// Delete the artifacts of every expired job under `root`. Returns the
+/// names of the jobs deleted.
+pub(crate) fn sweep(root: &Path) -> Vec<String> {
+    let now = Utc::now();
+    let mut swept = Vec::new();
+    for job in crate::paths::discover_jobs(root) {
+        let Ok(paths) = JobPaths::in_root(root, &job) else {
+            continue;
+        };
+        // A worker holding the lock is writing a new run.
+        if !expired(&paths, now) || crate::lock::is_held(&paths.lock) {
+            continue;
+        }
+        tracing::debug!(job, "removing the artifacts of an expired job");
+        paths.remove_all();
+        crate::last::forget(root, &job);
+        swept.push(job);
+    }
+    swept
+}
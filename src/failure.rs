@@ -0,0 +1,157 @@
+//! Why a job failed, in one word.
+//!
+//! The exit code alone does not tell a timeout from a crash: both may end
+//! in 137. The worker therefore records the cause of a failed run next to
+//! the exit code in `.json`:
+//!
+//! ```json
+//! "failure": { "kind": "killed_by_signal", "signal": 11 }
+//! ```
+//!
+//! `kind` is one of [`FailureKind`]; `signal` is only set for
+//! `killed_by_signal`. A successful run has no `failure`. `pend status
+//! --failed --reason <kind>` lists the jobs that failed that way.
+//!
+//! `oom_killed` covers both the kernel's OOM killer – seen as a `SIGKILL`
+//! while the `oom_kill` counter of the worker's cgroup (cgroup v2, Linux)
+//! went up – and a job exceeding its `--max-mem`.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// The signal the OOM killer sends, the same on every Unix.
+const SIGKILL: i32 = 9;
+
+/// The cause of a failed run, checked in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub(crate) enum FailureKind {
+    /// The command could not be started at all.
+    SpawnFailed,
+    /// `--timeout` killed it.
+    TimedOut,
+    /// The `--stall-timeout` watchdog terminated it.
+    Stalled,
+    /// It ran out of memory.
+    OomKilled,
+    /// A signal ended it (Unix).
+    KilledBySignal,
+    /// It exited with a non-zero code.
+    NonzeroExit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Failure {
+    pub(crate) kind: FailureKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) signal: Option<i32>,
+}
+
+/// How an attempt ended, as far as classifying it goes.
+pub(crate) struct Outcome {
+    pub(crate) exit_code: i32,
+    pub(crate) timed_out: bool,
+    pub(crate) signal: Option<i32>,
+    pub(crate) limit_exceeded: Option<&'static str>,
+    pub(crate) oom_killed: bool,
+    pub(crate) stalled: bool,
+}
+
+impl Failure {
+    pub(crate) fn spawn_failed() -> Self {
+        Self {
+            kind: FailureKind::SpawnFailed,
+            signal: None,
+        }
+    }
+
+    /// The failure `outcome` stands for; `None` for a success.
+    pub(crate) fn classify(outcome: &Outcome) -> Option<Self> {
+        let kind = if outcome.timed_out {
+            FailureKind::TimedOut
+        } else if outcome.stalled && outcome.exit_code != 0 {
+            // Leftovers terminated after the command succeeded spoil nothing.
+            FailureKind::Stalled
+        } else if outcome.oom_killed || outcome.limit_exceeded == Some("memory") {
+            FailureKind::OomKilled
+        } else if outcome.signal.is_some() {
+            FailureKind::KilledBySignal
+        } else if outcome.exit_code != 0 {
+            FailureKind::NonzeroExit
+        } else {
+            return None;
+        };
+        Some(Self {
+            kind,
+            signal: outcome
+                .signal
+                .filter(|_| kind == FailureKind::KilledBySignal),
+        })
+    }
+
+    /// A few words for `pend status`.
+    pub(crate) fn describe(&self) -> String {
+        match (self.kind, self.signal) {
+            (FailureKind::SpawnFailed, _) => "failed to start".into(),
+            (FailureKind::TimedOut, _) => "timed out".into(),
+            (FailureKind::Stalled, _) => "stalled".into(),
+            (FailureKind::OomKilled, _) => "out of memory".into(),
+            (FailureKind::KilledBySignal, Some(signal)) => format!("killed by signal {signal}"),
+            (FailureKind::KilledBySignal, None) => "killed by a signal".into(),
+            (FailureKind::NonzeroExit, _) => "non-zero exit".into(),
+        }
+    }
+}
+
+/// The OOM kills of the worker's cgroup, counted before an attempt so a
+/// rise afterwards points at the kernel's OOM killer.
+pub(crate) struct OomWatch {
+    events: Option<PathBuf>,
+    before: u64,
+}
+
+impl OomWatch {
+    pub(crate) fn start() -> Self {
+        let events = memory_events();
+        let before = events.as_ref().and_then(|p| oom_kills(p)).unwrap_or(0);
+        Self { events, before }
+    }
+
+    /// Whether an attempt that ended by `signal` was killed by the OOM
+    /// killer since [`OomWatch::start`].
+    pub(crate) fn killed(&self, signal: Option<i32>) -> bool {
+        signal == Some(SIGKILL) && self.fired()
+    }
+
+    fn fired(&self) -> bool {
+        self.events
+            .as_ref()
+            .and_then(|p| oom_kills(p))
+            .is_some_and(|now| now > self.before)
+    }
+}
+
+/// `memory.events` of the cgroup v2 this process belongs to.
+fn memory_events() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let cgroups = fs::read_to_string("/proc/self/cgroup").ok()?;
+    // cgroup v2 has a single hierarchy, listed as `0::/path`.
+    let path = cgroups.lines().find_map(|line| line.strip_prefix("0::"))?;
+    let events = PathBuf::from("/sys/fs/cgroup")
+        .join(path.trim_start_matches('/'))
+        .join("memory.events");
+    events.exists().then_some(events)
+}
+
+fn oom_kills(events: &std::path::Path) -> Option<u64> {
+    fs::read_to_string(events)
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("oom_kill "))?
+        .trim()
+        .parse()
+        .ok()
+}
@@ -0,0 +1,72 @@
+//! `pend wait --fold-repeats`: collapse runs of identical output lines.
+//!
+//! Tools stuck in a retry loop or redrawing a spinner line by line can bury
+//! everything else in a log. With folding, the first line of a run is
+//! replayed as usual and the rest of the run is replaced by a single
+//! `… last line repeated N times` once a different line arrives or the
+//! stream ends. Lines compare byte for byte; the log itself is untouched.
+
+/// Folding state of one output stream.
+#[derive(Default)]
+pub(crate) struct Fold {
+    /// Bytes of an incomplete trailing line.
+    partial: Vec<u8>,
+    /// The last complete line passed on, newline included.
+    last: Option<Vec<u8>>,
+    /// How many copies of `last` were swallowed since.
+    repeats: u64,
+    /// Write the notice with ASCII symbols only (see `glyphs.rs`).
+    ascii: bool,
+}
+
+impl Fold {
+    pub(crate) fn new(ascii: bool) -> Self {
+        Self {
+            ascii,
+            ..Self::default()
+        }
+    }
+
+    /// Feed `bytes` of the stream and return what to replay instead. With
+    /// `flush` the stream ended: a pending repeat count and an incomplete
+    /// last line are let through too.
+    pub(crate) fn feed(&mut self, bytes: &[u8], flush: bool) -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len());
+        self.partial.extend_from_slice(bytes);
+        let complete = match self.partial.iter().rposition(|&b| b == b'\n') {
+            Some(pos) => pos + 1,
+            None => 0,
+        };
+        for line in self.partial[..complete].split_inclusive(|&b| b == b'\n') {
+            if self.last.as_deref() == Some(line) {
+                self.repeats += 1;
+                continue;
+            }
+            push_repeats(&mut out, &mut self.repeats, self.ascii);
+            out.extend_from_slice(line);
+            self.last = Some(line.to_vec());
+        }
+        self.partial.drain(..complete);
+        if flush {
+            push_repeats(&mut out, &mut self.repeats, self.ascii);
+            out.append(&mut self.partial);
+        }
+        out
+    }
+}
+
+fn push_repeats(out: &mut Vec<u8>, repeats: &mut u64, ascii: bool) {
+    let notice = match *repeats {
+        0 => return,
+        1 => format!(
+            "{} last line repeated once\n",
+            crate::glyphs::ellipsis(ascii)
+        ),
+        n => format!(
+            "{} last line repeated {n} times\n",
+            crate::glyphs::ellipsis(ascii)
+        ),
+    };
+    out.extend_from_slice(notice.as_bytes());
+    *repeats = 0;
+}
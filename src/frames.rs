@@ -0,0 +1,181 @@
+//! `<job>.frames`: checksummed records of the combined log (`--checksum-log`).
+//!
+//! On network filesystems (NFS, CIFS) a reader may see a file grow before
+//! the bytes that grew it arrive: a torn tail that reads as zeros or stale
+//! data. With `--checksum-log` the worker frames every chunk it appends to
+//! `.log` with a line
+//!
+//! ```text
+//! <offset> <len> <crc32>
+//! ```
+//!
+//! written after the chunk itself, the CRC-32 in hex. `pend wait` then
+//! replays only log bytes covered by a record whose checksum matches; a
+//! record that does not match yet is read again on the next poll, once the
+//! rest of it has arrived. Bytes that still fail their checksum when the job
+//! has finished and a grace period has passed are skipped with a warning
+//! rather than printed. The file is started afresh whenever `.log` rotates,
+//! like `.idx`.
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// The line framing `bytes`, written at `offset` of the log.
+pub(crate) fn line(offset: u64, bytes: &[u8]) -> String {
+    format!("{offset} {} {:08x}\n", bytes.len(), crc32fast::hash(bytes))
+}
+
+/// Appends the frame of every log chunk.
+pub(crate) struct FrameWriter {
+    path: PathBuf,
+    file: File,
+}
+
+impl FrameWriter {
+    /// Open `path`, continuing an existing file when `append` is set.
+    pub(crate) fn open(path: &Path, append: bool) -> io::Result<Self> {
+        let file = if append {
+            OpenOptions::new().create(true).append(true).open(path)?
+        } else {
+            File::create(path)?
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    /// Frame `bytes`, just written at `offset` of the log.
+    pub(crate) fn record(&mut self, offset: u64, bytes: &[u8]) -> io::Result<()> {
+        self.file.write_all(line(offset, bytes).as_bytes())
+    }
+
+    /// Start over after the log rotated.
+    pub(crate) fn restart(&mut self) -> io::Result<()> {
+        self.file = File::create(&self.path)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    offset: u64,
+    len: u64,
+    crc: u32,
+}
+
+impl Frame {
+    fn end(&self) -> u64 {
+        self.offset + self.len
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.split(' ');
+        let frame = Self {
+            offset: parts.next()?.parse().ok()?,
+            len: parts.next()?.parse().ok()?,
+            crc: u32::from_str_radix(parts.next()?, 16).ok()?,
+        };
+        parts.next().is_none().then_some(frame)
+    }
+}
+
+/// Reads the log of a job submitted with `--checksum-log`, one verified
+/// record at a time.
+pub(crate) struct FrameReader {
+    path: PathBuf,
+    /// How much of `.frames` has been parsed.
+    parsed: u64,
+    /// Records listed but not yet verified against the log, in order.
+    pending: VecDeque<Frame>,
+}
+
+impl FrameReader {
+    /// A reader for `path`, when the job writes it.
+    pub(crate) fn open(path: &Path) -> Option<Self> {
+        path.exists().then(|| Self {
+            path: path.to_path_buf(),
+            parsed: 0,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Whether every record listed so far has been verified.
+    pub(crate) fn settled(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// The verified bytes of `log` from `from` on. They end where the first
+    /// record that is incomplete or fails its checksum starts.
+    pub(crate) fn read(&mut self, log: &Path, from: u64) -> io::Result<Vec<u8>> {
+        self.parse_new()?;
+        while self.pending.front().is_some_and(|f| f.end() <= from) {
+            self.pending.pop_front();
+        }
+        let (Some(first), Some(last)) = (self.pending.front(), self.pending.back()) else {
+            return Ok(Vec::new());
+        };
+        // A record may begin before `from` when replay started mid-record.
+        let base = first.offset.min(from);
+        let mut buffer = Vec::new();
+        let mut file = File::open(log)?;
+        file.seek(SeekFrom::Start(base))?;
+        file.take(last.end() - base).read_to_end(&mut buffer)?;
+
+        let mut verified = from;
+        while let Some(frame) = self.pending.front() {
+            let range = (frame.offset - base) as usize..(frame.end() - base) as usize;
+            let intact = buffer
+                .get(range)
+                .is_some_and(|bytes| crc32fast::hash(bytes) == frame.crc);
+            if !intact {
+                tracing::debug!(?frame, "log record not intact yet");
+                break;
+            }
+            verified = verified.max(frame.end());
+            self.pending.pop_front();
+        }
+        buffer.truncate((verified - base) as usize);
+        buffer.drain(..(from - base) as usize);
+        Ok(buffer)
+    }
+
+    /// Give up on the first record that never verified; returns the log
+    /// offset replay continues from.
+    pub(crate) fn skip(&mut self, from: u64) -> u64 {
+        self.pending
+            .pop_front()
+            .map_or(from, |frame| frame.end().max(from))
+    }
+
+    /// Parse the complete lines appended to `.frames` since the last call.
+    /// A line that does not parse is a torn write too: it is read again later.
+    fn parse_new(&mut self) -> io::Result<()> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        if fs::metadata(&self.path)?.len() < self.parsed {
+            // The log rotated and the file started over.
+            self.parsed = 0;
+            self.pending.clear();
+        }
+        file.seek(SeekFrom::Start(self.parsed))?;
+        let mut text = Vec::new();
+        file.read_to_end(&mut text)?;
+        for line in text.split_inclusive(|&b| b == b'\n') {
+            let Some(frame) = line
+                .strip_suffix(b"\n")
+                .and_then(|line| std::str::from_utf8(line).ok())
+                .and_then(Frame::parse)
+            else {
+                break;
+            };
+            self.pending.push_back(frame);
+            self.parsed += line.len() as u64;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+//! The non-ASCII symbols pend prints, and their ASCII stand-ins.
+//!
+//! Summaries use `✓`/`✗`, en dashes and ellipses, and the progress footer a
+//! braille spinner with a block bar. Terminals and CI log viewers that do
+//! not decode UTF-8 show those as mojibake, so `--ascii` switches to `OK`,
+//! `FAIL`, `-`, `...` and friends. Without the option ASCII is picked when
+//! the locale (`LC_ALL`, else `LC_CTYPE`, else `LANG`) names a character set
+//! other than UTF-8 – `C`, `POSIX`, `en_US.ISO-8859-1`. An unset locale
+//! keeps the Unicode symbols: Windows has none, and neither do many CI
+//! runners whose log viewers render UTF-8 fine.
+//!
+//! The symbols take the `ascii` setting of `config::Config`.
+
+/// Whether the locale's character set is UTF-8.
+pub(crate) fn locale_is_utf8() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        None => true,
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+    }
+}
+
+/// Marks a job that succeeded.
+pub(crate) fn ok(ascii: bool) -> &'static str {
+    if ascii {
+        "OK"
+    } else {
+        "✓"
+    }
+}
+
+/// Marks a job that failed.
+pub(crate) fn fail(ascii: bool) -> &'static str {
+    if ascii {
+        "FAIL"
+    } else {
+        "✗"
+    }
+}
+
+/// Separates a job from what is said about it.
+pub(crate) fn dash(ascii: bool) -> &'static str {
+    if ascii {
+        "-"
+    } else {
+        "–"
+    }
+}
+
+/// Stands for text left out.
+pub(crate) fn ellipsis(ascii: bool) -> &'static str {
+    if ascii {
+        "..."
+    } else {
+        "…"
+    }
+}
+
+/// Frames of the progress spinner.
+pub(crate) fn spinner(ascii: bool) -> &'static [char] {
+    if ascii {
+        &['|', '/', '-', '\\']
+    } else {
+        &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏']
+    }
+}
+
+/// The filled and empty cells of a progress bar.
+pub(crate) fn bar(ascii: bool) -> (char, char) {
+    if ascii {
+        ('#', '.')
+    } else {
+        ('█', '░')
+    }
+}
+
+/// Separates the counts in the progress footer.
+pub(crate) fn dot(ascii: bool) -> &'static str {
+    if ascii {
+        ", "
+    } else {
+        " · "
+    }
+}
+
+/// Separates the counts from the running jobs in the progress footer.
+pub(crate) fn divider(ascii: bool) -> &'static str {
+    if ascii {
+        " | "
+    } else {
+        " │ "
+    }
+}
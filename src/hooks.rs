@@ -0,0 +1,164 @@
+//! Hook scripts the worker runs at points of a job's life.
+//!
+//! Executables in `<jobs root>/hooks/` (or the config file's `hooks_dir`)
+//! named after an event – `pre-start`, `on-retry`, `post-success`,
+//! `post-failure` – run when it happens, optionally with an extension
+//! (`post-failure.sh`, `pre-start.cmd`); several hooks for one event run in
+//! name order. Unix hooks need the executable bit, so `pre-start.sample`
+//! style files are skipped.
+//!
+//! Each hook runs in the job's working directory with stdin, stdout and
+//! stderr detached and these variables set:
+//!
+//! | Variable         | Value |
+//! |------------------|-------|
+//! | `PEND_HOOK`      | The event. |
+//! | `PEND_JOB`       | The job name. |
+//! | `PEND_JOB_LOG`   | Path of the combined `.log`. |
+//! | `PEND_JOB_META`  | Path of the `.json` metadata. |
+//! | `PEND_ATTEMPT`   | The attempt about to start (`pre-start`, `on-retry`) or the last one. |
+//! | `PEND_EXIT_CODE` | Exit code of the last attempt (all events but `pre-start`). |
+//! | `PEND_FAILED_RUNS` | Failed runs in a row of a recurring job, this one included (`post-failure`; see `streak.rs`). |
+//! | `PEND_FAILING_SINCE` | When the first of them ended (`post-failure`). |
+//!
+//! Hooks run synchronously: the job starts once its `pre-start` hooks
+//! returned and counts as finished once its `post-*` hooks did. Their exit
+//! status is only logged; a hook cannot fail the job.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::paths::JobPaths;
+
+/// A point of the job's life hooks can attach to.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Event {
+    PreStart,
+    OnRetry,
+    PostSuccess,
+    PostFailure,
+}
+
+impl Event {
+    fn name(self) -> &'static str {
+        match self {
+            Event::PreStart => "pre-start",
+            Event::OnRetry => "on-retry",
+            Event::PostSuccess => "post-success",
+            Event::PostFailure => "post-failure",
+        }
+    }
+
+    /// `post-success` or `post-failure`, depending on `exit_code`.
+    pub(crate) fn finished(exit_code: i32) -> Self {
+        match exit_code {
+            0 => Event::PostSuccess,
+            _ => Event::PostFailure,
+        }
+    }
+}
+
+/// The hooks directory of the current jobs root.
+pub(crate) struct Hooks {
+    dir: Option<PathBuf>,
+}
+
+impl Hooks {
+    /// The config file's `hooks_dir`, else `<jobs root>/hooks`.
+    pub(crate) fn locate(config: &Config) -> Self {
+        let configured = match crate::config::load() {
+            Ok(file) => file.hooks_dir(),
+            Err(err) => {
+                tracing::warn!(%err, "ignoring the config file");
+                None
+            }
+        };
+        let dir = configured.or_else(|| Some(crate::paths::jobs_root(config).ok()?.join("hooks")));
+        Hooks {
+            dir: dir.filter(|dir| dir.is_dir()),
+        }
+    }
+
+    /// Run the hooks for `event`, waiting for each to finish.
+    pub(crate) fn run(
+        &self,
+        event: Event,
+        job_name: &str,
+        paths: &JobPaths,
+        attempt: u32,
+        exit_code: Option<i32>,
+    ) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let failing = match event {
+            Event::PostFailure => crate::streak::Streak::recorded(paths),
+            _ => None,
+        };
+        for hook in matching(dir, event.name()) {
+            let mut command = Command::new(&hook);
+            command
+                .env("PEND_HOOK", event.name())
+                .env("PEND_JOB", job_name)
+                .env("PEND_JOB_LOG", &paths.log)
+                .env("PEND_JOB_META", &paths.meta)
+                .env("PEND_ATTEMPT", attempt.to_string())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            if let Some(code) = exit_code {
+                command.env("PEND_EXIT_CODE", code.to_string());
+            }
+            if let Some(streak) = &failing {
+                command
+                    .env("PEND_FAILED_RUNS", streak.runs.to_string())
+                    .env("PEND_FAILING_SINCE", &streak.since);
+            }
+            match command.status() {
+                Ok(status) if status.success() => {
+                    tracing::debug!(job = job_name, hook = %hook.display(), "hook ran")
+                }
+                Ok(status) => {
+                    tracing::warn!(job = job_name, hook = %hook.display(), %status, "hook failed")
+                }
+                Err(err) => {
+                    tracing::warn!(job = job_name, hook = %hook.display(), %err, "cannot run hook")
+                }
+            }
+        }
+    }
+}
+
+/// Executables in `dir` called `event` or `event.<ext>`, in name order.
+fn matching(dir: &Path, event: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut hooks: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name == event
+                || name
+                    .strip_prefix(event)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .collect();
+    hooks.sort();
+    hooks
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
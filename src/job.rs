@@ -14,24 +14,374 @@
 //! The heavy lifting – capturing stdout/stderr, log rotation, metadata – is
 //! handled inside `worker.rs`. Keeping the synchronous "front door" logic in
 //! a dedicated module clarifies the control flow.
-use std::io;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
 
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::lock::JobLock;
 use crate::paths::JobPaths;
-use fs2::FileExt;
-use std::fs::OpenOptions;
+use crate::worker::JobOptions;
+
+/// Descriptor of a submitted job, printed by `pend do --json` so scripts
+/// need not reconstruct artifact paths themselves.
+#[derive(Debug, Serialize)]
+pub(crate) struct Submission {
+    pub(crate) job: String,
+    /// Tells this run apart from others of the same name (see `job_id.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) id: Option<String>,
+    pub(crate) dir: PathBuf,
+    /// Artifact paths keyed by extension (`out`, `err`, `log`, `exit`, …).
+    pub(crate) artifacts: BTreeMap<&'static str, PathBuf>,
+    /// `None` when a cached result was reused instead of starting a worker.
+    pub(crate) worker_pid: Option<u32>,
+    pub(crate) started: String,
+    pub(crate) cached: bool,
+}
+
+impl Submission {
+    fn new(
+        config: &Config,
+        job_name: &str,
+        paths: &JobPaths,
+        id: Option<String>,
+        worker_pid: Option<u32>,
+        started: String,
+    ) -> Result<Self> {
+        Ok(Self {
+            job: job_name.to_string(),
+            id,
+            dir: crate::paths::jobs_root(config)?,
+            artifacts: paths
+                .named()
+                .into_iter()
+                .map(|(ext, p)| (ext, p.clone()))
+                .collect(),
+            worker_pid,
+            started,
+            cached: worker_pid.is_none(),
+        })
+    }
+}
 
 /// Public helper equivalent to `pend do <job> <cmd …>`.
 pub(crate) fn do_job(
+    config: &Config,
     job_name: &str,
-    cmd: &[String],
-    timeout: Option<u64>,
-    retries: Option<u32>,
-) -> io::Result<()> {
+    cmd: &[OsString],
+    options: &JobOptions,
+) -> Result<Submission> {
+    validate(config, job_name, cmd, options)?;
+    let paths = JobPaths::new(config, job_name)?;
+
+    // ------------------------------------------------------------------
+    // Advisory lock to guard against concurrent `pend do` invocations for
+    // the *same* job name. We create (or open) a lightweight `.lock` file
+    // next to the other artifacts and attempt to obtain a non-blocking
+    // exclusive lock (see `lock.rs` for the network-filesystem fallback).
+    // If another process already holds the lock we surface a user-friendly
+    // error immediately instead of racing to create the artifact files
+    // only to fail later on.
+    // ------------------------------------------------------------------
+
+    let Some(lock) = JobLock::try_acquire(&paths.lock)? else {
+        return Err(PendError::AlreadyRunning(job_name.to_string()));
+    };
+
+    // `--cache`: a recent successful run of the very same command stands in
+    // for a new one; its artifacts stay untouched for `pend wait`.
+    if let Some(ttl) = options.cache {
+        let key = crate::cache::cache_key(cmd, options)?;
+        if let Some(age) = crate::cache::cached_run_age(&paths, &key, ttl) {
+            eprintln!("pend: reusing cached result of '{job_name}' from {age}s ago");
+            drop(lock);
+            let _ = std::fs::remove_file(&paths.lock);
+            let started = std::fs::read(&paths.meta)
+                .ok()
+                .and_then(|b| crate::version::parse_meta(&b).ok())
+                .and_then(|m| m.get("started")?.as_str().map(String::from))
+                .unwrap_or_default();
+            let id = crate::job_id::of_meta(&paths);
+            return Submission::new(config, job_name, &paths, id, None, started);
+        }
+    }
+
+    // `--max-jobs`: hold the count until our worker runs, so a concurrent
+    // `pend do` sees it.
+    let admission = crate::max_jobs::admit(config, job_name, options)?;
+
+    // At this point we exclusively own the advisory lock which guarantees
+    // that **no other** worker process for the same job name is currently
+    // running. Any pre-existing artifact files therefore stem from a
+    // *previous* finished run and can be safely removed so the new job starts
+    // with a clean slate. This makes workflows like
+    //
+    //   pend do build … && pend wait build && pend do build …
+    //
+    // convenient because users do not have to invoke `pend clean` in between.
+
+    // A recurring job carries on the failure streak of its previous run.
+    let failing = crate::streak::Streak::recorded(&paths);
+    if paths.any_exist() {
+        tracing::debug!(job = job_name, "removing artifacts of the previous run");
+        // Best-effort deletion – ignore individual failures and surface an
+        // error only when **all** attempts fail which usually indicates a
+        // more fundamental permissions problem.
+        let mut last_err: Option<std::io::Error> = None;
+
+        let rotated = paths.rotated();
+        for p in paths.all().into_iter().chain(&rotated) {
+            // The lock file is ours – keep it (and the lock) alive.
+            if p == &paths.lock {
+                continue;
+            }
+            if p.exists() {
+                if let Err(e) = std::fs::remove_file(p) {
+                    // Record but continue trying other paths so we clean up
+                    // as much as possible.
+                    last_err = Some(e);
+                }
+            }
+        }
+
+        if let Some(err) = last_err {
+            return Err(err.into());
+        }
+    }
+
+    // Hand the lock over to the worker so the job never appears unlocked
+    // (and therefore free for a concurrent `pend do`) between our exit and
+    // the worker start.
+    let handoff = lock.handoff_token();
+    tracing::debug!(
+        job = job_name,
+        handoff = handoff.is_some(),
+        "spawning worker"
+    );
+    let started = chrono::Utc::now().to_rfc3339();
+    let id = crate::job_id::generate(job_name);
+    let worker_pid = super::worker::spawn_worker(
+        config,
+        job_name,
+        cmd,
+        options,
+        &id,
+        failing.as_ref(),
+        handoff.as_deref(),
+    )?;
+    lock.hand_over(worker_pid)?;
+    drop(admission);
+    Submission::new(
+        config,
+        job_name,
+        &paths,
+        Some(id),
+        Some(worker_pid),
+        started,
+    )
+}
+
+/// A name for `pend do --auto-name`: the program's file name reduced to the
+/// characters job names allow, numbered `-1`, `-2`, … up to the first one
+/// no job uses yet.
+pub(crate) fn auto_name(config: &Config, program: &OsStr) -> Result<String> {
+    let file_name = std::path::Path::new(program)
+        .file_name()
+        .unwrap_or(program)
+        .to_string_lossy();
+    let mut stem = String::new();
+    for c in file_name.chars().take(80) {
+        let c = if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+            c
+        } else {
+            '-'
+        };
+        // No leading or repeated dots.
+        if c == '.' && (stem.is_empty() || stem.ends_with('.')) {
+            continue;
+        }
+        stem.push(c);
+    }
+    if stem.is_empty() {
+        stem.push_str("job");
+    }
+    for n in 1.. {
+        let name = format!("{stem}-{n}");
+        if !JobPaths::new(config, &name)?.any_exist() {
+            return Ok(name);
+        }
+    }
+    unreachable!("some number is free")
+}
+
+/// Entry point for `pend do --check`: run the submission checks plus those
+/// of what the worker would find – the program on `PATH`, the working
+/// directory – without starting anything. Returns the resolved program.
+pub(crate) fn check_job(
+    config: &Config,
+    job_name: &str,
+    cmd: &[OsString],
+    options: &JobOptions,
+) -> Result<PathBuf> {
+    validate(config, job_name, cmd, options)?;
+    JobPaths::new(config, job_name)?;
+
+    let mut problems = Vec::new();
+    if let Err(err) = std::env::current_dir() {
+        problems.push(format!("the working directory is unusable: {err}"));
+    }
+    let program: &OsStr = match (&options.container, options.shell) {
+        (Some(container), _) => container.engine.program().as_ref(),
+        (None, true) if cfg!(windows) => "cmd".as_ref(),
+        (None, true) => "sh".as_ref(),
+        (None, false) => &cmd[0],
+    };
+    let path_var = options
+        .env
+        .iter()
+        .rev()
+        .find(|(name, _)| name == "PATH")
+        .map(|(_, value)| value.into())
+        .or_else(|| std::env::var_os("PATH"))
+        .unwrap_or_default();
+    let resolved = find_program(program, &path_var);
+    if resolved.is_none() {
+        let program = program.to_string_lossy();
+        problems.push(match program.contains(['/', '\\']) {
+            true => format!("'{program}' does not exist or is not executable"),
+            false => format!("'{program}' was not found on PATH"),
+        });
+    }
+
+    match resolved {
+        Some(resolved) if problems.is_empty() => Ok(resolved),
+        _ => Err(PendError::Usage(format!(
+            "job '{job_name}' would fail:\n  {}",
+            problems.join("\n  ")
+        ))),
+    }
+}
+
+/// Locate `program` the way spawning it would: as a path when it contains
+/// a separator, else in the directories of `path_var` (trying the
+/// `PATHEXT` extensions on Windows).
+fn find_program(program: &OsStr, path_var: &OsStr) -> Option<PathBuf> {
+    if program.to_string_lossy().contains(['/', '\\']) {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+    let extensions: Vec<String> = match cfg!(windows) {
+        true => std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into())
+            .split(';')
+            .map(String::from)
+            .chain([String::new()])
+            .collect(),
+        false => vec![String::new()],
+    };
+    std::env::split_paths(path_var).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| {
+                let mut name = program.to_os_string();
+                name.push(ext);
+                dir.join(name)
+            })
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+}
+
+/// Checks of the job name and options that need no artifacts.
+fn validate(config: &Config, job_name: &str, cmd: &[OsString], options: &JobOptions) -> Result<()> {
+    validate_name(job_name)?;
+
+    if cmd.is_empty() {
+        return Err(PendError::Usage("command cannot be empty".into()));
+    }
+
+    // Fail fast on an unknown `--user` instead of letting the worker record
+    // a spawn error.
+    if let Some(user) = &options.user {
+        #[cfg(unix)]
+        crate::process::lookup_user(user)?;
+        #[cfg(not(unix))]
+        return Err(PendError::Usage(format!(
+            "--user {user}: running as another user is only supported on Unix"
+        )));
+    }
+
+    if let Some(target) = options.log_to {
+        target.check()?;
+    }
+    if let Some(path) = &options.mirror_fifo {
+        crate::mirror::prepare(path)?;
+    }
+    if options
+        .retry_cmd
+        .as_ref()
+        .is_some_and(|line| line.trim().is_empty())
+    {
+        return Err(PendError::Usage("--retry-cmd cannot be empty".into()));
+    }
+    let capture = options.capture.unwrap_or_default();
+    for (flag, limit, captured, other) in [
+        (
+            "--max-out-size",
+            options.max_out_size,
+            capture.stdout(),
+            "stderr",
+        ),
+        (
+            "--max-err-size",
+            options.max_err_size,
+            capture.stderr(),
+            "stdout",
+        ),
+    ] {
+        if limit.is_some() && !captured {
+            return Err(PendError::Usage(format!(
+                "{flag} has no effect with --capture {other}"
+            )));
+        }
+    }
+    if options.max_jobs == Some(0) {
+        return Err(PendError::Usage("max_jobs must be at least 1".into()));
+    }
+
+    // `--after` may only name jobs that have been submitted already;
+    // otherwise the worker would wait for something that never happens.
+    for dep in &options.after {
+        if dep.job == job_name {
+            return Err(PendError::Usage(format!(
+                "job '{job_name}' cannot run after itself"
+            )));
+        }
+        if !JobPaths::new(config, &dep.job)?.any_exist() {
+            return Err(PendError::NotFound(dep.job.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Checks of the job name alone.
+pub(crate) fn validate_name(job_name: &str) -> Result<()> {
     if job_name.trim().is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "job name cannot be empty",
-        ));
+        return Err(PendError::InvalidName("job name cannot be empty"));
     }
 
     // ------------------------------------------------------------------
@@ -48,24 +398,21 @@ pub(crate) fn do_job(
 
     // Quick path-separator rejection prevents directory traversal.
     if job_name.contains('/') || job_name.contains('\\') {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
+        return Err(PendError::InvalidName(
             "job name must not contain path separators",
         ));
     }
 
     // Length limit.
     if job_name.chars().count() > 100 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
+        return Err(PendError::InvalidName(
             "job name must not exceed 100 characters",
         ));
     }
 
     // No leading dot or repeated dots.
     if job_name.starts_with('.') || job_name.contains("..") {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
+        return Err(PendError::InvalidName(
             "job name must not start with a dot or contain repeated dots",
         ));
     }
@@ -80,8 +427,7 @@ pub(crate) fn do_job(
             !c.is_control()
         }
     }) {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
+        return Err(PendError::InvalidName(
             "job name contains invalid characters",
         ));
     }
@@ -90,84 +436,9 @@ pub(crate) fn do_job(
     // same canonical representation.
     use unicode_normalization::UnicodeNormalization;
     if job_name.nfc().collect::<String>() != job_name {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
+        return Err(PendError::InvalidName(
             "job name must be Unicode NFC normalised",
         ));
     }
-
-    if cmd.is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "command cannot be empty",
-        ));
-    }
-
-    let paths = JobPaths::new(job_name)?;
-
-    // ------------------------------------------------------------------
-    // Advisory lock to guard against concurrent `pend do` invocations for
-    // the *same* job name. We create (or open) a lightweight `.lock` file
-    // next to the other artifacts and attempt to obtain a non-blocking
-    // exclusive lock. If another process already holds the lock we surface
-    // a user-friendly error immediately instead of racing to create the
-    // artifact files only to fail later on.
-    // ------------------------------------------------------------------
-
-    let lock_file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(false)
-        .open(&paths.lock)?;
-
-    if let Err(err) = lock_file.try_lock_exclusive() {
-        if err.kind() == io::ErrorKind::WouldBlock {
-            return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!("job '{job_name}' is already running"),
-            ));
-        } else {
-            return Err(err);
-        }
-    }
-
-    // At this point we exclusively own the advisory lock which guarantees
-    // that **no other** worker process for the same job name is currently
-    // running. Any pre-existing artifact files therefore stem from a
-    // *previous* finished run and can be safely removed so the new job starts
-    // with a clean slate. This makes workflows like
-    //
-    //   pend do build … && pend wait build && pend do build …
-    //
-    // convenient because users do not have to invoke `pend clean` in between.
-
-    if paths.any_exist() {
-        // Best-effort deletion – ignore individual failures and surface an
-        // error only when **all** attempts fail which usually indicates a
-        // more fundamental permissions problem.
-        let mut last_err: Option<io::Error> = None;
-
-        for p in [
-            &paths.out,
-            &paths.err,
-            &paths.exit,
-            &paths.meta,
-            &paths.log,
-            &paths.signal,
-        ] {
-            if p.exists() {
-                if let Err(e) = std::fs::remove_file(p) {
-                    // Record but continue trying other paths so we clean up
-                    // as much as possible.
-                    last_err = Some(e);
-                }
-            }
-        }
-
-        if let Some(err) = last_err {
-            return Err(err);
-        }
-    }
-
-    super::worker::spawn_worker(job_name, cmd, timeout, retries)
+    Ok(())
 }
@@ -0,0 +1,128 @@
+//! Job IDs: telling one run of a job name from another.
+//!
+//! Names are reused – every `pend do build` replaces the previous `build` –
+//! so a name alone cannot tell whether the artifacts on disk all belong to
+//! the same run. `pend do` therefore gives every submission a random ID in
+//! UUID form, hands it to the worker in `$PEND_JOB_ID` and prints it with
+//! `--json`. The worker records it as `id` in `.json`, as `id=` in
+//! `.result`, and in a first line `# job <id>` of `.idx`, the index of the
+//! output. When these disagree – say a copy of an old job's files landed
+//! in the jobs directory after a `--dir` mix-up – `pend wait` and `pend
+//! status` warn that the output may be stale instead of replaying it
+//! silently. Artifacts of releases before IDs carry none and are trusted.
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader};
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+
+use crate::paths::JobPaths;
+
+/// Variable carrying the ID from `pend do` to the worker.
+const ENV: &str = "PEND_JOB_ID";
+
+/// Start of the header line of `.idx`.
+const IDX_HEADER: &str = "# job ";
+
+/// A new ID for a submission of `job_name`.
+pub(crate) fn generate(job_name: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(job_name.as_bytes());
+    hasher.update(std::process::id().to_le_bytes());
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.update(now.as_nanos().to_le_bytes());
+    // The standard library seeds every `RandomState` from the OS.
+    let mut random = RandomState::new().build_hasher();
+    random.write(job_name.as_bytes());
+    hasher.update(random.finish().to_le_bytes());
+    let mut bytes: [u8; 16] = hasher.finalize()[..16].try_into().expect("16 bytes");
+    // Version 4 (random), RFC 4122 variant.
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Hand `id` to the worker started by `command`.
+pub(crate) fn export(command: &mut Command, id: &str) {
+    command.env(ENV, id);
+}
+
+/// The ID `pend do` chose for the worker's job; a new one when it chose
+/// none.
+pub(crate) fn from_env(job_name: &str) -> String {
+    std::env::var(ENV)
+        .ok()
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| generate(job_name))
+}
+
+/// The header line `.idx` starts with.
+pub(crate) fn idx_header(id: &str) -> String {
+    format!("{IDX_HEADER}{id}\n")
+}
+
+/// Whether `line` of `.idx` is a header rather than a range.
+pub(crate) fn is_header(line: &str) -> bool {
+    line.starts_with('#')
+}
+
+/// The ID recorded in the job's `.json`.
+pub(crate) fn of_meta(paths: &JobPaths) -> Option<String> {
+    let meta = crate::version::parse_meta(&fs::read(&paths.meta).ok()?).ok()?;
+    Some(meta.get("id")?.as_str()?.to_string())
+}
+
+fn of_idx(paths: &JobPaths) -> Option<String> {
+    let mut line = String::new();
+    BufReader::new(fs::File::open(&paths.idx).ok()?)
+        .read_line(&mut line)
+        .ok()?;
+    // A header still being written is no evidence either way.
+    let id = line.strip_prefix(IDX_HEADER)?.strip_suffix('\n')?;
+    Some(id.trim_end().to_string())
+}
+
+/// Why the job's artifacts look like they come from different runs, if
+/// they do.
+pub(crate) fn mismatch(paths: &JobPaths) -> Option<String> {
+    let id = of_meta(paths)?;
+    [
+        ("log index", of_idx(paths)),
+        (
+            "result",
+            crate::quick::QuickStatus::read(&paths.result).and_then(|q| q.id),
+        ),
+    ]
+    .into_iter()
+    .find_map(|(what, other)| {
+        let other = other.filter(|other| *other != id)?;
+        Some(format!("its {what} is from job {other}, not {id}"))
+    })
+}
+
+/// Warn on stderr when [`mismatch`] finds the artifacts of `job_name` mixed.
+pub(crate) fn warn_if_mixed(job_name: &str, paths: &JobPaths) {
+    if let Some(why) = mismatch(paths) {
+        warn(job_name, &why);
+    }
+}
+
+/// Warn on stderr that `job_name` mixes runs, `why` coming from [`mismatch`].
+pub(crate) fn warn(job_name: &str, why: &str) {
+    eprintln!(
+        "pend: warning: '{job_name}' mixes artifacts of different runs ({why}); \
+         its output may be stale"
+    );
+}
@@ -0,0 +1,46 @@
+//! Implementation of `pend kill`.
+//!
+//! Killing is cooperative with the worker. When the worker's control socket
+//! is reachable we simply ask it to stop. Otherwise we drop a `<job>.cancel`
+//! marker so the worker skips any remaining retries, then terminate the
+//! process tree of the running attempt ourselves (its process group on Unix,
+//! its Job Object on Windows). Either way the worker observes the exit like
+//! any other and writes the usual `.exit` / `.signal` artifacts, so
+//! `pend wait` keeps working unchanged.
+use std::fs;
+use std::io;
+
+use crate::config::Config;
+use crate::paths::JobPaths;
+use crate::status::{job_status, State};
+
+/// Stop the running job `job_name`.
+pub(crate) fn kill_job(config: &Config, job_name: &str) -> io::Result<()> {
+    let status = job_status(config, job_name)?;
+    if status.state != State::Running {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("job '{job_name}' is not running"),
+        ));
+    }
+
+    let paths = JobPaths::new(config, job_name)?;
+    if crate::control::request_kill(&paths) {
+        return Ok(());
+    }
+
+    fs::write(&paths.cancel, b"")?;
+
+    // On Windows the attempt's Job Object reaches the whole process tree.
+    #[cfg(windows)]
+    if crate::process::JobObject::open(&paths).is_some_and(|job| job.terminate(1)) {
+        return Ok(());
+    }
+
+    // The worker may not have spawned the command yet; in that case it picks
+    // up the marker itself before (or right after) spawning.
+    if let Some(pid) = status.pid {
+        crate::process::terminate_process_tree(pid);
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+//! The jobs most recently submitted (`pend last`, `%last`, `%N`).
+//!
+//! `pend do` records the name of every job it submits twice: for the user
+//! in `<root>/.pend-last` and for the terminal session in
+//! `<root>/.pend-last.<pid>`. The session is keyed by `$PEND_SHELL_PID` when
+//! set (the functions of `pend shell-init` export it), otherwise by the
+//! session leader on Unix – normally the terminal's shell. Each file lists
+//! the latest [`HISTORY`] names, newest first. Job names cannot start with a
+//! dot, so the files never pass for artifacts. Files of sessions that are
+//! gone are removed whenever a new one is written, and `pend clean` forgets
+//! the jobs it removes.
+//!
+//! Commands taking job names accept references in the style of shell job
+//! control: `%last` (or `%%`, `%+`) is the latest job, `%-` the one before
+//! and `%N` the N-th latest. They resolve against the session's history,
+//! or the user's when the session has submitted nothing.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+
+/// Variable naming the PID of the shell a job is submitted from.
+pub(crate) const SHELL_PID_ENV: &str = "PEND_SHELL_PID";
+
+/// Name of the user's file; the sessions' files add `.<pid>`.
+const USER_FILE: &str = ".pend-last";
+
+/// Names each file remembers.
+const HISTORY: usize = 10;
+
+/// PID identifying the terminal session of the current command, if known.
+fn session() -> Option<u32> {
+    if let Ok(pid) = std::env::var(SHELL_PID_ENV) {
+        return pid.trim().parse().ok();
+    }
+    #[cfg(unix)]
+    {
+        // Safety: getsid(0) only queries the calling process.
+        let sid = unsafe { libc::getsid(0) };
+        (sid > 0).then_some(sid as u32)
+    }
+    #[cfg(not(unix))]
+    None
+}
+
+fn session_file(root: &Path, pid: u32) -> PathBuf {
+    root.join(format!("{USER_FILE}.{pid}"))
+}
+
+/// Names in `path`, newest first.
+fn history(path: &Path) -> io::Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+fn write_history(path: &Path, names: &[String]) -> io::Result<()> {
+    if names.is_empty() {
+        return match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        };
+    }
+    let text: String = names.iter().map(|name| format!("{name}\n")).collect();
+    crate::paths::replace_file(path, text.as_bytes())
+}
+
+/// Put `job_name` at the top of the history in `path`.
+fn push(path: &Path, job_name: &str) -> io::Result<()> {
+    let mut names = history(path)?;
+    names.retain(|name| name != job_name);
+    names.insert(0, job_name.to_string());
+    names.truncate(HISTORY);
+    write_history(path, &names)
+}
+
+/// Remember `job_name` as the latest job of the user and the session. Best
+/// effort: failing to record it is no reason to fail the submission.
+pub(crate) fn record(config: &Config, job_name: &str) {
+    let Ok(root) = crate::paths::jobs_root(config) else {
+        return;
+    };
+    let session = session();
+    let files =
+        std::iter::once(root.join(USER_FILE)).chain(session.map(|pid| session_file(&root, pid)));
+    for path in files {
+        if let Err(err) = push(&path, job_name) {
+            tracing::debug!(%err, path = %path.display(), "cannot record the last job");
+        }
+    }
+    if let Some(pid) = session {
+        prune(&root, pid);
+    }
+}
+
+/// The latest jobs of the session, or of the user when the session has
+/// none, newest first.
+fn recent(config: &Config) -> Result<Vec<String>> {
+    let root = crate::paths::jobs_root(config)?;
+    if let Some(pid) = session() {
+        let names = history(&session_file(&root, pid))?;
+        if !names.is_empty() {
+            return Ok(names);
+        }
+    }
+    Ok(history(&root.join(USER_FILE))?)
+}
+
+/// The job most recently submitted.
+pub(crate) fn last_job(config: &Config) -> Result<String> {
+    resolve(config, "%last")
+}
+
+/// `name` itself, or the job a `%…` reference stands for.
+pub(crate) fn resolve(config: &Config, name: &str) -> Result<String> {
+    let Some(reference) = name.strip_prefix('%') else {
+        return Ok(name.to_string());
+    };
+    let n = match reference {
+        "last" | "%" | "+" => 1,
+        "-" => 2,
+        n => n.parse::<usize>().ok().filter(|&n| n > 0).ok_or_else(|| {
+            PendError::Usage(format!(
+                "unknown job reference '{name}' (use %last, %-, or %N for the N-th latest job)"
+            ))
+        })?,
+    };
+    recent(config)?.into_iter().nth(n - 1).ok_or_else(|| {
+        PendError::Usage(if n == 1 {
+            "no job has been submitted yet".to_string()
+        } else {
+            format!("'{name}' does not refer to a job: fewer than {n} were submitted")
+        })
+    })
+}
+
+/// [`resolve`] every name of `names`.
+pub(crate) fn resolve_all(config: &Config, names: Vec<String>) -> Result<Vec<String>> {
+    names.iter().map(|name| resolve(config, name)).collect()
+}
+
+/// Delete the files of sessions that have ended.
+fn prune(root: &Path, current: u32) {
+    for (pid, path) in session_files(root) {
+        if pid != current && !crate::process::process_is_alive(pid) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Drop `job_name` from every history once it was cleaned.
+pub(crate) fn forget(root: &Path, job_name: &str) {
+    let files = std::iter::once(root.join(USER_FILE))
+        .chain(session_files(root).into_iter().map(|(_, path)| path));
+    for path in files {
+        let Ok(mut names) = history(&path) else {
+            continue;
+        };
+        let before = names.len();
+        names.retain(|name| name != job_name);
+        if names.len() != before {
+            let _ = write_history(&path, &names);
+        }
+    }
+}
+
+/// Forget every history (`pend clean --all`).
+pub(crate) fn forget_all(root: &Path) {
+    let _ = fs::remove_file(root.join(USER_FILE));
+    for (_, path) in session_files(root) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// The sessions' files in `root` with the PID they belong to.
+fn session_files(root: &Path) -> Vec<(u32, PathBuf)> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let prefix = format!("{USER_FILE}.");
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .parse()
+                .ok()?;
+            Some((pid, entry.path()))
+        })
+        .collect()
+}
@@ -0,0 +1,435 @@
+//! Per-job advisory locking with a network-filesystem-safe fallback.
+//!
+//! The default strategy uses `fs2` advisory locks (`flock` / `LockFileEx`) on
+//! `<job>.lock`. Those are unreliable on NFS and SMB mounts, which people end
+//! up using as soon as `PEND_DIR` points at a shared location. For such
+//! directories we switch to an *atomic lockfile*: the lock is held by whoever
+//! manages to create `<job>.lock` with `O_EXCL`, the file records the owner's
+//! host and PID, and a heartbeat thread keeps its mtime fresh so a crashed
+//! owner's lock can be recognised as stale and taken over.
+//!
+//! The strategy is picked automatically per jobs directory; the environment
+//! variable `PEND_LOCK` (`flock`, `lockfile`, `auto`) overrides detection.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use fs2::FileExt;
+
+/// How often a lockfile owner refreshes its heartbeat.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A lockfile whose heartbeat is older than this is considered abandoned.
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockStrategy {
+    /// OS advisory lock on the lock file.
+    Flock,
+    /// Atomic `O_EXCL` lockfile with owner info and heartbeat.
+    LockFile,
+}
+
+/// Pick the locking strategy for lock files inside `dir`.
+pub(crate) fn strategy_for(dir: &Path) -> LockStrategy {
+    match std::env::var("PEND_LOCK").as_deref() {
+        Ok("flock") => LockStrategy::Flock,
+        Ok("lockfile") => LockStrategy::LockFile,
+        _ => {
+            if is_network_fs(dir) {
+                LockStrategy::LockFile
+            } else {
+                LockStrategy::Flock
+            }
+        }
+    }
+}
+
+/// An acquired job lock. Released when dropped.
+pub(crate) struct JobLock {
+    inner: Inner,
+}
+
+enum Inner {
+    Flock(#[allow(dead_code)] File),
+    LockFile {
+        path: PathBuf,
+        owner: String,
+        /// Set once the heartbeat must stop. It refreshes the file while
+        /// holding this, so once set it writes no more.
+        stop: Arc<Mutex<bool>>,
+    },
+}
+
+impl JobLock {
+    /// Try to take the lock at `path` without blocking. Returns `Ok(None)`
+    /// when somebody else holds it.
+    pub(crate) fn try_acquire(path: &Path) -> io::Result<Option<Self>> {
+        let strategy = strategy_for(parent_dir(path));
+        tracing::trace!(path = %path.display(), ?strategy, "acquiring lock");
+        match strategy {
+            LockStrategy::Flock => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .read(true)
+                    .write(true)
+                    .truncate(false)
+                    .open(path)?;
+                match file.try_lock_exclusive() {
+                    Ok(()) => {
+                        if let Some(pid) = handed_to(&file) {
+                            if reserved(pid) {
+                                tracing::debug!(
+                                    path = %path.display(),
+                                    pid,
+                                    "lock is handed to a starting worker"
+                                );
+                                return Ok(None);
+                            }
+                            file.set_len(0)?;
+                        }
+                        Ok(Some(Self {
+                            inner: Inner::Flock(file),
+                        }))
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        tracing::debug!(path = %path.display(), "lock is held elsewhere");
+                        Ok(None)
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+            LockStrategy::LockFile => try_acquire_lockfile(path),
+        }
+    }
+
+    /// The token a child must present to [`JobLock::adopt`] this lock, for
+    /// strategies that hand over the lock itself.
+    pub(crate) fn handoff_token(&self) -> Option<String> {
+        match &self.inner {
+            Inner::Flock(_) => None,
+            Inner::LockFile { owner, .. } => Some(owner.clone()),
+        }
+    }
+
+    /// Give the lock to the worker `pid` started with [`Self::handoff_token`].
+    /// An advisory lock ends with this process, so the file records
+    /// `handoff <pid>` and [`JobLock::try_acquire`] leaves the lock to that
+    /// process while it lives. A lockfile is left in place for it to adopt,
+    /// naming the worker as its owner: it is not stale once we exit. Either
+    /// way a concurrent `pend do` cannot slip in before the worker starts.
+    pub(crate) fn hand_over(self, pid: u32) -> io::Result<()> {
+        match &self.inner {
+            Inner::Flock(file) => {
+                let mut file = file;
+                file.seek(SeekFrom::Start(0))?;
+                file.set_len(0)?;
+                writeln!(file, "{HANDOFF} {pid}")?;
+            }
+            Inner::LockFile { path, owner, stop } => {
+                let mut stopped = stop.lock().unwrap_or_else(|e| e.into_inner());
+                *stopped = true;
+                // Unless the worker was quicker and adopted it already.
+                if read_owner(path).as_deref() == Some(owner.as_str()) {
+                    let worker = format!("{} {pid}", hostname());
+                    crate::paths::replace_file(path, format!("{worker}\n").as_bytes())?;
+                }
+                drop(stopped);
+                // Skip `Drop`, which would delete the lockfile.
+                std::mem::forget(self);
+            }
+        }
+        Ok(())
+    }
+
+    /// Take over a lockfile handed off by the parent through `token`: it
+    /// names the parent, or us once the parent has handed it over. Falls
+    /// back to a regular [`JobLock::try_acquire`] when the lockfile is not
+    /// (or no longer) the one handed to us.
+    pub(crate) fn adopt(path: &Path, token: Option<&str>) -> io::Result<Option<Self>> {
+        if let Some(token) = token {
+            let owner = format!("{} {}", hostname(), std::process::id());
+            let current = read_owner(path);
+            if strategy_for(parent_dir(path)) == LockStrategy::LockFile
+                && (current.as_deref() == Some(token) || current.as_deref() == Some(&owner))
+            {
+                tracing::debug!(path = %path.display(), "adopting handed-off lockfile");
+                crate::paths::replace_file(path, format!("{owner}\n").as_bytes())?;
+                let stop = Arc::new(Mutex::new(false));
+                spawn_heartbeat(path.to_path_buf(), owner.clone(), Arc::clone(&stop));
+                return Ok(Some(Self {
+                    inner: Inner::LockFile {
+                        path: path.to_path_buf(),
+                        owner,
+                        stop,
+                    },
+                }));
+            }
+        }
+        Self::try_acquire(path)
+    }
+}
+
+impl Drop for JobLock {
+    fn drop(&mut self) {
+        if let Inner::LockFile { path, owner, stop } = &self.inner {
+            *stop.lock().unwrap_or_else(|e| e.into_inner()) = true;
+            // Only remove the file if it is still ours – a peer may have
+            // (wrongly) declared us stale and taken over in the meantime.
+            if read_owner(path).as_deref() == Some(owner.as_str()) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Whether the lock at `path` is currently held by a live owner. Used by
+/// `pend clean` to skip running jobs.
+pub(crate) fn is_held(path: &Path) -> bool {
+    if !path.exists() {
+        return false;
+    }
+    match strategy_for(parent_dir(path)) {
+        LockStrategy::Flock => match OpenOptions::new().read(true).open(path) {
+            Ok(file) => {
+                file.try_lock_exclusive().is_err() || handed_to(&file).is_some_and(reserved)
+            }
+            Err(_) => false,
+        },
+        LockStrategy::LockFile => !is_stale(path),
+    }
+}
+
+/// Marker an advisory lock file carries while its lock passes to a worker.
+const HANDOFF: &str = "handoff";
+
+/// The worker an advisory lock file was handed to, if any.
+fn handed_to(mut file: &File) -> Option<u32> {
+    let mut text = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut text).ok()?;
+    text.trim().strip_prefix(HANDOFF)?.trim().parse().ok()
+}
+
+/// Whether a lock handed to `pid` is still reserved for it: the worker may
+/// not have taken it yet. The worker itself takes it over.
+fn reserved(pid: u32) -> bool {
+    pid != std::process::id() && crate::process::process_is_alive(pid)
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new("."))
+}
+
+// -------------------------------------------------------------------------
+// Atomic lockfile strategy
+// -------------------------------------------------------------------------
+
+fn try_acquire_lockfile(path: &Path) -> io::Result<Option<JobLock>> {
+    let owner = format!("{} {}", hostname(), std::process::id());
+
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                writeln!(file, "{owner}")?;
+                file.sync_all()?;
+
+                let stop = Arc::new(Mutex::new(false));
+                spawn_heartbeat(path.to_path_buf(), owner.clone(), Arc::clone(&stop));
+                return Ok(Some(JobLock {
+                    inner: Inner::LockFile {
+                        path: path.to_path_buf(),
+                        owner,
+                        stop,
+                    },
+                }));
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                let Some(stale) = stale_owner(path) else {
+                    tracing::debug!(path = %path.display(), "lockfile is held elsewhere");
+                    return Ok(None);
+                };
+                tracing::debug!(path = %path.display(), "taking over stale lockfile");
+                if !move_aside(path, &stale) {
+                    return Ok(None);
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(None)
+}
+
+/// Move the stale lockfile at `path`, owned by `stale`, out of the way so
+/// it can be raced for once more. Removing it in place is not safe: a
+/// contender that saw the same stale lock and already replaced it would
+/// lose its fresh lock. A rename is atomic, so the file moved aside is
+/// checked instead, and put back when it turns out not to be the stale one.
+fn move_aside(path: &Path, stale: &str) -> bool {
+    let mut aside = path.to_path_buf().into_os_string();
+    aside.push(format!(".stale.{}", std::process::id()));
+    let aside = PathBuf::from(aside);
+    if fs::rename(path, &aside).is_err() {
+        // Gone already: somebody else moved it aside.
+        return !path.exists();
+    }
+    let moved = read_owner(&aside);
+    if moved.as_deref() == Some(stale) {
+        let _ = fs::remove_file(&aside);
+        return true;
+    }
+    tracing::debug!(path = %path.display(), "lockfile was taken over meanwhile");
+    // A hard link never replaces a lockfile created since.
+    let _ = fs::hard_link(&aside, path);
+    let _ = fs::remove_file(&aside);
+    false
+}
+
+/// Periodically refresh the lockfile so peers can tell we are alive. The
+/// file is replaced, never rewritten in place: a reader must not see it
+/// empty.
+fn spawn_heartbeat(path: PathBuf, owner: String, stop: Arc<Mutex<bool>>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(HEARTBEAT_INTERVAL);
+        let stopped = stop.lock().unwrap_or_else(|e| e.into_inner());
+        if *stopped {
+            break;
+        }
+        if read_owner(&path).as_deref() != Some(owner.as_str()) {
+            break;
+        }
+        let _ = crate::paths::replace_file(&path, format!("{owner}\n").as_bytes());
+    });
+}
+
+fn read_owner(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// A lockfile is stale when its owner is known to be dead (same host, PID
+/// gone) or its heartbeat has not been refreshed for [`STALE_AFTER`].
+fn is_stale(path: &Path) -> bool {
+    stale_owner(path).is_some()
+}
+
+/// The content of the lockfile at `path` if it is stale.
+fn stale_owner(path: &Path) -> Option<String> {
+    let owner = read_owner(path)?;
+    // Empty content is a lockfile still being written; foreign content
+    // (e.g. a leftover flock-style lock file) has no PID to check. Both go
+    // by their age.
+    if let Some((host, pid)) = owner.rsplit_once(' ') {
+        if host == hostname() {
+            if let Ok(pid) = pid.parse::<u32>() {
+                return (!crate::process::process_is_alive(pid)).then_some(owner);
+            }
+        }
+    }
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age > STALE_AFTER)
+        .then_some(owner)
+}
+
+pub(crate) fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        // Safety: buffer pointer and length are valid for the call.
+        let rc = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+        if rc == 0 {
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            return String::from_utf8_lossy(&buf[..len]).into_owned();
+        }
+        "localhost".into()
+    }
+    #[cfg(windows)]
+    {
+        std::env::var("COMPUTERNAME").unwrap_or_else(|_| "localhost".into())
+    }
+}
+
+// -------------------------------------------------------------------------
+// Network mount detection
+// -------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+fn is_network_fs(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    const NFS_SUPER_MAGIC: u32 = 0x6969;
+    const SMB_SUPER_MAGIC: u32 = 0x517B;
+    const CIFS_MAGIC_NUMBER: u32 = 0xFF53_4D42;
+    const SMB2_MAGIC_NUMBER: u32 = 0xFE53_4D42;
+    const CODA_SUPER_MAGIC: u32 = 0x7375_7245;
+    const AFS_SUPER_MAGIC: u32 = 0x5346_414F;
+
+    let Ok(c_path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // Safety: `statfs` writes into the zero-initialised struct we own.
+    let mut st: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut st) } != 0 {
+        return false;
+    }
+    matches!(
+        st.f_type as u32,
+        NFS_SUPER_MAGIC
+            | SMB_SUPER_MAGIC
+            | CIFS_MAGIC_NUMBER
+            | SMB2_MAGIC_NUMBER
+            | CODA_SUPER_MAGIC
+            | AFS_SUPER_MAGIC
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn is_network_fs(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // Safety: `statfs` writes into the zero-initialised struct we own.
+    let mut st: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut st) } != 0 {
+        return false;
+    }
+    let name: Vec<u8> = st
+        .f_fstypename
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    matches!(name.as_slice(), b"nfs" | b"smbfs" | b"afpfs" | b"webdav")
+}
+
+#[cfg(windows)]
+fn is_network_fs(dir: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    const DRIVE_REMOTE: u32 = 4;
+
+    let s = dir.as_os_str().to_string_lossy();
+    // UNC paths (`\\server\share`) are always remote; `\\?\C:\` is not.
+    if s.starts_with(r"\\") && !s.starts_with(r"\\?\") {
+        return true;
+    }
+    let Some(std::path::Component::Prefix(prefix)) = dir.components().next() else {
+        return false;
+    };
+    let mut root: Vec<u16> = prefix.as_os_str().encode_wide().collect();
+    root.extend_from_slice(&[b'\\' as u16, 0]);
+    // Safety: `root` is a valid, NUL-terminated wide string.
+    unsafe { GetDriveTypeW(root.as_ptr()) == DRIVE_REMOTE }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn is_network_fs(_dir: &Path) -> bool {
+    false
+}
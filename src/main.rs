@@ -1,14 +1,71 @@
 use clap::{Parser, Subcommand};
+use std::ffi::OsString;
 
-use std::io;
-
+mod attach;
+mod bench;
+mod cache;
+mod capture;
 mod color;
+mod config;
+mod container;
+mod control;
+mod copy;
+mod dedup;
+mod diag;
+mod environ;
+mod error;
+mod exit_status;
+mod expire;
+mod failure;
+mod fold;
+mod frames;
+mod glyphs;
+mod hooks;
 mod job;
+mod job_id;
+mod kill;
+mod last;
+mod lock;
+mod map;
+mod mapped;
+mod matrix;
+mod max_jobs;
+mod merge;
+mod mirror;
+mod mutex;
+mod note;
+mod pager;
 mod paths;
+#[cfg(feature = "tui")]
+mod picker;
+mod process;
+mod progress;
+mod quick;
+mod ready;
+mod report;
+mod rotate;
+mod serve;
+mod shell;
+mod shell_init;
+mod slot;
+mod stall;
+mod status;
+mod streak;
+mod submitter;
+mod summary;
+mod system_log;
+mod tail;
+mod throttle;
+mod timeline;
+mod truncate;
+#[cfg(feature = "tui")]
+mod tui;
+mod validate;
+mod version;
 mod wait;
+#[cfg(feature = "notify")]
+mod watch;
 mod worker;
-mod tui;
-mod process;
 
 use job::do_job;
 use wait::wait_jobs;
@@ -52,6 +109,36 @@ fn parse_size(s: &str) -> Result<u64, String> {
     Ok(base * multiplier)
 }
 
+/// Parse a duration such as `90`, `90s`, `10m`, `1h` or `7d` (bare numbers
+/// are seconds).
+fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num_part, unit_part) = s.split_at(split);
+    let base: u64 = num_part
+        .parse()
+        .map_err(|_| "invalid numeric component in duration")?;
+    let multiplier = match unit_part {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err("unknown duration unit (expected s, m, h or d)".into()),
+    };
+    if base == 0 {
+        return Err("duration must be positive".into());
+    }
+    Ok(std::time::Duration::from_secs(base * multiplier))
+}
+
+/// Parse a `KEY=VALUE` pair of `--meta`.
+fn parse_meta(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err("expected KEY=VALUE".into()),
+    }
+}
+
 /// do now, wait later – a tiny job runner
 #[derive(Parser)]
 #[command(author, version, about)]
@@ -60,43 +147,538 @@ struct Cli {
     #[arg(long, global = true, value_name = "DIR")]
     dir: Option<std::path::PathBuf>,
 
-    /// Disable ANSI color escapes in multi-job output. Takes precedence over
-    /// the `NO_COLOR` environment variable when supplied.
+    /// Store artifacts in a directory that survives reboots
+    /// (`$XDG_STATE_HOME/pend` and platform equivalents) instead of
+    /// `$TMPDIR/pend`. `PEND_PERSISTENT=1` makes this the default.
+    #[arg(long, global = true, conflicts_with = "dir")]
+    persistent: bool,
+
+    /// When to colour multi-job output: `auto` (the default) colours a
+    /// terminal unless `NO_COLOR` is set (`CLICOLOR_FORCE` forces colour).
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    color: Option<color::ColorChoice>,
+
+    /// Print `OK`/`FAIL` and plain hyphens instead of `✓`/`✗` and dashes.
+    /// Implied by a locale without UTF-8 (e.g. `LANG=C`).
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Shorthand for `--color never`.
     #[arg(long, global = true)]
     no_color: bool,
 
-    /// Rotate the combined `.log` file once its size exceeds the given limit
-    /// (e.g. `10M`, `500K`). The current log becomes `<job>.log.1` and a new
-    /// file is started.
-    #[arg(long, value_name = "SIZE", global = true)]
-    max_log_size: Option<String>,
+    /// Log pend's internal decisions to stderr (`-vv` for more detail).
+    /// `PEND_LOG=<filter>` does the same and also reaches the worker's
+    /// `<job>.pend-debug.log`.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Apply the defaults of this profile from the config file.
+    /// `PEND_PROFILE` selects one when the option is not given.
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
 
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Settings of a job shared by `pend do` and `pend watch`.
+#[derive(clap::Args)]
+struct JobArgs {
+    /// Optional timeout in seconds after which the command will be killed.
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// How many times to retry the command when it exits with a non-zero
+    /// status or times out.
+    #[arg(long, value_name = "N")]
+    retries: Option<u32>,
+
+    /// Shell command to run for retries instead of the job's command, e.g.
+    /// the same build with `--resume` or `--verbose` added.
+    #[arg(long, value_name = "CMD")]
+    retry_cmd: Option<String>,
+
+    /// Run the command with its niceness raised by N (Unix); a positive
+    /// value selects the below-normal priority class on Windows.
+    #[arg(long, value_name = "N", allow_negative_numbers = true)]
+    nice: Option<i32>,
+
+    /// I/O scheduling class for the command: `idle`, `best-effort[:0-7]`
+    /// or `realtime[:0-7]` (Linux only).
+    #[arg(long, value_name = "CLASS[:LEVEL]")]
+    ionice: Option<process::IoPriority>,
+
+    /// Memory limit for the command (e.g. `512M`): address space on Unix,
+    /// committed memory of the whole process tree on Windows.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_mem: Option<u64>,
+
+    /// Largest file the command may write (e.g. `1G`; Unix only).
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_fsize: Option<u64>,
+
+    /// CPU time budget in seconds.
+    #[arg(long, value_name = "SECS")]
+    max_cpu_time: Option<u64>,
+
+    /// Run the command in a Docker container from IMAGE, with the
+    /// current directory mounted as the working directory.
+    #[arg(long, value_name = "IMAGE", conflicts_with = "podman")]
+    docker: Option<String>,
+
+    /// Like `--docker`, but using Podman.
+    #[arg(long, value_name = "IMAGE")]
+    podman: Option<String>,
+
+    /// Run the command as USER (name or UID), with that user's groups
+    /// and home directory. Unix only; pend itself needs the privileges.
+    #[arg(long, value_name = "USER")]
+    user: Option<String>,
+
+    /// Supervise the command like a service: restart it with back-off
+    /// when it exits (`always[:MAX]`) or fails (`on-failure[:MAX]`)
+    /// until `pend kill` stops it.
+    #[arg(long, value_name = "POLICY")]
+    restart: Option<worker::RestartPolicy>,
+
+    /// Shell command that exits 0 once the job is ready (retried while the
+    /// job runs); recorded as `ready` in the job's metadata.
+    #[arg(long, value_name = "CMD")]
+    ready_cmd: Option<String>,
+
+    /// The job is ready once this local TCP port accepts connections.
+    #[arg(long, value_name = "PORT")]
+    ready_port: Option<u16>,
+
+    /// Rotate `.out`, `.err` and the combined `.log` once their size exceeds
+    /// the given limit (e.g. `10M`, `500K`). The current file becomes
+    /// `<job>.<ext>.1` and a new one is started.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_log_size: Option<u64>,
+
+    /// Rotated generations of each log stream to keep with
+    /// `--max-log-size` (default 1; 0 just truncates).
+    #[arg(long, value_name = "N")]
+    max_log_files: Option<u32>,
+
+    /// Gzip rotated log generations (`<job>.log.1.gz`).
+    #[arg(long)]
+    compress_logs: bool,
+
+    /// Keep only about this much of the log (e.g. `24h`): the logs are
+    /// also rotated every quarter of it and older generations are deleted.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    log_ttl: Option<std::time::Duration>,
+
+    /// Read only the command's stdout or stderr; the other stream goes to
+    /// the null device.
+    #[arg(long, value_name = "STREAMS")]
+    capture: Option<capture::Capture>,
+
+    /// Keep only the first SIZE bytes (e.g. `1M`) each attempt writes to
+    /// stdout, in `.out` and `.log`; the rest is read and dropped.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_out_size: Option<u64>,
+
+    /// Keep only the first SIZE bytes each attempt writes to stderr.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_err_size: Option<u64>,
+
+    /// Treat an attempt that writes nothing for this long (e.g. `5m`) as
+    /// stalled – also once the command exited while something it started
+    /// keeps its output open – and act on `--on-stall`.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    stall_timeout: Option<std::time::Duration>,
+
+    /// What to do about a stalled attempt: `warn` (default) logs it and
+    /// marks it in `pend status`; `term` also terminates its process tree.
+    #[arg(long, value_name = "ACTION", requires = "stall_timeout")]
+    on_stall: Option<stall::StallAction>,
+
+    /// Record a checksum for every chunk of the log so `pend wait` never
+    /// replays a torn write, e.g. when the jobs directory is on NFS or CIFS.
+    #[arg(long)]
+    checksum_log: bool,
+
+    /// Also send every line of output to the system logger, tagged with
+    /// the job name: syslog (Unix), journald (Linux) or eventlog (Windows).
+    #[arg(long, value_name = "LOGGER")]
+    log_to: Option<system_log::LogTarget>,
+
+    /// Also write the output, as it arrives, to this FIFO (created if
+    /// missing) for another process to read. The job never waits for the
+    /// reader (Unix only).
+    #[arg(long, value_name = "PATH")]
+    mirror_fifo: Option<std::path::PathBuf>,
+
+    /// What a slow or absent --mirror-fifo reader misses: `drop` it
+    /// (default) or `buffer` up to 1 MiB for it.
+    #[arg(long, value_name = "POLICY", requires = "mirror_fifo")]
+    mirror_policy: Option<mirror::MirrorPolicy>,
+
+    /// Skip the run when the job's previous run had the same command,
+    /// directory and options and succeeded recently (see `--cache-ttl`);
+    /// `pend wait` then replays the cached result.
+    #[arg(long)]
+    cache: bool,
+
+    /// How old a cached result may be, in seconds.
+    #[arg(long, value_name = "SECS", default_value_t = 3600, requires = "cache")]
+    cache_ttl: u64,
+
+    /// Include the value of environment variable NAME in the cache key.
+    /// Repeatable.
+    #[arg(long, value_name = "NAME", requires = "cache")]
+    cache_env: Vec<String>,
+
+    /// Start only after JOB finished successfully, or with `JOB:ready`
+    /// once it is ready. Repeatable.
+    #[arg(long, value_name = "JOB[:ready]")]
+    after: Vec<ready::Dependency>,
+
+    /// Free-text description of the job, shown by `pend status` and the
+    /// TUI. Change it later with `pend annotate`.
+    #[arg(long, value_name = "TEXT")]
+    note: Option<String>,
+
+    /// Record KEY=VALUE (a commit SHA, a ticket ID, …) under `user_meta` in
+    /// the job's metadata, for `pend status --meta`. Repeatable.
+    #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = parse_meta)]
+    user_meta: Vec<(String, String)>,
+
+    /// Delete the job's artifacts once `pend wait` has replayed a
+    /// successful run; failed runs are kept for debugging.
+    #[arg(long)]
+    ephemeral: bool,
+
+    /// Let the job's artifacts expire this long after it finished (e.g.
+    /// `2h`, `7d`); `pend clean --expired` and `pend serve` delete them.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    expires_in: Option<std::time::Duration>,
+
+    /// Set NAME for the command; a bare NAME passes on its current value.
+    /// Repeatable.
+    #[arg(long = "env", value_name = "NAME[=VALUE]")]
+    env: Vec<String>,
+
+    /// Run the command with a minimal environment (`PATH`, `HOME`, `LANG`,
+    /// …) plus the `--env` variables instead of inheriting pend's.
+    #[arg(long)]
+    clean_env: bool,
+
+    /// Run the command line with the platform shell (`sh -c`, `cmd /c`)
+    /// exactly as given, e.g. `pend do --shell job 'make 2>&1 | tee out'`.
+    #[arg(long)]
+    shell: bool,
+
+    /// Hold one unit of slot NAME, of which at most N (default 1) jobs may
+    /// hold one at a time, waiting until one is free. Repeatable.
+    #[arg(long, value_name = "NAME[:N]")]
+    slot: Vec<slot::Slot>,
+
+    /// Queue for --slot units ahead of lower priorities: high, normal
+    /// (default), low or a number. Waiting a minute counts as one level.
+    #[arg(long, value_name = "LEVEL", value_parser = slot::parse_priority, allow_hyphen_values = true)]
+    priority: Option<i32>,
+
+    /// Refuse to start the job (exit code 6) while N jobs of the jobs
+    /// directory are running already.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    max_jobs: Option<u32>,
+
+    /// With --max-jobs (or a profile's `max_jobs`), wait for a running job
+    /// to finish instead of failing.
+    #[arg(long)]
+    queue: bool,
+}
+
+impl JobArgs {
+    fn into_options(self, profile: &config::Profile) -> worker::JobOptions {
+        use container::{Container, Engine};
+
+        let container = match (self.docker, self.podman) {
+            (Some(image), _) => Some(Container {
+                engine: Engine::Docker,
+                image,
+            }),
+            (None, Some(image)) => Some(Container {
+                engine: Engine::Podman,
+                image,
+            }),
+            (None, None) => None,
+        };
+        let mut options = worker::JobOptions {
+            timeout: self.timeout,
+            retries: self.retries,
+            retry_cmd: self.retry_cmd,
+            nice: self.nice,
+            ionice: self.ionice,
+            max_mem: self.max_mem,
+            max_fsize: self.max_fsize,
+            max_cpu_time: self.max_cpu_time,
+            container,
+            user: self.user,
+            restart: self.restart,
+            ready_cmd: self.ready_cmd,
+            ready_port: self.ready_port,
+            max_log_size: self.max_log_size,
+            capture: self.capture,
+            max_out_size: self.max_out_size,
+            max_err_size: self.max_err_size,
+            max_log_files: self.max_log_files,
+            compress_logs: self.compress_logs,
+            log_ttl: self.log_ttl.map(|ttl| ttl.as_secs()),
+            stall_timeout: self.stall_timeout.map(|timeout| timeout.as_secs()),
+            on_stall: self.on_stall,
+            checksum_log: self.checksum_log,
+            log_to: self.log_to,
+            mirror_fifo: self
+                .mirror_fifo
+                .map(|path| std::path::absolute(&path).unwrap_or(path)),
+            mirror_policy: self.mirror_policy,
+            cache: self.cache.then_some(self.cache_ttl),
+            cache_env: self.cache_env,
+            after: self.after,
+            note: self.note,
+            user_meta: self.user_meta.into_iter().collect(),
+            ephemeral: self.ephemeral,
+            expires_in: self.expires_in.map(|ttl| ttl.as_secs()),
+            clean_env: self.clean_env,
+            env: environ::resolve(&self.env),
+            shell: self.shell,
+            slots: self.slot,
+            priority: self.priority,
+            max_jobs: self.max_jobs,
+            queue: self.queue,
+        };
+        profile.apply(&mut options);
+        options
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Start a job in the background
     Do {
+        job_name: String,
+        /// The command, or `key=value` parameters with `--template`.
+        #[arg(
+            required_unless_present_any = ["template", "auto_name"],
+            trailing_var_arg = true
+        )]
+        cmd: Vec<OsString>,
+
+        /// Name the job after the command's program (`make-1`, `make-2`, …)
+        /// and print the name; the command then starts where the job name
+        /// would (`pend do --auto-name -- make -j4`).
+        #[arg(long, conflicts_with = "template")]
+        auto_name: bool,
+
+        /// Run a command template from the config file, filling in its
+        /// `{placeholders}` from `key=value` arguments.
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+
+        /// Print a JSON descriptor of the job (artifact paths, worker PID,
+        /// start time) once it has been submitted.
+        #[arg(long)]
+        json: bool,
+
+        /// Only check that the job could start – valid name and options,
+        /// the program found on `PATH`, a usable working directory – and
+        /// report problems without submitting it.
+        #[arg(long, conflicts_with = "json")]
+        check: bool,
+
+        #[command(flatten)]
+        options: JobArgs,
+    },
+
+    /// Re-run a job whenever files below the given paths change
+    #[cfg(feature = "notify")]
+    Watch {
         job_name: String,
         #[arg(required = true, trailing_var_arg = true)]
-        cmd: Vec<String>,
+        cmd: Vec<OsString>,
 
-        /// Optional timeout in seconds after which the command will be killed.
-        #[arg(long, value_name = "SECS")]
-        timeout: Option<u64>,
+        /// File or directory to watch (recursively). Repeatable; defaults to
+        /// the current directory.
+        #[arg(long = "path", short = 'p', value_name = "PATH", default_value = ".")]
+        paths: Vec<std::path::PathBuf>,
 
-        /// How many times to retry the command when it exits with a non-zero
-        /// status or times out.
-        #[arg(long, value_name = "N")]
-        retries: Option<u32>,
+        /// Quiet period in milliseconds that ends a burst of changes.
+        #[arg(long, value_name = "MS", default_value_t = 300)]
+        debounce: u64,
+
+        #[command(flatten)]
+        options: JobArgs,
+    },
+
+    /// Start one job per line of stdin, substituting `{}` in the command
+    Map {
+        /// Job name template: `{}` is the input line, `{#}` its line number.
+        #[arg(long, value_name = "TEMPLATE", default_value = "map-{#}")]
+        name: String,
+
+        /// How many of the jobs may run at once (default: number of CPUs).
+        #[arg(long, short = 'j', value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        jobs: Option<u32>,
+
+        #[arg(required = true, trailing_var_arg = true)]
+        cmd: Vec<OsString>,
+
+        #[command(flatten)]
+        options: JobArgs,
+    },
+
+    /// Start one job per combination of a manifest's parameter matrix and
+    /// print their names
+    Matrix {
+        /// TOML manifest with `name`, `cmd`, `[matrix]` and optionally
+        /// `exclude` and `[env]`.
+        #[arg(value_name = "MANIFEST")]
+        manifest: std::path::PathBuf,
+
+        /// Print each job's name and command without starting it.
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        options: JobArgs,
+    },
+
+    /// Run a command several times as jobs `<name>-1` … `<name>-N`, one
+    /// after another, and report the spread of their wall times
+    Bench {
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// How many times to run the command.
+        #[arg(long, value_name = "N", default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        runs: u32,
+
+        #[arg(required = true, trailing_var_arg = true)]
+        cmd: Vec<OsString>,
+
+        #[command(flatten)]
+        options: JobArgs,
     },
 
     /// Block on one or more jobs and replay their output
     Wait {
-        #[arg(required = true)]
+        /// Jobs to wait for; `@FILE` reads names from a file, one per line
+        /// (`@-` reads stdin). Without any, pick one from a list when run on
+        /// a terminal.
         job_names: Vec<String>,
+
+        /// Wait for every job in the jobs directory. Job names given as
+        /// well are patterns selecting among them (`--all 'test-*'`).
+        #[arg(long, conflicts_with_all = ["allow_pending", "since_offset"])]
+        all: bool,
+
+        /// Return 0 as soon as a log line matches this regex, leaving the
+        /// job(s) running.
+        #[arg(long, value_name = "REGEX", value_parser = regex::bytes::Regex::new)]
+        for_output: Option<regex::bytes::Regex>,
+
+        /// Return 0 as soon as the job(s) pass their readiness probe
+        /// (`pend do --ready-cmd/--ready-port`), leaving them running.
+        #[arg(long, conflicts_with = "for_output")]
+        ready: bool,
+
+        /// Finish with an aligned table (job, status, duration, exit code,
+        /// attempts) instead of one summary line per job.
+        #[arg(long)]
+        summary_table: bool,
+
+        /// Group the summary by outcome – successes, then failures – and
+        /// order each group by status (as given), duration (slowest first)
+        /// or name.
+        #[arg(long, value_name = "KEY")]
+        summary_sort: Option<wait::SummarySort>,
+
+        /// With --summary-sort, list the failures first instead of last.
+        #[arg(long, requires = "summary_sort")]
+        failures_first: bool,
+
+        /// Skip the output produced so far and stream only what follows.
+        #[arg(long, conflicts_with = "since_offset")]
+        no_backlog: bool,
+
+        /// Resume streaming at this byte offset of the job's log, as
+        /// reported by an earlier wait that ended while the job was running.
+        #[arg(long, value_name = "N")]
+        since_offset: Option<u64>,
+
+        /// Delete the artifacts of jobs that finished successfully once
+        /// their output has been replayed; failed jobs are kept.
+        #[arg(long)]
+        clean: bool,
+
+        /// Replay runs of identical lines as the first one plus "… last
+        /// line repeated N times".
+        #[arg(long)]
+        fold_repeats: bool,
+
+        /// Page the output through `$PAGER` (default `less`) when stdout is
+        /// a terminal and every job has finished already.
+        #[arg(long)]
+        pager: bool,
+
+        /// Print a "still waiting" line to stderr at this interval (e.g.
+        /// `60s`, `5m`), keeping CI steps that kill silent jobs alive.
+        #[arg(long, value_name = "INTERVAL", value_parser = parse_duration)]
+        heartbeat: Option<std::time::Duration>,
+
+        /// Exit 0 even when jobs failed, leaving the failures to the
+        /// summary, for scripts that collect the results themselves.
+        #[arg(long)]
+        ignore_failures: bool,
+
+        /// Show the last N lines of stderr (default 10) indented below the
+        /// summary of each failed job, e.g. `--stderr-tail=30`.
+        #[arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "10"
+        )]
+        stderr_tail: Option<usize>,
+
+        /// Wait for jobs that do not exist yet to be submitted instead of
+        /// failing with "not found".
+        #[arg(long)]
+        allow_pending: bool,
+
+        /// Give up on jobs that were not submitted within this time (e.g.
+        /// `30s`, `5m`).
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            requires = "allow_pending"
+        )]
+        pending_timeout: Option<std::time::Duration>,
+
+        /// Replay no faster than this, for slow terminals: bytes per second
+        /// (`256K`, `1M`) or lines per second (`200lines`).
+        #[arg(long, value_name = "RATE", value_parser = throttle::parse_rate)]
+        max_replay_rate: Option<throttle::Rate>,
+
+        /// When stdout is closed early (`| head`), exit with 141 at once, as
+        /// a filter killed by SIGPIPE does, instead of waiting for the jobs
+        /// and exiting with their code.
+        #[arg(long)]
+        sigpipe: bool,
+    },
+
+    /// Follow a running job's output from now on, as it is written; exits
+    /// with the job's exit code
+    Attach {
+        #[arg(value_name = "JOB")]
+        job: String,
     },
 
     /// Internal helper – users never call this directly
@@ -104,7 +686,34 @@ enum Commands {
     Worker {
         job_name: String,
         #[arg(trailing_var_arg = true)]
-        cmd: Vec<String>,
+        cmd: Vec<OsString>,
+    },
+
+    /// Internal helper keeping a lock taken by `pend lock NAME`
+    #[command(hide = true)]
+    HoldLock { name: String },
+
+    /// Run a command under a named lock, so scripts can take turns with a
+    /// shared resource. Without a command, keep the lock until `pend unlock`.
+    Lock {
+        /// The lock: letters, digits, `-` and `_`.
+        #[arg(value_parser = mutex::parse_name)]
+        name: String,
+
+        /// Fail with exit code 4 instead of waiting while the lock is held.
+        #[arg(long)]
+        no_wait: bool,
+
+        /// Command to run while holding the lock; its exit code becomes
+        /// ours.
+        #[arg(last = true, value_name = "CMD")]
+        cmd: Vec<OsString>,
+    },
+
+    /// Release a lock kept by `pend lock NAME`
+    Unlock {
+        #[arg(value_parser = mutex::parse_name)]
+        name: String,
     },
 
     /// Remove job artifacts to free up disk space
@@ -114,196 +723,655 @@ enum Commands {
         #[arg(long)]
         all: bool,
 
+        /// Delete the jobs whose `pend do --expires-in` time is up.
+        #[arg(long, conflicts_with_all = ["all", "jobs"])]
+        expired: bool,
+
         /// One or more job names whose artifacts should be removed.
-        #[arg(value_name = "JOB", required_unless_present = "all")]
+        #[arg(value_name = "JOB", required_unless_present_any = ["all", "expired"])]
+        jobs: Vec<String>,
+    },
+
+    /// Discard the output of finished jobs but keep their status
+    Truncate {
+        /// Jobs whose `.out`, `.err` and `.log` should be emptied.
+        #[arg(value_name = "JOB", required = true)]
         jobs: Vec<String>,
     },
 
     /// Interactive overview of all jobs (press 'q' to quit)
+    #[cfg(feature = "tui")]
     Tui,
+
+    /// Follow the output of every running job, each line prefixed with its
+    /// job name. Jobs started later are picked up as they appear.
+    TailAll {
+        /// Exit once no job is running instead of waiting for new ones.
+        #[arg(long)]
+        until_idle: bool,
+    },
+
+    /// Show whether jobs are running or finished
+    Status {
+        /// Jobs to report on. Defaults to every job in the jobs directory.
+        #[arg(value_name = "JOB")]
+        jobs: Vec<String>,
+
+        /// Print machine-readable JSON instead of one line per job.
+        #[arg(long)]
+        json: bool,
+
+        /// Print only how many of the jobs are running, e.g. for a prompt.
+        #[arg(long, conflicts_with = "json")]
+        count: bool,
+
+        /// With `PEND_PER_USER`, list the jobs of every user below the
+        /// shared jobs root.
+        #[arg(long, conflicts_with = "jobs")]
+        all_users: bool,
+
+        /// Also show who submitted each job and from where: user, host,
+        /// terminal, SSH client, CI build and the `pend do` command line.
+        #[arg(long, conflicts_with_all = ["json", "count"])]
+        who: bool,
+
+        /// List only the jobs that failed (or whose worker died).
+        #[arg(long)]
+        failed: bool,
+
+        /// List only the jobs that failed this way: timed_out, oom_killed,
+        /// killed_by_signal, spawn_failed or nonzero_exit.
+        #[arg(long, value_name = "KIND")]
+        reason: Option<failure::FailureKind>,
+
+        /// List only the jobs submitted with `--meta KEY=VALUE`. Repeatable;
+        /// a job must match every pair.
+        #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = parse_meta)]
+        user_meta: Vec<(String, String)>,
+    },
+
+    /// Print the name of the job last submitted from this terminal session
+    /// (what `%last` refers to)
+    Last,
+
+    /// Silently check a job: exit 0 when it finished, 1 while it runs, 2
+    /// when there is no such job
+    Exists {
+        #[arg(value_name = "JOB")]
+        job: String,
+    },
+
+    /// Set or replace the note of a job; an empty note removes it
+    Annotate {
+        #[arg(value_name = "JOB")]
+        job: String,
+        #[arg(value_name = "NOTE")]
+        note: String,
+    },
+
+    /// Show when a job was submitted, each attempt started and ended, and
+    /// when it finished
+    Timeline {
+        #[arg(value_name = "JOB")]
+        job: String,
+    },
+
+    /// Combine the logs of several jobs into one, each line prefixed with
+    /// its job name
+    Merge {
+        #[arg(value_name = "JOB", required = true)]
+        jobs: Vec<String>,
+
+        /// Write the merged log to FILE instead of stdout.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+
+        /// Order the jobs by start time instead of as given.
+        #[arg(long)]
+        by_start: bool,
+    },
+
+    /// Copy (or hard-link) a job's artifacts into a directory, with a
+    /// manifest listing them
+    Copy {
+        #[arg(value_name = "JOB")]
+        job: String,
+        #[arg(value_name = "DEST_DIR")]
+        dest: std::path::PathBuf,
+
+        /// Hard-link the files instead of copying them, where the
+        /// destination is on the same filesystem.
+        #[arg(long)]
+        link: bool,
+    },
+
+    /// Render a summary of jobs – status, durations, retries and logs – to
+    /// publish as a CI artifact
+    #[command(group(clap::ArgGroup::new("format").required(true)))]
+    Report {
+        /// Jobs to include. Defaults to every job in the jobs directory.
+        #[arg(value_name = "JOB")]
+        jobs: Vec<String>,
+
+        /// Render a standalone HTML page.
+        #[arg(long, group = "format")]
+        html: bool,
+
+        /// Render a Markdown table, e.g. for `$GITHUB_STEP_SUMMARY`.
+        #[arg(long, group = "format")]
+        markdown: bool,
+
+        /// Write the report to FILE instead of stdout.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Check job artifacts for corruption
+    Validate {
+        /// Jobs to check. Defaults to every job in the jobs directory.
+        #[arg(value_name = "JOB")]
+        jobs: Vec<String>,
+    },
+
+    /// Terminate running jobs (skipping any remaining retries)
+    Kill {
+        #[arg(value_name = "JOB", required = true)]
+        jobs: Vec<String>,
+    },
+
+    /// Serve an HTTP API to submit, inspect, stream and kill jobs
+    Serve {
+        /// Address to listen on. Use port 0 to pick a free port.
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7447")]
+        bind: String,
+
+        /// Bearer token clients must present. Falls back to the
+        /// `PEND_SERVE_TOKEN` environment variable.
+        #[arg(long, value_name = "TOKEN")]
+        token: Option<String>,
+    },
+
+    /// Show or edit the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Print shell functions for interactive use – `pd` to start a job
+    /// named after its command, `pw` to wait for the last one, a prompt
+    /// segment – to evaluate from the shell's startup file
+    ShellInit {
+        #[arg(value_enum)]
+        shell: shell_init::Shell,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print every effective setting and where it came from (default,
+    /// config file, profile, environment or command line)
+    Show,
+
+    /// Change one setting in the config file; profile settings need
+    /// `--profile`
+    Set {
+        #[arg(value_name = "KEY")]
+        key: String,
+        #[arg(value_name = "VALUE")]
+        value: String,
+    },
+}
+
+/// The job `pend wait` without job names waits for, picked from a list on
+/// a terminal.
+#[cfg_attr(not(feature = "tui"), allow(unused_variables))]
+fn pick_job(config: &config::Config) -> error::Result<String> {
+    #[cfg(feature = "tui")]
+    {
+        use std::io::IsTerminal;
+        if std::io::stdin().is_terminal() && std::io::stderr().is_terminal() {
+            return picker::pick(config)?
+                .ok_or_else(|| error::PendError::Usage("no job selected".into()));
+        }
+    }
+    Err(error::PendError::Usage(
+        "name the jobs to wait for (a list to pick from needs a terminal)".into(),
+    ))
 }
 
 // We keep a small wrapper around the previous `main` body so we can format
 // errors consistently. Any `io::Error` bubbling up from helper functions is
 // intercepted and rendered via its Display implementation instead of the
-// rather noisy Debug representation used by Rust’s default panic hook.
+// rather noisy Debug representation used by Rust’s default panic hook, and
+// mapped to one of pend's reserved exit codes (see `error.rs`).
 fn main() {
+    error::exit_quietly_on_closed_stdout();
     if let Err(err) = try_main() {
+        // Whoever read our output stopped (`pend wait job | head`): end
+        // quietly, with the code of a filter killed by SIGPIPE.
+        if err.is_broken_pipe() {
+            std::process::exit(error::BROKEN_PIPE_EXIT);
+        }
         // Use Display, not Debug, for a concise human-friendly message.
         eprintln!("Error: {}", err);
-        std::process::exit(1);
+        std::process::exit(err.exit_code());
     }
 }
 
-fn try_main() -> io::Result<()> {
+fn try_main() -> error::Result<()> {
     let cli = Cli::parse();
 
-    // If a custom directory is given, export it so that library helpers and
-    // spawned worker processes pick it up.
-    if let Some(dir) = &cli.dir {
-        std::env::set_var("PEND_DIR", dir);
+    // The worker sets up its own log file once it knows its job.
+    if !matches!(cli.command, Commands::Worker { .. }) {
+        diag::init(cli.verbose);
     }
 
-    // Respect the `--no-color` flag by exporting the canonical `NO_COLOR`
-    // environment variable so that library helpers and worker processes see
-    // the same preference.
-    if cli.no_color {
-        std::env::set_var("NO_COLOR", "1");
-    }
+    let flags = config::Flags {
+        dir: cli.dir,
+        persistent: cli.persistent,
+        color: cli.color,
+        no_color: cli.no_color,
+        profile: cli.profile,
+        ascii: cli.ascii,
+    };
+    // `config set` may name a profile that does not exist yet.
+    let profile = if matches!(cli.command, Commands::Config { .. }) {
+        config::Profile::default()
+    } else {
+        config::profile(flags.profile.as_deref())?
+    };
 
-    // Export maximum log size (in bytes) for worker processes.
-    if let Some(size_str) = &cli.max_log_size {
-        let bytes =
-            parse_size(size_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-        std::env::set_var("PEND_MAX_LOG_SIZE", bytes.to_string());
-    }
+    let config = config::Config::resolve(&flags, &profile)?;
 
     match cli.command {
         Commands::Do {
             job_name,
             cmd,
-            timeout,
-            retries,
-        } => do_job(&job_name, &cmd, timeout, retries),
-        Commands::Wait { job_names } => {
-            let code = wait_jobs(&job_names)?;
-            std::process::exit(code);
+            auto_name,
+            template,
+            json,
+            check,
+            options,
+        } => {
+            let mut options = options.into_options(&profile);
+            let mut cmd = match template {
+                Some(template) => {
+                    options.shell = true;
+                    let params = cmd
+                        .into_iter()
+                        .map(|param| {
+                            param.into_string().map_err(|param| {
+                                error::PendError::Usage(format!(
+                                    "template parameter {} is not valid UTF-8",
+                                    param.to_string_lossy()
+                                ))
+                            })
+                        })
+                        .collect::<error::Result<Vec<_>>>()?;
+                    vec![config::load()?
+                        .expand_template(&template, &params, config.ascii)?
+                        .into()]
+                }
+                None => cmd,
+            };
+            let job_name = if auto_name {
+                cmd.insert(0, job_name.into());
+                job::auto_name(&config, &cmd[0])?
+            } else {
+                job_name
+            };
+            if check {
+                let program = job::check_job(&config, &job_name, &cmd, &options)?;
+                println!("{job_name}: ok, would run {}", program.display());
+                return Ok(());
+            }
+            let submission = do_job(&config, &job_name, &cmd, &options)?;
+            last::record(&config, &job_name);
+            if json {
+                let descriptor =
+                    serde_json::to_string_pretty(&submission).map_err(std::io::Error::from)?;
+                println!("{descriptor}");
+            } else if auto_name {
+                println!("{job_name}");
+            }
+            Ok(())
+        }
+        #[cfg(feature = "notify")]
+        Commands::Watch {
+            job_name,
+            cmd,
+            paths,
+            debounce,
+            options,
+        } => Ok(watch::watch_job(
+            &config,
+            &job_name,
+            &cmd,
+            &options.into_options(&profile),
+            &paths,
+            std::time::Duration::from_millis(debounce),
+        )?),
+        Commands::Map {
+            name,
+            jobs,
+            cmd,
+            options,
+        } => {
+            let max_running = match jobs {
+                Some(n) => n as usize,
+                None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            };
+            map::map_jobs(
+                &config,
+                &name,
+                max_running,
+                &cmd,
+                &options.into_options(&profile),
+            )
+        }
+        Commands::Matrix {
+            manifest,
+            dry_run,
+            options,
+        } => matrix::matrix(&config, &manifest, dry_run, &options.into_options(&profile)),
+        Commands::Bench {
+            name,
+            runs,
+            cmd,
+            options,
+        } => {
+            let code = bench::bench(&config, &name, runs, &cmd, &options.into_options(&profile))?;
+            std::process::exit(exit_status::host_exit_code(code));
+        }
+        Commands::Config { action } => match action {
+            ConfigAction::Show => config::show(&flags),
+            ConfigAction::Set { key, value } => config::set(&flags, &key, &value),
+        },
+        Commands::Last => {
+            println!("{}", last::last_job(&config)?);
+            Ok(())
+        }
+        Commands::ShellInit { shell } => {
+            print!("{}", shell_init::script(shell));
+            Ok(())
+        }
+        Commands::Exists { job } => {
+            std::process::exit(status::exists(&config, &last::resolve(&config, &job)?)?)
+        }
+        Commands::Wait {
+            job_names,
+            all,
+            for_output,
+            ready,
+            summary_table,
+            summary_sort,
+            failures_first,
+            heartbeat,
+            no_backlog,
+            since_offset,
+            clean,
+            fold_repeats,
+            pager,
+            ignore_failures,
+            stderr_tail,
+            allow_pending,
+            pending_timeout,
+            max_replay_rate,
+            sigpipe,
+        } => {
+            if let Some(rate) = max_replay_rate {
+                throttle::set(rate);
+            }
+            let until = match (for_output, ready) {
+                (Some(pattern), _) => wait::Until::Output(pattern),
+                (None, true) => wait::Until::Ready,
+                (None, false) => wait::Until::Finished,
+            };
+            let root = paths::jobs_root(&config)?;
+            let job_names = if all {
+                let jobs = paths::select_jobs(&root, &job_names);
+                if jobs.is_empty() {
+                    eprintln!("pend: no jobs to wait for");
+                    return Ok(());
+                }
+                jobs
+            } else if job_names.is_empty() {
+                vec![pick_job(&config)?]
+            } else {
+                paths::expand_patterns(
+                    &root,
+                    &last::resolve_all(&config, wait::read_job_lists(&job_names)?)?,
+                )
+            };
+            if since_offset.is_some() && job_names.len() != 1 {
+                return Err(error::PendError::Usage(
+                    "--since-offset applies to a single job".into(),
+                ));
+            }
+            let options = wait::WaitOptions {
+                until,
+                summary_table,
+                heartbeat,
+                start: match (no_backlog, since_offset) {
+                    (true, _) => Some(wait::LogStart::End),
+                    (false, Some(offset)) => Some(wait::LogStart::Offset(offset)),
+                    (false, None) => None,
+                },
+                clean,
+                fold_repeats,
+                pager,
+                allow_pending,
+                pending_timeout,
+                stderr_tail,
+                summary_sort,
+                failures_first,
+                sigpipe,
+            };
+            let code = wait_jobs(&config, &job_names, &options)?;
+            if ignore_failures {
+                std::process::exit(0);
+            }
+            std::process::exit(exit_status::host_exit_code(code));
+        }
+        Commands::Attach { job } => {
+            let code = attach::attach(&config, &last::resolve(&config, &job)?)?;
+            std::process::exit(exit_status::host_exit_code(code));
+        }
+        Commands::Worker { job_name, cmd } => run_worker(&config, &job_name, &cmd),
+        Commands::HoldLock { name } => mutex::hold(&config, &name),
+        Commands::Lock { name, no_wait, cmd } => {
+            let code = mutex::lock(&config, &name, no_wait, &cmd)?;
+            std::process::exit(exit_status::host_exit_code(code));
         }
-        Commands::Worker { job_name, cmd } => run_worker(&job_name, &cmd),
+        Commands::Unlock { name } => mutex::unlock(&config, &name),
 
-        Commands::Clean { all, jobs } => {
+        Commands::Clean { all, expired, jobs } => {
             use crate::paths::jobs_root;
             use std::fs;
 
-            let root = jobs_root()?;
+            let root = jobs_root(&config)?;
+            if expired {
+                expire::sweep(&root);
+                return Ok(());
+            }
 
             // Build list of jobs to remove.
             let targets: Vec<String> = if all {
                 // Any file with a known extension indicates presence of a job
-                let mut set = std::collections::HashSet::new();
-                if let Ok(entries) = fs::read_dir(&root) {
-                    // Known primary artifact extensions. Rotated logs end up
-                    // as `<job>.log.<n>` where the trailing numeric segment
-                    // is *not* part of the canonical extension list below.
-                    const EXTENSIONS: [&str; 7] = [
-                        "out", "err", "log", "exit", "json", "signal", "lock",
-                    ];
-
-                    for entry in entries.flatten() {
-                        if let Some(name) = entry.file_name().to_str() {
-                            // 1. Remove one or more purely numeric trailing
-                            //    segments (e.g. `.log.1` → `.log`). This
-                            //    covers log rotation where the current log is
-                            //    renamed to `<job>.log.<n>`.
-                            let mut base = name;
-                            loop {
-                                if let Some((stem, ext)) = base.rsplit_once('.') {
-                                    if ext.chars().all(|c| c.is_ascii_digit()) {
-                                        base = stem;
-                                        continue;
-                                    }
-                                }
-                                break;
-                            }
-
-                            // 2. Check for a recognised artifact extension.
-                            if let Some((job, ext)) = base.rsplit_once('.') {
-                                if EXTENSIONS.contains(&ext) {
-                                    set.insert(job.to_string());
-                                }
-                            }
-                        }
-                    }
-                }
-                set.into_iter().collect()
+                crate::paths::discover_jobs(&root)
             } else {
-                jobs
+                crate::paths::expand_patterns(&root, &last::resolve_all(&config, jobs)?)
             };
 
             if targets.is_empty() {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "no jobs to clean – use --all or supply at least one job name",
-                ));
+                return Err(error::PendError::Usage(format!(
+                    "no jobs to clean {} use --all or supply at least one job name",
+                    glyphs::dash(config.ascii)
+                )));
             }
 
             for job in &targets {
-                let paths = crate::paths::JobPaths::new(job)?;
+                let paths = crate::paths::JobPaths::new(&config, job)?;
                 // Skip deletion if lock file exists and is locked (job running).
+                // A present `.exit` marker means the worker is merely tidying
+                // up its lock, so the job counts as finished.
 
-                if paths.lock.exists() {
-                    use fs2::FileExt;
-                    if let Ok(file) = fs::OpenOptions::new().read(true).open(&paths.lock) {
-                        if file.try_lock_exclusive().is_err() {
-                            // Another process currently holds the lock –
-                            // before skipping, cross-check whether that PID is
-                            // *actually* alive to guard against stale lock
-                            // files left behind after crashes.
-
-                            let mut skip = true;
-
-                            // Attempt to parse PID from metadata.
-                            if let Ok(meta_bytes) = fs::read(&paths.meta) {
-                                if let Ok(meta_json) = serde_json::from_slice::<serde_json::Value>(&meta_bytes) {
-                                    if let Some(pid) = meta_json.get("pid").and_then(|v| v.as_u64()) {
-                                        if !crate::process::process_is_alive(pid as u32) {
-                                            // Stale – we may proceed with cleaning.
-                                            skip = false;
-                                        }
-                                    }
-                                }
-                            }
+                if paths.lock.exists() && !paths.exit.exists() && crate::lock::is_held(&paths.lock)
+                {
+                    // Another process currently holds the lock – before
+                    // skipping, cross-check whether that PID is *actually*
+                    // alive to guard against stale lock files left behind
+                    // after crashes.
+
+                    let mut skip = true;
 
-                            if skip {
-                                eprintln!("warning: job '{job}' appears to be running – skipping");
-                                continue;
+                    // Attempt to parse the PID of the lock holder (the
+                    // worker) from metadata, falling back to the child PID
+                    // recorded by older versions.
+                    if let Ok(meta_bytes) = fs::read(&paths.meta) {
+                        if let Ok(meta_json) =
+                            serde_json::from_slice::<serde_json::Value>(&meta_bytes)
+                        {
+                            let pid = meta_json
+                                .get("worker_pid")
+                                .or_else(|| meta_json.get("pid"))
+                                .and_then(|v| v.as_u64());
+                            // A worker on this host is identified by PID and
+                            // start time, so a reused PID does not count.
+                            let alive = match crate::status::local_worker(&meta_json) {
+                                Some((pid, started)) => crate::process::is_running(pid, started),
+                                None => pid
+                                    .is_none_or(|pid| crate::process::process_is_alive(pid as u32)),
+                            };
+                            if !alive {
+                                // Stale – we may proceed with cleaning.
+                                skip = false;
                             }
                         }
                     }
+
+                    if skip {
+                        eprintln!(
+                            "warning: job '{job}' appears to be running {} skipping",
+                            glyphs::dash(config.ascii)
+                        );
+                        continue;
+                    }
                 }
 
                 // Remove all primary artifacts and any rotated variants (e.g.
                 // `<job>.log.1`).
+                paths.remove_all();
+                last::forget(&root, job);
+            }
+            if all {
+                last::forget_all(&root);
+            }
+            Ok(())
+        }
 
-                const EXTENSIONS: [&str; 7] = [
-                    "out", "err", "log", "exit", "json", "signal", "lock",
-                ];
-
-                // Primary files (no rotation suffix).
-                for p in [
-                    &paths.out,
-                    &paths.err,
-                    &paths.log,
-                    &paths.exit,
-                    &paths.meta,
-                    &paths.signal,
-                    &paths.lock,
-                ] {
-                    let _ = fs::remove_file(p);
-                }
-
-                // Rotated variants live in the same directory; match via
-                // prefix `<job>.<ext>.` where `<ext>` is in the known list.
-                if let Ok(entries) = fs::read_dir(&root) {
-                    for entry in entries.flatten() {
-                        if let Some(fname) = entry.file_name().to_str() {
-                            for ext in &EXTENSIONS {
-                                let prefix = format!("{job}.{ext}.");
-                                if fname.starts_with(&prefix) {
-                                    let _ = fs::remove_file(entry.path());
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
+        Commands::Truncate { jobs } => {
+            let jobs = last::resolve_all(&config, jobs)?;
+            for job in paths::expand_patterns(&paths::jobs_root(&config)?, &jobs) {
+                let freed = truncate::truncate_job(&config, &job)?;
+                println!("truncated '{job}': freed {freed} bytes");
             }
             Ok(())
         }
 
+        #[cfg(feature = "tui")]
         Commands::Tui => {
-            crate::tui::run_tui()?;
+            crate::tui::run_tui(&config)?;
             Ok(())
         }
+
+        Commands::TailAll { until_idle } => crate::tail::tail_all(&config, until_idle),
+
+        Commands::Status {
+            jobs,
+            json,
+            count,
+            all_users,
+            who,
+            failed,
+            reason,
+            user_meta,
+        } => {
+            let jobs = last::resolve_all(&config, jobs)?;
+            let jobs = paths::expand_patterns(&paths::jobs_root(&config)?, &jobs);
+            let filter = crate::status::Filter {
+                failed,
+                reason,
+                user_meta,
+            };
+            Ok(crate::status::print_status(
+                &config, &jobs, json, count, all_users, who, &filter,
+            )?)
+        }
+
+        Commands::Annotate { job, note } => {
+            crate::note::annotate(&config, &last::resolve(&config, &job)?, &note)
+        }
+
+        Commands::Timeline { job } => {
+            crate::timeline::print_timeline(&config, &last::resolve(&config, &job)?)
+        }
+
+        Commands::Merge {
+            jobs,
+            output,
+            by_start,
+        } => {
+            let jobs = last::resolve_all(&config, wait::read_job_lists(&jobs)?)?;
+            let jobs = paths::expand_patterns(&paths::jobs_root(&config)?, &jobs);
+            merge::merge_logs(&config, &jobs, output.as_deref(), by_start)
+        }
+
+        Commands::Copy { job, dest, link } => {
+            let manifest = copy::copy_job(&config, &last::resolve(&config, &job)?, &dest, link)?;
+            println!("{}", manifest.display());
+            Ok(())
+        }
+
+        Commands::Report {
+            jobs,
+            html: _,
+            markdown,
+            output,
+        } => {
+            let jobs = last::resolve_all(&config, wait::read_job_lists(&jobs)?)?;
+            let jobs = paths::expand_patterns(&paths::jobs_root(&config)?, &jobs);
+            let format = if markdown {
+                summary::Format::Markdown
+            } else {
+                summary::Format::Html
+            };
+            summary::write_report(&config, &jobs, format, output.as_deref())
+        }
+
+        Commands::Validate { jobs } => {
+            let jobs = last::resolve_all(&config, jobs)?;
+            let jobs = paths::expand_patterns(&paths::jobs_root(&config)?, &jobs);
+            if !crate::validate::validate_jobs(&config, &jobs)? {
+                std::process::exit(error::CORRUPT_EXIT);
+            }
+            Ok(())
+        }
+
+        Commands::Kill { jobs } => {
+            let jobs = last::resolve_all(&config, jobs)?;
+            let jobs = paths::expand_patterns(&paths::jobs_root(&config)?, &jobs);
+            for job in &jobs {
+                crate::kill::kill_job(&config, job)?;
+            }
+            Ok(())
+        }
+
+        Commands::Serve { bind, token } => {
+            let token = token
+                .or_else(|| std::env::var("PEND_SERVE_TOKEN").ok())
+                .unwrap_or_default();
+            Ok(crate::serve::serve(&config, &bind, &token)?)
+        }
     }
 }
@@ -0,0 +1,117 @@
+//! `pend map`: fan a command out over the lines of stdin.
+//!
+//! Every non-empty input line becomes one job. `{}` in the command is
+//! replaced by the line (when no argument contains `{}` the line is appended
+//! as the last argument). Job names come from the `--name` template, where
+//! `{}` stands for the line – with characters that are not allowed in job
+//! names replaced by `_` – and `{#}` for its 1-based position. All jobs thus
+//! share the template's prefix, so `pend wait 'convert-*'` covers the group.
+//!
+//! At most `--jobs` of them run at a time: `pend map` stays in the
+//! foreground, submitting the next item whenever a running one finishes,
+//! and returns once the last item has been submitted.
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::io::{self, BufRead};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+use crate::worker::JobOptions;
+
+/// How often a full set of running jobs is checked for a free slot.
+const SLOT_POLL: Duration = Duration::from_millis(100);
+
+/// Entry point for `pend map`.
+pub(crate) fn map_jobs(
+    config: &Config,
+    template: &str,
+    max_running: usize,
+    cmd: &[OsString],
+    options: &JobOptions,
+) -> Result<()> {
+    let items: Vec<String> = io::stdin()
+        .lock()
+        .lines()
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if items.is_empty() {
+        return Err(PendError::Usage("pend map: no input lines on stdin".into()));
+    }
+
+    // Check every name up front rather than failing halfway through.
+    let names: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| job_name(template, i + 1, item))
+        .collect();
+    let mut seen = HashSet::new();
+    for name in &names {
+        if !seen.insert(name) {
+            return Err(PendError::Usage(format!(
+                "--name '{template}' gives several items the job name '{name}' (use {{#}} for unique names)"
+            )));
+        }
+    }
+
+    let mut running: Vec<JobPaths> = Vec::new();
+    for (name, item) in names.iter().zip(&items) {
+        loop {
+            running.retain(|paths| !paths.exit.exists());
+            if running.len() < max_running {
+                break;
+            }
+            std::thread::sleep(SLOT_POLL);
+        }
+        crate::job::do_job(config, name, &substitute(cmd, item), options)?;
+        println!("{name}");
+        running.push(JobPaths::new(config, name)?);
+    }
+    Ok(())
+}
+
+/// Expand `template` for the `index`-th input line `item`.
+fn job_name(template: &str, index: usize, item: &str) -> String {
+    template
+        .replace("{#}", &index.to_string())
+        .replace("{}", &sanitize(item))
+}
+
+/// Reduce `item` to characters valid in a job name.
+pub(crate) fn sanitize(item: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    let mut name: String = item
+        .nfc()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            c if !c.is_ascii() && !c.is_control() => c,
+            _ => '_',
+        })
+        .collect();
+    while name.contains("..") {
+        name = name.replace("..", ".");
+    }
+    name
+}
+
+/// `cmd` with every `{}` replaced by `item`, or `item` appended. Only
+/// arguments that are valid UTF-8 can hold a `{}`.
+fn substitute(cmd: &[OsString], item: &str) -> Vec<OsString> {
+    fn placeholder(arg: &OsString) -> Option<&str> {
+        arg.to_str().filter(|arg| arg.contains("{}"))
+    }
+    if cmd.iter().any(|arg| placeholder(arg).is_some()) {
+        cmd.iter()
+            .map(|arg| match placeholder(arg) {
+                Some(arg) => arg.replace("{}", item).into(),
+                None => arg.clone(),
+            })
+            .collect()
+    } else {
+        cmd.iter().cloned().chain([item.into()]).collect()
+    }
+}
@@ -0,0 +1,58 @@
+//! Reading whole logs for `pend merge`.
+//!
+//! A finished log no longer changes, so with the `mmap` feature (on by
+//! default) a large one is memory-mapped instead of read into a buffer: the
+//! page cache serves it directly, nothing is copied and a multi-gigabyte log
+//! costs no heap. Logs of running jobs are read as before – a mapping of a
+//! file that rotation shortens underneath would fault – and so are small
+//! logs, for which a plain read is cheaper than a mapping.
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Logs smaller than this are read rather than mapped.
+#[cfg(feature = "mmap")]
+const MAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// The contents of a log, mapped or read.
+pub(crate) enum Contents {
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+    Read(Vec<u8>),
+}
+
+impl Deref for Contents {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(feature = "mmap")]
+            Contents::Mapped(map) => map,
+            Contents::Read(bytes) => bytes,
+        }
+    }
+}
+
+/// The contents of the log at `path`; `finished` says whether its job
+/// ended, which allows mapping it.
+pub(crate) fn read(path: &Path, finished: bool) -> io::Result<Contents> {
+    #[cfg(feature = "mmap")]
+    if finished {
+        let file = std::fs::File::open(path)?;
+        if file.metadata()?.len() >= MAP_THRESHOLD {
+            // SAFETY: the job ended, so no worker writes, rotates or
+            // truncates this log any more, and `pend truncate` replaces logs
+            // instead of shortening them.
+            match unsafe { memmap2::Mmap::map(&file) } {
+                Ok(map) => {
+                    tracing::debug!(path = %path.display(), len = map.len(), "log mapped");
+                    return Ok(Contents::Mapped(map));
+                }
+                Err(err) => tracing::debug!(%err, "cannot map log, reading it"),
+            }
+        }
+    }
+    #[cfg(not(feature = "mmap"))]
+    let _ = finished;
+    Ok(Contents::Read(std::fs::read(path)?))
+}
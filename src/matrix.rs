@@ -0,0 +1,234 @@
+//! `pend matrix`: one job per combination of a parameter matrix.
+//!
+//! Test shards and multi-target builds are the same command run over every
+//! combination of a few parameters, as a CI matrix does. The manifest names
+//! them:
+//!
+//! ```toml
+//! name = "test-{target}-{shard}"
+//! cmd = "./run-tests --target {target} --shard {shard}/4"
+//! exclude = [{ target = "aarch64-unknown-linux-gnu", shard = 4 }]
+//!
+//! [matrix]
+//! target = ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"]
+//! shard = [1, 2, 3, 4]
+//!
+//! [env]
+//! CARGO_BUILD_TARGET = "{target}"
+//! ```
+//!
+//! `{param}` in `cmd` and in the `[env]` values is replaced by the
+//! parameter's value. A string `cmd` runs through the shell, an array is the
+//! command and its arguments. `name` is the job name template; without any
+//! `{param}` the values are appended to it in the order of the parameter
+//! names, alphabetical: `name = "test"` gives `test-1-aarch64-unknown-…`.
+//! Characters not allowed in job names become `_`. `exclude` leaves out the
+//! combinations matching every parameter listed in one of its entries.
+//!
+//! Every job is checked before the first one starts. `pend matrix` prints
+//! the job names, one per line, so the group is waited on with
+//! `pend matrix m.toml | pend wait @-`.
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::worker::JobOptions;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    name: String,
+    cmd: Cmd,
+    matrix: BTreeMap<String, Vec<toml::Value>>,
+    #[serde(default)]
+    exclude: Vec<BTreeMap<String, toml::Value>>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Cmd {
+    Line(String),
+    Args(Vec<String>),
+}
+
+/// One combination: every parameter with its value.
+type Combination = BTreeMap<String, String>;
+
+/// One job of the matrix, ready to be submitted.
+struct Job {
+    name: String,
+    cmd: Vec<OsString>,
+    options: JobOptions,
+}
+
+/// Entry point for `pend matrix <manifest>`.
+pub(crate) fn matrix(
+    config: &Config,
+    path: &Path,
+    dry_run: bool,
+    options: &JobOptions,
+) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|err| {
+        PendError::Usage(format!("cannot read manifest {}: {err}", path.display()))
+    })?;
+    let manifest: Manifest = toml::from_str(&text)
+        .map_err(|err| PendError::Usage(format!("invalid manifest {}: {err}", path.display())))?;
+    let jobs = expand(&manifest, options)?;
+
+    for job in &jobs {
+        if dry_run {
+            let cmd: Vec<_> = job.cmd.iter().map(|arg| arg.to_string_lossy()).collect();
+            println!("{}: {}", job.name, cmd.join(" "));
+        } else {
+            crate::job::do_job(config, &job.name, &job.cmd, &job.options)?;
+            println!("{}", job.name);
+        }
+    }
+    Ok(())
+}
+
+/// The jobs of the manifest, checked.
+fn expand(manifest: &Manifest, options: &JobOptions) -> Result<Vec<Job>> {
+    for (param, values) in &manifest.matrix {
+        if values.is_empty() {
+            return Err(PendError::Usage(format!(
+                "matrix parameter '{param}' has no values"
+            )));
+        }
+    }
+    if manifest.matrix.is_empty() {
+        return Err(PendError::Usage("the manifest's [matrix] is empty".into()));
+    }
+    let exclude = manifest
+        .exclude
+        .iter()
+        .map(|entry| {
+            entry
+                .iter()
+                .map(|(param, value)| {
+                    if !manifest.matrix.contains_key(param) {
+                        return Err(PendError::Usage(format!(
+                            "exclude names '{param}', which is not a matrix parameter"
+                        )));
+                    }
+                    Ok((param.clone(), value_text(param, value)?))
+                })
+                .collect::<Result<Combination>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut jobs = Vec::new();
+    let mut seen = HashSet::new();
+    for combination in combinations(&manifest.matrix)? {
+        let excluded = exclude.iter().any(|entry| {
+            entry
+                .iter()
+                .all(|(param, value)| combination.get(param) == Some(value))
+        });
+        if excluded {
+            continue;
+        }
+        let name = job_name(&manifest.name, &combination);
+        crate::job::validate_name(&name)?;
+        if !seen.insert(name.clone()) {
+            return Err(PendError::Usage(format!(
+                "name '{}' gives several combinations the job name '{name}'",
+                manifest.name
+            )));
+        }
+        let mut options = options.clone();
+        let cmd = match &manifest.cmd {
+            Cmd::Line(line) => {
+                options.shell = true;
+                vec![fill(line, &combination).into()]
+            }
+            Cmd::Args(args) => args
+                .iter()
+                .map(|arg| fill(arg, &combination).into())
+                .collect(),
+        };
+        options.env.extend(
+            manifest
+                .env
+                .iter()
+                .map(|(var, value)| (var.clone(), fill(value, &combination))),
+        );
+        jobs.push(Job { name, cmd, options });
+    }
+    if jobs.is_empty() {
+        return Err(PendError::Usage(
+            "exclude leaves no combination of the matrix".into(),
+        ));
+    }
+    Ok(jobs)
+}
+
+/// Every combination of the parameters' values, the last parameter
+/// varying fastest.
+fn combinations(matrix: &BTreeMap<String, Vec<toml::Value>>) -> Result<Vec<Combination>> {
+    let mut combinations = vec![Combination::new()];
+    for (param, values) in matrix {
+        let values = values
+            .iter()
+            .map(|value| value_text(param, value))
+            .collect::<Result<Vec<_>>>()?;
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.insert(param.clone(), value.clone());
+                    combination
+                })
+            })
+            .collect();
+    }
+    Ok(combinations)
+}
+
+/// A parameter value as substituted: strings as they are, numbers and
+/// booleans as written.
+fn value_text(param: &str, value: &toml::Value) -> Result<String> {
+    match value {
+        toml::Value::String(text) => Ok(text.clone()),
+        toml::Value::Integer(n) => Ok(n.to_string()),
+        toml::Value::Float(n) => Ok(n.to_string()),
+        toml::Value::Boolean(b) => Ok(b.to_string()),
+        _ => Err(PendError::Usage(format!(
+            "matrix parameter '{param}' must have strings, numbers or booleans as values"
+        ))),
+    }
+}
+
+/// `template` with every `{param}` replaced by its value.
+fn fill(template: &str, combination: &Combination) -> String {
+    combination
+        .iter()
+        .fold(template.to_string(), |text, (param, value)| {
+            text.replace(&format!("{{{param}}}"), value)
+        })
+}
+
+/// The job name of `combination` under the `name` template.
+fn job_name(template: &str, combination: &Combination) -> String {
+    let sanitized: Combination = combination
+        .iter()
+        .map(|(param, value)| (param.clone(), crate::map::sanitize(value)))
+        .collect();
+    if combination
+        .keys()
+        .any(|param| template.contains(&format!("{{{param}}}")))
+    {
+        fill(template, &sanitized)
+    } else {
+        std::iter::once(template.to_string())
+            .chain(sanitized.into_values())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+}
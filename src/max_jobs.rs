@@ -0,0 +1,78 @@
+//! `pend do --max-jobs N`: a cap on the jobs running at once.
+//!
+//! A script that fires off `pend do` in a loop can easily start hundreds
+//! of workers on a shared build machine. With a cap – from `--max-jobs` or
+//! the `max_jobs` setting of a profile – `pend do` counts the jobs of the
+//! jobs root whose worker is alive (holding its `.lock` without having
+//! written `.exit`) before starting another one, and refuses with
+//! [`PendError::TooManyJobs`] once `N` run. `--queue` waits for one of them
+//! to finish instead.
+//!
+//! The count and the start happen under `<jobs root>/slots/max-jobs.lock`,
+//! so concurrent `pend do` calls cannot both take the last free place.
+//! Jobs waiting for `--after` or `--slot` count as running: their workers
+//! are.
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::lock::JobLock;
+use crate::worker::JobOptions;
+
+/// How often a queued `pend do` counts the running jobs again.
+const QUEUE_POLL: Duration = Duration::from_millis(200);
+
+/// How often taking the lock around the count is retried.
+const LOCK_POLL: Duration = Duration::from_millis(10);
+
+/// Wait until fewer than `--max-jobs` jobs besides `job_name` run. The
+/// returned lock keeps others from counting until the caller has started
+/// its worker; `None` when there is no cap.
+pub(crate) fn admit(
+    config: &Config,
+    job_name: &str,
+    options: &JobOptions,
+) -> Result<Option<JobLock>> {
+    let Some(limit) = options.max_jobs else {
+        return Ok(None);
+    };
+    let root = crate::paths::jobs_root(config)?;
+    let dir = root.join("slots");
+    std::fs::create_dir_all(&dir)?;
+    let lock_path = dir.join("max-jobs.lock");
+
+    let mut announced = false;
+    loop {
+        let lock = loop {
+            if let Some(lock) = JobLock::try_acquire(&lock_path)? {
+                break lock;
+            }
+            std::thread::sleep(LOCK_POLL);
+        };
+        let running = running_jobs(&root, job_name);
+        if running < limit as usize {
+            tracing::debug!(running, limit, "admitted under --max-jobs");
+            return Ok(Some(lock));
+        }
+        drop(lock);
+        if !options.queue {
+            return Err(PendError::TooManyJobs { running, limit });
+        }
+        if !announced {
+            eprintln!(
+                "pend: {running} jobs are running (--max-jobs {limit}); '{job_name}' waits for one to finish"
+            );
+            announced = true;
+        }
+        std::thread::sleep(QUEUE_POLL);
+    }
+}
+
+/// How many jobs in `root` other than `except` have a live worker.
+fn running_jobs(root: &Path, except: &str) -> usize {
+    crate::status::running_jobs(root)
+        .iter()
+        .filter(|job| *job != except)
+        .count()
+}
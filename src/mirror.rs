@@ -0,0 +1,191 @@
+//! `pend do --mirror-fifo PATH`: a live copy of the output for another
+//! process.
+//!
+//! The worker writes everything that goes into `.log` to the FIFO as well,
+//! so a consumer can `cat` it – or a log shipper can read it – as the job
+//! runs instead of polling the log. `pend do` creates the FIFO when it does
+//! not exist yet; any other writable file works too. The job never waits
+//! for the reader: the FIFO is written without blocking, and the worker
+//! keeps trying to open it while nobody reads. What a slow or absent
+//! reader cannot take follows `--mirror-policy`:
+//!
+//! * `drop` (the default) discards it, finishing only a chunk already
+//!   begun so the reader never sees half of one;
+//! * `buffer` holds up to [`BUFFER_LIMIT`] bytes for the reader to catch up
+//!   on, dropping the oldest beyond that.
+//!
+//! The FIFO stays open across retries and restarts; the reader sees the end
+//! of the stream once the worker finishes. Unix only.
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::error::{PendError, Result};
+
+/// Most output `--mirror-policy buffer` holds for a reader.
+const BUFFER_LIMIT: usize = 1024 * 1024;
+
+/// How often opening the FIFO is retried while nobody reads it.
+const REOPEN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What becomes of output the reader cannot take right away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum MirrorPolicy {
+    #[default]
+    Drop,
+    Buffer,
+}
+
+/// Check `--mirror-fifo` on `pend do`, creating the FIFO if needed.
+pub(crate) fn prepare(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        if path.exists() {
+            return Ok(());
+        }
+        use std::os::unix::ffi::OsStrExt;
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| PendError::Usage("--mirror-fifo: path contains a NUL byte".into()))?;
+        // Safety: `c_path` is a valid NUL-terminated string.
+        if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+            let err = io::Error::last_os_error();
+            return Err(PendError::Usage(format!(
+                "--mirror-fifo: cannot create {}: {err}",
+                path.display()
+            )));
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Err(PendError::Usage(
+            "--mirror-fifo is only available on Unix".into(),
+        ))
+    }
+}
+
+/// The worker's end of the FIFO.
+pub(crate) struct Mirror {
+    path: PathBuf,
+    policy: MirrorPolicy,
+    file: Option<std::fs::File>,
+    last_open: Option<Instant>,
+    /// Output not written yet, oldest first.
+    queue: VecDeque<Vec<u8>>,
+    queued: usize,
+    /// Bytes of the front chunk already written.
+    written: usize,
+}
+
+impl Mirror {
+    pub(crate) fn new(path: PathBuf, policy: MirrorPolicy) -> Self {
+        Self {
+            path,
+            policy,
+            file: None,
+            last_open: None,
+            queue: VecDeque::new(),
+            queued: 0,
+            written: 0,
+        }
+    }
+
+    /// Pass on `chunk`, or keep or drop it as the policy says.
+    pub(crate) fn write(&mut self, chunk: &[u8]) {
+        self.flush();
+        match self.policy {
+            // A chunk already begun is still finished.
+            MirrorPolicy::Drop if !self.queue.is_empty() => return,
+            MirrorPolicy::Drop => {}
+            MirrorPolicy::Buffer => {
+                // The oldest chunk goes, unless it is being written.
+                let oldest = usize::from(self.written > 0);
+                while self.queued + chunk.len() > BUFFER_LIMIT {
+                    let Some(dropped) = self.queue.remove(oldest) else {
+                        break;
+                    };
+                    self.queued -= dropped.len();
+                }
+            }
+        }
+        self.queued += chunk.len();
+        self.queue.push_back(chunk.to_vec());
+        self.flush();
+        if self.policy == MirrorPolicy::Drop && self.written == 0 {
+            // Nothing of it went out; drop it rather than keep it.
+            if let Some(unsent) = self.queue.pop_front() {
+                self.queued -= unsent.len();
+            }
+        }
+    }
+
+    /// Write as much of the queue as the reader takes without blocking.
+    fn flush(&mut self) {
+        while !self.queue.is_empty() && self.reader_present() {
+            let (Some(file), Some(front)) = (&mut self.file, self.queue.front()) else {
+                return;
+            };
+            let len = front.len();
+            match io::Write::write(file, &front[self.written..]) {
+                Ok(n) => {
+                    self.written += n;
+                    if self.written == len {
+                        self.queued -= len;
+                        self.queue.pop_front();
+                        self.written = 0;
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                // The reader went away; a new one starts at a chunk
+                // boundary.
+                Err(err) => {
+                    tracing::debug!(%err, "mirror reader gone");
+                    self.file = None;
+                    if self.written > 0 {
+                        if let Some(torn) = self.queue.pop_front() {
+                            self.queued -= torn.len();
+                        }
+                        self.written = 0;
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Whether the FIFO is open, opening it when a reader may have arrived.
+    fn reader_present(&mut self) -> bool {
+        if self.file.is_none()
+            && self
+                .last_open
+                .is_none_or(|at| at.elapsed() >= REOPEN_INTERVAL)
+        {
+            self.last_open = Some(Instant::now());
+            self.file = open_nonblocking(&self.path)
+                .inspect_err(|err| tracing::trace!(%err, "mirror has no reader"))
+                .ok();
+        }
+        self.file.is_some()
+    }
+}
+
+#[cfg(unix)]
+fn open_nonblocking(path: &Path) -> io::Result<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    // Without a reader, opening a FIFO for writing fails with ENXIO rather
+    // than waiting for one.
+    std::fs::OpenOptions::new()
+        .append(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn open_nonblocking(_path: &Path) -> io::Result<std::fs::File> {
+    Err(io::ErrorKind::Unsupported.into())
+}
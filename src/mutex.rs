@@ -0,0 +1,196 @@
+//! `pend lock` / `pend unlock`: named locks for scripts.
+//!
+//! The locks that keep two workers off one job serve ad-hoc scripts just as
+//! well. A lock called `NAME` is the file `<jobs root>/locks/NAME.lock`,
+//! locked the same way as job locks (see `lock.rs`), so it works across
+//! shells, users sharing a jobs directory and – with the lockfile strategy
+//! – machines sharing it over NFS.
+//!
+//! `pend lock NAME -- CMD …` takes the lock, waiting while somebody else
+//! holds it (`--no-wait` fails with exit code 4 instead), runs the command
+//! in the foreground and releases the lock when it exits, passing on its
+//! exit code. `pend lock NAME` without a command leaves the lock to a
+//! detached holder process until `pend unlock NAME` asks it to let go by
+//! creating `NAME.release` next to the lock:
+//!
+//! ```sh
+//! pend lock deploy
+//! ./migrate && ./restart
+//! pend unlock deploy
+//! ```
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::lock::JobLock;
+
+/// How often a waiting `pend lock` tries the lock again.
+const LOCK_POLL: Duration = Duration::from_millis(100);
+
+/// How often a holder looks for the release marker.
+const RELEASE_POLL: Duration = Duration::from_millis(100);
+
+/// How long `pend unlock` waits for the holder to let go.
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Clap value parser for lock names: letters, digits, `-` and `_`, as for
+/// slots.
+pub(crate) fn parse_name(name: &str) -> std::result::Result<String, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid lock name '{name}' (use letters, digits, '-' and '_')"
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// The lock file and release marker of the lock `name`.
+fn lock_paths(config: &Config, name: &str) -> Result<(PathBuf, PathBuf)> {
+    let dir = crate::paths::jobs_root(config)?.join("locks");
+    std::fs::create_dir_all(&dir)?;
+    Ok((
+        dir.join(format!("{name}.lock")),
+        dir.join(format!("{name}.release")),
+    ))
+}
+
+/// Entry point for `pend lock NAME [-- CMD …]`. Returns the exit code to
+/// exit with.
+pub(crate) fn lock(config: &Config, name: &str, no_wait: bool, cmd: &[OsString]) -> Result<i32> {
+    let (path, release) = lock_paths(config, name)?;
+    let lock = acquire(name, &path, no_wait)?;
+    tracing::debug!(lock = name, "lock taken");
+
+    if cmd.is_empty() {
+        // A marker left behind by an `unlock` that gave up must not release
+        // the new holder at once.
+        let _ = std::fs::remove_file(&release);
+        let mut holder = spawn_holder(config, name, lock.handoff_token().as_deref())?;
+        lock.hand_over(holder.id())?;
+        // Until the holder has the lock, a lockfile still names us – and we
+        // are about to exit, which would make it look abandoned.
+        let mut line = String::new();
+        if let Some(stdout) = holder.stdout.take() {
+            BufReader::new(stdout).read_line(&mut line)?;
+        }
+        if line.is_empty() {
+            return Err(PendError::LockHeld(name.to_string()));
+        }
+        return Ok(0);
+    }
+
+    let code = match crate::shell::command(cmd).status() {
+        Ok(status) => exit_code(status),
+        Err(err) => {
+            eprintln!("pend: cannot run {}: {err}", cmd[0].to_string_lossy());
+            // What shells report for a command they cannot run.
+            127
+        }
+    };
+    drop(lock);
+    Ok(code)
+}
+
+/// Take the lock at `path`, waiting unless `no_wait` is set.
+fn acquire(name: &str, path: &Path, no_wait: bool) -> Result<JobLock> {
+    let mut announced = false;
+    loop {
+        if let Some(lock) = JobLock::try_acquire(path)? {
+            return Ok(lock);
+        }
+        if no_wait {
+            return Err(PendError::LockHeld(name.to_string()));
+        }
+        if !announced {
+            eprintln!("pend: waiting for lock '{name}'");
+            announced = true;
+        }
+        std::thread::sleep(LOCK_POLL);
+    }
+}
+
+/// Start the detached process that keeps the lock until `pend unlock`. It
+/// prints a line once it took the lock over.
+fn spawn_holder(config: &Config, name: &str, handoff: Option<&str>) -> Result<Child> {
+    let mut holder = Command::new(std::env::current_exe()?);
+    holder.arg("hold-lock").arg(name);
+    config.export(&mut holder);
+    crate::diag::export(&mut holder);
+    if let Some(token) = handoff {
+        holder.env("PEND_LOCK_HANDOFF", token);
+    }
+    crate::process::detach(&mut holder);
+    holder
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    Ok(holder.spawn()?)
+}
+
+/// Entry point of the hidden `hold-lock` sub-command: take over the lock
+/// `pend lock` handed to us and keep it until released.
+pub(crate) fn hold(config: &Config, name: &str) -> Result<()> {
+    let (path, release) = lock_paths(config, name)?;
+    let handoff = std::env::var("PEND_LOCK_HANDOFF").ok();
+    let deadline = Instant::now() + RELEASE_TIMEOUT;
+    let lock = loop {
+        if let Some(lock) = JobLock::adopt(&path, handoff.as_deref())? {
+            break lock;
+        }
+        if Instant::now() >= deadline {
+            return Err(PendError::LockHeld(name.to_string()));
+        }
+        std::thread::sleep(LOCK_POLL);
+    };
+    // `pend lock` may exit now; the pipe goes with it.
+    let _ = writeln!(std::io::stdout(), "locked");
+    while !release.exists() {
+        std::thread::sleep(RELEASE_POLL);
+    }
+    let _ = std::fs::remove_file(&release);
+    drop(lock);
+    tracing::debug!(lock = name, "lock released");
+    Ok(())
+}
+
+/// Entry point for `pend unlock NAME`.
+pub(crate) fn unlock(config: &Config, name: &str) -> Result<()> {
+    let (path, release) = lock_paths(config, name)?;
+    if !crate::lock::is_held(&path) {
+        return Err(PendError::Usage(format!("lock '{name}' is not held")));
+    }
+    std::fs::write(&release, "")?;
+    let deadline = Instant::now() + RELEASE_TIMEOUT;
+    while crate::lock::is_held(&path) {
+        if Instant::now() >= deadline {
+            let _ = std::fs::remove_file(&release);
+            return Err(PendError::Usage(format!(
+                "lock '{name}' is held by a running `pend lock {name} -- …`; \
+                 it is released when the command exits"
+            )));
+        }
+        std::thread::sleep(RELEASE_POLL);
+    }
+    Ok(())
+}
+
+/// The code to exit with for a command that ended with `status`: its exit
+/// code, or `128 + n` when signal `n` killed it, as shells report it.
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
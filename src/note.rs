@@ -0,0 +1,52 @@
+//! Free-text job notes: `pend do --note` and `pend annotate`.
+//!
+//! The note is the `note` field of `<job>.json`. The worker rewrites that
+//! file while the job runs, so it takes the note from disk before every
+//! write; both sides replace the file in one step (see
+//! [`JobPaths::write_meta`]) so an annotation is never half-read.
+use std::fs;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+
+/// The note currently recorded for a job: `None` when there is no readable
+/// metadata, `Some(None)` when it has no note.
+pub(crate) fn read(paths: &JobPaths) -> Option<Option<String>> {
+    let bytes = fs::read(&paths.meta).ok()?;
+    let meta: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    let fields = meta.as_object()?;
+    Some(
+        fields
+            .get("note")
+            .and_then(|n| n.as_str())
+            .map(String::from),
+    )
+}
+
+/// Entry point for `pend annotate <job> <note>`.
+pub(crate) fn annotate(config: &Config, job_name: &str, note: &str) -> Result<()> {
+    let paths = JobPaths::new(config, job_name)?;
+    let bytes = fs::read(&paths.meta).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => PendError::NotFound(job_name.to_string()),
+        _ => err.into(),
+    })?;
+    let corrupt = |reason: &str| PendError::Corrupt {
+        path: paths.meta.clone(),
+        reason: reason.to_string(),
+    };
+    let mut meta: serde_json::Value =
+        serde_json::from_slice(&bytes).map_err(|_| corrupt("invalid JSON"))?;
+    let fields = meta
+        .as_object_mut()
+        .ok_or_else(|| corrupt("expected a JSON object"))?;
+
+    if note.trim().is_empty() {
+        fields.remove("note");
+    } else {
+        fields.insert("note".into(), note.into());
+    }
+    let json = serde_json::to_vec_pretty(&meta).map_err(std::io::Error::from)?;
+    paths.write_meta(&json)?;
+    Ok(())
+}
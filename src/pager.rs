@@ -0,0 +1,139 @@
+//! `pend wait --pager`: page the replay of finished jobs like `git log`.
+//!
+//! The pager is `$PEND_PAGER`, else `$PAGER`, else `less`, run through the
+//! platform shell so values such as `less -S` work; an empty value or
+//! `cat` turns paging off. Unless `LESS` is set it is started with
+//! `LESS=FRX`, so output that fits the screen is printed as is, colours
+//! pass through, and the screen is not cleared on exit.
+//!
+//! While paging, pend's stdout and stderr both point at the pager's stdin:
+//! the replay writes to them directly, and a job's stderr belongs in the
+//! pager as much as its stdout. Both are restored before pend reports
+//! anything of its own.
+use std::ffi::OsString;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, ChildStdin, Stdio};
+
+/// A running pager receiving pend's stdout and stderr. Dropping it hands
+/// the output back to the terminal and waits for the user to quit.
+pub(crate) struct Pager {
+    child: Child,
+    #[cfg(unix)]
+    saved: [std::os::fd::RawFd; 2],
+    #[cfg(windows)]
+    saved: [windows_sys::Win32::Foundation::HANDLE; 2],
+    #[cfg(windows)]
+    stdin: Option<ChildStdin>,
+}
+
+impl Pager {
+    /// Start the pager when stdout is a terminal and one is configured.
+    pub(crate) fn start() -> Option<Self> {
+        if !io::stdout().is_terminal() {
+            return None;
+        }
+        let command = std::env::var_os("PEND_PAGER")
+            .or_else(|| std::env::var_os("PAGER"))
+            .unwrap_or_else(|| OsString::from("less"));
+        if command.is_empty() || command == "cat" {
+            return None;
+        }
+        let mut pager = crate::shell::shell(&command);
+        if std::env::var_os("LESS").is_none() {
+            pager.env("LESS", "FRX");
+        }
+        let mut child = match pager.stdin(Stdio::piped()).spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                tracing::debug!(%err, "cannot start the pager");
+                return None;
+            }
+        };
+        let stdin = child.stdin.take()?;
+        crate::color::set_paged();
+        Some(redirect(child, stdin))
+    }
+}
+
+#[cfg(unix)]
+fn redirect(child: Child, stdin: ChildStdin) -> Pager {
+    let saved = unsafe { [libc::dup(1), libc::dup(2)] };
+    point_at(&stdin);
+    // Only stdout and stderr keep the pipe open now.
+    drop(stdin);
+    Pager { child, saved }
+}
+
+#[cfg(windows)]
+fn redirect(child: Child, stdin: ChildStdin) -> Pager {
+    use windows_sys::Win32::System::Console::{GetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE};
+    let saved = unsafe {
+        [
+            GetStdHandle(STD_OUTPUT_HANDLE),
+            GetStdHandle(STD_ERROR_HANDLE),
+        ]
+    };
+    point_at(&stdin);
+    Pager {
+        child,
+        saved,
+        stdin: Some(stdin),
+    }
+}
+
+#[cfg(unix)]
+const NULL_DEVICE: &str = "/dev/null";
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+
+/// Make stdout and stderr write to `file`.
+#[cfg(unix)]
+fn point_at(file: &impl std::os::fd::AsRawFd) {
+    unsafe {
+        libc::dup2(file.as_raw_fd(), 1);
+        libc::dup2(file.as_raw_fd(), 2);
+    }
+}
+
+#[cfg(windows)]
+fn point_at(file: &impl std::os::windows::io::AsRawHandle) {
+    use windows_sys::Win32::System::Console::{SetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE};
+    // The standard library looks the handles up on every write.
+    unsafe {
+        SetStdHandle(STD_OUTPUT_HANDLE, file.as_raw_handle());
+        SetStdHandle(STD_ERROR_HANDLE, file.as_raw_handle());
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        // Output the pager quit before reading stays buffered after a failed
+        // flush; it must not surface on the terminal later.
+        if io::stdout().flush().is_err() || io::stderr().flush().is_err() {
+            if let Ok(null) = std::fs::OpenOptions::new().write(true).open(NULL_DEVICE) {
+                point_at(&null);
+                let _ = io::stdout().flush();
+                let _ = io::stderr().flush();
+            }
+        }
+        #[cfg(unix)]
+        unsafe {
+            libc::dup2(self.saved[0], 1);
+            libc::dup2(self.saved[1], 2);
+            libc::close(self.saved[0]);
+            libc::close(self.saved[1]);
+        }
+        #[cfg(windows)]
+        unsafe {
+            use windows_sys::Win32::System::Console::{
+                SetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE,
+            };
+            SetStdHandle(STD_OUTPUT_HANDLE, self.saved[0]);
+            SetStdHandle(STD_ERROR_HANDLE, self.saved[1]);
+        }
+        // The pager sees the end of its input once the pipe is closed.
+        #[cfg(windows)]
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
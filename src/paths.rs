@@ -1,16 +1,20 @@
 //! Centralised handling of on-disk artifact locations.
 //!
 //! All files created by *pend* live in a single directory which defaults to
-//! `$TMPDIR/pend` but can be overridden through the environment variable
-//! `PEND_DIR`. Grouping paths in the [`JobPaths`] struct keeps the logic for
-//! constructing and validating those filenames in one place and avoids ad-hoc
-//! string formatting throughout the code base.
+//! `$TMPDIR/pend` but can be overridden through `--dir` or the environment
+//! variable `PEND_DIR`, as resolved into `config::Config`. Grouping paths in
+//! the [`JobPaths`] struct keeps the logic for constructing and validating
+//! those filenames in one place and avoids ad-hoc string formatting
+//! throughout the code base.
 //!
 //! Responsibilities:
 //!   • Create / ensure the root directory exists (including environment
 //!     override).
 //!   • Derive deterministic filenames for the various artifacts
-//!     (`.out`, `.err`, `.log`, `.exit`, `.json`, `.lock`, `.signal`).
+//!     (`.out`, `.err`, `.log`, `.idx`, `.frames`, `.exit`, `.json`, `.lock`,
+//!     `.signal`, `.cancel`, `.sock`, `.status`, `.progress`, `.result`,
+//!     `.pend-debug.log`).
+//!   • Discover which jobs exist inside the root directory.
 //!   • Reject paths that would exceed platform path length limits *up front*
 //!     so that callers get a clear error instead of an obscure I/O failure
 //!     half-way through execution.
@@ -19,23 +23,240 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
-/// Return the directory into which all job artifacts are written.
-///
-/// Users can override the default temporary location by setting the
-/// `PEND_DIR` environment variable.
+use crate::config::Config;
+
+/// Environment variable that, set to anything but `0`, gives every user a
+/// subdirectory of the jobs root so job names on a shared `PEND_DIR` cannot
+/// collide across users.
+pub(crate) const PER_USER_ENV: &str = "PEND_PER_USER";
+
 /// Determine the directory into which all job artifacts are written and ensure
-/// that it exists on the file system.
-pub(crate) fn jobs_root() -> io::Result<PathBuf> {
-    if let Ok(p) = env::var("PEND_DIR") {
-        let path = PathBuf::from(p);
-        fs::create_dir_all(&path)?;
-        Ok(path)
-    } else {
-        let mut dir = env::temp_dir();
-        dir.push("pend");
-        fs::create_dir_all(&dir)?;
-        Ok(dir)
+/// that it exists on the file system. With [`PER_USER_ENV`] set this is the
+/// current user's subdirectory `<root>/<user>`.
+pub(crate) fn jobs_root(config: &Config) -> io::Result<PathBuf> {
+    let dir = jobs_root_path(config)?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// [`jobs_root`] without creating it.
+pub(crate) fn jobs_root_path(config: &Config) -> io::Result<PathBuf> {
+    let mut dir = config.dir.clone();
+    if config.per_user {
+        dir.push(current_user()?);
+    }
+    Ok(dir)
+}
+
+/// Environment variable that, set to anything but `0`, moves the default
+/// jobs root from the temporary directory to [`persistent_root`].
+pub(crate) const PERSISTENT_ENV: &str = "PEND_PERSISTENT";
+
+/// The jobs root when no option or profile picks one: `PEND_DIR`, else
+/// `$TMPDIR/pend` (the persistent root with [`PERSISTENT_ENV`]).
+pub(crate) fn default_root() -> io::Result<PathBuf> {
+    if let Some(dir) = env::var_os("PEND_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    if env_flag(PERSISTENT_ENV) {
+        return persistent_root();
+    }
+    Ok(env::temp_dir().join("pend"))
+}
+
+/// A jobs root that survives reboots and temp cleaners: the XDG state
+/// directory (`$XDG_STATE_HOME/pend`, default `~/.local/state/pend`), on
+/// macOS `~/Library/Application Support/pend` and on Windows
+/// `%LOCALAPPDATA%\pend`.
+pub(crate) fn persistent_root() -> io::Result<PathBuf> {
+    let not_found = |var: &str| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("cannot locate the persistent jobs root: {var} is not set"),
+        )
+    };
+    let non_empty = |var: &str| {
+        env::var_os(var)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    #[cfg(windows)]
+    let base = non_empty("LOCALAPPDATA").ok_or_else(|| not_found("LOCALAPPDATA"))?;
+    #[cfg(target_os = "macos")]
+    let base = non_empty("HOME")
+        .ok_or_else(|| not_found("HOME"))?
+        .join("Library/Application Support");
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let base = match non_empty("XDG_STATE_HOME") {
+        Some(dir) => dir,
+        None => non_empty("HOME")
+            .ok_or_else(|| not_found("HOME"))?
+            .join(".local/state"),
+    };
+    Ok(base.join("pend"))
+}
+
+/// Whether the switch `var` is on: set to anything but `0`.
+pub(crate) fn env_flag(var: &str) -> bool {
+    env::var(var).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+fn current_user() -> io::Result<String> {
+    #[cfg(unix)]
+    let name = crate::process::current_user_name();
+    #[cfg(windows)]
+    let name = env::var("USERNAME").ok();
+    match name {
+        Some(name) if !name.is_empty() && !name.contains(['/', '\\']) && name != ".." => Ok(name),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{PER_USER_ENV} is set but the current user name cannot be determined"),
+        )),
+    }
+}
+
+/// Every user's jobs root below the shared root, as `(user, dir)` sorted by
+/// user. Directories this user may not read are skipped.
+pub(crate) fn user_roots(config: &Config) -> Vec<(String, PathBuf)> {
+    let mut roots: Vec<(String, PathBuf)> = fs::read_dir(&config.dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+        .filter(|(_, dir)| fs::read_dir(dir).is_ok())
+        .collect();
+    roots.sort();
+    roots
+}
+
+/// Extensions of every artifact a job may leave behind. Rotated logs end up as
+/// `<job>.log.<n>` (or `<job>.log.<n>.gz`) where the trailing segments are
+/// *not* part of the canonical extension.
+pub(crate) const ARTIFACT_EXTENSIONS: [&str; 14] = [
+    "out", "err", "log", "idx", "frames", "exit", "json", "signal", "lock", "cancel", "sock",
+    "status", "progress", "result",
+];
+
+/// Extension of the worker's diagnostics log. It contains a dot itself, so
+/// it is matched separately from [`ARTIFACT_EXTENSIONS`].
+pub(crate) const DEBUG_LOG_EXTENSION: &str = "pend-debug.log";
+
+/// Return the names of all jobs that have at least one artifact inside
+/// `root`, sorted alphabetically.
+pub(crate) fn discover_jobs(root: &Path) -> Vec<String> {
+    let mut set = std::collections::BTreeSet::new();
+    if let Ok(entries) = fs::read_dir(root) {
+        for entry in entries.flatten() {
+            if let Some(name) = entry.file_name().to_str() {
+                if let Some(job) = name
+                    .strip_suffix(DEBUG_LOG_EXTENSION)
+                    .and_then(|n| n.strip_suffix('.'))
+                {
+                    set.insert(job.to_string());
+                    continue;
+                }
+
+                // 1. Remove one or more purely numeric trailing segments
+                //    (e.g. `.log.1` → `.log`). This covers log rotation where
+                //    the current log is renamed to `<job>.log.<n>`.
+                //    Compressed generations carry an extra `.gz`.
+                let mut base = name.strip_suffix(".gz").unwrap_or(name);
+                while let Some((stem, ext)) = base.rsplit_once('.') {
+                    if !ext.is_empty() && ext.chars().all(|c| c.is_ascii_digit()) {
+                        base = stem;
+                    } else {
+                        break;
+                    }
+                }
+
+                // 2. Check for a recognised artifact extension.
+                if let Some((job, ext)) = base.rsplit_once('.') {
+                    if ARTIFACT_EXTENSIONS.contains(&ext) {
+                        set.insert(job.to_string());
+                    }
+                }
+            }
+        }
     }
+    set.into_iter().collect()
+}
+
+/// Expand shell-style patterns (`*`, `?`) among `names` against the jobs in
+/// `root`, so `pend wait 'convert-*'` covers a whole `pend map` fan-out.
+/// Plain names pass through unchanged; a pattern matching nothing is kept
+/// as-is so the caller reports it as not found.
+pub(crate) fn expand_patterns(root: &Path, names: &[String]) -> Vec<String> {
+    let mut jobs: Option<Vec<String>> = None;
+    let mut expanded = Vec::new();
+    for name in names {
+        if !name.contains(['*', '?']) {
+            expanded.push(name.clone());
+            continue;
+        }
+        let jobs = jobs.get_or_insert_with(|| discover_jobs(root));
+        let matches: Vec<&String> = jobs.iter().filter(|job| glob_match(name, job)).collect();
+        if matches.is_empty() {
+            expanded.push(name.clone());
+        }
+        for job in matches {
+            if !expanded.contains(job) {
+                expanded.push(job.clone());
+            }
+        }
+    }
+    expanded
+}
+
+/// The jobs in `root` matching any of `patterns`, or all of them when
+/// there are none; for `pend wait --all`.
+pub(crate) fn select_jobs(root: &Path, patterns: &[String]) -> Vec<String> {
+    discover_jobs(root)
+        .into_iter()
+        .filter(|job| patterns.is_empty() || patterns.iter().any(|p| glob_match(p, job)))
+        .collect()
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Classic backtracking matcher: remember the last `*` and retry from
+    // one character further whenever the rest fails to match.
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Write `contents` to a temporary sibling of `path` and rename it over
+/// `path`.
+pub(crate) fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp = path.to_path_buf().into_os_string();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
 }
 
 /// Helper holding all paths used for a given job name.
@@ -46,21 +267,53 @@ pub(crate) struct JobPaths {
     pub(crate) exit: PathBuf,
     pub(crate) meta: PathBuf,
     pub(crate) log: PathBuf,
+    /// Byte ranges of `.log` that came from stderr, one `<offset> <len>` line
+    /// per chunk, so replay can route them back to stderr.
+    pub(crate) idx: PathBuf,
+    /// Checksummed records of `.log` (`--checksum-log`, see `frames.rs`).
+    pub(crate) frames: PathBuf,
     pub(crate) lock: PathBuf,
     pub(crate) signal: PathBuf,
+    /// Marker written by `pend kill` asking the worker to stop and skip any
+    /// remaining retries.
+    pub(crate) cancel: PathBuf,
+    /// Control socket of the running worker (on Windows the name of its
+    /// pipe, see `control.rs`).
+    pub(crate) sock: PathBuf,
+    /// The worker's diagnostics log (`PEND_LOG` / `-v`, see `diag.rs`).
+    pub(crate) debug: PathBuf,
+    /// One line describing what the worker is doing right now
+    /// (`running attempt 2/3`, `timed out, retrying`, …).
+    pub(crate) status: PathBuf,
+    /// Latest progress reported by the command (see `report.rs`).
+    pub(crate) progress: PathBuf,
+    /// One-line outcome of the finished job (see `quick.rs`).
+    pub(crate) result: PathBuf,
 }
 
 impl JobPaths {
-    pub(crate) fn new(job_name: &str) -> io::Result<Self> {
-        let root = jobs_root()?;
+    pub(crate) fn new(config: &Config, job_name: &str) -> io::Result<Self> {
+        Self::in_root(&jobs_root(config)?, job_name)
+    }
+
+    /// Paths of `job_name` inside `root` rather than the current jobs root.
+    pub(crate) fn in_root(root: &Path, job_name: &str) -> io::Result<Self> {
         let paths = Self {
             out: root.join(format!("{}.out", job_name)),
             err: root.join(format!("{}.err", job_name)),
             exit: root.join(format!("{}.exit", job_name)),
             meta: root.join(format!("{}.json", job_name)),
             log: root.join(format!("{}.log", job_name)),
+            idx: root.join(format!("{}.idx", job_name)),
+            frames: root.join(format!("{}.frames", job_name)),
             lock: root.join(format!("{}.lock", job_name)),
             signal: root.join(format!("{}.signal", job_name)),
+            cancel: root.join(format!("{}.cancel", job_name)),
+            sock: root.join(format!("{}.sock", job_name)),
+            debug: root.join(format!("{}.{}", job_name, DEBUG_LOG_EXTENSION)),
+            status: root.join(format!("{}.status", job_name)),
+            progress: root.join(format!("{}.progress", job_name)),
+            result: root.join(format!("{}.result", job_name)),
         };
 
         paths.assert_paths_within_limit()?;
@@ -77,15 +330,7 @@ impl JobPaths {
         #[cfg(unix)]
         const MAX_PATH: usize = 4096; // typical PATH_MAX on Linux/Unix
 
-        for path in [
-            &self.out,
-            &self.err,
-            &self.exit,
-            &self.meta,
-            &self.log,
-            &self.lock,
-            &self.signal,
-        ] {
+        for path in self.all() {
             if let Some(s) = path.to_str() {
                 if s.len() >= MAX_PATH {
                     return Err(io::Error::new(
@@ -103,6 +348,65 @@ impl JobPaths {
         Ok(())
     }
 
+    /// Every artifact path of the job, in no particular order.
+    pub(crate) fn all(&self) -> [&PathBuf; 15] {
+        self.named().map(|(_, path)| path)
+    }
+
+    /// Every artifact path keyed by its extension.
+    pub(crate) fn named(&self) -> [(&'static str, &PathBuf); 15] {
+        [
+            ("out", &self.out),
+            ("err", &self.err),
+            ("exit", &self.exit),
+            ("json", &self.meta),
+            ("log", &self.log),
+            ("idx", &self.idx),
+            ("frames", &self.frames),
+            ("lock", &self.lock),
+            ("signal", &self.signal),
+            ("cancel", &self.cancel),
+            ("sock", &self.sock),
+            (DEBUG_LOG_EXTENSION, &self.debug),
+            ("status", &self.status),
+            ("progress", &self.progress),
+            ("result", &self.result),
+        ]
+    }
+
+    /// Rotated generations (`<job>.<ext>.<n>`, optionally `.gz`) of the
+    /// job's log streams.
+    pub(crate) fn rotated(&self) -> Vec<PathBuf> {
+        let mut found = Vec::new();
+        let Some(dir) = self.log.parent() else {
+            return found;
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return found;
+        };
+        let prefixes: Vec<String> = [&self.out, &self.err, &self.log]
+            .iter()
+            .filter_map(|p| p.file_name()?.to_str().map(|n| format!("{n}.")))
+            .collect();
+        for entry in entries.flatten() {
+            if let Some(name) = entry.file_name().to_str() {
+                if prefixes.iter().any(|p| name.starts_with(p.as_str())) {
+                    found.push(entry.path());
+                }
+            }
+        }
+        found
+    }
+
+    /// Delete every artifact of the job, rotated generations included.
+    /// Best effort: files that are already gone or cannot be removed are
+    /// skipped.
+    pub(crate) fn remove_all(&self) {
+        for path in self.all().into_iter().chain(&self.rotated()) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
     pub(crate) fn any_exist(&self) -> bool {
         self.out.exists()
             || self.err.exists()
@@ -118,6 +422,23 @@ impl JobPaths {
             || self.lock.exists()
     }
 
+    /// Replace `.json` in one step, so concurrent readers (and `pend
+    /// annotate` racing with the worker) never see a half-written file.
+    pub(crate) fn write_meta(&self, contents: &[u8]) -> io::Result<()> {
+        replace_file(&self.meta, contents)
+    }
+
+    /// Replace `.status` in one step (see [`Self::write_meta`]).
+    pub(crate) fn write_status(&self, line: &str) -> io::Result<()> {
+        replace_file(&self.status, format!("{line}\n").as_bytes())
+    }
+
+    /// The worker's current `.status` line, if any.
+    pub(crate) fn read_status(&self) -> Option<String> {
+        let text = fs::read_to_string(&self.status).ok()?;
+        Some(text.trim().to_string()).filter(|line| !line.is_empty())
+    }
+
     /// Generic helper returning the file size for the given path or `0` if the
     /// file does not exist. Used by the waiting helpers.
     pub(crate) fn file_len(path: &Path) -> u64 {
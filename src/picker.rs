@@ -0,0 +1,154 @@
+//! Pick a job interactively for `pend wait` without job names.
+//!
+//! Lists the jobs in the directory, most recently active first, with their
+//! state. Typing narrows the list to names containing the typed characters
+//! in order (`nb` matches `nightly-build`); the arrow keys move the
+//! selection, Enter picks it and Esc or Ctrl-C gives up. The list is drawn
+//! on stderr so stdout stays free for the job's output.
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, style, terminal};
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::{discover_jobs, jobs_root, JobPaths};
+use crate::status::{job_status, State};
+
+const PROMPT: &str = "wait for> ";
+
+/// A job as listed by the picker.
+struct Candidate {
+    job: String,
+    label: String,
+}
+
+/// Let the user choose a job; `None` when they cancel.
+pub(crate) fn pick(config: &Config) -> Result<Option<String>> {
+    let candidates = candidates(config)?;
+    if candidates.is_empty() {
+        return Err(PendError::Usage("there are no jobs to wait for".into()));
+    }
+
+    let mut stderr = io::stderr();
+    terminal::enable_raw_mode()?;
+    execute!(stderr, terminal::EnterAlternateScreen)?;
+    let picked = run(&mut stderr, &candidates);
+    execute!(stderr, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    Ok(picked?)
+}
+
+/// Every job with its state and when it was last active, newest first.
+fn candidates(config: &Config) -> io::Result<Vec<Candidate>> {
+    let mut jobs: Vec<(SystemTime, Candidate)> = discover_jobs(&jobs_root(config)?)
+        .into_iter()
+        .filter_map(|job| {
+            let paths = JobPaths::new(config, &job).ok()?;
+            let status = job_status(config, &job).ok()?;
+            let touched = [&paths.exit, &paths.log, &paths.meta]
+                .iter()
+                .filter_map(|path| path.metadata().and_then(|m| m.modified()).ok())
+                .max()
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let state = match (status.state, status.exit_code) {
+                (State::Finished, Some(code)) => format!("exit {code}"),
+                (State::Finished, None) => "finished".to_string(),
+                (State::Running, _) => "running".to_string(),
+                (State::Died, _) => "died".to_string(),
+            };
+            let when: DateTime<Local> = touched.into();
+            let label = format!("{job:<24} {state:<9} {}", when.format("%Y-%m-%d %H:%M"));
+            Some((touched, Candidate { job, label }))
+        })
+        .collect();
+    jobs.sort_by_key(|(touched, _)| std::cmp::Reverse(*touched));
+    Ok(jobs.into_iter().map(|(_, candidate)| candidate).collect())
+}
+
+/// Whether the characters of `query` occur in `name` in order, ignoring case.
+fn matches(name: &str, query: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| name.any(|n| n == c))
+}
+
+fn run(out: &mut impl Write, candidates: &[Candidate]) -> io::Result<Option<String>> {
+    let mut query = String::new();
+    let mut selected = 0;
+    loop {
+        let shown: Vec<&Candidate> = candidates
+            .iter()
+            .filter(|candidate| matches(&candidate.job, &query))
+            .collect();
+        selected = selected.min(shown.len().saturating_sub(1));
+        draw(out, &query, &shown, selected)?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Enter => return Ok(shown.get(selected).map(|c| c.job.clone())),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected += 1,
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(
+    out: &mut impl Write,
+    query: &str,
+    shown: &[&Candidate],
+    selected: usize,
+) -> io::Result<()> {
+    // Some pseudo-terminals report a size of zero.
+    let rows = terminal::size()
+        .ok()
+        .map(|(_, rows)| rows)
+        .filter(|&rows| rows > 2)
+        .unwrap_or(24) as usize
+        - 2;
+    // Keep the selection on screen.
+    let first = (selected + 1).saturating_sub(rows);
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All),
+        style::Print(format!("{PROMPT}{query}")),
+    )?;
+    for (row, candidate) in shown.iter().enumerate().skip(first).take(rows) {
+        queue!(out, cursor::MoveTo(0, (row - first + 2) as u16))?;
+        if row == selected {
+            queue!(
+                out,
+                style::SetAttribute(style::Attribute::Reverse),
+                style::Print(&candidate.label),
+                style::SetAttribute(style::Attribute::Reset),
+            )?;
+        } else {
+            queue!(out, style::Print(&candidate.label))?;
+        }
+    }
+    queue!(
+        out,
+        cursor::MoveTo((PROMPT.len() + query.chars().count()) as u16, 0)
+    )?;
+    out.flush()
+}
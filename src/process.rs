@@ -1,4 +1,12 @@
-//! Small cross-platform helper to query whether a given PID is currently alive.
+//! Small cross-platform helpers to query whether a given PID is currently alive
+//! and to terminate the processes started for a job.
+//!
+//! A bare PID can be recycled once its process is gone, so workers also record
+//! their start time; [`is_running`] and [`ProcessWatch`] check both.
+//!
+//! On Windows there are no process groups, so each attempt is additionally
+//! placed in a named Job Object (see [`JobObject`]) that owns the complete
+//! process tree.
 
 #[cfg(unix)]
 pub(crate) fn process_is_alive(pid: u32) -> bool {
@@ -11,15 +19,19 @@ pub(crate) fn process_is_alive(pid: u32) -> bool {
     // Fall back to a conservative answer on Windows – if we cannot confirm
     // the process has exited we assume it is still running so that callers
     // play it safe.
-    use std::ptr::null_mut;
     use windows_sys::Win32::Foundation::CloseHandle;
     use windows_sys::Win32::System::Threading::{
-        OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, WaitForSingleObject, INFINITE,
+        OpenProcess, WaitForSingleObject, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SYNCHRONIZE,
     };
 
     unsafe {
-        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
-        if handle == 0 {
+        // Waiting on the handle requires SYNCHRONIZE access.
+        let handle = OpenProcess(
+            PROCESS_SYNCHRONIZE | PROCESS_QUERY_LIMITED_INFORMATION,
+            0,
+            pid,
+        );
+        if handle.is_null() {
             return false;
         }
         // Query immediately whether the process has already exited.
@@ -30,3 +42,766 @@ pub(crate) fn process_is_alive(pid: u32) -> bool {
         alive
     }
 }
+
+/// When the process `pid` started, in an opaque unit that only needs to be
+/// comparable: ticks since boot on Linux, a `FILETIME` on Windows. Together
+/// the PID and its start time identify a process even after the PID has been
+/// reused. `None` where the platform does not tell (or the process is gone).
+#[cfg(target_os = "linux")]
+pub(crate) fn start_time(pid: u32) -> Option<u64> {
+    proc_stat(pid).map(|(_, started)| started)
+}
+
+/// State letter and start time of `pid` from `/proc/<pid>/stat`.
+#[cfg(target_os = "linux")]
+fn proc_stat(pid: u32) -> Option<(char, u64)> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name in parentheses may contain spaces; the state is the
+    // first field after it and `starttime` (field 22) the 20th.
+    let (_, rest) = stat.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    Some((state, fields.nth(18)?.parse().ok()?))
+}
+
+#[cfg(windows)]
+pub(crate) fn start_time(pid: u32) -> Option<u64> {
+    use windows_sys::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows_sys::Win32::System::Threading::{
+        GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let zero = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        let (mut created, mut exited, mut kernel, mut user) = (zero, zero, zero, zero);
+        let ok = GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user) != 0;
+        CloseHandle(handle);
+        ok.then(|| (u64::from(created.dwHighDateTime) << 32) | u64::from(created.dwLowDateTime))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub(crate) fn start_time(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Whether the process `pid` recorded as having started at `started` (see
+/// [`start_time`]) is still running. Without a recorded start time this is
+/// [`process_is_alive`], which a reused PID can fool.
+pub(crate) fn is_running(pid: u32, started: Option<u64>) -> bool {
+    if !process_is_alive(pid) {
+        return false;
+    }
+    // An exited process nobody has reaped yet still answers to its PID.
+    #[cfg(target_os = "linux")]
+    if proc_stat(pid).is_some_and(|(state, _)| state == 'Z') {
+        return false;
+    }
+    match (started, start_time(pid)) {
+        (Some(recorded), Some(actual)) => recorded == actual,
+        _ => true,
+    }
+}
+
+/// A process watched for its exit. Holds a pidfd (Linux) or process handle
+/// (Windows) on that very process, so exits are noticed at once and a
+/// reused PID cannot pass for it; elsewhere it falls back to [`is_running`].
+pub(crate) struct ProcessWatch {
+    pid: u32,
+    started: Option<u64>,
+    #[cfg(target_os = "linux")]
+    pidfd: Option<std::os::fd::OwnedFd>,
+    #[cfg(windows)]
+    handle: windows_sys::Win32::Foundation::HANDLE,
+}
+
+impl ProcessWatch {
+    /// Watch `pid`, which started at `started`. `None` when that process is
+    /// no longer running.
+    pub(crate) fn open(pid: u32, started: Option<u64>) -> Option<Self> {
+        #[cfg(target_os = "linux")]
+        let watch = {
+            use std::os::fd::FromRawFd;
+            // Safety: pidfd_open takes a PID and flags and returns a new fd.
+            let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+            Self {
+                pid,
+                started,
+                // Kernels before 5.3 lack pidfd_open; poll the PID instead.
+                pidfd: (fd >= 0).then(|| unsafe { std::os::fd::OwnedFd::from_raw_fd(fd as i32) }),
+            }
+        };
+        #[cfg(windows)]
+        let watch = {
+            use windows_sys::Win32::System::Threading::{
+                OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SYNCHRONIZE,
+            };
+            let handle = unsafe {
+                OpenProcess(
+                    PROCESS_SYNCHRONIZE | PROCESS_QUERY_LIMITED_INFORMATION,
+                    0,
+                    pid,
+                )
+            };
+            if handle.is_null() {
+                return None;
+            }
+            Self {
+                pid,
+                started,
+                handle,
+            }
+        };
+        #[cfg(not(any(target_os = "linux", windows)))]
+        let watch = Self { pid, started };
+
+        // The start time is checked once the process is held, so it cannot
+        // exit and hand its PID on in between.
+        is_running(pid, started).then_some(watch)
+    }
+
+    /// Whether the watched process has exited.
+    pub(crate) fn has_exited(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        if let Some(fd) = &self.pidfd {
+            use std::os::fd::AsRawFd;
+            // A pidfd becomes readable once its process terminates.
+            let mut poll = libc::pollfd {
+                fd: fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // Safety: one valid pollfd, no timeout.
+            return unsafe { libc::poll(&mut poll, 1, 0) } > 0;
+        }
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::Foundation::WAIT_OBJECT_0;
+            use windows_sys::Win32::System::Threading::WaitForSingleObject;
+            // Safety: the handle stays open for the lifetime of `self`.
+            return unsafe { WaitForSingleObject(self.handle, 0) } == WAIT_OBJECT_0;
+        }
+        #[allow(unreachable_code)]
+        !is_running(self.pid, self.started)
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessWatch {
+    fn drop(&mut self) {
+        // Safety: the handle was opened by `ProcessWatch::open`.
+        unsafe { windows_sys::Win32::Foundation::CloseHandle(self.handle) };
+    }
+}
+
+/// Ask the process `pid` and every process in its process group to terminate.
+///
+/// Workers place each user command in a dedicated process group (see
+/// `worker.rs`), so signalling the group reaches grandchildren spawned by
+/// shell scripts as well. Returns `false` when nothing could be signalled.
+#[cfg(unix)]
+pub(crate) fn terminate_process_tree(pid: u32) -> bool {
+    if pid == 0 {
+        return false;
+    }
+    // Safety: plain syscalls with validated, non-zero PIDs.
+    unsafe {
+        if libc::kill(-(pid as libc::pid_t), libc::SIGTERM) == 0 {
+            return true;
+        }
+        libc::kill(pid as libc::pid_t, libc::SIGTERM) == 0
+    }
+}
+
+#[cfg(windows)]
+pub(crate) fn terminate_process_tree(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+    if pid == 0 {
+        return false;
+    }
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let ok = TerminateProcess(handle, 1) != 0;
+        CloseHandle(handle);
+        ok
+    }
+}
+
+/// I/O scheduling class accepted by `--ionice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IoClass {
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+/// I/O priority as given to `--ionice`: `idle`, `best-effort[:LEVEL]` or
+/// `realtime[:LEVEL]`, where `LEVEL` ranges from 0 (highest) to 7 (lowest)
+/// and defaults to 4. Only honoured on Linux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) struct IoPriority {
+    class: IoClass,
+    level: u8,
+}
+
+impl std::str::FromStr for IoPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (class, level) = match s.split_once(':') {
+            Some((class, level)) => (class, Some(level)),
+            None => (s, None),
+        };
+        let class = match class {
+            "realtime" | "rt" | "1" => IoClass::Realtime,
+            "best-effort" | "be" | "2" => IoClass::BestEffort,
+            "idle" | "3" => IoClass::Idle,
+            other => return Err(format!("unknown I/O class '{other}'")),
+        };
+        let level = match (class, level) {
+            (IoClass::Idle, Some(_)) => return Err("the idle class takes no level".into()),
+            (IoClass::Idle, None) => 0,
+            (_, None) => 4,
+            (_, Some(l)) => match l.parse::<u8>() {
+                Ok(l) if l <= 7 => l,
+                _ => return Err(format!("invalid I/O priority level '{l}' (expected 0-7)")),
+            },
+        };
+        Ok(Self { class, level })
+    }
+}
+
+impl std::fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.class {
+            IoClass::Realtime => write!(f, "realtime:{}", self.level),
+            IoClass::BestEffort => write!(f, "best-effort:{}", self.level),
+            IoClass::Idle => f.write_str("idle"),
+        }
+    }
+}
+
+impl TryFrom<String> for IoPriority {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<IoPriority> for String {
+    fn from(p: IoPriority) -> Self {
+        p.to_string()
+    }
+}
+
+/// Start `command` in a session (Unix) or process group (Windows) of its
+/// own, so it survives the terminal and the process that started it.
+pub(crate) fn detach(command: &mut std::process::Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        unsafe {
+            command.pre_exec(|| {
+                libc::setsid();
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Arrange for `command` to run with a niceness increment of `nice` and the
+/// I/O priority `ionice`, applied in the child right before `exec`.
+#[cfg(unix)]
+pub(crate) fn set_priority(
+    command: &mut std::process::Command,
+    nice: Option<i32>,
+    ionice: Option<IoPriority>,
+) {
+    use std::os::unix::process::CommandExt;
+
+    if nice.is_none() && ionice.is_none() {
+        return;
+    }
+    // Safety: the hook only issues async-signal-safe syscalls.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(increment) = nice {
+                let current = libc::getpriority(libc::PRIO_PROCESS, 0);
+                if libc::setpriority(libc::PRIO_PROCESS, 0, current.saturating_add(increment)) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(prio) = ionice {
+                const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+                const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+                let class = match prio.class {
+                    IoClass::Realtime => 1,
+                    IoClass::BestEffort => 2,
+                    IoClass::Idle => 3,
+                };
+                let value = (class << IOPRIO_CLASS_SHIFT) | libc::c_int::from(prio.level);
+                if libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, value) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = ionice;
+            Ok(())
+        });
+    }
+}
+
+/// Windows only knows priority *classes*: a positive `nice` maps to
+/// `BELOW_NORMAL_PRIORITY_CLASS`, a negative one to `ABOVE_NORMAL`. There is
+/// no per-process I/O priority to set from outside, so `ionice` is ignored.
+#[cfg(windows)]
+pub(crate) fn set_priority(
+    command: &mut std::process::Command,
+    nice: Option<i32>,
+    _ionice: Option<IoPriority>,
+) {
+    use std::os::windows::process::CommandExt;
+
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+    const ABOVE_NORMAL_PRIORITY_CLASS: u32 = 0x0000_8000;
+
+    match nice {
+        Some(n) if n > 0 => {
+            command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+        }
+        Some(n) if n < 0 => {
+            command.creation_flags(ABOVE_NORMAL_PRIORITY_CLASS);
+        }
+        _ => {}
+    }
+}
+
+/// OS-enforced resource limits for a job's command (`--max-mem`,
+/// `--max-fsize`, `--max-cpu-time`). Applied with `setrlimit` on Unix and as
+/// Job Object limits on Windows, which has no per-file size limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ResourceLimits {
+    pub(crate) max_mem: Option<u64>,
+    #[cfg_attr(windows, allow(dead_code))]
+    pub(crate) max_fsize: Option<u64>,
+    pub(crate) max_cpu_time: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// The limit a Unix process killed by `signal` most likely ran into.
+    ///
+    /// CPU and file-size limits announce themselves with dedicated signals.
+    /// An exhausted address space merely makes allocations fail, which
+    /// typically ends in an abort or a segmentation fault, so those are
+    /// attributed to the memory limit when one was set.
+    #[cfg(unix)]
+    pub(crate) fn exceeded_by_signal(&self, signal: i32) -> Option<&'static str> {
+        match signal {
+            libc::SIGXCPU if self.max_cpu_time.is_some() => Some("cpu-time"),
+            libc::SIGXFSZ if self.max_fsize.is_some() => Some("file-size"),
+            libc::SIGSEGV | libc::SIGABRT | libc::SIGBUS if self.max_mem.is_some() => {
+                Some("memory")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Apply `limits` to `command` via `setrlimit` right before `exec`.
+///
+/// The CPU limit's hard value sits one second above the soft one so the
+/// command first receives `SIGXCPU`, which identifies the cause of death,
+/// rather than an anonymous `SIGKILL`.
+#[cfg(unix)]
+pub(crate) fn set_limits(command: &mut std::process::Command, limits: &ResourceLimits) {
+    use std::os::unix::process::CommandExt;
+
+    let limits = *limits;
+    if limits.max_mem.is_none() && limits.max_fsize.is_none() && limits.max_cpu_time.is_none() {
+        return;
+    }
+    // Safety: the hook only issues async-signal-safe syscalls.
+    unsafe {
+        command.pre_exec(move || {
+            let set = |resource, soft: u64, hard: u64| {
+                let limit = libc::rlimit {
+                    rlim_cur: soft as libc::rlim_t,
+                    rlim_max: hard as libc::rlim_t,
+                };
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            };
+            if let Some(bytes) = limits.max_mem {
+                set(libc::RLIMIT_AS, bytes, bytes)?;
+            }
+            if let Some(bytes) = limits.max_fsize {
+                set(libc::RLIMIT_FSIZE, bytes, bytes)?;
+            }
+            if let Some(secs) = limits.max_cpu_time {
+                set(libc::RLIMIT_CPU, secs, secs.saturating_add(1))?;
+            }
+            Ok(())
+        });
+    }
+}
+
+/// A local user account the command can be run as (`pend do --user`).
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub(crate) struct UserAccount {
+    pub(crate) name: String,
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+    home: std::path::PathBuf,
+    groups: Vec<libc::gid_t>,
+}
+
+/// Resolve `name` (or a numeric UID) through the system user database,
+/// including its supplementary groups.
+#[cfg(unix)]
+pub(crate) fn lookup_user(name: &str) -> std::io::Result<UserAccount> {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+
+    let unknown = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown user '{name}'"),
+        )
+    };
+    let c_name = CString::new(name).map_err(|_| unknown())?;
+
+    let mut buf = vec![0 as libc::c_char; 4096];
+    loop {
+        // Safety: `pwd`, `result` and `buf` outlive the call, which only
+        // writes within the given buffer length.
+        let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let rc = unsafe {
+            match name.parse::<libc::uid_t>() {
+                Ok(uid) => {
+                    libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result)
+                }
+                Err(_) => libc::getpwnam_r(
+                    c_name.as_ptr(),
+                    &mut pwd,
+                    buf.as_mut_ptr(),
+                    buf.len(),
+                    &mut result,
+                ),
+            }
+        };
+        if rc == libc::ERANGE {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 {
+            return Err(std::io::Error::from_raw_os_error(rc));
+        }
+        if result.is_null() {
+            return Err(unknown());
+        }
+
+        // Safety: on success the fields point into `buf`.
+        let (user, home) = unsafe {
+            (
+                CStr::from_ptr(pwd.pw_name).to_owned(),
+                std::ffi::OsStr::from_bytes(CStr::from_ptr(pwd.pw_dir).to_bytes()).to_owned(),
+            )
+        };
+        return Ok(UserAccount {
+            groups: group_list(&user, pwd.pw_gid),
+            name: user.to_string_lossy().into_owned(),
+            uid: pwd.pw_uid,
+            gid: pwd.pw_gid,
+            home: home.into(),
+        });
+    }
+}
+
+/// Supplementary groups of `user`, the equivalent of `initgroups` computed
+/// ahead of `fork` where the call would not be async-signal-safe.
+#[cfg(unix)]
+fn group_list(user: &std::ffi::CStr, gid: libc::gid_t) -> Vec<libc::gid_t> {
+    let mut groups: Vec<libc::gid_t> = vec![0; 64];
+    loop {
+        let mut count = groups.len() as libc::c_int;
+        // Safety: `groups` has room for `count` entries.
+        let rc = unsafe {
+            libc::getgrouplist(
+                user.as_ptr(),
+                gid as _,
+                groups.as_mut_ptr() as *mut _,
+                &mut count,
+            )
+        };
+        if rc >= 0 {
+            groups.truncate(count.max(0) as usize);
+            return groups;
+        }
+        if groups.len() >= 65536 {
+            return vec![gid];
+        }
+        groups.resize((count.max(0) as usize).max(groups.len() * 2), 0);
+    }
+}
+
+/// Name of the user this process runs as, for the job metadata.
+#[cfg(unix)]
+pub(crate) fn current_user_name() -> Option<String> {
+    // Safety: `geteuid` cannot fail.
+    let uid = unsafe { libc::geteuid() };
+    lookup_user(&uid.to_string()).ok().map(|u| u.name)
+}
+
+/// Make `command` drop to `user` right before `exec`: supplementary groups,
+/// then GID, then UID, plus the usual `HOME`/`USER`/`LOGNAME` variables.
+///
+/// Register this after every other `pre_exec` hook – raising the priority or
+/// limits may need the privileges given up here.
+#[cfg(unix)]
+pub(crate) fn set_user(command: &mut std::process::Command, user: &UserAccount) {
+    use std::os::unix::process::CommandExt;
+
+    command
+        .env("HOME", &user.home)
+        .env("USER", &user.name)
+        .env("LOGNAME", &user.name);
+
+    let (uid, gid, groups) = (user.uid, user.gid, user.groups.clone());
+    // Safety: the hook only issues async-signal-safe syscalls.
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setgroups(groups.len() as _, groups.as_ptr()) != 0
+                || libc::setgid(gid) != 0
+                || libc::setuid(uid) != 0
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// A named Windows Job Object holding every process of one job attempt.
+///
+/// The worker creates it with `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE`, so the
+/// whole tree dies with the worker, and assigns the user command to it right
+/// after spawning; descendants inherit the membership. Other `pend`
+/// processes open it by name to terminate the tree (`pend kill`) or to count
+/// the processes still alive (`pend status`).
+#[cfg(windows)]
+pub(crate) struct JobObject {
+    handle: windows_sys::Win32::Foundation::HANDLE,
+}
+
+/// Job Object times are counted in 100 ns ticks.
+#[cfg(windows)]
+const TICKS_PER_SECOND: i64 = 10_000_000;
+
+#[cfg(windows)]
+impl JobObject {
+    /// Create (or, if a handle to it is still open somewhere, reuse) the job
+    /// object for `paths`, enforcing the memory and CPU parts of `limits`.
+    pub(crate) fn create(
+        paths: &crate::paths::JobPaths,
+        limits: &ResourceLimits,
+    ) -> std::io::Result<Self> {
+        use windows_sys::Win32::System::JobObjects::{
+            CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_JOB_MEMORY,
+            JOB_OBJECT_LIMIT_JOB_TIME, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        let name = Self::wide_name(paths);
+        // Safety: `name` is NUL-terminated; a null security descriptor is allowed.
+        let handle = unsafe { CreateJobObjectW(std::ptr::null(), name.as_ptr()) };
+        if handle.is_null() {
+            return Err(std::io::Error::last_os_error());
+        }
+        let job = Self { handle };
+
+        // Safety: the struct is plain data and fully initialised by zeroing.
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        if let Some(bytes) = limits.max_mem {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+            info.JobMemoryLimit = bytes as usize;
+        }
+        if let Some(secs) = limits.max_cpu_time {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_TIME;
+            info.BasicLimitInformation.PerJobUserTimeLimit =
+                (secs as i64).saturating_mul(TICKS_PER_SECOND);
+        }
+        let ok = unsafe {
+            SetInformationJobObject(
+                job.handle,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of_val(&info) as u32,
+            )
+        };
+        if ok == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(job)
+    }
+
+    /// Open the job object of a running job, if there is one.
+    pub(crate) fn open(paths: &crate::paths::JobPaths) -> Option<Self> {
+        use windows_sys::Win32::System::JobObjects::OpenJobObjectW;
+
+        const JOB_OBJECT_QUERY: u32 = 0x0004;
+        const JOB_OBJECT_TERMINATE: u32 = 0x0008;
+
+        let name = Self::wide_name(paths);
+        // Safety: `name` is NUL-terminated.
+        let handle =
+            unsafe { OpenJobObjectW(JOB_OBJECT_QUERY | JOB_OBJECT_TERMINATE, 0, name.as_ptr()) };
+        (!handle.is_null()).then_some(Self { handle })
+    }
+
+    /// Add the freshly spawned `child` (and thereby its future descendants).
+    pub(crate) fn assign(&self, child: &std::process::Child) -> std::io::Result<()> {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+
+        // Safety: both handles are valid for the duration of the call.
+        if unsafe { AssignProcessToJobObject(self.handle, child.as_raw_handle() as _) } == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Terminate every process in the job with `exit_code`.
+    pub(crate) fn terminate(&self, exit_code: u32) -> bool {
+        use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+        // Safety: `self.handle` is a valid job handle.
+        unsafe { TerminateJobObject(self.handle, exit_code) != 0 }
+    }
+
+    /// The limit from `limits` that most likely ended an attempt which exited
+    /// with `exit_code`, judged by the job's peak memory and CPU usage.
+    pub(crate) fn limit_exceeded(
+        &self,
+        limits: &ResourceLimits,
+        exit_code: i32,
+    ) -> Option<&'static str> {
+        use windows_sys::Win32::System::JobObjects::{
+            JobObjectBasicAccountingInformation, JobObjectExtendedLimitInformation,
+            QueryInformationJobObject, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        };
+
+        // Allocations close to the limit fail as well, so allow some slack.
+        const MEMORY_SLACK: u64 = 1 << 20;
+
+        if exit_code == 0 {
+            return None;
+        }
+        if let Some(limit) = limits.max_mem {
+            // Safety: plain data struct; the size passed matches it.
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+            let ok = unsafe {
+                QueryInformationJobObject(
+                    self.handle,
+                    JobObjectExtendedLimitInformation,
+                    &mut info as *mut _ as *mut std::ffi::c_void,
+                    std::mem::size_of_val(&info) as u32,
+                    std::ptr::null_mut(),
+                )
+            };
+            if ok != 0 && info.PeakJobMemoryUsed as u64 + MEMORY_SLACK >= limit {
+                return Some("memory");
+            }
+        }
+        if let Some(secs) = limits.max_cpu_time {
+            // Safety: plain data struct; the size passed matches it.
+            let mut info: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION = unsafe { std::mem::zeroed() };
+            let ok = unsafe {
+                QueryInformationJobObject(
+                    self.handle,
+                    JobObjectBasicAccountingInformation,
+                    &mut info as *mut _ as *mut std::ffi::c_void,
+                    std::mem::size_of_val(&info) as u32,
+                    std::ptr::null_mut(),
+                )
+            };
+            if ok != 0 && info.TotalUserTime >= (secs as i64).saturating_mul(TICKS_PER_SECOND) {
+                return Some("cpu-time");
+            }
+        }
+        None
+    }
+
+    /// Number of processes in the job that are still running.
+    pub(crate) fn active_processes(&self) -> Option<u32> {
+        use windows_sys::Win32::System::JobObjects::{
+            JobObjectBasicAccountingInformation, QueryInformationJobObject,
+            JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+        };
+
+        // Safety: the struct is plain data; the size passed matches it.
+        let mut info: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION = unsafe { std::mem::zeroed() };
+        let ok = unsafe {
+            QueryInformationJobObject(
+                self.handle,
+                JobObjectBasicAccountingInformation,
+                &mut info as *mut _ as *mut std::ffi::c_void,
+                std::mem::size_of_val(&info) as u32,
+                std::ptr::null_mut(),
+            )
+        };
+        (ok != 0).then_some(info.ActiveProcesses)
+    }
+
+    /// `Local\pend-<hash>` – derived from the lock path so that jobs of the
+    /// same name in different jobs directories do not collide.
+    fn wide_name(paths: &crate::paths::JobPaths) -> Vec<u16> {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        paths.lock.hash(&mut hasher);
+        format!("Local\\pend-{:016x}", hasher.finish())
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect()
+    }
+}
+
+#[cfg(windows)]
+impl Drop for JobObject {
+    fn drop(&mut self) {
+        // Safety: we own the handle. Closing the last handle of the worker's
+        // job kills any process left in it (KILL_ON_JOB_CLOSE).
+        unsafe {
+            windows_sys::Win32::Foundation::CloseHandle(self.handle);
+        }
+    }
+}
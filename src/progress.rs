@@ -0,0 +1,186 @@
+//! Live status footer for `pend wait` on several jobs.
+//!
+//! While output streams past, the last terminal line shows how many of the
+//! awaited jobs are running, done or failed, followed by a spinner and the
+//! elapsed time (and reported progress, see `report.rs`) of every job still
+//! running, with the time left before `--timeout` kills it. Waiting for a
+//! single job shows only a progress bar, and only once the job reports
+//! progress or runs with a timeout. The footer is erased before new output is
+//! written and redrawn afterwards, so it always stays beneath the streamed
+//! lines; it disappears once waiting ends. Only shown when both stdout and
+//! stderr are terminals, and in builds with the `tui` feature.
+use std::io::{self, IsTerminal, Write};
+
+use crate::glyphs;
+
+/// Where an awaited job stands, as far as the footer is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Phase {
+    Running,
+    Done,
+    Failed,
+}
+
+/// Width of the single-job progress bar, in cells.
+const BAR_WIDTH: usize = 20;
+
+/// One awaited job as shown in the footer.
+pub(crate) struct Row<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) phase: Phase,
+    /// Seconds since the job started.
+    pub(crate) elapsed: Option<i64>,
+    /// Percentage reported by the command.
+    pub(crate) progress: Option<f64>,
+    /// Seconds until `--timeout` kills the running attempt.
+    pub(crate) time_left: Option<i64>,
+}
+
+pub(crate) struct Footer {
+    frame: usize,
+    drawn: bool,
+    /// Draw with ASCII symbols only (see `glyphs.rs`).
+    ascii: bool,
+}
+
+impl Footer {
+    /// A footer for `job_count` jobs, or `None` when it should not be shown.
+    pub(crate) fn for_jobs(job_count: usize, ascii: bool) -> Option<Self> {
+        (cfg!(feature = "tui")
+            && job_count > 0
+            && io::stdout().is_terminal()
+            && io::stderr().is_terminal())
+        .then_some(Self {
+            frame: 0,
+            drawn: false,
+            ascii,
+        })
+    }
+
+    /// Erase the footer (if drawn) so regular output can take its place.
+    pub(crate) fn clear(&mut self) -> io::Result<()> {
+        if !self.drawn {
+            return Ok(());
+        }
+        let mut err = io::stderr().lock();
+        erase_line(&mut err)?;
+        err.flush()?;
+        self.drawn = false;
+        Ok(())
+    }
+
+    /// Draw the footer for `jobs`.
+    pub(crate) fn draw(&mut self, jobs: &[Row]) -> io::Result<()> {
+        self.clear()?;
+        let spinner = glyphs::spinner(self.ascii);
+        let spinner = spinner[self.frame % spinner.len()];
+
+        let mut line = match jobs {
+            [job] => match (job.phase, job.progress) {
+                (Phase::Running, Some(percent)) => {
+                    let filled = (percent / 100.0 * BAR_WIDTH as f64).round() as usize;
+                    let (full, empty) = glyphs::bar(self.ascii);
+                    let mut line = format!(
+                        "{spinner} {} [{}{}] {percent:.0}%",
+                        job.name,
+                        full.to_string().repeat(filled),
+                        empty.to_string().repeat(BAR_WIDTH - filled)
+                    );
+                    if let Some(secs) = job.elapsed {
+                        line.push_str(&format!(" {secs}s"));
+                    }
+                    line.push_str(&time_left(job));
+                    line
+                }
+                (Phase::Running, None) if job.time_left.is_some() => {
+                    let mut line = format!("{spinner} {}", job.name);
+                    if let Some(secs) = job.elapsed {
+                        line.push_str(&format!(" {secs}s"));
+                    }
+                    line.push_str(&time_left(job));
+                    line
+                }
+                _ => return Ok(()),
+            },
+            _ => {
+                let count = |phase| jobs.iter().filter(|job| job.phase == phase).count();
+                let mut line = format!(
+                    "{spinner} {} running{dot}{} done{dot}{} failed",
+                    count(Phase::Running),
+                    count(Phase::Done),
+                    count(Phase::Failed),
+                    dot = glyphs::dot(self.ascii),
+                );
+                let running: Vec<String> = jobs
+                    .iter()
+                    .filter(|job| job.phase == Phase::Running)
+                    .map(|job| {
+                        let mut entry = job.name.to_string();
+                        if let Some(percent) = job.progress {
+                            entry.push_str(&format!(" {percent:.0}%"));
+                        }
+                        if let Some(secs) = job.elapsed {
+                            entry.push_str(&format!(" {secs}s"));
+                        }
+                        entry.push_str(&time_left(job));
+                        entry
+                    })
+                    .collect();
+                if !running.is_empty() {
+                    line.push_str(glyphs::divider(self.ascii));
+                    line.push_str(&running.join(", "));
+                }
+                line
+            }
+        };
+        self.frame += 1;
+
+        // Never wrap: a wrapped footer could not be erased in place.
+        let width = terminal_width().unwrap_or(80);
+        if line.chars().count() >= width {
+            line = line.chars().take(width.saturating_sub(2)).collect();
+            line.push_str(glyphs::ellipsis(self.ascii));
+        }
+
+        let mut err = io::stderr().lock();
+        err.write_all(line.as_bytes())?;
+        err.flush()?;
+        self.drawn = true;
+        Ok(())
+    }
+}
+
+/// ` (timeout in 48s)` for a job running with `--timeout`.
+fn time_left(job: &Row) -> String {
+    job.time_left
+        .map(|secs| format!(" (timeout in {secs}s)"))
+        .unwrap_or_default()
+}
+
+/// Move to the start of the line and clear it.
+#[cfg(feature = "tui")]
+fn erase_line(out: &mut impl Write) -> io::Result<()> {
+    use crossterm::{cursor, terminal, QueueableCommand};
+
+    out.queue(cursor::MoveToColumn(0))?
+        .queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "tui"))]
+fn erase_line(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(b"\r\x1b[2K")
+}
+
+#[cfg(feature = "tui")]
+fn terminal_width() -> Option<usize> {
+    match crossterm::terminal::size() {
+        Ok((w, _)) if w > 0 => Some(w as usize),
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn terminal_width() -> Option<usize> {
+    None
+}
@@ -0,0 +1,72 @@
+//! `<job>.result`: the outcome of a finished job in one short line.
+//!
+//! Listing a large jobs directory should not mean parsing every job's
+//! `.json`. The worker therefore writes, just before `.exit`, a line such as
+//!
+//! ```text
+//! exit=137 secs=12.503 attempts=2 signal=9 id=0f6c…
+//! ```
+//!
+//! holding the exit code, the wall time from submission to the end, the
+//! number of attempts, the number of the signal that killed the last one
+//! (if one did) and the job's ID (see `job_id.rs`). Fields are `key=value`
+//! pairs separated by spaces; readers skip keys they do not know, so later
+//! releases may add some.
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The record kept in `<job>.result`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct QuickStatus {
+    pub(crate) exit_code: i32,
+    pub(crate) wall_secs: f64,
+    pub(crate) attempts: u32,
+    pub(crate) signal: Option<i32>,
+    pub(crate) id: Option<String>,
+}
+
+impl QuickStatus {
+    /// Replace the record at `path` in one step.
+    pub(crate) fn write(&self, path: &Path) -> io::Result<()> {
+        let mut line = format!(
+            "exit={} secs={:.3} attempts={}",
+            self.exit_code, self.wall_secs, self.attempts
+        );
+        if let Some(signal) = self.signal {
+            line.push_str(&format!(" signal={signal}"));
+        }
+        if let Some(id) = &self.id {
+            line.push_str(&format!(" id={id}"));
+        }
+        line.push('\n');
+        crate::paths::replace_file(path, line.as_bytes())
+    }
+
+    /// The record at `path`; `None` when missing or lacking a field.
+    pub(crate) fn read(path: &Path) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        let (mut exit_code, mut wall_secs, mut attempts, mut signal, mut id) =
+            (None, None, None, None, None);
+        for field in text.split_whitespace() {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            match key {
+                "exit" => exit_code = value.parse().ok(),
+                "secs" => wall_secs = value.parse().ok(),
+                "attempts" => attempts = value.parse().ok(),
+                "signal" => signal = value.parse().ok(),
+                "id" => id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Some(Self {
+            exit_code: exit_code?,
+            wall_secs: wall_secs?,
+            attempts: attempts?,
+            signal,
+            id,
+        })
+    }
+}
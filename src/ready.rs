@@ -0,0 +1,213 @@
+//! Readiness probes and dependency ordering between jobs.
+//!
+//! A long-running job (a dev server, a database) is rarely interesting for
+//! its exit code; what dependants need to know is when it is *ready*. A job
+//! may declare a probe on `pend do` – a shell command that must succeed
+//! (`--ready-cmd`) or a local TCP port that must accept connections
+//! (`--ready-port`). While an attempt runs, the worker retries the probe and
+//! records the time it first passed as `ready` in `<job>.json`. Without a
+//! probe a job counts as ready as soon as its command has been spawned.
+//!
+//! `pend wait --ready` blocks on that state, and `pend do --after JOB:ready`
+//! holds a job back until `JOB` is ready (plain `--after JOB` waits for it to
+//! finish successfully).
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use wait_timeout::ChildExt;
+
+use crate::config::Config;
+use crate::paths::JobPaths;
+
+/// Pause between two probe attempts.
+const PROBE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A `--ready-cmd` run taking longer than this counts as failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often `--after` re-checks its dependencies.
+const DEPENDENCY_POLL: Duration = Duration::from_millis(200);
+
+/// How a job signals that it is ready.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Probe {
+    pub(crate) cmd: Option<String>,
+    pub(crate) port: Option<u16>,
+}
+
+impl Probe {
+    /// Whether neither a command nor a port was configured.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.cmd.is_none() && self.port.is_none()
+    }
+
+    /// Run every configured check once.
+    fn passes(&self) -> bool {
+        self.port.is_none_or(port_open) && self.cmd.as_deref().is_none_or(command_succeeds)
+    }
+
+    /// Retry the probe until it passes (returns `true`) or `stop` is set.
+    pub(crate) fn wait_until_ready(&self, stop: &AtomicBool) -> bool {
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                return false;
+            }
+            if self.passes() {
+                return true;
+            }
+            std::thread::sleep(PROBE_INTERVAL);
+        }
+    }
+}
+
+fn port_open(port: u16) -> bool {
+    let Ok(addrs) = ("localhost", port).to_socket_addrs() else {
+        return false;
+    };
+    addrs
+        .into_iter()
+        .any(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(1)).is_ok())
+}
+
+fn command_succeeds(cmd: &str) -> bool {
+    let Ok(mut child) = crate::shell::shell(cmd.as_ref())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    match child.wait_timeout(PROBE_TIMEOUT) {
+        Ok(Some(status)) => status.success(),
+        _ => {
+            let _ = child.kill();
+            let _ = child.wait();
+            false
+        }
+    }
+}
+
+/// Whether the worker has recorded the job as ready in its metadata file
+/// `meta`.
+pub(crate) fn is_ready(meta: &Path) -> bool {
+    std::fs::read(meta)
+        .ok()
+        .and_then(|b| crate::version::parse_meta(&b).ok())
+        .is_some_and(|meta| meta.get("ready").is_some_and(|v| !v.is_null()))
+}
+
+/// A `--after` dependency: `JOB` (finished successfully) or `JOB:ready`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) struct Dependency {
+    pub(crate) job: String,
+    pub(crate) ready: bool,
+}
+
+impl std::str::FromStr for Dependency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (job, ready) = match s.split_once(':') {
+            Some((job, "ready")) => (job, true),
+            Some((_, other)) => {
+                return Err(format!(
+                    "unknown dependency condition '{other}' (expected ready)"
+                ))
+            }
+            None => (s, false),
+        };
+        if job.is_empty() {
+            return Err("dependency needs a job name".into());
+        }
+        Ok(Self {
+            job: job.to_string(),
+            ready,
+        })
+    }
+}
+
+impl std::fmt::Display for Dependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ready {
+            write!(f, "{}:ready", self.job)
+        } else {
+            f.write_str(&self.job)
+        }
+    }
+}
+
+impl TryFrom<String> for Dependency {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Dependency> for String {
+    fn from(d: Dependency) -> Self {
+        d.to_string()
+    }
+}
+
+/// Block until every dependency is satisfied. Fails as soon as one of them
+/// can no longer be satisfied, or with `Interrupted` once `job` (the
+/// dependant) is killed.
+pub(crate) fn wait_for_dependencies(
+    config: &Config,
+    deps: &[Dependency],
+    job: &JobPaths,
+) -> io::Result<()> {
+    let paths = deps
+        .iter()
+        .map(|d| JobPaths::new(config, &d.job))
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut pending: Vec<(&Dependency, &JobPaths)> = deps.iter().zip(&paths).collect();
+
+    while !pending.is_empty() {
+        tracing::trace!(pending = pending.len(), "waiting for dependencies");
+        if job.cancel.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "job was killed while waiting for its dependencies",
+            ));
+        }
+        let mut still_pending = Vec::new();
+        for (dep, paths) in pending {
+            if dep.ready && is_ready(&paths.meta) {
+                continue;
+            }
+            let exit_code = std::fs::read_to_string(&paths.exit)
+                .ok()
+                .and_then(|s| s.trim().parse::<i32>().ok());
+            match exit_code {
+                Some(0) if !dep.ready => {}
+                Some(code) => {
+                    return Err(io::Error::other(format!(
+                        "dependency '{dep}' can no longer be satisfied: '{}' exited with {}",
+                        dep.job,
+                        crate::exit_status::describe(code)
+                    )))
+                }
+                None if !paths.any_exist() => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("dependency '{}' not found", dep.job),
+                    ))
+                }
+                None => still_pending.push((dep, paths)),
+            }
+        }
+        pending = still_pending;
+        if !pending.is_empty() {
+            std::thread::sleep(DEPENDENCY_POLL);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Progress reported by the job's command.
+//!
+//! A command can tell pend how far along it is in either of two ways:
+//!
+//! * print a line `::pend::progress 42%` on stdout or stderr;
+//! * write to the file named by `$PEND_PROGRESS_FILE`.
+//!
+//! Both accept a percentage (`42%` or `42`) or a count (`3/10`). The worker
+//! copies marker lines into `<job>.progress`, the file the variable points
+//! to, so `pend wait`, `pend status` and the TUI only ever read that file.
+use std::path::Path;
+
+/// Environment variable naming the progress file inside the command.
+pub(crate) const ENV: &str = "PEND_PROGRESS_FILE";
+
+/// Prefix of a progress line in the command's output.
+const MARKER: &[u8] = b"::pend::progress ";
+
+/// Longest line the scanner holds on to; longer ones cannot be markers.
+const MAX_LINE: usize = 256;
+
+/// Parse a progress value into a percentage between 0 and 100.
+pub(crate) fn parse(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let percent = match text.split_once('/') {
+        Some((done, total)) => {
+            let total: f64 = total.trim().parse().ok()?;
+            (total > 0.0).then_some(done.trim().parse::<f64>().ok()? * 100.0 / total)?
+        }
+        None => text.strip_suffix('%').unwrap_or(text).trim().parse().ok()?,
+    };
+    percent.is_finite().then(|| percent.clamp(0.0, 100.0))
+}
+
+/// The latest progress recorded in `path`: its last non-empty line.
+pub(crate) fn read(path: &Path) -> Option<f64> {
+    let text = std::fs::read_to_string(path).ok()?;
+    parse(text.lines().rev().find(|line| !line.trim().is_empty())?)
+}
+
+/// Picks marker lines out of one output stream, chunk by chunk.
+#[derive(Default)]
+pub(crate) struct Scanner {
+    line: Vec<u8>,
+    /// The current line is too long to be a marker; skip to its end.
+    skipping: bool,
+}
+
+impl Scanner {
+    /// Feed the next chunk of output; returns the last progress value
+    /// completed within it.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Option<f64> {
+        let mut latest = None;
+        for &byte in chunk {
+            if byte == b'\n' {
+                if !self.skipping {
+                    latest = self.finish_line().or(latest);
+                }
+                self.line.clear();
+                self.skipping = false;
+            } else if !self.skipping {
+                self.line.push(byte);
+                if self.line.len() > MAX_LINE {
+                    self.line.clear();
+                    self.skipping = true;
+                }
+            }
+        }
+        latest
+    }
+
+    fn finish_line(&self) -> Option<f64> {
+        let line = self.line.strip_suffix(b"\r").unwrap_or(&self.line);
+        let value = line.strip_prefix(MARKER)?;
+        parse(std::str::from_utf8(value).ok()?)
+    }
+}
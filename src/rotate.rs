@@ -0,0 +1,172 @@
+//! Size- and age-based rotation of the `.out`, `.err` and `.log` streams.
+//!
+//! Once a stream would grow beyond `--max-log-size`, the current file is
+//! renamed to `<file>.1`, older generations shift up (`.1` → `.2`, …) and
+//! anything beyond `--max-log-files` generations is dropped. With
+//! `--compress-logs` rotated generations are gzipped (`<file>.1.gz`).
+//!
+//! `--log-ttl` keeps only roughly the last TTL of output, for services that
+//! run for weeks: the current file is also rotated once it has been written
+//! to for a [`TTL_SLICES`]th of the TTL, and rotated generations whose last
+//! write (their modification time, kept through renames and compression)
+//! is older than the TTL are deleted.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// With a TTL, the current file covers at most this fraction of it, so the
+/// output dropped at once is a slice rather than everything.
+pub(crate) const TTL_SLICES: u32 = 4;
+
+/// How a stream is rotated.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RotationPolicy {
+    /// Rotate before the file would exceed this many bytes.
+    pub(crate) max_size: Option<u64>,
+    /// Rotated generations to keep; `0` simply truncates.
+    pub(crate) keep: u32,
+    /// Gzip rotated generations.
+    pub(crate) compress: bool,
+    /// Delete rotated generations last written longer ago than this.
+    pub(crate) ttl: Option<Duration>,
+}
+
+/// An append-only artifact file subject to a [`RotationPolicy`].
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    /// `None` only transiently while rotating, so the handle is closed before
+    /// the file is renamed (required on Windows).
+    file: Option<File>,
+    len: u64,
+    /// When the current file was started (or this writer opened it).
+    since: Instant,
+    policy: RotationPolicy,
+}
+
+impl RotatingFile {
+    /// Open `path`, continuing an existing file when `append` is set.
+    pub(crate) fn open(path: &Path, append: bool, policy: RotationPolicy) -> io::Result<Self> {
+        let mut opts = OpenOptions::new();
+        opts.create(true);
+        if append {
+            opts.append(true);
+        } else {
+            opts.write(true).truncate(true);
+        }
+        let file = opts.open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Some(file),
+            len,
+            since: Instant::now(),
+            policy,
+        })
+    }
+
+    /// Current size of the file in bytes.
+    pub(crate) fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Rotate if writing `incoming` more bytes would exceed the size limit,
+    /// or the current file has covered its slice of the TTL. Returns whether
+    /// the file was rotated.
+    pub(crate) fn make_room(&mut self, incoming: u64) -> io::Result<bool> {
+        let full = self
+            .policy
+            .max_size
+            .is_some_and(|limit| self.len + incoming > limit);
+        let aged = self
+            .policy
+            .ttl
+            .is_some_and(|ttl| self.since.elapsed() >= ttl / TTL_SLICES);
+        if self.len == 0 || !(full || aged) {
+            return Ok(false);
+        }
+        self.rotate()?;
+        if let Some(ttl) = self.policy.ttl {
+            self.expire(ttl);
+        }
+        Ok(true)
+    }
+
+    pub(crate) fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let file = self.file.as_mut().expect("file is open outside rotate()");
+        file.write_all(buf)?;
+        self.len += buf.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        tracing::debug!(path = %self.path.display(), len = self.len, "rotating log");
+        drop(self.file.take());
+
+        let keep = self.policy.keep;
+        if keep > 0 {
+            let _ = fs::remove_file(self.generation(keep, false));
+            let _ = fs::remove_file(self.generation(keep, true));
+            for n in (1..keep).rev() {
+                for gz in [false, true] {
+                    let from = self.generation(n, gz);
+                    if from.exists() {
+                        let _ = fs::rename(&from, self.generation(n + 1, gz));
+                    }
+                }
+            }
+            let first = self.generation(1, false);
+            if fs::rename(&self.path, &first).is_ok()
+                && self.policy.compress
+                && gzip(&first, &self.generation(1, true)).is_ok()
+            {
+                let _ = fs::remove_file(&first);
+            }
+        }
+
+        self.file = Some(File::create(&self.path)?);
+        self.len = 0;
+        self.since = Instant::now();
+        Ok(())
+    }
+
+    /// Delete the rotated generations last written more than `ttl` ago.
+    fn expire(&self, ttl: Duration) {
+        for n in 1..=self.policy.keep {
+            for gz in [false, true] {
+                let path = self.generation(n, gz);
+                let expired = fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age > ttl);
+                if expired {
+                    tracing::debug!(path = %path.display(), "deleting expired log generation");
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+    }
+
+    /// Path of rotated generation `n`, e.g. `foo.log.2` or `foo.log.2.gz`.
+    fn generation(&self, n: u32, gz: bool) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(format!(".{n}"));
+        if gz {
+            name.push(".gz");
+        }
+        PathBuf::from(name)
+    }
+}
+
+/// Compress `src` into `dst`, which keeps the modification time of `src`.
+fn gzip(src: &Path, dst: &Path) -> io::Result<()> {
+    let mut input = File::open(src)?;
+    let modified = input.metadata()?.modified()?;
+    let mut encoder =
+        flate2::write::GzEncoder::new(File::create(dst)?, flate2::Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    let output = encoder.finish()?;
+    output.set_modified(modified)?;
+    output.sync_all()
+}
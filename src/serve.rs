@@ -0,0 +1,396 @@
+//! `pend serve` – a tiny HTTP/1.1 API for driving pend remotely.
+//!
+//! | Method | Path                 | Action                                         |
+//! |--------|----------------------|------------------------------------------------|
+//! | GET    | `/jobs`              | Status of every job in the jobs directory.     |
+//! | POST   | `/jobs`              | Submit `{"name", "cmd", "timeout", "retries"}`. |
+//! | GET    | `/jobs/<job>`        | Status of a single job.                        |
+//! | GET    | `/jobs/<job>/log`    | Stream the combined log until the job ends.    |
+//! | POST   | `/jobs/<job>/kill`   | Terminate a running job.                       |
+//!
+//! Every request must carry `Authorization: Bearer <token>`. Logs are sent
+//! with chunked transfer encoding, or as Server-Sent Events when the client
+//! asks for `text/event-stream` (a final `exit` event carries the exit code).
+//!
+//! In keeping with the rest of the crate there is no async runtime: each
+//! connection is served on its own thread and closed after one response.
+//! Another thread deletes jobs whose `--expires-in` time is up, every
+//! minute.
+use serde::Deserialize;
+use serde_json::json;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::PendError;
+use crate::paths::JobPaths;
+
+/// How often expired jobs are deleted while serving (see `expire.rs`).
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Upper bound for request headers and bodies – the API only ever receives
+/// small JSON documents.
+const MAX_REQUEST_BYTES: usize = 1 << 20;
+
+/// Body accepted by `POST /jobs`.
+#[derive(Deserialize)]
+struct SubmitRequest {
+    name: String,
+    cmd: Vec<String>,
+    /// `timeout`, `retries`, … – the same settings as `pend do` flags.
+    #[serde(flatten)]
+    options: crate::worker::JobOptions,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Entry point for `pend serve`. Runs until the process is terminated.
+pub(crate) fn serve(config: &Config, bind: &str, token: &str) -> io::Result<()> {
+    if token.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "an API token is required – pass --token or set PEND_SERVE_TOKEN",
+        ));
+    }
+
+    let listener = TcpListener::bind(bind)?;
+    // Announce the actual address (useful with port 0) before serving.
+    println!("listening on http://{}", listener.local_addr()?);
+    io::stdout().flush()?;
+
+    // The server is pend's one long-running process, so it looks after jobs
+    // that asked to expire.
+    let root = crate::paths::jobs_root(config)?;
+    std::thread::spawn(move || loop {
+        crate::expire::sweep(&root);
+        std::thread::sleep(SWEEP_INTERVAL);
+    });
+
+    let token: Arc<str> = Arc::from(token);
+    let config = Arc::new(config.clone());
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let token = Arc::clone(&token);
+        let config = Arc::clone(&config);
+        std::thread::spawn(move || {
+            let _ = handle_connection(&config, stream, &token);
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(config: &Config, mut stream: TcpStream, token: &str) -> io::Result<()> {
+    let req = match read_request(&mut stream) {
+        Ok(req) => req,
+        Err(e) => return respond_error(&mut stream, 400, &e.to_string()),
+    };
+
+    let authorized = req
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|t| constant_time_eq(t.trim().as_bytes(), token.as_bytes()));
+    if !authorized {
+        return respond_error(&mut stream, 401, "missing or invalid token");
+    }
+
+    let path = req.path.split('?').next().unwrap_or("");
+    let segments: Vec<String> = path
+        .trim_matches('/')
+        .split('/')
+        .map(percent_decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    // The name is decoded from the URL, so `..%2F` would otherwise reach
+    // outside the jobs directory.
+    if let ["jobs", name, ..] = segments.as_slice() {
+        if let Err(e) = crate::job::validate_name(name) {
+            return respond_error(&mut stream, 400, &e.to_string());
+        }
+    }
+
+    let result = match (req.method.as_str(), segments.as_slice()) {
+        ("GET", ["jobs"]) => list_jobs(config, &mut stream),
+        ("POST", ["jobs"]) => submit_job(config, &mut stream, &req.body),
+        ("GET", ["jobs", name]) => match crate::status::job_status(config, name) {
+            Ok(st) => respond_json(&mut stream, 200, &serde_json::to_value(st)?),
+            Err(e) => Err(e),
+        },
+        ("GET", ["jobs", name, "log"]) => {
+            let sse = req
+                .header("accept")
+                .is_some_and(|v| v.contains("text/event-stream"));
+            stream_log(config, &mut stream, name, sse)
+        }
+        ("POST", ["jobs", name, "kill"]) => match crate::kill::kill_job(config, name) {
+            Ok(()) => respond_json(&mut stream, 202, &json!({ "job": name, "killed": true })),
+            Err(e) => Err(e),
+        },
+        _ => respond_error(&mut stream, 404, "no such endpoint"),
+    };
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let status = match e.kind() {
+                io::ErrorKind::NotFound => 404,
+                io::ErrorKind::AlreadyExists => 409,
+                io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => 400,
+                _ => 500,
+            };
+            respond_error(&mut stream, status, &e.to_string())
+        }
+    }
+}
+
+fn list_jobs(config: &Config, stream: &mut TcpStream) -> io::Result<()> {
+    let root = crate::paths::jobs_root(config)?;
+    let statuses = crate::paths::discover_jobs(&root)
+        .iter()
+        .filter_map(|name| crate::status::job_status(config, name).ok())
+        .collect::<Vec<_>>();
+    respond_json(stream, 200, &serde_json::to_value(statuses)?)
+}
+
+fn submit_job(config: &Config, stream: &mut TcpStream, body: &[u8]) -> io::Result<()> {
+    let req: SubmitRequest =
+        serde_json::from_slice(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let cmd: Vec<OsString> = req.cmd.into_iter().map(OsString::from).collect();
+    crate::job::do_job(config, &req.name, &cmd, &req.options)?;
+    respond_json(stream, 201, &json!({ "job": req.name, "state": "running" }))
+}
+
+/// Stream the combined log of `job_name` while the job runs and finish once
+/// the `.exit` marker shows up and all output has been sent.
+fn stream_log(
+    config: &Config,
+    stream: &mut TcpStream,
+    job_name: &str,
+    sse: bool,
+) -> io::Result<()> {
+    let paths = JobPaths::new(config, job_name)?;
+    if !paths.any_exist() {
+        return Err(PendError::NotFound(job_name.to_string()).into());
+    }
+
+    let content_type = if sse {
+        "text/event-stream"
+    } else {
+        "text/plain; charset=utf-8"
+    };
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nCache-Control: no-cache\r\n\
+         Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n"
+    )?;
+
+    let mut offset = 0u64;
+    // Tail of the log after its last newline, held back from SSE clients.
+    let mut pending = Vec::new();
+    loop {
+        // Check for completion *before* reading so no output written between
+        // the two steps can be lost.
+        let finished = paths.exit.exists();
+
+        let size = JobPaths::file_len(&paths.log);
+        if size > offset {
+            let mut file = File::open(&paths.log)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut buf = Vec::with_capacity((size - offset) as usize);
+            Read::by_ref(&mut file)
+                .take(size - offset)
+                .read_to_end(&mut buf)?;
+            offset += buf.len() as u64;
+
+            if sse {
+                // Only complete lines make an event: a read can end in the
+                // middle of a line or of a multibyte character.
+                pending.extend_from_slice(&buf);
+                if let Some(pos) = pending.iter().rposition(|&b| b == b'\n') {
+                    let lines: Vec<u8> = pending.drain(..=pos).collect();
+                    write_chunk(stream, &sse_event(&lines))?;
+                }
+            } else {
+                write_chunk(stream, &buf)?;
+            }
+        }
+
+        if finished {
+            if !pending.is_empty() {
+                write_chunk(stream, &sse_event(&pending))?;
+            }
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+
+    if sse {
+        let code = fs::read_to_string(&paths.exit).unwrap_or_default();
+        write_chunk(
+            stream,
+            format!("event: exit\ndata: {}\n\n", code.trim()).as_bytes(),
+        )?;
+    }
+    stream.write_all(b"0\r\n\r\n")?;
+    stream.flush()
+}
+
+/// One `data:` line per line of `lines`.
+fn sse_event(lines: &[u8]) -> Vec<u8> {
+    let mut event = String::new();
+    for line in String::from_utf8_lossy(lines).lines() {
+        event.push_str("data: ");
+        event.push_str(line);
+        event.push('\n');
+    }
+    event.push('\n');
+    event.into_bytes()
+}
+
+fn write_chunk(stream: &mut TcpStream, data: &[u8]) -> io::Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+    write!(stream, "{:x}\r\n", data.len())?;
+    stream.write_all(data)?;
+    stream.write_all(b"\r\n")?;
+    stream.flush()
+}
+
+// -------------------------------------------------------------------------
+// Minimal HTTP plumbing
+// -------------------------------------------------------------------------
+
+fn read_request(stream: &mut TcpStream) -> io::Result<Request> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| invalid("empty request"))?
+        .to_string();
+    let path = parts
+        .next()
+        .ok_or_else(|| invalid("missing request path"))?
+        .to_string();
+
+    let mut headers = Vec::new();
+    let mut total = line.len();
+    loop {
+        line.clear();
+        let n = reader.read_line(&mut line)?;
+        total += n;
+        if total > MAX_REQUEST_BYTES {
+            return Err(invalid("request headers too large"));
+        }
+        let trimmed = line.trim_end();
+        if n == 0 || trimmed.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = trimmed.split_once(':') {
+            headers.push((k.trim().to_string(), v.trim().to_string()));
+        }
+    }
+
+    let mut req = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    let len = match req.header("content-length") {
+        Some(v) => v
+            .parse::<usize>()
+            .map_err(|_| invalid("bad content-length"))?,
+        None => 0,
+    };
+    if len > MAX_REQUEST_BYTES {
+        return Err(invalid("request body too large"));
+    }
+    req.body.resize(len, 0);
+    reader.read_exact(&mut req.body)?;
+    Ok(req)
+}
+
+fn respond_json(stream: &mut TcpStream, status: u16, body: &serde_json::Value) -> io::Result<()> {
+    let body = serde_json::to_vec_pretty(body)?;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        reason_phrase(status),
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+fn respond_error(stream: &mut TcpStream, status: u16, message: &str) -> io::Result<()> {
+    respond_json(stream, status, &json!({ "error": message }))
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Decode `%XX` escapes in a URL path segment.
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = |b: u8| (b as char).to_digit(16);
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Compare two byte strings without leaking the position of the first
+/// mismatch through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
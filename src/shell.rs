@@ -0,0 +1,62 @@
+//! Turning a job's command into the process pend starts.
+//!
+//! `Command` quotes arguments for programs that split their command line
+//! like the Microsoft C runtime. `cmd.exe` does not: it sees the quotes and
+//! backslashes `Command` adds, so carets, `&` and embedded quotes arrive
+//! mangled. Command lines for `cmd /c` – whether given as `pend do job cmd
+//! /c …`, with `--shell`, by a template or as a `--ready-cmd` – are
+//! therefore handed over verbatim. Elsewhere, and on Unix, arguments pass
+//! through unchanged.
+use std::ffi::{OsStr, OsString};
+use std::process::Command;
+
+/// A `Command` running `argv`.
+pub(crate) fn command(argv: &[OsString]) -> Command {
+    let mut command = Command::new(&argv[0]);
+    #[cfg(windows)]
+    if argv.len() > 2 && is_cmd(&argv[0]) && is_run_switch(&argv[1]) {
+        use std::os::windows::process::CommandExt;
+        command
+            .arg(&argv[1])
+            .raw_arg(argv[2..].join(OsStr::new(" ")));
+        return command;
+    }
+    command.args(&argv[1..]);
+    command
+}
+
+/// A `Command` running `line` with the platform shell: `sh -c` on Unix,
+/// `cmd /d /s /c` on Windows.
+pub(crate) fn shell(line: &OsStr) -> Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let mut command = Command::new("cmd");
+        // `/s` makes cmd strip exactly the outer pair of quotes and run
+        // what is between them as typed.
+        let mut quoted = OsString::from("\"");
+        quoted.push(line);
+        quoted.push("\"");
+        command.args(["/d", "/s", "/c"]).raw_arg(quoted);
+        command
+    }
+    #[cfg(unix)]
+    {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(line);
+        command
+    }
+}
+
+/// `cmd`, `cmd.exe` or a path to either.
+#[cfg(windows)]
+fn is_cmd(program: &OsStr) -> bool {
+    let program = program.to_string_lossy();
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(&program);
+    name.eq_ignore_ascii_case("cmd") || name.eq_ignore_ascii_case("cmd.exe")
+}
+
+#[cfg(windows)]
+fn is_run_switch(arg: &OsStr) -> bool {
+    arg.eq_ignore_ascii_case("/c") || arg.eq_ignore_ascii_case("/k")
+}
@@ -0,0 +1,77 @@
+//! `pend shell-init <shell>`: shell functions for interactive use.
+//!
+//! Evaluated from the shell's startup file, the script exports
+//! `PEND_SHELL_PID` so every `pend do` from that shell is remembered as its
+//! last job (see `last.rs`), and defines
+//!
+//! * `pd CMD …` – start `CMD` as a job named after it and print the name;
+//! * `pw [OPTIONS]` – `pend wait %last`, the shell's last job;
+//! * `__pend_prompt` – `[pend N] ` while N jobs are running, for the prompt.
+
+/// Shells `pend shell-init` has a script for.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub(crate) enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+const POSIX: &str = r#"# pend shell integration
+export PEND_SHELL_PID=$$
+
+# pd CMD...: start CMD as a job named after it and print the name.
+pd() {
+    pend do --auto-name -- "$@"
+}
+
+# pw [OPTIONS]: wait for the job last submitted from this shell.
+pw() {
+    pend wait %last "$@"
+}
+
+# __pend_prompt: "[pend N] " while N jobs are running.
+__pend_prompt() {
+    local n
+    n=$(pend status --count 2>/dev/null) || return 0
+    if [ "${n:-0}" -gt 0 ]; then
+        printf '[pend %s] ' "$n"
+    fi
+}
+"#;
+
+const FISH: &str = r#"# pend shell integration
+set -gx PEND_SHELL_PID $fish_pid
+
+function pd --description 'Start a pend job named after the command'
+    pend do --auto-name -- $argv
+end
+
+function pw --description 'Wait for the pend job last submitted from this shell'
+    pend wait %last $argv
+end
+
+function __pend_prompt --description 'Running pend jobs, for the prompt'
+    set -l n (pend status --count 2>/dev/null); or return 0
+    if test "$n" -gt 0
+        printf '[pend %s] ' $n
+    end
+end
+"#;
+
+/// The script for `shell`, with a line showing how to put the job count
+/// into its prompt.
+pub(crate) fn script(shell: Shell) -> String {
+    let (body, prompt) = match shell {
+        Shell::Bash => (POSIX, r#"PS1='$(__pend_prompt)'"$PS1""#),
+        Shell::Zsh => (
+            POSIX,
+            r#"setopt PROMPT_SUBST; PROMPT='$(__pend_prompt)'"$PROMPT""#,
+        ),
+        Shell::Fish => (
+            FISH,
+            "functions -c fish_prompt __pend_fish_prompt; \
+             function fish_prompt; __pend_prompt; __pend_fish_prompt; end",
+        ),
+    };
+    format!("{body}\n# To show running jobs in the prompt:\n#   {prompt}\n")
+}
@@ -0,0 +1,305 @@
+//! Named resource slots: `pend do --slot gpu:2`.
+//!
+//! A slot `NAME:N` stands for `N` interchangeable units of some resource –
+//! GPUs, licenses, a database that tolerates two importers. Each unit is a
+//! lock file `<jobs root>/slots/NAME.<index>.lock` (locked the same way as
+//! job locks, see `lock.rs`), so independent `pend do` invocations agree on
+//! who holds what without any daemon. The worker takes one unit of every
+//! slot the job asks for before the first attempt – waiting as long as all
+//! are busy – and keeps them until the job finished. Units are taken all at
+//! once or not at all, so two jobs that each want `gpu` and `license`
+//! cannot deadlock by holding one and waiting for the other.
+//!
+//! The count belongs to each job's request rather than to the slot: jobs
+//! asking for `gpu:1` and `gpu:2` share unit 0 but only the latter may also
+//! use unit 1.
+//!
+//! The slot called [`GPU_SLOT`] numbers the machine's GPUs: a job holding
+//! its unit `N` gets `CUDA_VISIBLE_DEVICES=N` (unless it sets the variable
+//! with `--env`), so `pend do --slot gpu:4 …` spreads parallel training
+//! jobs over four devices instead of piling them onto the first one.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::lock::JobLock;
+use crate::paths::JobPaths;
+use crate::worker::JobOptions;
+
+/// The slot whose units are GPU device indices.
+const GPU_SLOT: &str = "gpu";
+
+/// The variable telling CUDA programs which device to use.
+const DEVICE_VAR: &str = "CUDA_VISIBLE_DEVICES";
+
+/// How often a job waiting for a slot looks for a free unit.
+const SLOT_POLL: Duration = Duration::from_millis(100);
+
+/// How long a job waits for a slot to gain one priority level.
+const AGING: Duration = Duration::from_secs(60);
+
+/// Parse a `--priority`: `high`, `normal`, `low` or a number, higher first.
+pub(crate) fn parse_priority(s: &str) -> Result<i32, String> {
+    match s {
+        "high" => Ok(1),
+        "normal" => Ok(0),
+        "low" => Ok(-1),
+        _ => s.parse().map_err(|_| {
+            format!("invalid priority '{s}' (expected high, normal, low or a number)")
+        }),
+    }
+}
+
+/// A `--slot NAME[:N]` request; `N` defaults to 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) struct Slot {
+    pub(crate) name: String,
+    pub(crate) count: u32,
+}
+
+impl std::str::FromStr for Slot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, count) = match s.split_once(':') {
+            Some((name, count)) => match count.parse::<u32>() {
+                Ok(count) if count > 0 => (name, count),
+                _ => return Err(format!("invalid slot count '{count}' (expected 1 or more)")),
+            },
+            None => (s, 1),
+        };
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "invalid slot name '{name}' (use letters, digits, '-' and '_')"
+            ));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            count,
+        })
+    }
+}
+
+impl std::fmt::Display for Slot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.name, self.count)
+    }
+}
+
+impl TryFrom<String> for Slot {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Slot> for String {
+    fn from(slot: Slot) -> Self {
+        slot.to_string()
+    }
+}
+
+/// One unit of each requested slot, held until dropped.
+#[derive(Default)]
+pub(crate) struct Held {
+    units: Vec<(String, u32, JobLock)>,
+}
+
+impl Held {
+    /// The unit index taken of each slot, for the metadata.
+    pub(crate) fn indices(&self) -> BTreeMap<String, u32> {
+        self.units
+            .iter()
+            .map(|(name, index, _)| (name.clone(), *index))
+            .collect()
+    }
+
+    /// Point the job at the GPU whose [`GPU_SLOT`] unit it holds, unless
+    /// `--env` picked a device already.
+    pub(crate) fn assign_device(&self, options: &mut JobOptions) {
+        let Some((_, index, _)) = self.units.iter().find(|(name, ..)| name == GPU_SLOT) else {
+            return;
+        };
+        if !options.env.iter().any(|(name, _)| name == DEVICE_VAR) {
+            options
+                .env
+                .push((DEVICE_VAR.to_string(), index.to_string()));
+        }
+    }
+}
+
+/// Block until one unit of every slot in `slots` is free and no waiter
+/// with a higher rank wants one of them, and take them. Fails with
+/// `Interrupted` once `job` is killed while waiting.
+pub(crate) fn acquire(
+    config: &Config,
+    slots: &[Slot],
+    priority: i32,
+    name: &str,
+    job: &JobPaths,
+) -> io::Result<Held> {
+    if slots.is_empty() {
+        return Ok(Held::default());
+    }
+    let dir = crate::paths::jobs_root(config)?.join("slots");
+    std::fs::create_dir_all(&dir)?;
+    let queue = dir.join("queue");
+    let mut ticket: Option<Ticket> = None;
+    let mut announced = false;
+    loop {
+        if job.cancel.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "job was killed while waiting for a slot",
+            ));
+        }
+        let mine = match &ticket {
+            Some(ticket) => ticket.entry.clone(),
+            None => Entry::new(name, priority, slots),
+        };
+        if !outranked(&queue, &mine) {
+            let mut held = Held::default();
+            for slot in slots {
+                match try_take(&dir, slot)? {
+                    Some((index, lock)) => held.units.push((slot.name.clone(), index, lock)),
+                    None => break,
+                }
+            }
+            if held.units.len() == slots.len() {
+                tracing::debug!(slots = ?held.indices(), "slots acquired");
+                return Ok(held);
+            }
+            // Give back what we got so others can make progress.
+            drop(held);
+        }
+        if ticket.is_none() {
+            ticket = Ticket::take(&queue, mine)?;
+        }
+        if !announced {
+            let names: Vec<&str> = slots.iter().map(|s| s.name.as_str()).collect();
+            let _ = job.write_status(&format!("waiting for slot {}", names.join(", ")));
+            announced = true;
+        }
+        std::thread::sleep(SLOT_POLL);
+    }
+}
+
+/// A place in the queue of jobs waiting for slots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    job: String,
+    priority: i32,
+    /// When the job started waiting, in seconds since the epoch.
+    since: f64,
+    slots: Vec<String>,
+}
+
+impl Entry {
+    fn new(job: &str, priority: i32, slots: &[Slot]) -> Self {
+        Entry {
+            job: job.to_string(),
+            priority,
+            since: now(),
+            slots: slots.iter().map(|slot| slot.name.clone()).collect(),
+        }
+    }
+
+    /// The priority plus one level per [`AGING`] waited.
+    fn rank(&self, now: f64) -> f64 {
+        self.priority as f64 + (now - self.since).max(0.0) / AGING.as_secs_f64()
+    }
+
+    /// Whether `self` goes before `other`.
+    fn outranks(&self, other: &Entry, now: f64) -> bool {
+        let (mine, theirs) = (self.rank(now), other.rank(now));
+        mine > theirs
+            || (mine == theirs
+                && (self.since < other.since
+                    || (self.since == other.since && self.job < other.job)))
+    }
+}
+
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Whether a live waiter in `queue` that wants one of the slots of `mine`
+/// goes first.
+fn outranked(queue: &Path, mine: &Entry) -> bool {
+    let Ok(dir) = std::fs::read_dir(queue) else {
+        return false;
+    };
+    let now = now();
+    dir.flatten().any(|file| {
+        let path = file.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            return false;
+        }
+        let Some(other) = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Entry>(&bytes).ok())
+        else {
+            return false;
+        };
+        other.job != mine.job
+            && other.slots.iter().any(|slot| mine.slots.contains(slot))
+            && other.outranks(mine, now)
+            // A ticket whose lock is free was left behind by a dead worker.
+            && crate::lock::is_held(&path.with_extension("lock"))
+    })
+}
+
+/// A job's entry in the queue, withdrawn when dropped.
+struct Ticket {
+    entry: Entry,
+    path: PathBuf,
+    _lock: JobLock,
+}
+
+impl Ticket {
+    /// Enter the queue; `None` while the ticket's lock is busy.
+    fn take(queue: &Path, entry: Entry) -> io::Result<Option<Self>> {
+        std::fs::create_dir_all(queue)?;
+        let path = queue.join(format!("{}.json", entry.job));
+        // The lock comes first: readers take a ticket without a held lock
+        // for a leftover.
+        let Some(lock) = JobLock::try_acquire(&path.with_extension("lock"))? else {
+            return Ok(None);
+        };
+        crate::paths::replace_file(&path, &serde_json::to_vec(&entry)?)?;
+        Ok(Some(Ticket {
+            entry,
+            path,
+            _lock: lock,
+        }))
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Lock the first free unit of `slot`.
+fn try_take(dir: &Path, slot: &Slot) -> io::Result<Option<(u32, JobLock)>> {
+    for index in 0..slot.count {
+        let path = dir.join(format!("{}.{index}.lock", slot.name));
+        if let Some(lock) = JobLock::try_acquire(&path)? {
+            return Ok(Some((index, lock)));
+        }
+    }
+    Ok(None)
+}
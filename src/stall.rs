@@ -0,0 +1,119 @@
+//! `pend do --stall-timeout`: a watchdog for attempts that hang.
+//!
+//! Two kinds of hang keep a worker waiting forever: a command that stops
+//! writing and never exits, and a command that exited while something it
+//! started in the background still holds its stdout or stderr – the worker
+//! reads both pipes to their end before it records the exit. With
+//! `--stall-timeout 5m` the worker notices when an attempt has written
+//! nothing for that long, before or after the command exited, and acts on
+//! `--on-stall`:
+//!
+//! * `warn` (default) logs a warning and has `pend status` show
+//!   `stalled (no output for 300s)` until output resumes;
+//! * `term` also terminates the attempt's process tree – process group,
+//!   Job Object or container – so the attempt ends, recorded as failure
+//!   kind `stalled`, and `--retries` apply.
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::paths::JobPaths;
+
+/// How often the watchdog looks at the attempt.
+const CHECK: Duration = Duration::from_millis(100);
+
+/// What the watchdog does about a stalled attempt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum StallAction {
+    #[default]
+    Warn,
+    Term,
+}
+
+/// When an attempt last wrote something, shared by its reader threads.
+pub(crate) struct Activity {
+    started: Instant,
+    /// Milliseconds after `started`.
+    last: AtomicU64,
+}
+
+impl Activity {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            started: Instant::now(),
+            last: AtomicU64::new(0),
+        })
+    }
+
+    pub(crate) fn touch(&self) {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.last.fetch_max(now, Ordering::Relaxed);
+    }
+
+    fn quiet_for(&self) -> Duration {
+        self.started
+            .elapsed()
+            .saturating_sub(Duration::from_millis(self.last.load(Ordering::Relaxed)))
+    }
+}
+
+/// The watchdog of one attempt. Stop it once the attempt's output was read
+/// to the end.
+pub(crate) struct Watchdog {
+    stop: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<bool>,
+}
+
+impl Watchdog {
+    /// Watch `activity`; `terminate` ends the attempt's process tree.
+    pub(crate) fn start(
+        job_name: &str,
+        paths: &JobPaths,
+        timeout: Duration,
+        action: StallAction,
+        activity: Arc<Activity>,
+        terminate: Box<dyn Fn() + Send>,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let job = job_name.to_string();
+        let paths = paths.clone();
+        let stopped = Arc::clone(&stop);
+        let handle = std::thread::spawn(move || {
+            let mut terminated = false;
+            // The status line to go back to once output resumes.
+            let mut resume: Option<Option<String>> = None;
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(CHECK);
+                let quiet = activity.quiet_for();
+                if quiet < timeout {
+                    if let Some(line) = resume.take() {
+                        tracing::info!(job, "output resumed");
+                        let _ = paths.write_status(line.as_deref().unwrap_or("running"));
+                    }
+                    continue;
+                }
+                if resume.is_some() || terminated {
+                    continue;
+                }
+                let secs = quiet.as_secs();
+                tracing::warn!(job, secs, ?action, "attempt stalled: no output");
+                resume = Some(paths.read_status());
+                let _ = paths.write_status(&format!("stalled (no output for {secs}s)"));
+                if action == StallAction::Term {
+                    terminate();
+                    terminated = true;
+                }
+            }
+            terminated
+        });
+        Self { stop, handle }
+    }
+
+    /// Stop watching. Returns whether the watchdog terminated the attempt.
+    pub(crate) fn stop(self) -> bool {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().unwrap_or(false)
+    }
+}
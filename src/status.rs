@@ -0,0 +1,465 @@
+//! Read-only view of a job assembled from its on-disk artifacts.
+//!
+//! Used by `pend status` and the HTTP API of `pend serve`. A job counts as
+//! *running* for as long as its `.exit` marker is missing; the worker records
+//! PIDs and timestamps in `<job>.json` as soon as it starts, so those fields
+//! are available for running jobs too.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use crate::config::Config;
+use crate::error::PendError;
+use crate::paths::JobPaths;
+
+/// Coarse lifecycle state of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum State {
+    Running,
+    Finished,
+    /// The worker is gone but never recorded an exit code.
+    Died,
+}
+
+/// Snapshot of a single job.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct JobStatus {
+    pub(crate) job: String,
+    /// Tells this run apart from others of the same name (see `job_id.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) id: Option<String>,
+    /// Owner of the job, for `pend status --all-users`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) user: Option<String>,
+    pub(crate) state: State,
+    pub(crate) exit_code: Option<i32>,
+    /// Current attempt number (retries and restarts included), straight from
+    /// the worker's control socket when reachable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) attempt: Option<u32>,
+    /// PID of the user command (current attempt while running).
+    pub(crate) pid: Option<u32>,
+    pub(crate) worker_pid: Option<u32>,
+    /// Processes still alive in the attempt's Job Object (Windows only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) processes: Option<u32>,
+    pub(crate) started: Option<String>,
+    pub(crate) ended: Option<String>,
+    /// When the current (or last) attempt passed its readiness probe.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ready: Option<String>,
+    pub(crate) cmd: Vec<String>,
+    /// Free-text description (`--note`, `pend annotate`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) note: Option<String>,
+    /// The submitter's own key/value pairs (`--meta`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) user_meta: BTreeMap<String, String>,
+    /// What the worker of a running job is doing (its `.status` line).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) activity: Option<String>,
+    /// Wall time from submission to the end of a finished job.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) wall_secs: Option<f64>,
+    /// Signal that killed the last attempt of a finished job (Unix).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) signal: Option<i32>,
+    /// When `--timeout` kills the running attempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) deadline: Option<String>,
+    /// Percentage the running command last reported (see `report.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) progress: Option<f64>,
+    /// Corrupt artifacts found while reading the job (see `validate.rs`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) corrupt: Vec<String>,
+    /// Why the artifacts look like they come from different runs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) mixed: Option<String>,
+    /// `pend truncate` discarded the output.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) truncated: bool,
+    /// Why a finished job failed (see `failure.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) failure: Option<crate::failure::Failure>,
+    /// Who submitted the job and from where (see `submitter.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) submitter: Option<crate::submitter::Submitter>,
+    /// Failed runs in a row of a recurring job (see `streak.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) failing: Option<crate::streak::Streak>,
+    /// When the finished job's artifacts expire (`--expires-in`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) expires: Option<String>,
+}
+
+/// Gather the status of `job_name`. Fails with `NotFound` when the job has
+/// no artifacts at all.
+pub(crate) fn job_status(config: &Config, job_name: &str) -> io::Result<JobStatus> {
+    status_at(&JobPaths::new(config, job_name)?, job_name)
+}
+
+fn status_at(paths: &JobPaths, job_name: &str) -> io::Result<JobStatus> {
+    if !paths.any_exist() {
+        return Err(PendError::NotFound(job_name.to_string()).into());
+    }
+
+    let exit_code = crate::validate::read_exit_code(&paths.exit).ok().flatten();
+    let quick = exit_code.and_then(|_| crate::quick::QuickStatus::read(&paths.result));
+    let corrupt = crate::validate::quick_check(paths)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let meta = fs::read(&paths.meta)
+        .ok()
+        .and_then(|b| crate::version::parse_meta(&b).ok())
+        .unwrap_or(serde_json::Value::Null);
+
+    let state = if paths.exit.exists() {
+        State::Finished
+    } else if local_worker(&meta)
+        .is_some_and(|(pid, started)| !crate::process::is_running(pid, started))
+        // The worker writes `.exit` before it exits.
+        && !paths.exit.exists()
+    {
+        State::Died
+    } else {
+        State::Running
+    };
+
+    let str_field = |key: &str| meta.get(key).and_then(|v| v.as_str()).map(String::from);
+    let pid_field = |key: &str| meta.get(key).and_then(|v| v.as_u64()).map(|v| v as u32);
+
+    // A running worker knows its current attempt first-hand; prefer its
+    // answer over metadata that might be a few milliseconds stale.
+    let live = if state == State::Running {
+        crate::control::request(paths, "status")
+            .and_then(|l| serde_json::from_str::<serde_json::Value>(&l).ok())
+    } else {
+        None
+    };
+    let attempt = live
+        .as_ref()
+        .and_then(|v| v.get("attempt"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .or(quick.as_ref().map(|quick| quick.attempts))
+        .or_else(|| pid_field("attempt"));
+    let pid = live
+        .as_ref()
+        .and_then(|v| v.get("pid"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .or_else(|| pid_field("pid"));
+
+    #[cfg(windows)]
+    let processes = if state == State::Running {
+        crate::process::JobObject::open(paths).and_then(|job| job.active_processes())
+    } else {
+        None
+    };
+    #[cfg(not(windows))]
+    let processes = None;
+
+    Ok(JobStatus {
+        job: job_name.to_string(),
+        id: str_field("id"),
+        user: None,
+        state,
+        exit_code,
+        attempt,
+        pid,
+        worker_pid: pid_field("worker_pid"),
+        processes,
+        started: str_field("started"),
+        ended: str_field("ended"),
+        ready: str_field("ready"),
+        cmd: meta
+            .get("cmd")
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        note: str_field("note"),
+        user_meta: meta
+            .get("user_meta")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default(),
+        activity: (state == State::Running)
+            .then(|| paths.read_status())
+            .flatten(),
+        wall_secs: quick.as_ref().map(|quick| quick.wall_secs),
+        signal: quick.as_ref().and_then(|quick| quick.signal),
+        deadline: (state == State::Running)
+            .then(|| attempt_deadline(&meta))
+            .flatten()
+            .map(|deadline| deadline.to_rfc3339()),
+        progress: (state == State::Running)
+            .then(|| crate::report::read(&paths.progress))
+            .flatten(),
+        corrupt,
+        mixed: crate::job_id::mismatch(paths),
+        truncated: crate::truncate::was_truncated(&meta),
+        failure: exit_code
+            .and(meta.get("failure"))
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        submitter: meta
+            .get("submitter")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        failing: exit_code
+            .and(meta.get("failing"))
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        expires: exit_code.and(str_field("expires")),
+    })
+}
+
+/// When `--timeout` kills the attempt in progress, from the job's metadata.
+pub(crate) fn attempt_deadline(meta: &serde_json::Value) -> Option<DateTime<Utc>> {
+    let attempt = meta.get("attempts")?.as_array()?.last()?;
+    if attempt.get("ended").is_some() {
+        return None;
+    }
+    let deadline = DateTime::parse_from_rfc3339(attempt.get("deadline")?.as_str()?).ok()?;
+    Some(deadline.with_timezone(&Utc))
+}
+
+/// `timeout in 3m07s` for an attempt killed at `deadline`.
+pub(crate) fn describe_deadline(deadline: DateTime<Utc>) -> String {
+    format!(
+        "timeout in {}",
+        crate::timeline::format_duration(deadline - Utc::now())
+    )
+}
+
+/// Exit code of `pend exists <job>`: 0 once the job finished (or its worker
+/// died, which ends it just the same), 1 while it runs, 2 without a trace
+/// of it. Looks at file presence and the lock first, so the common cases
+/// need no metadata.
+pub(crate) fn exists(config: &Config, job_name: &str) -> io::Result<i32> {
+    let paths = JobPaths::new(config, job_name)?;
+    if paths.exit.exists() {
+        return Ok(0);
+    }
+    if !paths.any_exist() {
+        return Ok(2);
+    }
+    if crate::lock::is_held(&paths.lock) {
+        return Ok(1);
+    }
+    Ok(match status_at(&paths, job_name)?.state {
+        State::Running => 1,
+        State::Finished | State::Died => 0,
+    })
+}
+
+/// The jobs in `root` whose worker is alive: it holds the job's lock and
+/// has not written `.exit` yet.
+pub(crate) fn running_jobs(root: &std::path::Path) -> Vec<String> {
+    crate::paths::discover_jobs(root)
+        .into_iter()
+        .filter(|job| {
+            JobPaths::in_root(root, job)
+                .is_ok_and(|paths| !paths.exit.exists() && crate::lock::is_held(&paths.lock))
+        })
+        .collect()
+}
+
+/// The worker recorded in `meta` as `(pid, start time)`, provided it runs on
+/// this host and can therefore be checked directly.
+pub(crate) fn local_worker(meta: &serde_json::Value) -> Option<(u32, Option<u64>)> {
+    if meta.get("worker_host")?.as_str()? != crate::lock::hostname() {
+        return None;
+    }
+    let pid = meta.get("worker_pid")?.as_u64()?;
+    let started = meta.get("worker_started").and_then(|v| v.as_u64());
+    Some((pid as u32, started))
+}
+
+/// Which jobs `pend status` lists.
+pub(crate) struct Filter {
+    /// `--failed`: failed runs only.
+    pub(crate) failed: bool,
+    /// `--reason`: runs that failed this way only.
+    pub(crate) reason: Option<crate::failure::FailureKind>,
+    /// `--meta`: jobs submitted with all of these pairs only.
+    pub(crate) user_meta: Vec<(String, String)>,
+}
+
+/// Entry point for `pend status [job …]`. Without job names every job in the
+/// jobs directory is listed.
+pub(crate) fn print_status(
+    config: &Config,
+    job_names: &[String],
+    json: bool,
+    count: bool,
+    all_users: bool,
+    who: bool,
+    filter: &Filter,
+) -> io::Result<()> {
+    let mut statuses = if all_users {
+        all_user_statuses(config)?
+    } else {
+        let names = if job_names.is_empty() {
+            crate::paths::discover_jobs(&crate::paths::jobs_root(config)?)
+        } else {
+            job_names.to_vec()
+        };
+        names
+            .iter()
+            .map(|n| job_status(config, n))
+            .collect::<io::Result<Vec<_>>>()?
+    };
+
+    // `--failed`, `--reason`: failed runs, optionally of one kind only.
+    if filter.failed || filter.reason.is_some() {
+        statuses.retain(|st| match filter.reason {
+            Some(kind) => st.failure.is_some_and(|f| f.kind == kind),
+            None => {
+                st.state == State::Died
+                    || st.state == State::Finished && st.exit_code.is_some_and(|c| c != 0)
+            }
+        });
+    }
+    statuses.retain(|st| {
+        filter
+            .user_meta
+            .iter()
+            .all(|(key, value)| st.user_meta.get(key) == Some(value))
+    });
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
+    if count {
+        let running = statuses
+            .iter()
+            .filter(|st| st.state == State::Running)
+            .count();
+        println!("{running}");
+        return Ok(());
+    }
+
+    for st in &statuses {
+        if let Some(why) = &st.mixed {
+            crate::job_id::warn(&st.job, why);
+        }
+        let line = describe(st);
+        match &st.note {
+            Some(note) => println!("{line} {} {note}", crate::glyphs::dash(config.ascii)),
+            None => println!("{line}"),
+        }
+        if let Some(submitter) = st.submitter.as_ref().filter(|_| who) {
+            println!("    {}", submitter.describe());
+        }
+    }
+    Ok(())
+}
+
+/// Every job of every user below a shared `PEND_DIR` (`--all-users`).
+fn all_user_statuses(config: &Config) -> io::Result<Vec<JobStatus>> {
+    if !config.per_user {
+        return Err(PendError::Usage(format!(
+            "--all-users requires {}",
+            crate::paths::PER_USER_ENV
+        ))
+        .into());
+    }
+    let mut statuses = Vec::new();
+    for (user, root) in crate::paths::user_roots(config) {
+        for name in crate::paths::discover_jobs(&root) {
+            let mut status = status_at(&JobPaths::in_root(&root, &name)?, &name)?;
+            status.user = Some(user.clone());
+            statuses.push(status);
+        }
+    }
+    Ok(statuses)
+}
+
+/// One line of `pend status` output, without the note.
+fn describe(st: &JobStatus) -> String {
+    let job = match &st.user {
+        Some(user) => format!("{user}/{}", st.job),
+        None => st.job.clone(),
+    };
+    if !st.corrupt.is_empty() {
+        return format!("{:<20} corrupt ({})", job, st.corrupt.join("; "));
+    }
+    let line = match st.state {
+        State::Running => {
+            let mut details = Vec::new();
+            if let Some(pid) = st.pid {
+                details.push(format!("pid {pid}"));
+            }
+            if let Some(n) = st.processes {
+                details.push(format!("{n} processes"));
+            }
+            if let Some(percent) = st.progress {
+                details.push(format!("{percent:.0}%"));
+            }
+            if let Some(deadline) = st
+                .deadline
+                .as_deref()
+                .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+            {
+                details.push(describe_deadline(deadline.with_timezone(&Utc)));
+            }
+            // The worker's own account names the attempt already.
+            let activity = match &st.activity {
+                Some(activity) => activity.clone(),
+                None => {
+                    if let Some(attempt) = st.attempt.filter(|&a| a > 1) {
+                        details.push(format!("attempt {attempt}"));
+                    }
+                    "running".to_string()
+                }
+            };
+            if details.is_empty() {
+                format!("{:<20} {activity}", job)
+            } else {
+                format!("{:<20} {activity} ({})", job, details.join(", "))
+            }
+        }
+        State::Finished => match st.exit_code {
+            Some(code) => {
+                let code = crate::exit_status::describe(code);
+                match st
+                    .failure
+                    .filter(|f| f.kind != crate::failure::FailureKind::NonzeroExit)
+                {
+                    Some(failure) => format!("{:<20} exit {code} ({})", job, failure.describe()),
+                    None => format!("{:<20} exit {code}", job),
+                }
+            }
+            None => format!("{:<20} finished", job),
+        },
+        State::Died => format!("{:<20} died (worker exited without an exit code)", job),
+    };
+    let line = match st.failing.as_ref().and_then(|s| s.describe()) {
+        Some(streak) => format!("{line}, {streak}"),
+        None => line,
+    };
+    let line = match st
+        .expires
+        .as_deref()
+        .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+    {
+        Some(expires) if expires <= Utc::now() => format!("{line}, expired"),
+        Some(expires) => format!(
+            "{line}, expires in {}",
+            crate::timeline::format_duration(expires.with_timezone(&Utc) - Utc::now())
+        ),
+        None => line,
+    };
+    if st.truncated {
+        format!("{line} (output truncated)")
+    } else {
+        line
+    }
+}
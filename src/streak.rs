@@ -0,0 +1,100 @@
+//! Consecutive failed runs of a recurring job.
+//!
+//! A job submitted again and again under the same name – from cron, a CI
+//! schedule or `pend watch` – replaces its artifacts on every run, so each
+//! failure would look like the first. `pend do` therefore reads the streak
+//! of the previous run before removing its artifacts and hands it to the
+//! worker in `$PEND_FAILING`; the worker records the streak including its
+//! own run in `.json`:
+//!
+//! ```json
+//! "failing": { "runs": 4, "since": "2026-10-16T09:00:00+00:00" }
+//! ```
+//!
+//! `since` is when the first failed run of the streak ended. A successful
+//! run ends the streak and records none; a run stopped by `pend kill`
+//! neither extends nor ends it. `pend status` shows streaks of two runs or
+//! more, and `post-failure` hooks get them in `$PEND_FAILED_RUNS` and
+//! `$PEND_FAILING_SINCE`.
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use crate::paths::JobPaths;
+
+/// Variable carrying the previous streak from `pend do` to the worker.
+const ENV: &str = "PEND_FAILING";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Streak {
+    /// Failed runs in a row, the latest included.
+    pub(crate) runs: u32,
+    /// When the first of them ended (RFC 3339).
+    pub(crate) since: String,
+}
+
+impl Streak {
+    /// The streak recorded by the run whose artifacts `paths` holds.
+    pub(crate) fn recorded(paths: &JobPaths) -> Option<Self> {
+        let meta = crate::version::parse_meta(&std::fs::read(&paths.meta).ok()?).ok()?;
+        serde_json::from_value(meta.get("failing")?.clone()).ok()
+    }
+
+    /// The streak after a run that ended at `ended` with `exit_code`.
+    pub(crate) fn after(
+        previous: Option<Self>,
+        exit_code: i32,
+        killed: bool,
+        ended: DateTime<Utc>,
+    ) -> Option<Self> {
+        if killed {
+            previous
+        } else if exit_code == 0 {
+            None
+        } else {
+            Some(match previous {
+                Some(streak) => Self {
+                    runs: streak.runs + 1,
+                    ..streak
+                },
+                None => Self {
+                    runs: 1,
+                    since: ended.to_rfc3339(),
+                },
+            })
+        }
+    }
+
+    /// "failed 4 runs in a row, first failure at …", for streaks worth
+    /// pointing out.
+    pub(crate) fn describe(&self) -> Option<String> {
+        if self.runs < 2 {
+            return None;
+        }
+        let since = DateTime::parse_from_rfc3339(&self.since)
+            .map(|t| {
+                t.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.since.clone());
+        Some(format!(
+            "failed {} runs in a row, first failure at {since}",
+            self.runs
+        ))
+    }
+}
+
+/// Hand the streak of the previous run to the worker `command` starts.
+pub(crate) fn export(command: &mut Command, previous: Option<&Streak>) {
+    // A job submitted from within another job must not inherit its streak.
+    match previous.and_then(|s| serde_json::to_string(s).ok()) {
+        Some(json) => command.env(ENV, json),
+        None => command.env_remove(ENV),
+    };
+}
+
+/// The streak of the previous run `pend do` handed over, if any.
+pub(crate) fn from_env() -> Option<Streak> {
+    serde_json::from_str(&std::env::var(ENV).ok()?).ok()
+}
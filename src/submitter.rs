@@ -0,0 +1,176 @@
+//! Who submitted a job, and from where.
+//!
+//! On a shared machine `pend status` tells that `build` runs but not whose
+//! it is. `pend do` therefore describes its own invocation to the worker in
+//! `$PEND_SUBMITTER`, and the worker records it as `submitter` in `.json`:
+//!
+//! ```json
+//! "submitter": {
+//!   "argv": ["pend", "do", "build", "make"],
+//!   "user": "alice",
+//!   "host": "buildbox",
+//!   "parent_pid": 4242,
+//!   "tty": "/dev/pts/3",
+//!   "ssh_client": "10.0.0.5",
+//!   "ci": "github-actions",
+//!   "ci_url": "https://github.com/o/r/actions/runs/1"
+//! }
+//! ```
+//!
+//! `parent_pid` is the process that ran `pend do`, usually a shell; `tty`
+//! its controlling terminal, if any. `ssh_client` comes from
+//! `$SSH_CONNECTION`, `ci` and `ci_url` from the variables CI systems set
+//! ([`CI_SYSTEMS`]). `pend status --who` prints it below each job.
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Variable carrying the description from `pend do` to the worker.
+const ENV: &str = "PEND_SUBMITTER";
+
+/// CI systems recognised by a variable they set, with the variable holding
+/// the URL of the build.
+const CI_SYSTEMS: &[(&str, &str, Option<&str>)] = &[
+    ("GITHUB_ACTIONS", "github-actions", None),
+    ("GITLAB_CI", "gitlab", Some("CI_JOB_URL")),
+    ("BUILDKITE", "buildkite", Some("BUILDKITE_BUILD_URL")),
+    ("CIRCLECI", "circleci", Some("CIRCLE_BUILD_URL")),
+    ("JENKINS_URL", "jenkins", Some("BUILD_URL")),
+    ("TF_BUILD", "azure-pipelines", None),
+    ("CI", "ci", None),
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Submitter {
+    pub(crate) argv: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) user: Option<String>,
+    pub(crate) host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) parent_pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tty: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ssh_client: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ci: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ci_url: Option<String>,
+}
+
+impl Submitter {
+    /// The invocation of this process.
+    fn current() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let ci = CI_SYSTEMS.iter().find(|(marker, ..)| var(marker).is_some());
+        let ci_url = ci.and_then(|(_, name, url)| match url {
+            Some(url) => var(url),
+            None if *name == "github-actions" => github_url(),
+            None => None,
+        });
+        Self {
+            argv: std::env::args_os()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            user: user(),
+            host: crate::lock::hostname(),
+            parent_pid: parent_pid(),
+            tty: tty(),
+            // "client_ip client_port server_ip server_port"
+            ssh_client: var("SSH_CONNECTION")
+                .and_then(|conn| conn.split_whitespace().next().map(String::from)),
+            ci: ci.map(|(_, name, _)| name.to_string()),
+            ci_url,
+        }
+    }
+
+    /// One line for `pend status --who`.
+    pub(crate) fn describe(&self) -> String {
+        let mut line = format!(
+            "submitted by {}@{}",
+            self.user.as_deref().unwrap_or("?"),
+            self.host
+        );
+        if let Some(tty) = &self.tty {
+            line.push_str(&format!(" on {tty}"));
+        }
+        if let Some(client) = &self.ssh_client {
+            line.push_str(&format!(" over SSH from {client}"));
+        }
+        match (&self.ci, &self.ci_url) {
+            (Some(ci), Some(url)) => line.push_str(&format!(" in {ci} ({url})")),
+            (Some(ci), None) => line.push_str(&format!(" in {ci}")),
+            _ => {}
+        }
+        if let Some(pid) = self.parent_pid {
+            line.push_str(&format!(", parent pid {pid}"));
+        }
+        format!("{line}: {}", self.argv.join(" "))
+    }
+}
+
+/// Describe this process to the worker started by `command`.
+pub(crate) fn export(command: &mut Command) {
+    if let Ok(json) = serde_json::to_string(&Submitter::current()) {
+        command.env(ENV, json);
+    }
+}
+
+/// The submission `pend do` described, if any.
+pub(crate) fn from_env() -> Option<Submitter> {
+    serde_json::from_str(&std::env::var(ENV).ok()?).ok()
+}
+
+/// The run URL of a GitHub Actions job, which has no variable of its own.
+fn github_url() -> Option<String> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    Some(format!(
+        "{}/{}/actions/runs/{}",
+        var("GITHUB_SERVER_URL")?,
+        var("GITHUB_REPOSITORY")?,
+        var("GITHUB_RUN_ID")?
+    ))
+}
+
+#[cfg(unix)]
+fn user() -> Option<String> {
+    crate::process::current_user_name()
+}
+
+#[cfg(windows)]
+fn user() -> Option<String> {
+    std::env::var("USERNAME").ok()
+}
+
+#[cfg(unix)]
+fn parent_pid() -> Option<u32> {
+    // Safety: `getppid` cannot fail.
+    Some(unsafe { libc::getppid() } as u32)
+}
+
+#[cfg(windows)]
+fn parent_pid() -> Option<u32> {
+    None
+}
+
+/// The terminal of the first standard stream attached to one.
+#[cfg(unix)]
+fn tty() -> Option<String> {
+    let mut buf = [0 as libc::c_char; 256];
+    [0, 1, 2].into_iter().find_map(|fd| {
+        // Safety: `buf` is writable for its whole length.
+        if unsafe { libc::ttyname_r(fd, buf.as_mut_ptr(), buf.len()) } != 0 {
+            return None;
+        }
+        // Safety: on success `ttyname_r` stored a NUL-terminated string.
+        let name = unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) };
+        Some(name.to_string_lossy().into_owned())
+    })
+}
+
+#[cfg(windows)]
+fn tty() -> Option<String> {
+    use std::io::IsTerminal;
+    std::io::stdin()
+        .is_terminal()
+        .then(|| "console".to_string())
+}
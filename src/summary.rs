@@ -0,0 +1,372 @@
+//! `pend report`: a self-contained summary of jobs to publish, e.g. as a CI
+//! artifact.
+//!
+//! `--html` renders a standalone page – no external stylesheets or scripts
+//! – with a status badge, duration and exit code per job, the retry history
+//! recorded in `<job>.json`, the resource limit a job ran into, and its log
+//! in a collapsible section (open for jobs that did not succeed). Logs are
+//! cut to their last [`LOG_TAIL`] bytes so a chatty job cannot bloat the
+//! page.
+//!
+//! `--markdown` renders a table with a status emoji, duration, exit code
+//! and attempt count per job, followed by the last [`STDERR_LINES`] lines of
+//! stderr of each job that failed – small enough to append to
+//! `$GITHUB_STEP_SUMMARY` so a fan-out shows up in the checks UI.
+use chrono::{DateTime, FixedOffset, Utc};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::paths::JobPaths;
+use crate::status::{JobStatus, State};
+use crate::timeline::format_duration;
+
+/// How much of the end of each log the report includes.
+const LOG_TAIL: u64 = 256 * 1024;
+
+/// How many lines of stderr the Markdown report shows per failed job.
+const STDERR_LINES: usize = 20;
+
+/// The output format of a report.
+#[derive(Clone, Copy)]
+pub(crate) enum Format {
+    Html,
+    Markdown,
+}
+
+/// The outcome shown as a job's badge.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Succeeded,
+    Failed,
+    TimedOut,
+    Running,
+    Died,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Succeeded => "succeeded",
+            Outcome::Failed => "failed",
+            Outcome::TimedOut => "timed out",
+            Outcome::Running => "running",
+            Outcome::Died => "died",
+        }
+    }
+
+    fn emoji(self) -> &'static str {
+        match self {
+            Outcome::Succeeded => "✅",
+            Outcome::Failed => "❌",
+            Outcome::TimedOut => "⏱️",
+            Outcome::Running => "⏳",
+            Outcome::Died => "💀",
+        }
+    }
+}
+
+/// One attempt from the job's metadata.
+struct Attempt {
+    started: Option<DateTime<FixedOffset>>,
+    ended: Option<DateTime<FixedOffset>>,
+    exit_code: Option<i64>,
+    timed_out: bool,
+}
+
+/// Everything the report shows about one job.
+struct Entry {
+    status: JobStatus,
+    outcome: Outcome,
+    duration: Option<chrono::TimeDelta>,
+    attempts: Vec<Attempt>,
+    limit_exceeded: Option<String>,
+    /// The end of the combined log and whether earlier output was cut.
+    log: String,
+    truncated: bool,
+    /// The last [`STDERR_LINES`] lines of stderr, for jobs that did not
+    /// succeed.
+    stderr: String,
+}
+
+/// Entry point for `pend report --html|--markdown [job …]`. Without job
+/// names every job in the jobs directory is included.
+pub(crate) fn write_report(
+    config: &Config,
+    job_names: &[String],
+    format: Format,
+    output: Option<&Path>,
+) -> Result<()> {
+    let names = if job_names.is_empty() {
+        crate::paths::discover_jobs(&crate::paths::jobs_root(config)?)
+    } else {
+        job_names.to_vec()
+    };
+    let entries = names
+        .iter()
+        .map(|name| entry(config, name))
+        .collect::<io::Result<Vec<_>>>()?;
+    let page = match format {
+        Format::Html => html(&entries),
+        Format::Markdown => markdown(&entries),
+    };
+    match output {
+        Some(path) => fs::write(path, page)?,
+        None => print!("{page}"),
+    }
+    Ok(())
+}
+
+fn entry(config: &Config, name: &str) -> io::Result<Entry> {
+    let status = crate::status::job_status(config, name)?;
+    let paths = JobPaths::new(config, name)?;
+    let meta: serde_json::Value = fs::read(&paths.meta)
+        .ok()
+        .and_then(|bytes| crate::version::parse_meta(&bytes).ok())
+        .unwrap_or_default();
+    let time =
+        |value: Option<&serde_json::Value>| DateTime::parse_from_rfc3339(value?.as_str()?).ok();
+
+    let attempts: Vec<Attempt> = meta
+        .get("attempts")
+        .and_then(|a| a.as_array())
+        .into_iter()
+        .flatten()
+        .map(|a| Attempt {
+            started: time(a.get("started")),
+            ended: time(a.get("ended")),
+            exit_code: a.get("exit_code").and_then(|c| c.as_i64()),
+            timed_out: a.get("timed_out").and_then(|t| t.as_bool()) == Some(true),
+        })
+        .collect();
+    let outcome = match (status.state, status.exit_code) {
+        (State::Running, _) => Outcome::Running,
+        (State::Died, _) => Outcome::Died,
+        (State::Finished, Some(0)) => Outcome::Succeeded,
+        _ if attempts.last().is_some_and(|a| a.timed_out) => Outcome::TimedOut,
+        _ => Outcome::Failed,
+    };
+    let started = time(meta.get("started"));
+    let ended = time(meta.get("ended"));
+    let duration = match (started, ended) {
+        (Some(started), Some(ended)) => Some(ended - started),
+        (Some(started), None) if outcome == Outcome::Running => {
+            Some(Utc::now().fixed_offset() - started)
+        }
+        _ => None,
+    };
+    let (log, truncated) = log_tail(&paths.log)?;
+    let stderr = match outcome {
+        Outcome::Succeeded | Outcome::Running => String::new(),
+        _ => {
+            let (err, _) = log_tail(&paths.err)?;
+            let lines: Vec<&str> = err.lines().collect();
+            lines[lines.len().saturating_sub(STDERR_LINES)..].join("\n")
+        }
+    };
+
+    Ok(Entry {
+        status,
+        outcome,
+        duration,
+        attempts,
+        limit_exceeded: meta
+            .get("limit_exceeded")
+            .and_then(|l| l.as_str())
+            .map(String::from),
+        log,
+        truncated,
+        stderr,
+    })
+}
+
+/// The last [`LOG_TAIL`] bytes of `path`, starting at a line boundary when
+/// cut.
+fn log_tail(path: &Path) -> io::Result<(String, bool)> {
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok((String::new(), false)),
+        Err(err) => return Err(err),
+    };
+    let len = file.metadata()?.len();
+    let truncated = len > LOG_TAIL;
+    file.seek(SeekFrom::Start(len.saturating_sub(LOG_TAIL)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    if truncated {
+        let start = bytes.iter().position(|&b| b == b'\n').map_or(0, |i| i + 1);
+        bytes.drain(..start);
+    }
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), truncated))
+}
+
+fn duration_text(duration: Option<chrono::TimeDelta>) -> String {
+    duration.map_or_else(|| "–".to_string(), format_duration)
+}
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2em;color:#222}
+table{border-collapse:collapse;margin-bottom:2em}
+th,td{padding:.3em .8em;border-bottom:1px solid #ddd;text-align:left}
+.badge{display:inline-block;padding:.1em .6em;border-radius:1em;color:#fff;font-size:.85em}
+.succeeded{background:#2e7d32}.failed,.died{background:#c62828}
+.timed-out{background:#ef6c00}.running{background:#1565c0}
+section{margin-bottom:2em}
+code,pre{font-family:ui-monospace,monospace}
+pre{background:#f6f8fa;padding:1em;overflow-x:auto;white-space:pre-wrap}
+.muted{color:#777}
+";
+
+fn badge(outcome: Outcome) -> String {
+    format!(
+        r#"<span class="badge {}">{}</span>"#,
+        outcome.label().replace(' ', "-"),
+        outcome.label()
+    )
+}
+
+fn html(entries: &[Entry]) -> String {
+    let count = |outcome| entries.iter().filter(|e| e.outcome == outcome).count();
+    let mut page = String::new();
+    let _ = write!(
+        page,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>pend report</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n\
+         <h1>pend report</h1>\n<p class=\"muted\">{} jobs: {} succeeded, {} failed, {} running \
+         – generated {}</p>\n",
+        entries.len(),
+        count(Outcome::Succeeded),
+        count(Outcome::Failed) + count(Outcome::TimedOut) + count(Outcome::Died),
+        count(Outcome::Running),
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+    );
+
+    page.push_str(
+        "<table>\n<tr><th>Job</th><th>Status</th><th>Duration</th><th>Exit code</th>\
+         <th>Attempts</th></tr>\n",
+    );
+    for (i, e) in entries.iter().enumerate() {
+        let _ = writeln!(
+            page,
+            "<tr><td><a href=\"#job-{i}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&e.status.job),
+            badge(e.outcome),
+            duration_text(e.duration),
+            e.status.exit_code.map_or("–".to_string(), |c| c.to_string()),
+            e.attempts.len().max(1),
+        );
+    }
+    page.push_str("</table>\n");
+
+    for (i, e) in entries.iter().enumerate() {
+        let _ = writeln!(
+            page,
+            "<section id=\"job-{i}\">\n<h2>{} {}</h2>\n<p><code>{}</code></p>",
+            escape(&e.status.job),
+            badge(e.outcome),
+            escape(&e.status.cmd.join(" ")),
+        );
+        if let Some(note) = &e.status.note {
+            let _ = writeln!(page, "<p>{}</p>", escape(note));
+        }
+        if let Some(limit) = &e.limit_exceeded {
+            let _ = writeln!(page, "<p>Exceeded its {} limit.</p>", escape(limit));
+        }
+        if e.attempts.len() > 1 {
+            page.push_str("<ol>\n");
+            for a in &e.attempts {
+                let ran = match (a.started, a.ended) {
+                    (Some(started), Some(ended)) => format_duration(ended - started),
+                    _ => "still running".to_string(),
+                };
+                let how = match a.exit_code {
+                    _ if a.timed_out => "killed by timeout".to_string(),
+                    Some(code) => format!("exit {code}"),
+                    None => "running".to_string(),
+                };
+                let _ = writeln!(page, "<li>{how} after {ran}</li>");
+            }
+            page.push_str("</ol>\n");
+        }
+        let open = if e.outcome == Outcome::Succeeded {
+            ""
+        } else {
+            " open"
+        };
+        let cut = if e.truncated {
+            ", earlier output omitted"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            page,
+            "<details{open}><summary>Log{cut}</summary>\n<pre>{}</pre>\n</details>\n</section>",
+            escape(&e.log),
+        );
+    }
+    page.push_str("</body>\n</html>\n");
+    page
+}
+
+fn markdown(entries: &[Entry]) -> String {
+    let count = |outcome| entries.iter().filter(|e| e.outcome == outcome).count();
+    let mut text = String::new();
+    let _ = write!(
+        text,
+        "## pend report\n\n{} jobs: {} succeeded, {} failed, {} running\n\n\
+         | | Job | Duration | Exit code | Attempts |\n|---|---|---|---|---|\n",
+        entries.len(),
+        count(Outcome::Succeeded),
+        count(Outcome::Failed) + count(Outcome::TimedOut) + count(Outcome::Died),
+        count(Outcome::Running),
+    );
+    for e in entries {
+        let _ = writeln!(
+            text,
+            "| {} | `{}` | {} | {} | {} |",
+            e.outcome.emoji(),
+            e.status.job,
+            duration_text(e.duration),
+            e.status
+                .exit_code
+                .map_or("–".to_string(), |c| c.to_string()),
+            e.attempts.len().max(1),
+        );
+    }
+    for e in entries.iter().filter(|e| !e.stderr.is_empty()) {
+        let fence = "`".repeat(longest_backtick_run(&e.stderr).max(2) + 1);
+        let _ = write!(
+            text,
+            "\n### {} `{}` ({})\n\n{fence}text\n{}\n{fence}\n",
+            e.outcome.emoji(),
+            e.status.job,
+            e.outcome.label(),
+            e.stderr,
+        );
+    }
+    text
+}
+
+/// The fence must be longer than any run of backticks in the block.
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Escape text for HTML element content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
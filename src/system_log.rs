@@ -0,0 +1,278 @@
+//! `pend do --log-to syslog|journald|eventlog`: mirror output to the system
+//! logger.
+//!
+//! Besides writing the artifacts, the worker then forwards every line the
+//! command prints – stdout at informational, stderr at error priority – so
+//! jobs show up in whatever already collects the machine's logs:
+//!
+//! * `syslog` (Unix) sends each line to `/dev/log` tagged `pend/<job>`;
+//! * `journald` (Linux) uses the journal's native protocol, adding the
+//!   fields `PEND_JOB` and `PEND_STREAM` to filter on (`journalctl
+//!   PEND_JOB=build`);
+//! * `eventlog` (Windows) reports each line to the Application log with
+//!   source `pend`, prefixed by `[<job>]`.
+//!
+//! `$PEND_LOG_SOCKET` names another socket for `syslog` or `journald`, e.g.
+//! the host's inside a container. Forwarding is best effort: lines the
+//! logger refuses are dropped rather than failing the job. Lines longer
+//! than [`MAX_RECORD`] bytes are split.
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use crate::error::{PendError, Result};
+
+/// Longest record sent; longer lines are split.
+const MAX_RECORD: usize = 8 * 1024;
+
+/// Variable naming the socket to send `syslog` / `journald` records to.
+#[cfg(unix)]
+const SOCKET_ENV: &str = "PEND_LOG_SOCKET";
+
+/// System logger a job's output is mirrored to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogTarget {
+    Syslog,
+    Journald,
+    Eventlog,
+}
+
+impl LogTarget {
+    /// Fail on `pend do` when the logger does not exist on this platform.
+    pub(crate) fn check(self) -> Result<()> {
+        let supported = match self {
+            LogTarget::Syslog => cfg!(unix),
+            LogTarget::Journald => cfg!(target_os = "linux"),
+            LogTarget::Eventlog => cfg!(windows),
+        };
+        if supported {
+            Ok(())
+        } else {
+            let platform = match self {
+                LogTarget::Syslog => "Unix",
+                LogTarget::Journald => "Linux",
+                LogTarget::Eventlog => "Windows",
+            };
+            Err(PendError::Usage(format!(
+                "--log-to {}: only available on {platform}",
+                self.name()
+            )))
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            LogTarget::Syslog => "syslog",
+            LogTarget::Journald => "journald",
+            LogTarget::Eventlog => "eventlog",
+        }
+    }
+}
+
+/// Splits the captured chunks of both streams into lines and hands them to
+/// the logger.
+pub(crate) struct Forwarder {
+    sink: Sink,
+    job: String,
+    /// Incomplete last line of stdout and stderr.
+    pending: [Vec<u8>; 2],
+}
+
+impl Forwarder {
+    pub(crate) fn open(target: LogTarget, job_name: &str) -> io::Result<Self> {
+        Ok(Self {
+            sink: Sink::open(target, job_name)?,
+            job: job_name.to_string(),
+            pending: [Vec::new(), Vec::new()],
+        })
+    }
+
+    /// Forward the complete lines of `chunk`, keeping a partial last line
+    /// until the rest arrives.
+    pub(crate) fn feed(&mut self, is_stderr: bool, chunk: &[u8]) {
+        let stream = usize::from(is_stderr);
+        let mut pending = std::mem::take(&mut self.pending[stream]);
+        pending.extend_from_slice(chunk);
+        let mut start = 0;
+        while let Some(end) = pending[start..].iter().position(|&b| b == b'\n') {
+            self.send(is_stderr, &pending[start..start + end]);
+            start += end + 1;
+        }
+        pending.drain(..start);
+        while pending.len() > MAX_RECORD {
+            let rest = pending.split_off(MAX_RECORD);
+            self.send(is_stderr, &pending);
+            pending = rest;
+        }
+        self.pending[stream] = pending;
+    }
+
+    /// Forward what is left of unterminated last lines.
+    pub(crate) fn finish(&mut self) {
+        for (stream, is_stderr) in [(0, false), (1, true)] {
+            let pending = std::mem::take(&mut self.pending[stream]);
+            if !pending.is_empty() {
+                self.send(is_stderr, &pending);
+            }
+        }
+    }
+
+    fn send(&self, is_stderr: bool, line: &[u8]) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        for record in line.chunks(MAX_RECORD) {
+            if let Err(err) = self.sink.send(&self.job, is_stderr, record) {
+                tracing::debug!(%err, "cannot forward a line to the system logger");
+            }
+        }
+    }
+}
+
+/// syslog severities.
+#[cfg(unix)]
+const LOG_ERR: u8 = 3;
+#[cfg(unix)]
+const LOG_INFO: u8 = 6;
+/// syslog facility `user`.
+#[cfg(unix)]
+const LOG_USER: u8 = 1 << 3;
+
+#[cfg(unix)]
+fn severity(is_stderr: bool) -> u8 {
+    if is_stderr {
+        LOG_ERR
+    } else {
+        LOG_INFO
+    }
+}
+
+enum Sink {
+    #[cfg(unix)]
+    Syslog {
+        socket: std::os::unix::net::UnixDatagram,
+        tag: String,
+    },
+    #[cfg(target_os = "linux")]
+    Journald(std::os::unix::net::UnixDatagram),
+    #[cfg(windows)]
+    EventLog(windows_sys::Win32::Foundation::HANDLE),
+}
+
+// Safety: an event source handle may be used from any thread.
+#[cfg(windows)]
+unsafe impl Send for Sink {}
+
+#[cfg(unix)]
+fn connect(default: &str) -> io::Result<std::os::unix::net::UnixDatagram> {
+    let path = std::env::var_os(SOCKET_ENV).unwrap_or_else(|| default.into());
+    let socket = std::os::unix::net::UnixDatagram::unbound()?;
+    socket.connect(path)?;
+    // A logger that falls behind loses lines instead of stalling the job.
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+impl Sink {
+    fn open(target: LogTarget, job_name: &str) -> io::Result<Self> {
+        #[cfg(windows)]
+        let _ = job_name;
+        match target {
+            #[cfg(unix)]
+            LogTarget::Syslog => Ok(Sink::Syslog {
+                socket: connect("/dev/log")?,
+                tag: format!("pend/{job_name}[{}]", std::process::id()),
+            }),
+            #[cfg(target_os = "linux")]
+            LogTarget::Journald => Ok(Sink::Journald(connect("/run/systemd/journal/socket")?)),
+            #[cfg(windows)]
+            LogTarget::Eventlog => {
+                let source: Vec<u16> = "pend\0".encode_utf16().collect();
+                // Safety: `source` is NUL-terminated and outlives the call.
+                let handle = unsafe {
+                    windows_sys::Win32::System::EventLog::RegisterEventSourceW(
+                        std::ptr::null(),
+                        source.as_ptr(),
+                    )
+                };
+                if handle.is_null() {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(Sink::EventLog(handle))
+                }
+            }
+            #[allow(unreachable_patterns)]
+            other => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} is not available on this platform", other.name()),
+            )),
+        }
+    }
+
+    fn send(&self, job_name: &str, is_stderr: bool, line: &[u8]) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Sink::Syslog { socket, tag } => {
+                let stamp = chrono::Local::now().format("%b %e %H:%M:%S");
+                let mut record =
+                    format!("<{}>{stamp} {tag}: ", LOG_USER | severity(is_stderr)).into_bytes();
+                record.extend_from_slice(line);
+                socket.send(&record).map(drop)
+            }
+            #[cfg(target_os = "linux")]
+            Sink::Journald(socket) => {
+                let stream = if is_stderr { "stderr" } else { "stdout" };
+                let mut record = format!(
+                    "SYSLOG_IDENTIFIER=pend\nPEND_JOB={job_name}\nPEND_STREAM={stream}\n\
+                     PRIORITY={}\nMESSAGE=",
+                    severity(is_stderr)
+                )
+                .into_bytes();
+                record.extend_from_slice(line);
+                record.push(b'\n');
+                socket.send(&record).map(drop)
+            }
+            #[cfg(windows)]
+            Sink::EventLog(handle) => {
+                use windows_sys::Win32::System::EventLog::{
+                    ReportEventW, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE,
+                };
+                let text = format!("[{job_name}] {}", String::from_utf8_lossy(line));
+                let wide: Vec<u16> = text.encode_utf16().chain([0]).collect();
+                let strings = [wide.as_ptr()];
+                let kind = if is_stderr {
+                    EVENTLOG_ERROR_TYPE
+                } else {
+                    EVENTLOG_INFORMATION_TYPE
+                };
+                // Safety: the handle is open and `strings` holds one
+                // NUL-terminated string that outlives the call.
+                let ok = unsafe {
+                    ReportEventW(
+                        *handle,
+                        kind,
+                        0,
+                        0,
+                        std::ptr::null_mut(),
+                        1,
+                        0,
+                        strings.as_ptr(),
+                        std::ptr::null(),
+                    )
+                };
+                if ok == 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Sink {
+    fn drop(&mut self) {
+        let Sink::EventLog(handle) = self;
+        // Safety: the handle came from RegisterEventSourceW.
+        unsafe { windows_sys::Win32::System::EventLog::DeregisterEventSource(*handle) };
+    }
+}
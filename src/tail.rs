@@ -0,0 +1,148 @@
+//! `pend tail-all`: follow every running job at once.
+//!
+//! Like `kubectl logs -f` across pods: each line a job prints is shown
+//! behind `[<job>]` in the job's colour, stdout on stdout and stderr on
+//! stderr. Jobs running when the command starts are followed from the end
+//! of their log; jobs started afterwards – noticed through a watcher on the
+//! jobs directory, or by polling without one – from their first byte. A job
+//! that finishes gets the summary line `pend wait` prints. The command runs
+//! until interrupted, or with `--until-idle` until no job runs any more.
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::wait::{JobState, LogStart};
+
+/// How often the jobs are looked at without a watcher.
+const POLL: Duration = Duration::from_millis(250);
+
+/// How often the jobs directory is scanned for new jobs even though the
+/// watcher reported nothing.
+const RESCAN: Duration = Duration::from_secs(1);
+
+/// How long watcher events are collected before the jobs are read.
+const COALESCE: Duration = Duration::from_millis(20);
+
+/// What a watcher event asks for.
+#[cfg_attr(not(feature = "notify"), allow(dead_code))]
+enum Wake {
+    /// A followed job wrote something.
+    Output,
+    /// A job may have started: its lock or metadata changed.
+    Rescan,
+}
+
+pub(crate) fn tail_all(config: &Config, until_idle: bool) -> Result<()> {
+    let root = crate::paths::jobs_root(config)?;
+    let (tx, rx) = channel();
+    let watcher = watch(&root, tx);
+    let poll = if watcher.is_some() { RESCAN } else { POLL };
+
+    let mut followed: Vec<JobState> = Vec::new();
+    let mut colors = 0;
+    let mut start = Some(LogStart::End);
+    let mut last_scan: Option<Instant> = None;
+    let mut followed_any = false;
+    let mut wake = Some(Wake::Rescan);
+    loop {
+        if matches!(wake, Some(Wake::Rescan))
+            || last_scan.is_none_or(|at| at.elapsed() >= RESCAN)
+            || watcher.is_none()
+        {
+            last_scan = Some(Instant::now());
+            let known: BTreeSet<String> = followed.iter().map(|j| j.name().to_string()).collect();
+            for name in crate::status::running_jobs(&root) {
+                if known.contains(&name) {
+                    continue;
+                }
+                let mut job = JobState::new(config, &name, crate::color::job_style(colors))?;
+                colors += 1;
+                job.prefix_lines();
+                if let Some(start) = start {
+                    job.skip_backlog(start);
+                }
+                tracing::debug!(job = name, "following");
+                followed.push(job);
+                followed_any = true;
+            }
+            // Jobs found later started after us: show all of their output.
+            start = None;
+        }
+
+        let mut still = Vec::with_capacity(followed.len());
+        for mut job in followed {
+            match job.poll() {
+                Ok((false, _)) => still.push(job),
+                Ok((true, _)) => {
+                    job.drain()?;
+                    if let Some(code) = job.exit_code() {
+                        crate::wait::emit_summary(config, job.name(), code, job.meta_path())?;
+                    }
+                }
+                Err(err @ PendError::WorkerDied(_)) => eprintln!("pend: {err}"),
+                Err(err) => return Err(err),
+            }
+        }
+        followed = still;
+
+        if until_idle && followed.is_empty() {
+            if !followed_any {
+                eprintln!("pend: no jobs are running");
+            }
+            return Ok(());
+        }
+        wake = next_wake(&rx, poll);
+    }
+}
+
+/// Wait for the next watcher event, collecting a burst of them into one.
+fn next_wake(rx: &Receiver<Wake>, timeout: Duration) -> Option<Wake> {
+    let mut wake = match rx.recv_timeout(timeout) {
+        Ok(wake) => wake,
+        Err(RecvTimeoutError::Timeout) => return None,
+        Err(RecvTimeoutError::Disconnected) => {
+            std::thread::sleep(timeout);
+            return None;
+        }
+    };
+    let window = Instant::now() + COALESCE;
+    while let Some(left) = window.checked_duration_since(Instant::now()) {
+        match rx.recv_timeout(left) {
+            Ok(Wake::Rescan) => wake = Wake::Rescan,
+            Ok(Wake::Output) => {}
+            Err(_) => break,
+        }
+    }
+    Some(wake)
+}
+
+#[cfg(feature = "notify")]
+fn watch(root: &Path, tx: std::sync::mpsc::Sender<Wake>) -> Option<notify::RecommendedWatcher> {
+    use notify::{RecursiveMode, Watcher};
+
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let rescan = match &res {
+            Ok(event) if !event.need_rescan() => event.paths.iter().any(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "lock" || ext == "json")
+            }),
+            _ => true,
+        };
+        let _ = tx.send(if rescan { Wake::Rescan } else { Wake::Output });
+    })
+    .inspect_err(|err| tracing::debug!(%err, "file watcher unavailable, polling"))
+    .ok()?;
+    watcher
+        .watch(root, RecursiveMode::NonRecursive)
+        .inspect_err(|err| tracing::debug!(%err, "cannot watch the jobs directory, polling"))
+        .ok()?;
+    Some(watcher)
+}
+
+#[cfg(not(feature = "notify"))]
+fn watch(_root: &Path, _tx: std::sync::mpsc::Sender<Wake>) -> Option<()> {
+    None
+}
@@ -0,0 +1,128 @@
+//! `pend wait --max-replay-rate`: keep a replay from flooding the terminal.
+//!
+//! Replaying a gigabyte log over a slow SSH connection fills every buffer
+//! between pend and the screen, and Ctrl-C then takes minutes to show. With
+//! `--max-replay-rate 256K` (bytes per second) or `--max-replay-rate
+//! 200lines` the replay of every job together is written no faster than
+//! that. The allowance saved up while idle is capped at one second's worth,
+//! so output paused with Ctrl-S – or by a job that was quiet for a while –
+//! does not come out in one burst afterwards.
+use std::io::{self, Write};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A replay rate limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Rate {
+    BytesPerSec(u64),
+    LinesPerSec(u64),
+}
+
+/// Clap value parser: a size (`64K`, `1M`) per second, or `<N>lines`.
+pub(crate) fn parse_rate(s: &str) -> Result<Rate, String> {
+    let s = s.trim();
+    let rate = match s.strip_suffix("lines") {
+        Some(lines) => Rate::LinesPerSec(
+            lines
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid line count in '{s}'"))?,
+        ),
+        None => Rate::BytesPerSec(crate::parse_size(s)?),
+    };
+    match rate {
+        Rate::BytesPerSec(0) | Rate::LinesPerSec(0) => Err("the rate must be above 0".into()),
+        rate => Ok(rate),
+    }
+}
+
+struct Bucket {
+    rate: Rate,
+    /// Bytes or lines that may be written right away.
+    credit: f64,
+    refilled: Instant,
+}
+
+impl Bucket {
+    fn per_sec(&self) -> f64 {
+        match self.rate {
+            Rate::BytesPerSec(n) | Rate::LinesPerSec(n) => n as f64,
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let earned = now.duration_since(self.refilled).as_secs_f64() * self.per_sec();
+        self.credit = (self.credit + earned).min(self.per_sec());
+        self.refilled = now;
+    }
+
+    /// How much of `bytes` the credit covers, and what that costs. Lines
+    /// cost one each at their newline; a partial line is free.
+    fn take<'a>(&self, bytes: &'a [u8]) -> (&'a [u8], f64) {
+        let allowed = self.credit.floor() as usize;
+        match self.rate {
+            Rate::BytesPerSec(_) => {
+                let len = allowed.min(bytes.len());
+                (&bytes[..len], len as f64)
+            }
+            Rate::LinesPerSec(_) => {
+                let mut newlines = bytes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &b)| b == b'\n')
+                    .map(|(pos, _)| pos + 1);
+                let end = match allowed {
+                    0 if bytes.contains(&b'\n') => 0,
+                    0 => bytes.len(),
+                    n => newlines.nth(n - 1).unwrap_or(bytes.len()),
+                };
+                let piece = &bytes[..end];
+                let lines = piece.iter().filter(|&&b| b == b'\n').count();
+                (piece, lines as f64)
+            }
+        }
+    }
+}
+
+static LIMIT: OnceLock<Mutex<Bucket>> = OnceLock::new();
+
+/// Record `--max-replay-rate`; called once from `main`.
+pub(crate) fn set(rate: Rate) {
+    let mut bucket = Bucket {
+        rate,
+        credit: 0.0,
+        refilled: Instant::now(),
+    };
+    // Start with a second's worth, as after any pause.
+    bucket.credit = bucket.per_sec();
+    let _ = LIMIT.set(Mutex::new(bucket));
+}
+
+/// Write all of `bytes` to `out`, sleeping as the rate limit requires.
+pub(crate) fn write_all(out: &mut dyn Write, mut bytes: &[u8]) -> io::Result<()> {
+    let Some(limit) = LIMIT.get() else {
+        return out.write_all(bytes);
+    };
+    while !bytes.is_empty() {
+        let (piece, wait) = {
+            let mut bucket = limit.lock().unwrap();
+            bucket.refill();
+            let (piece, cost) = bucket.take(bytes);
+            bucket.credit -= cost;
+            // Nothing fits: wait until one more byte or line does.
+            let wait = piece.is_empty().then(|| {
+                Duration::from_secs_f64((1.0 - bucket.credit).max(0.0) / bucket.per_sec())
+            });
+            (piece, wait)
+        };
+        if let Some(wait) = wait {
+            std::thread::sleep(wait.max(Duration::from_millis(1)));
+            continue;
+        }
+        out.write_all(piece)?;
+        out.flush()?;
+        bytes = &bytes[piece.len()..];
+    }
+    Ok(())
+}
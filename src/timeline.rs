@@ -0,0 +1,119 @@
+//! `pend timeline <job>`: where the time of a job went.
+//!
+//! Lists the lifecycle events recorded in `<job>.json` – submission, the
+//! start, readiness and end of every attempt (marking timeout kills) and the
+//! final exit – each with its offset from submission. Attempt ends also show
+//! how long that attempt ran, so slow retries and restart backoff stand out;
+//! a retry that ran `--retry-cmd` names that command.
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+
+/// The part of the worker's metadata the timeline is built from.
+#[derive(Deserialize)]
+struct Meta {
+    started: String,
+    ended: Option<String>,
+    exit_code: Option<i32>,
+    #[serde(default)]
+    attempts: Vec<Attempt>,
+}
+
+#[derive(Deserialize)]
+struct Attempt {
+    started: String,
+    ready: Option<String>,
+    ended: Option<String>,
+    exit_code: Option<i32>,
+    cmd: Option<String>,
+    #[serde(default)]
+    timed_out: bool,
+    #[serde(default)]
+    stalled: bool,
+}
+
+/// Entry point for `pend timeline <job>`.
+pub(crate) fn print_timeline(config: &Config, job_name: &str) -> Result<()> {
+    let paths = JobPaths::new(config, job_name)?;
+    let bytes = match fs::read(&paths.meta) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(PendError::NotFound(job_name.to_string()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let corrupt = |reason: String| PendError::Corrupt {
+        path: paths.meta.clone(),
+        reason,
+    };
+    let meta: Meta = crate::version::parse_meta(&bytes)
+        .and_then(serde_json::from_value)
+        .map_err(|err| corrupt(format!("invalid JSON: {err}")))?;
+    let time = |value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map_err(|_| corrupt(format!("invalid timestamp {value:?}")))
+    };
+
+    let submitted = time(&meta.started)?;
+    let mut events = vec![(submitted, "submitted".to_string())];
+    for (n, attempt) in meta.attempts.iter().enumerate() {
+        let n = n + 1;
+        let started = time(&attempt.started)?;
+        let what = match &attempt.cmd {
+            Some(cmd) => format!("attempt {n} started: {cmd}"),
+            None => format!("attempt {n} started"),
+        };
+        events.push((started, what));
+        if let Some(ready) = &attempt.ready {
+            events.push((time(ready)?, format!("attempt {n} ready")));
+        }
+        if let Some(ended) = &attempt.ended {
+            let ended = time(ended)?;
+            let ran = format_duration(ended - started);
+            let what = match attempt.exit_code {
+                _ if attempt.timed_out => format!("attempt {n} killed by timeout after {ran}"),
+                _ if attempt.stalled => format!("attempt {n} terminated as stalled after {ran}"),
+                Some(code) => format!("attempt {n} exited {code} after {ran}"),
+                None => format!("attempt {n} ended after {ran}"),
+            };
+            events.push((ended, what));
+        }
+    }
+    match (&meta.ended, meta.exit_code) {
+        (Some(ended), code) => {
+            let code = code.map_or("?".to_string(), |c| c.to_string());
+            events.push((time(ended)?, format!("finished with exit code {code}")));
+        }
+        (None, _) => events.push((Utc::now().fixed_offset(), "still running".to_string())),
+    }
+
+    for (at, what) in events {
+        println!("{:>10}  {what}", format_offset(at, submitted));
+    }
+    Ok(())
+}
+
+/// `+1.5s` etc.; events a few milliseconds early due to clock jitter
+/// count as `+0.0s`.
+fn format_offset(at: DateTime<FixedOffset>, since: DateTime<FixedOffset>) -> String {
+    format!(
+        "+{}",
+        format_duration((at - since).max(chrono::TimeDelta::zero()))
+    )
+}
+
+/// `4.2s`, `3m07s` or `1h05m`.
+pub(crate) fn format_duration(duration: chrono::TimeDelta) -> String {
+    let millis = duration.num_milliseconds().max(0);
+    let secs = millis / 1000;
+    match secs {
+        0..=59 => format!("{:.1}s", millis as f64 / 1000.0),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
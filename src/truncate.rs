@@ -0,0 +1,127 @@
+//! `pend truncate <job …>`: drop the captured output of finished jobs.
+//!
+//! A job that logged gigabytes may still be worth keeping around for its
+//! exit code, timings and command. Truncating empties `.out`, `.err` and
+//! `.log` (and the `.idx` / `.frames` indexes describing the log), deletes
+//! rotated generations, and leaves every other artifact alone, so `pend
+//! status` and `pend wait` go on reporting the job. The sizes the output
+//! had are recorded in `.json` as
+//!
+//! ```json
+//! "truncated": { "at": "2024-05-01T12:00:00+00:00", "out": 1048576, "err": 0, "log": 1048576 }
+//! ```
+//!
+//! counting rotated generations too. Truncating again adds to them.
+use std::fs::{self, File};
+use std::io;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+
+/// Bytes of output `pend truncate` discarded, per stream.
+#[derive(Debug, Clone, Copy)]
+struct Discarded {
+    out: u64,
+    err: u64,
+    log: u64,
+}
+
+impl Discarded {
+    /// Space freed on disk, counting `.out` and `.log` once when they share
+    /// their storage (see `dedup.rs`).
+    fn total(&self, shared: bool) -> u64 {
+        if shared {
+            self.out + self.err
+        } else {
+            self.out + self.err + self.log
+        }
+    }
+}
+
+/// Whether the output of the job was truncated.
+pub(crate) fn was_truncated(meta: &serde_json::Value) -> bool {
+    meta.get("truncated").is_some_and(|t| t.is_object())
+}
+
+/// Truncate the output of `job_name`, returning the bytes freed.
+pub(crate) fn truncate_job(config: &Config, job_name: &str) -> Result<u64> {
+    let paths = JobPaths::new(config, job_name)?;
+    if !paths.any_exist() {
+        return Err(PendError::NotFound(job_name.to_string()));
+    }
+    if crate::validate::read_exit_code(&paths.exit)?.is_none() {
+        return Err(PendError::Usage(format!(
+            "job '{job_name}' has not finished; truncate it once it has"
+        )));
+    }
+    let corrupt = |reason: &str| PendError::Corrupt {
+        path: paths.meta.clone(),
+        reason: reason.to_string(),
+    };
+    let bytes = fs::read(&paths.meta).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => corrupt("missing"),
+        _ => err.into(),
+    })?;
+    let mut meta = crate::version::parse_meta(&bytes).map_err(|_| corrupt("invalid JSON"))?;
+
+    let rotated = paths.rotated();
+    let size = |path: &std::path::Path| {
+        let prefix = format!(
+            "{}.",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        JobPaths::file_len(path)
+            + rotated
+                .iter()
+                .filter(|r| {
+                    r.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+                })
+                .map(|r| JobPaths::file_len(r))
+                .sum::<u64>()
+    };
+    let discarded = Discarded {
+        out: size(&paths.out),
+        err: size(&paths.err),
+        log: size(&paths.log),
+    };
+    let shared = crate::dedup::same_file(&paths.out, &paths.log);
+
+    // Record the sizes before the bytes are gone, so an interrupted
+    // truncation never loses them.
+    let fields = meta
+        .as_object_mut()
+        .ok_or_else(|| corrupt("expected a JSON object"))?;
+    let previous = fields.get("truncated").cloned().unwrap_or_default();
+    let earlier = |key: &str| previous.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    fields.insert(
+        "truncated".into(),
+        serde_json::json!({
+            "at": chrono::Utc::now().to_rfc3339(),
+            "out": earlier("out") + discarded.out,
+            "err": earlier("err") + discarded.err,
+            "log": earlier("log") + discarded.log,
+        }),
+    );
+    let json = serde_json::to_vec_pretty(&meta).map_err(io::Error::from)?;
+    paths.write_meta(&json)?;
+
+    for path in &rotated {
+        fs::remove_file(path)?;
+    }
+    // Replaced rather than emptied in place: a `pend merge` that mapped the
+    // log keeps reading the old contents instead of faulting.
+    for path in [&paths.out, &paths.err, &paths.log, &paths.frames] {
+        if path.exists() {
+            crate::paths::replace_file(path, b"")?;
+        }
+    }
+    // The index keeps only its header naming the job's run.
+    if let Some(id) = crate::job_id::of_meta(&paths) {
+        crate::paths::replace_file(&paths.idx, crate::job_id::idx_header(&id).as_bytes())?;
+    } else if paths.idx.exists() {
+        File::create(&paths.idx)?;
+    }
+    Ok(discarded.total(shared))
+}
@@ -4,12 +4,13 @@
 
 use std::io::{self, Write};
 
+use crate::config::Config;
 use crate::paths::jobs_root;
 
 use crossterm::{cursor, event, execute, style, terminal, ExecutableCommand};
 
 /// Entry point called from `main.rs` when the `tui` subcommand is used.
-pub(crate) fn run_tui() -> io::Result<()> {
+pub(crate) fn run_tui(config: &Config) -> io::Result<()> {
     let mut stdout = io::stdout();
     terminal::enable_raw_mode()?;
     execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
@@ -26,13 +27,16 @@ pub(crate) fn run_tui() -> io::Result<()> {
             }
 
             // Gather job info.
-            let root = jobs_root()?;
+            let root = jobs_root(config)?;
             let mut jobs: Vec<(String, String)> = Vec::new();
             if let Ok(entries) = std::fs::read_dir(&root) {
                 for entry in entries.flatten() {
                     if let Some(name) = entry.file_name().to_str() {
                         if let Some((job, ext)) = name.rsplit_once('.') {
-                            if matches!(ext, "out" | "err" | "log" | "exit" | "json" | "signal" | "lock") {
+                            if matches!(
+                                ext,
+                                "out" | "err" | "log" | "exit" | "json" | "signal" | "lock"
+                            ) {
                                 jobs.push((job.to_string(), ext.to_string()));
                             }
                         }
@@ -57,6 +61,11 @@ pub(crate) fn run_tui() -> io::Result<()> {
 
             for job in unique {
                 let exit_path = root.join(format!("{job}.exit"));
+                let note = crate::paths::JobPaths::new(config, &job)
+                    .ok()
+                    .and_then(|paths| crate::note::read(&paths).flatten())
+                    .map(|note| format!("  {note}"))
+                    .unwrap_or_default();
 
                 if exit_path.exists() {
                     // Finished – read exit code and modification time.
@@ -69,14 +78,45 @@ pub(crate) fn run_tui() -> io::Result<()> {
                         })
                         .unwrap_or_else(|_| "?".into());
                     stdout.execute(cursor::MoveTo(0, y))?;
+                    let took = crate::quick::QuickStatus::read(&root.join(format!("{job}.result")))
+                        .map(|quick| {
+                            format!(
+                                "  {}",
+                                crate::timeline::format_duration(chrono::TimeDelta::milliseconds(
+                                    (quick.wall_secs * 1000.0) as i64
+                                ))
+                            )
+                        })
+                        .unwrap_or_default();
                     stdout.execute(style::Print(format!(
-                        "{:<20} {:<8} {}",
-                        job, format!("exit {code}"), ts
+                        "{:<20} {:<8} {}{took}{note}",
+                        job,
+                        format!("exit {code}"),
+                        ts
                     )))?;
                 } else {
                     // Still running.
                     stdout.execute(cursor::MoveTo(0, y))?;
-                    stdout.execute(style::Print(format!("{:<20} running", job)))?;
+                    let paths = crate::paths::JobPaths::new(config, &job).ok();
+                    let mut activity = paths
+                        .as_ref()
+                        .and_then(|paths| paths.read_status())
+                        .unwrap_or_else(|| "running".into());
+                    if let Some(percent) = paths
+                        .as_ref()
+                        .and_then(|paths| crate::report::read(&paths.progress))
+                    {
+                        activity.push_str(&format!(" {percent:.0}%"));
+                    }
+                    if let Some(deadline) = paths
+                        .and_then(|paths| std::fs::read(paths.meta).ok())
+                        .and_then(|bytes| crate::version::parse_meta(&bytes).ok())
+                        .and_then(|meta| crate::status::attempt_deadline(&meta))
+                    {
+                        activity
+                            .push_str(&format!(", {}", crate::status::describe_deadline(deadline)));
+                    }
+                    stdout.execute(style::Print(format!("{:<20} {activity}{note}", job)))?;
                 }
 
                 y += 1;
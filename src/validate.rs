@@ -0,0 +1,204 @@
+//! Integrity checks of job artifacts.
+//!
+//! Artifacts are plain files that users (and crashes) can truncate or edit.
+//! Rather than quietly treating an unreadable `.exit` as a failure or
+//! dropping a broken `.json` from the summary, readers go through the
+//! helpers here and report [`PendError::Corrupt`]. `pend validate [job …]`
+//! runs the full set of checks:
+//!
+//! * `.exit` and `.signal` hold a single integer;
+//! * `.idx` lines are `<offset> <len>` ranges (inside `.log` once the job
+//!   finished);
+//! * once the job finished, `.json` is a metadata object with RFC 3339
+//!   timestamps whose `exit_code` agrees with `.exit`;
+//! * once the job finished, `.out` / `.err` match their recorded SHA-256
+//!   (unless rotation moved older output aside or `pend truncate` dropped
+//!   it).
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::paths::JobPaths;
+
+fn corrupt(path: &Path, reason: impl Into<String>) -> PendError {
+    PendError::Corrupt {
+        path: path.to_path_buf(),
+        reason: reason.into(),
+    }
+}
+
+/// Read the exit code recorded at `path`. `None` while there is none yet –
+/// including an empty file, which is the worker caught mid-write.
+pub(crate) fn read_exit_code(path: &Path) -> Result<Option<i32>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse()
+        .map(Some)
+        .map_err(|_| corrupt(path, format!("expected an exit code, found {text:?}")))
+}
+
+/// Check the metadata of a finished job. The worker rewrites `.json` in
+/// place while running, so only the final version (written before `.exit`)
+/// is held to this standard.
+pub(crate) fn check_meta(paths: &JobPaths) -> Result<()> {
+    let bytes = match fs::read(&paths.meta) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let meta = crate::version::parse_meta(&bytes)
+        .map_err(|err| corrupt(&paths.meta, format!("invalid JSON: {err}")))?;
+    let Some(fields) = meta.as_object() else {
+        return Err(corrupt(&paths.meta, "expected a JSON object"));
+    };
+    if !fields.get("cmd").is_some_and(|cmd| cmd.is_array()) {
+        return Err(corrupt(&paths.meta, "missing command"));
+    }
+    for key in ["started", "ended", "ready"] {
+        match fields.get(key) {
+            None | Some(serde_json::Value::Null) => {}
+            Some(value) => {
+                let valid = value
+                    .as_str()
+                    .is_some_and(|s| chrono::DateTime::parse_from_rfc3339(s).is_ok());
+                if !valid {
+                    return Err(corrupt(&paths.meta, format!("invalid '{key}' timestamp")));
+                }
+            }
+        }
+    }
+    if let (Some(recorded), Ok(Some(code))) = (
+        fields.get("exit_code").and_then(|v| v.as_i64()),
+        read_exit_code(&paths.exit),
+    ) {
+        if recorded != i64::from(code) {
+            return Err(corrupt(
+                &paths.meta,
+                format!(
+                    "exit_code {recorded} disagrees with {}",
+                    paths.exit.display()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Problems cheap enough to look for on every `pend status`.
+pub(crate) fn quick_check(paths: &JobPaths) -> Vec<PendError> {
+    let mut problems = Vec::new();
+    match read_exit_code(&paths.exit) {
+        Err(err) => problems.push(err),
+        Ok(Some(_)) => problems.extend(check_meta(paths).err()),
+        Ok(None) => {}
+    }
+    problems
+}
+
+/// Every problem with the artifacts of one job.
+pub(crate) fn check(paths: &JobPaths) -> Vec<PendError> {
+    let mut problems = quick_check(paths);
+    let finished = matches!(read_exit_code(&paths.exit), Ok(Some(_)));
+
+    if let Ok(text) = fs::read_to_string(&paths.signal) {
+        if text.trim().parse::<i32>().is_err() {
+            problems.push(corrupt(&paths.signal, "expected a signal number"));
+        }
+    }
+    problems.extend(check_index(paths, finished).err());
+
+    if finished && paths.rotated().is_empty() {
+        let meta = fs::read(&paths.meta)
+            .ok()
+            .and_then(|b| crate::version::parse_meta(&b).ok());
+        let meta = meta.filter(|m| !crate::truncate::was_truncated(m));
+        for (path, key) in [(&paths.out, "out_sha256"), (&paths.err, "err_sha256")] {
+            let Some(expected) = meta.as_ref().and_then(|m| m.get(key)?.as_str()) else {
+                continue;
+            };
+            match sha256_file(path) {
+                Ok(actual) if actual == expected => {}
+                Ok(_) => problems.push(corrupt(path, format!("does not match {key}"))),
+                Err(err) => problems.push(err.into()),
+            }
+        }
+    }
+    problems
+}
+
+/// Complete `.idx` lines must be ranges; once the job finished they must lie
+/// inside `.log` (entries are written just before their bytes).
+fn check_index(paths: &JobPaths, finished: bool) -> Result<()> {
+    let text = match fs::read_to_string(&paths.idx) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let log_len = JobPaths::file_len(&paths.log);
+    // A trailing line without newline may still be being written.
+    let complete = text.rfind('\n').map_or("", |end| &text[..end]);
+    for (n, line) in complete.lines().enumerate() {
+        if crate::job_id::is_header(line) {
+            continue;
+        }
+        let range = line
+            .split_once(' ')
+            .and_then(|(start, len)| Some((start.parse::<u64>().ok()?, len.parse::<u64>().ok()?)));
+        match range {
+            Some((start, len)) if !finished || start + len <= log_len => {}
+            Some(_) => {
+                return Err(corrupt(
+                    &paths.idx,
+                    format!("line {} points past the end of the log", n + 1),
+                ))
+            }
+            None => return Err(corrupt(&paths.idx, format!("malformed line {}", n + 1))),
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Entry point for `pend validate [job …]`; without job names every job is
+/// checked. Returns whether all artifacts are intact.
+pub(crate) fn validate_jobs(config: &Config, job_names: &[String]) -> Result<bool> {
+    let names = if job_names.is_empty() {
+        crate::paths::discover_jobs(&crate::paths::jobs_root(config)?)
+    } else {
+        job_names.to_vec()
+    };
+
+    let mut intact = true;
+    for name in &names {
+        let paths = JobPaths::new(config, name)?;
+        if !paths.any_exist() {
+            return Err(PendError::NotFound(name.clone()));
+        }
+        let problems = check(&paths);
+        if problems.is_empty() {
+            println!("{name:<20} ok");
+            continue;
+        }
+        intact = false;
+        println!("{name:<20} corrupt");
+        for problem in problems {
+            println!("  {problem}");
+        }
+    }
+    Ok(intact)
+}
@@ -0,0 +1,90 @@
+//! Compatibility between pend releases sharing a jobs directory.
+//!
+//! Every `<job>.json` records the `pend_version` that wrote it and the
+//! [`SCHEMA`] of its fields. Readers accept metadata without a schema (from
+//! before it was recorded) as is: fields were only ever added, so what is
+//! missing is simply absent. Metadata from a newer schema may have changed
+//! what a field means, so readers keep only the [`CORE_FIELDS`] every schema
+//! promises to leave alone and treat everything else as unknown rather than
+//! misreading it.
+//!
+//! `pend do` re-executes its own binary as the worker. When that binary was
+//! replaced in between – an upgrade while jobs are being submitted – the
+//! worker may not understand the options handed over; it then refuses to run
+//! the command and fails the job with a message saying so.
+use std::process::Command;
+
+/// This build's version.
+pub(crate) const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Version of the metadata layout, raised when a field changes meaning.
+pub(crate) const SCHEMA: u32 = 1;
+
+/// Fields whose meaning no schema change may alter.
+const CORE_FIELDS: [&str; 14] = [
+    "schema",
+    "pend_version",
+    "job",
+    "cmd",
+    "pid",
+    "worker_pid",
+    "worker_started",
+    "worker_host",
+    "started",
+    "ended",
+    "exit_code",
+    "out_sha256",
+    "err_sha256",
+    "note",
+];
+
+/// Variable telling the worker which version submitted the job.
+const FRONTEND_ENV: &str = "PEND_FRONTEND_VERSION";
+
+/// Tell the worker started by `command` which version submitted the job.
+pub(crate) fn export(command: &mut Command) {
+    command.env(FRONTEND_ENV, VERSION);
+}
+
+/// Why the worker cannot run a job submitted by the front-end that started
+/// it, if it cannot. Releases of the same series (same major version, and
+/// same minor version before 1.0) understand each other.
+pub(crate) fn frontend_mismatch() -> Option<String> {
+    let frontend = std::env::var(FRONTEND_ENV).ok();
+    if frontend
+        .as_deref()
+        .is_some_and(|frontend| series(frontend) == series(VERSION))
+    {
+        return None;
+    }
+    Some(format!(
+        "pend {VERSION} cannot run a job submitted by pend {}; submit it again",
+        frontend.as_deref().unwrap_or("of an older release")
+    ))
+}
+
+/// The part of a version that has to match.
+fn series(version: &str) -> (&str, Option<&str>) {
+    let mut parts = version.split('.');
+    let major = parts.next().unwrap_or_default();
+    (
+        major,
+        (major == "0").then(|| parts.next().unwrap_or_default()),
+    )
+}
+
+/// Parse `<job>.json`, leaving out what a newer schema may have redefined.
+pub(crate) fn parse_meta(bytes: &[u8]) -> serde_json::Result<serde_json::Value> {
+    let mut meta: serde_json::Value = serde_json::from_slice(bytes)?;
+    let schema = meta.get("schema").and_then(|v| v.as_u64()).unwrap_or(0);
+    if schema > u64::from(SCHEMA) {
+        if let Some(fields) = meta.as_object_mut() {
+            tracing::debug!(
+                schema,
+                "metadata from a newer schema; reading core fields only"
+            );
+            fields.retain(|key, _| CORE_FIELDS.contains(&key.as_str()));
+        }
+    }
+    Ok(meta)
+}
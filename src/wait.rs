@@ -11,43 +11,140 @@
 //!     initialised we degrade gracefully to exponential back-off polling.
 //!   • For multi-job waits we keep each job's current read position and only
 //!     tail the delta since the previous iteration which avoids re-reading
-//!     files over and over.
+//!     files over and over. Watcher events are mapped to the job whose
+//!     artifact changed and coalesced over [`COALESCE`], so a bursty writer
+//!     costs one read of its own log per window rather than a re-poll of
+//!     every job per event.
 //!
 //! The public surface of this module is the [`wait_jobs`] function which is
 //! called from `main.rs`.
-use anstyle::{AnsiColor, Color, Style};
+use regex::bytes::Regex;
+use std::collections::VecDeque;
 use std::fs::{self, File};
 use std::io::{self, Read, Seek, SeekFrom, Write};
-
-fn color_style(idx: usize) -> Style {
-    let color = match idx % 6 {
-        0 => AnsiColor::Red,
-        1 => AnsiColor::Green,
-        2 => AnsiColor::Yellow,
-        3 => AnsiColor::Blue,
-        4 => AnsiColor::Magenta,
-        _ => AnsiColor::Cyan,
-    };
-    Style::new().fg_color(Some(Color::Ansi(color)))
-}
+use std::time::{Duration, Instant};
 
 // For efficient change detection we attempt to use a platform file watcher at
 // runtime. When that fails (e.g. unsupported platform or too many open
 // descriptors) we transparently fall back to the previous exponential back-
 // off polling loop so behaviour remains correct albeit slightly less
-// efficient.
+// efficient. Builds without the `notify` feature always poll.
+#[cfg(feature = "notify")]
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 
-use crate::color::colors_enabled;
+use crate::color::{colors_enabled, Style};
+use crate::config::Config;
+use crate::error::{PendError, Result};
+use crate::frames::FrameReader;
+use crate::glyphs;
 use crate::paths::JobPaths;
+use crate::progress::{Footer, Phase, Row};
+use crate::quick::QuickStatus;
+
+/// What `pend wait` waits for.
+#[derive(Clone)]
+pub(crate) enum Until {
+    /// The job finished (the default).
+    Finished,
+    /// `--for-output`: a log line matches the pattern.
+    Output(Regex),
+    /// `--ready`: the worker recorded the job as ready.
+    Ready,
+}
+
+/// Flags of `pend wait`.
+pub(crate) struct WaitOptions {
+    /// Unless this is [`Until::Finished`] the wait ends as soon as every job
+    /// has reached the requested state; the jobs themselves keep running.
+    pub(crate) until: Until,
+    /// Finish with an aligned table instead of one line per job.
+    pub(crate) summary_table: bool,
+    /// `--heartbeat`: interval of "still waiting" lines on stderr.
+    pub(crate) heartbeat: Option<Duration>,
+    /// Where to start replaying the log; `None` replays all of it.
+    pub(crate) start: Option<LogStart>,
+    /// `--clean`: delete the artifacts of jobs that succeeded.
+    pub(crate) clean: bool,
+    /// `--fold-repeats`: collapse runs of identical lines.
+    pub(crate) fold_repeats: bool,
+    /// `--pager`: page the output when every job has finished already.
+    pub(crate) pager: bool,
+    /// `--allow-pending`: wait for jobs that were not submitted yet.
+    pub(crate) allow_pending: bool,
+    /// `--pending-timeout`: how long to wait for them to appear.
+    pub(crate) pending_timeout: Option<Duration>,
+    /// `--stderr-tail`: lines of stderr to show below each failed job.
+    pub(crate) stderr_tail: Option<usize>,
+    /// `--summary-sort`: group the summary by outcome and order each group.
+    pub(crate) summary_sort: Option<SummarySort>,
+    /// `--failures-first`: put the group of failed jobs at the top rather
+    /// than at the bottom, next to the prompt.
+    pub(crate) failures_first: bool,
+    /// `--sigpipe`: stop as soon as stdout is closed, exiting with 141,
+    /// instead of waiting for the jobs' exit code.
+    pub(crate) sigpipe: bool,
+}
+
+/// `--summary-sort`: how the jobs within each outcome group are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum SummarySort {
+    /// In the order given.
+    Status,
+    /// Slowest first.
+    Duration,
+    /// By name.
+    Name,
+}
+
+/// `--no-backlog` / `--since-offset`: skip output produced before.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum LogStart {
+    /// The end of the log as it is when waiting starts.
+    End,
+    /// A byte offset into the log. An offset beyond the end means the log
+    /// was rotated since, so everything in the current one is new.
+    Offset(u64),
+}
+
+/// Replace `@FILE` arguments by the job names listed in that file (`@-`
+/// reads stdin): one per line, with `#` starting a comment. Every name is
+/// kept once, at its first occurrence.
+pub(crate) fn read_job_lists(args: &[String]) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    let mut add = |name: &str| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    };
+    for arg in args {
+        let Some(source) = arg.strip_prefix('@') else {
+            add(arg);
+            continue;
+        };
+        let text = if source == "-" {
+            io::read_to_string(io::stdin())
+        } else {
+            fs::read_to_string(source)
+        }
+        .map_err(|err| PendError::Usage(format!("cannot read job list '{source}': {err}")))?;
+        for line in text.lines() {
+            let name = line.split('#').next().unwrap_or_default().trim();
+            if !name.is_empty() {
+                add(name);
+            }
+        }
+    }
+    Ok(names)
+}
 
 /// Public helper mirroring `pend wait <job …>`.
-pub(crate) fn wait_jobs(job_names: &[String]) -> io::Result<i32> {
+pub(crate) fn wait_jobs(
+    config: &Config,
+    job_names: &[String],
+    options: &WaitOptions,
+) -> Result<i32> {
     if job_names.is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "no job names supplied",
-        ));
+        return Err(PendError::Usage("no job names supplied".into()));
     }
 
     // Before blocking verify that the supplied job names actually refer to
@@ -57,22 +154,90 @@ pub(crate) fn wait_jobs(job_names: &[String]) -> io::Result<i32> {
     // require that *all* artifact files are present already – creating the
     // first files might race the `pend do` command that launched the job –
     // but at least **one** indicator must exist.
+    if options.allow_pending {
+        await_submission(config, job_names, options.pending_timeout)?;
+    }
     for name in job_names {
-        let paths = JobPaths::new(name)?;
+        let paths = JobPaths::new(config, name)?;
 
         if !paths.any_exist() {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("job '{}' not found", name),
-            ));
+            return Err(PendError::NotFound(name.clone()));
+        }
+        crate::job_id::warn_if_mixed(name, &paths);
+        if read_meta(&paths.meta).is_some_and(|meta| crate::truncate::was_truncated(&meta)) {
+            eprintln!("pend: the output of '{name}' was discarded by pend truncate");
+        }
+    }
+
+    // Only a replay of finished jobs is paged; live output keeps streaming.
+    let pager = if options.pager
+        && matches!(options.until, Until::Finished)
+        && job_names.iter().all(|name| finished(config, name))
+    {
+        crate::pager::Pager::start()
+    } else {
+        None
+    };
+
+    let result = if job_names.len() == 1 {
+        wait_single_streaming(config, &job_names[0], options)
+    } else {
+        wait_interleaved(config, job_names, options)
+    };
+    match result {
+        // The user quit the pager before reaching the end.
+        Err(PendError::Io(err)) if pager.is_some() && err.kind() == io::ErrorKind::BrokenPipe => {
+            drop(pager);
+            recorded_code(config, job_names)
         }
+        result => result,
     }
+}
 
-    if job_names.len() == 1 {
-        return wait_single_streaming(&job_names[0]);
+/// How often `--allow-pending` looks for jobs that do not exist yet.
+const PENDING_POLL: Duration = Duration::from_millis(50);
+
+/// How long records of a checksummed log that do not verify are waited for
+/// once the job has finished.
+const TORN_GRACE: Duration = Duration::from_secs(2);
+
+/// Block until every job has at least one artifact. A job still missing
+/// after `timeout` is reported as not found.
+fn await_submission(
+    config: &Config,
+    job_names: &[String],
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    for name in job_names {
+        let paths = JobPaths::new(config, name)?;
+        if !paths.any_exist() {
+            tracing::debug!(job = %name, "waiting for the job to be submitted");
+        }
+        while !paths.any_exist() {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Err(PendError::NotFound(name.clone()));
+            }
+            std::thread::sleep(PENDING_POLL);
+        }
     }
+    Ok(())
+}
 
-    wait_interleaved(job_names)
+fn finished(config: &Config, job_name: &str) -> bool {
+    JobPaths::new(config, job_name).is_ok_and(|paths| paths.exit.exists())
+}
+
+/// The first non-zero exit code recorded for `job_names`, else 0.
+fn recorded_code(config: &Config, job_names: &[String]) -> Result<i32> {
+    for name in job_names {
+        let paths = JobPaths::new(config, name)?;
+        match crate::validate::read_exit_code(&paths.exit)? {
+            Some(0) | None => {}
+            Some(code) => return Ok(code),
+        }
+    }
+    Ok(0)
 }
 
 // -------------------------------------------------------------------------
@@ -81,45 +246,208 @@ pub(crate) fn wait_jobs(job_names: &[String]) -> io::Result<i32> {
 
 /// Wait for the given job to finish and replay its captured logs to the
 /// current stdout/stderr. Returns the job's exit code.
-fn wait_single_streaming(job_name: &str) -> io::Result<i32> {
-    let mut job = JobState::new(job_name, Style::new())?;
+fn wait_single_streaming(config: &Config, job_name: &str, options: &WaitOptions) -> Result<i32> {
+    let mut job = JobState::new(config, job_name, Style::new())?;
     job.style = None; // disable colour for single-job waits
+    job.until = options.until.clone();
+    job.fold_repeats(options.fold_repeats, config.ascii);
+    if let Some(start) = options.start {
+        job.skip_backlog(start);
+    }
 
     let mut jobs = vec![job];
-
-    match wait_interleaved_with_watcher(&mut jobs) {
-        Ok(code) => Ok(code),
-        Err(_e) => wait_interleaved_polling(&mut jobs),
-    }
+    let mut live = Live::new(Footer::for_jobs(1, config.ascii), options.heartbeat);
+    follow(config, &mut jobs, &mut live, options)
 }
 
 // -------------------------------------------------------------------------
 // Interleaved waiting for multiple jobs
 // -------------------------------------------------------------------------
 
-struct JobState {
+/// One job being followed: where its log has been read to and how its
+/// output is replayed. `pend tail-all` follows jobs the same way.
+pub(crate) struct JobState {
     name: String,
+    paths: JobPaths,
     log_path: std::path::PathBuf,
+    idx_path: std::path::PathBuf,
+    frames_path: std::path::PathBuf,
     exit_path: std::path::PathBuf,
+    meta_path: std::path::PathBuf,
+    lock_path: std::path::PathBuf,
+    progress_path: std::path::PathBuf,
     log_offset: u64,
+    /// How much of `.idx` has been parsed into `stderr_ranges`.
+    idx_offset: u64,
+    /// Not yet replayed `[start, end)` ranges of the log that came from
+    /// stderr, in ascending order.
+    stderr_ranges: VecDeque<(u64, u64)>,
+    /// `--checksum-log`: the log's records, once the job turns out to have
+    /// them.
+    frames: Option<FrameReader>,
     exit_code: Option<i32>,
-    style: Option<anstyle::Style>,
+    style: Option<Style>,
+    /// `pend tail-all`: start every line with `[<job>] `, in the job's
+    /// colour, instead of colouring the line itself.
+    prefix: Option<String>,
+    /// Styled or prefixed output only: bytes of an incomplete trailing
+    /// line, per stream (`[stdout, stderr]`).
+    pending: [Vec<u8>; 2],
+    /// `--fold-repeats`: folding state per stream (`[stdout, stderr]`).
+    folds: Option<[crate::fold::Fold; 2]>,
+    until: Until,
+    /// `--for-output`: incomplete trailing log line not yet matched.
+    unmatched: Vec<u8>,
+    /// Whether the job reached the state `until` asks for while running.
+    ready: bool,
+    /// Start of the job according to its metadata, once known.
+    started: Option<chrono::DateTime<chrono::Utc>>,
+    /// The job's worker, once its metadata names one on this host.
+    worker: Option<crate::process::ProcessWatch>,
+    /// Whether the job runs with `--timeout`, once its metadata is read.
+    timed: Option<bool>,
+    /// Nobody reads the replay any more: drop the output instead.
+    discard: bool,
 }
 
 impl JobState {
-    fn new(name: &str, style: anstyle::Style) -> io::Result<Self> {
-        let style_opt = if colors_enabled() { Some(style) } else { None };
-        let paths = JobPaths::new(name)?;
+    pub(crate) fn new(config: &Config, name: &str, style: Style) -> io::Result<Self> {
+        let style_opt = if colors_enabled(config.color) {
+            Some(style)
+        } else {
+            None
+        };
+        let paths = JobPaths::new(config, name)?;
         Ok(Self {
             name: name.to_string(),
-            log_path: paths.log,
-            exit_path: paths.exit,
+            log_path: paths.log.clone(),
+            idx_path: paths.idx.clone(),
+            frames_path: paths.frames.clone(),
+            exit_path: paths.exit.clone(),
+            meta_path: paths.meta.clone(),
+            lock_path: paths.lock.clone(),
+            progress_path: paths.progress.clone(),
+            paths,
             log_offset: 0,
+            idx_offset: 0,
+            stderr_ranges: VecDeque::new(),
+            frames: None,
             exit_code: None,
             style: style_opt,
+            prefix: None,
+            pending: [Vec::new(), Vec::new()],
+            folds: None,
+            until: Until::Finished,
+            unmatched: Vec::new(),
+            ready: false,
+            started: None,
+            worker: None,
+            timed: None,
+            discard: false,
         })
     }
 
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub(crate) fn meta_path(&self) -> &std::path::Path {
+        &self.meta_path
+    }
+
+    /// Prefix every replayed line with the job name.
+    pub(crate) fn prefix_lines(&mut self) {
+        self.prefix = Some(format!("[{}] ", self.name));
+    }
+
+    /// Start replaying at `start` instead of the beginning of the log.
+    /// Stderr ranges before it are dropped as the log is split.
+    pub(crate) fn skip_backlog(&mut self, start: LogStart) {
+        let len = JobPaths::file_len(&self.log_path);
+        self.log_offset = match start {
+            LogStart::End => len,
+            LogStart::Offset(offset) if offset <= len => offset,
+            LogStart::Offset(_) => 0,
+        };
+    }
+
+    /// Collapse repeated lines in the replay (see `fold.rs`).
+    fn fold_repeats(&mut self, enabled: bool, ascii: bool) {
+        self.folds =
+            enabled.then(|| [crate::fold::Fold::new(ascii), crate::fold::Fold::new(ascii)]);
+    }
+
+    /// Whole seconds the job has been running, for the progress footer.
+    fn elapsed(&mut self) -> Option<i64> {
+        if self.started.is_none() {
+            self.started = read_meta(&self.meta_path)
+                .and_then(|m| m.get("started")?.as_str().map(String::from))
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|t| t.with_timezone(&chrono::Utc));
+        }
+        let started = self.started?;
+        Some(
+            chrono::Utc::now()
+                .signed_duration_since(started)
+                .num_seconds()
+                .max(0),
+        )
+    }
+
+    /// Whole seconds until `--timeout` kills the running attempt.
+    fn time_left(&mut self) -> Option<i64> {
+        if self.done() || self.timed == Some(false) {
+            return None;
+        }
+        let meta = read_meta(&self.meta_path)?;
+        self.timed = Some(meta.get("timeout").is_some());
+        let deadline = crate::status::attempt_deadline(&meta)?;
+        Some((deadline - chrono::Utc::now()).num_seconds().max(0))
+    }
+
+    /// Whether there is nothing left to wait for: the job finished or reached
+    /// the awaited state.
+    fn done(&self) -> bool {
+        self.exit_code.is_some() || self.ready
+    }
+
+    /// The code this job contributes to `pend wait`'s own exit status, if it
+    /// counts as a failure. A job that finished without ever reaching the
+    /// awaited state fails even when it exited 0.
+    fn failure(&self) -> Option<i32> {
+        if self.ready {
+            return None;
+        }
+        match self.exit_code? {
+            0 if !matches!(self.until, Until::Finished) => Some(1),
+            0 => None,
+            code => Some(code),
+        }
+    }
+
+    /// Match complete log lines in `bytes` against the `--for-output`
+    /// pattern; a partial line is kept until its newline arrives.
+    fn scan_for_output(&mut self, bytes: &[u8]) {
+        let Until::Output(pattern) = &self.until else {
+            return;
+        };
+        if self.ready {
+            return;
+        }
+        self.unmatched.extend_from_slice(bytes);
+        let Some(complete) = self.unmatched.iter().rposition(|&b| b == b'\n') else {
+            return;
+        };
+        self.ready = self.unmatched[..complete]
+            .split(|&b| b == b'\n')
+            .any(|line| pattern.is_match(line.strip_suffix(b"\r").unwrap_or(line)));
+        self.unmatched.drain(..=complete);
+    }
+
     /// Poll job state once.
     ///
     /// Returns `(finished, progress)` where
@@ -127,58 +455,270 @@ impl JobState {
     ///    is present and has been parsed), and
     ///  * `progress` is true when new information became available during this
     ///    poll iteration (either log output or a newly discovered exit code).
-    fn poll(&mut self) -> io::Result<(bool /* finished */, bool /* progress */)> {
-        // Helper closure reading newly appended bytes from the combined log.
-        let read_log = |path: &std::path::Path, offset: &mut u64| -> io::Result<bool> {
-            if !path.exists() {
-                return Ok(false);
-            }
+    pub(crate) fn poll(&mut self) -> Result<(bool /* finished */, bool /* progress */)> {
+        // The worker writes `.exit` only after the last log byte, so once the
+        // exit code was known *before* this read nothing else will follow –
+        // unless records of a checksummed log are still in flight.
+        let was_finished =
+            self.exit_code.is_some() && self.frames.as_ref().is_none_or(FrameReader::settled);
 
-            let size = fs::metadata(path)?.len();
-            if size <= *offset {
-                return Ok(false);
+        let start = self.log_offset;
+        let buffer = self.read_new_log_bytes()?;
+        let mut progress = !buffer.is_empty();
+        if progress {
+            self.read_stderr_ranges()?;
+            for (is_stderr, range) in self.split_streams(start, buffer.len()) {
+                self.replay(is_stderr, &buffer[range], false)?;
             }
+        }
+        self.scan_for_output(&buffer);
+        if was_finished {
+            self.replay(false, &[], true)?;
+            self.replay(true, &[], true)?;
+        }
 
-            let mut file = File::open(path)?;
-            file.seek(SeekFrom::Start(*offset))?;
+        if matches!(self.until, Until::Ready)
+            && !self.ready
+            && crate::ready::is_ready(&self.meta_path)
+        {
+            self.ready = true;
+            progress = true;
+        }
 
-            let mut buffer = Vec::with_capacity((size - *offset) as usize);
-            file.read_to_end(&mut buffer)?;
-            *offset = size;
+        // Check exit code.
+        if self.exit_code.is_none() {
+            self.exit_code = crate::validate::read_exit_code(&self.exit_path)?;
+            progress |= self.exit_code.is_some();
+        }
 
-            if !buffer.is_empty() {
-                if let Some(style) = &self.style {
-                    let txt = String::from_utf8_lossy(&buffer);
-                    let styled = format!("{}{}{}", style.render(), txt, style.render_reset());
-                    io::stdout().write_all(styled.as_bytes())?;
-                } else {
-                    io::stdout().write_all(&buffer)?;
+        if self.exit_code.is_none() && !progress && self.worker_vanished() {
+            return Err(PendError::WorkerDied(self.name.clone()));
+        }
+
+        Ok((self.exit_code.is_some(), progress))
+    }
+
+    /// Whether the worker is gone without having written `.exit` (killed,
+    /// or lost with its machine). A worker on this host is watched directly;
+    /// otherwise its metadata shows it had started, and a worker releases
+    /// its lock only after writing `.exit`.
+    fn worker_vanished(&mut self) -> bool {
+        if self.worker.is_none() {
+            let Ok(bytes) = fs::read(&self.meta_path) else {
+                return false;
+            };
+            let meta = crate::version::parse_meta(&bytes).unwrap_or(serde_json::Value::Null);
+            match crate::status::local_worker(&meta) {
+                Some((pid, started)) => {
+                    self.worker = crate::process::ProcessWatch::open(pid, started);
+                    if self.worker.is_none() {
+                        // Already gone; `.exit` is written before it exits.
+                        return !self.exit_path.exists();
+                    }
                 }
-                io::stdout().flush()?;
+                None => return !crate::lock::is_held(&self.lock_path) && !self.exit_path.exists(),
             }
+        }
+        self.worker.as_ref().is_some_and(|w| w.has_exited()) && !self.exit_path.exists()
+    }
+
+    /// Read the bytes appended to the combined log since the previous call.
+    fn read_new_log_bytes(&mut self) -> io::Result<Vec<u8>> {
+        if !self.log_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        if self.frames.is_none() {
+            self.frames = FrameReader::open(&self.frames_path);
+        }
+        if let Some(frames) = &mut self.frames {
+            let buffer = frames.read(&self.log_path, self.log_offset)?;
+            self.log_offset += buffer.len() as u64;
+            return Ok(buffer);
+        }
+
+        let size = fs::metadata(&self.log_path)?.len();
+        if size <= self.log_offset {
+            return Ok(Vec::new());
+        }
 
-            Ok(!buffer.is_empty())
+        let mut file = File::open(&self.log_path)?;
+        file.seek(SeekFrom::Start(self.log_offset))?;
+
+        // Stop at `size` even if the log has grown meanwhile: the stream index
+        // read next is only guaranteed to cover bytes that existed by then.
+        let mut buffer = Vec::with_capacity((size - self.log_offset) as usize);
+        file.take(size - self.log_offset).read_to_end(&mut buffer)?;
+        self.log_offset += buffer.len() as u64;
+        Ok(buffer)
+    }
+
+    /// Read what is left once the job is done. Records of a checksummed log
+    /// that do not verify get [`TORN_GRACE`] to arrive in full; after that
+    /// they are skipped rather than replayed as garbage.
+    pub(crate) fn drain(&mut self) -> Result<()> {
+        let give_up = Instant::now() + TORN_GRACE;
+        let mut skipped = 0;
+        loop {
+            self.poll()?;
+            let Some(frames) = &mut self.frames else {
+                break;
+            };
+            if frames.settled() {
+                break;
+            }
+            if Instant::now() < give_up {
+                std::thread::sleep(PENDING_POLL);
+                continue;
+            }
+            let resume = frames.skip(self.log_offset);
+            skipped += resume - self.log_offset;
+            self.log_offset = resume;
+        }
+        if skipped > 0 {
+            self.replay(false, &[], true)?;
+            self.replay(true, &[], true)?;
+            eprintln!(
+                "pend: skipped {skipped} bytes of '{}' output that failed their checksum",
+                self.name
+            );
+        }
+        Ok(())
+    }
+
+    /// Pick up stderr ranges the worker appended to `.idx`. Only complete
+    /// lines are consumed; a partially written entry is retried next time.
+    fn read_stderr_ranges(&mut self) -> io::Result<()> {
+        let mut file = match File::open(&self.idx_path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
         };
+        file.seek(SeekFrom::Start(self.idx_offset))?;
+        let mut text = Vec::new();
+        file.read_to_end(&mut text)?;
 
-        let mut progress = read_log(&self.log_path, &mut self.log_offset)?;
+        let Some(last_newline) = text.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        self.idx_offset += last_newline as u64 + 1;
 
-        // Check exit code.
-        if self.exit_code.is_none() && self.exit_path.exists() {
-            let code_str = fs::read_to_string(&self.exit_path)?.trim().to_string();
-            self.exit_code = code_str.parse::<i32>().ok();
-            progress = true;
+        for line in String::from_utf8_lossy(&text[..last_newline]).lines() {
+            let mut parts = line.split_whitespace().map(|p| p.parse::<u64>().ok());
+            if let (Some(Some(start)), Some(Some(len))) = (parts.next(), parts.next()) {
+                self.stderr_ranges.push_back((start, start + len));
+            }
+        }
+        Ok(())
+    }
+
+    /// Split `len` log bytes starting at offset `start` into consecutive
+    /// `(is_stderr, range)` segments, `range` being relative to `start`.
+    fn split_streams(&mut self, start: u64, len: usize) -> Vec<(bool, std::ops::Range<usize>)> {
+        let end = start + len as u64;
+        let mut segments = Vec::new();
+        let mut pos = start;
+        while pos < end {
+            while self.stderr_ranges.front().is_some_and(|&(_, e)| e <= pos) {
+                self.stderr_ranges.pop_front();
+            }
+            let (is_stderr, seg_end) = match self.stderr_ranges.front() {
+                Some(&(s, e)) if s <= pos => (true, e.min(end)),
+                Some(&(s, _)) => (false, s.min(end)),
+                None => (false, end),
+            };
+            segments.push((
+                is_stderr,
+                (pos - start) as usize..(seg_end - start) as usize,
+            ));
+            pos = seg_end;
         }
+        segments
+    }
 
-        Ok((self.exit_code.is_some(), progress))
+    /// Copy `bytes` to stdout, or to stderr when they were captured from the
+    /// command's stderr.
+    ///
+    /// Without a style or prefix the bytes pass through untouched. Otherwise
+    /// only complete lines are emitted, each wrapped in colour codes or
+    /// behind the prefix; the bytes themselves are never re-encoded, so
+    /// multi-byte characters split across reads and binary output survive
+    /// intact. A trailing partial line is held back until its newline
+    /// arrives or `flush` is set.
+    fn replay(&mut self, is_stderr: bool, bytes: &[u8], flush: bool) -> io::Result<()> {
+        if self.discard {
+            return Ok(());
+        }
+        let folded;
+        let bytes = match &mut self.folds {
+            Some(folds) => {
+                folded = folds[is_stderr as usize].feed(bytes, flush);
+                &folded[..]
+            }
+            None => bytes,
+        };
+        let mut out: Box<dyn Write> = if is_stderr {
+            Box::new(io::stderr().lock())
+        } else {
+            Box::new(io::stdout().lock())
+        };
+        if self.style.is_none() && self.prefix.is_none() {
+            crate::throttle::write_all(&mut out, bytes)?;
+            return out.flush();
+        }
+        let (before, after) = self.style.as_ref().map_or(("".into(), "".into()), |style| {
+            (style.render().to_string(), style.render_reset().to_string())
+        });
+
+        let pending = &mut self.pending[is_stderr as usize];
+        pending.extend_from_slice(bytes);
+        let complete = if flush {
+            pending.len()
+        } else {
+            match pending.iter().rposition(|&b| b == b'\n') {
+                Some(pos) => pos + 1,
+                None => return Ok(()),
+            }
+        };
+
+        let mut rendered = Vec::with_capacity(complete);
+        for line in pending[..complete].split_inclusive(|&b| b == b'\n') {
+            let (body, newline) = match line.strip_suffix(b"\n") {
+                Some(body) => (body, &b"\n"[..]),
+                None => (line, &b""[..]),
+            };
+            match &self.prefix {
+                Some(prefix) => {
+                    write!(rendered, "{before}{prefix}{after}")?;
+                    rendered.extend_from_slice(body);
+                }
+                None => {
+                    write!(rendered, "{before}")?;
+                    rendered.extend_from_slice(body);
+                    write!(rendered, "{after}")?;
+                }
+            }
+            rendered.extend_from_slice(newline);
+        }
+        pending.drain(..complete);
+        crate::throttle::write_all(&mut out, &rendered)?;
+        out.flush()
     }
 }
 
-fn wait_interleaved(job_names: &[String]) -> io::Result<i32> {
+fn wait_interleaved(config: &Config, job_names: &[String], options: &WaitOptions) -> Result<i32> {
     let mut jobs: Vec<JobState> = job_names
         .iter()
         .enumerate()
-        .map(|(idx, name)| JobState::new(name, color_style(idx)))
-        .collect::<Result<_, _>>()?;
+        .map(|(idx, name)| JobState::new(config, name, crate::color::job_style(idx)))
+        .collect::<io::Result<_>>()?;
+    for job in jobs.iter_mut() {
+        job.until = options.until.clone();
+        job.fold_repeats(options.fold_repeats, config.ascii);
+        if let Some(start) = options.start {
+            job.skip_backlog(start);
+        }
+    }
 
     // NOTE: We no longer abort immediately when no artifact files exist yet
     // for a given job. Creation of the first `.log`/`.out` or `.exit` file
@@ -189,9 +729,54 @@ fn wait_interleaved(job_names: &[String]) -> io::Result<i32> {
 
     // Try the watcher-based implementation first. If anything fails we'll
     // transparently fall back to the legacy polling loop.
-    match wait_interleaved_with_watcher(&mut jobs) {
-        Ok(code) => Ok(code),
-        Err(_err) => wait_interleaved_polling(&mut jobs),
+    let mut live = Live::new(
+        Footer::for_jobs(jobs.len(), config.ascii),
+        options.heartbeat,
+    );
+    follow(config, &mut jobs, &mut live, options)
+}
+
+/// Follow `jobs` until the wait is over, then summarise and clean up.
+/// Returns `pend wait`'s exit code.
+///
+/// When whoever reads the replay goes away (`pend wait job | head`), the
+/// jobs are still followed to the end without replaying them, so the exit
+/// code is theirs and `--clean` still applies; the summaries are skipped,
+/// having nowhere to go. `--sigpipe` gives up at once instead.
+fn follow(
+    config: &Config,
+    jobs: &mut [JobState],
+    live: &mut Live,
+    options: &WaitOptions,
+) -> Result<i32> {
+    let mut stdout_closed = false;
+    let code = match follow_jobs(jobs, live) {
+        Err(PendError::Io(err)) if err.kind() == io::ErrorKind::BrokenPipe && !options.sigpipe => {
+            tracing::debug!("stdout closed, following the jobs without replaying them");
+            stdout_closed = true;
+            for job in jobs.iter_mut() {
+                job.discard = true;
+            }
+            follow_jobs(jobs, live)?
+        }
+        result => result?,
+    };
+    if !stdout_closed {
+        emit_summaries(config, jobs, options)?;
+    }
+    clean_successful(jobs, options.clean)?;
+    Ok(code)
+}
+
+/// Follow `jobs` with the file watcher, or by polling when there is none.
+fn follow_jobs(jobs: &mut [JobState], live: &mut Live) -> Result<i32> {
+    match wait_interleaved_with_watcher(jobs, live) {
+        // Failing to write the replay is no reason to try polling instead.
+        Err(PendError::Io(err)) if err.kind() != io::ErrorKind::BrokenPipe => {
+            tracing::debug!(%err, "file watcher unavailable, falling back to polling");
+            wait_interleaved_polling(jobs, live)
+        }
+        result => result,
     }
 }
 
@@ -199,7 +784,19 @@ fn wait_interleaved(job_names: &[String]) -> io::Result<i32> {
 // Watcher-based implementation
 // -------------------------------------------------------------------------
 
-fn wait_interleaved_with_watcher(jobs: &mut [JobState]) -> io::Result<i32> {
+#[cfg(not(feature = "notify"))]
+fn wait_interleaved_with_watcher(_jobs: &mut [JobState], _live: &mut Live) -> Result<i32> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "built without the notify feature",
+    )
+    .into())
+}
+
+#[cfg(feature = "notify")]
+fn wait_interleaved_with_watcher(jobs: &mut [JobState], live: &mut Live) -> Result<i32> {
+    use std::collections::HashMap;
+    use std::ffi::OsString;
     use std::sync::mpsc::channel;
     use std::sync::mpsc::RecvTimeoutError;
 
@@ -207,91 +804,138 @@ fn wait_interleaved_with_watcher(jobs: &mut [JobState]) -> io::Result<i32> {
     let root_dir = jobs
         .first()
         .and_then(|j| j.log_path.parent())
-        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid job path"))?;
+        .ok_or_else(|| io::Error::other("invalid job path"))?;
 
-    let (event_tx, event_rx) = channel();
+    let (event_tx, event_rx) = channel::<Wake>();
 
-    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res| {
-        let _ = event_tx.send(res);
-    })
-    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    // Artifacts that matter to `poll`, by file name.
+    let owners: HashMap<OsString, usize> = jobs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, job)| {
+            [
+                &job.log_path,
+                &job.idx_path,
+                &job.frames_path,
+                &job.exit_path,
+                &job.meta_path,
+            ]
+            .into_iter()
+            .filter_map(move |path| Some((path.file_name()?.to_os_string(), index)))
+        })
+        .collect();
+    let watcher_tx = event_tx.clone();
+    let mut watcher: RecommendedWatcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let event = match res {
+                Ok(event) if !event.need_rescan() && !event.paths.is_empty() => event,
+                // Missed events or an error: look at everything.
+                _ => {
+                    let _ = watcher_tx.send(Wake::All);
+                    return;
+                }
+            };
+            for path in &event.paths {
+                if let Some(&index) = path.file_name().and_then(|name| owners.get(name)) {
+                    let _ = watcher_tx.send(Wake::Job(index));
+                }
+            }
+        })
+        .map_err(io::Error::other)?;
+
+    spawn_exit_subscribers(jobs, &event_tx);
+    drop(event_tx);
 
     watcher
         .watch(root_dir, RecursiveMode::NonRecursive)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        .map_err(io::Error::other)?;
 
     // Initial poll flush.
     let mut first_error: Option<i32> = None;
     for job in jobs.iter_mut() {
         let (finished, _progress) = job.poll()?;
         if finished {
-            if let Some(code) = job.exit_code {
-                if code != 0 && first_error.is_none() {
-                    first_error = Some(code);
-                }
+            if let Some(code) = job.failure() {
+                first_error.get_or_insert(code);
             }
         }
     }
 
     // Main event-driven loop.
-    while jobs.iter().any(|j| j.exit_code.is_none()) {
+    while jobs.iter().any(|j| !j.done()) {
         // Wait for any FS event with a generous timeout so we do not block
         // forever in case the watcher misses an update.
-        match event_rx.recv_timeout(std::time::Duration::from_secs(2)) {
-            Ok(_) | Err(RecvTimeoutError::Timeout) => {
-                // On any event (or timeout) re-poll all jobs for progress.
-                for job in jobs.iter_mut() {
-                    let (finished, _progress) = job.poll()?;
-                    if finished {
-                        if let Some(code) = job.exit_code {
-                            if code != 0 && first_error.is_none() {
-                                first_error = Some(code);
-                            }
-                        }
+        let mut dirty = vec![false; jobs.len()];
+        match event_rx.recv_timeout(live.tick(Duration::from_secs(2))) {
+            Ok(wake) => {
+                // Let a burst of events settle, then read each affected job
+                // once.
+                wake.mark(&mut dirty);
+                let window = Instant::now() + COALESCE;
+                while let Some(left) = window.checked_duration_since(Instant::now()) {
+                    match event_rx.recv_timeout(left) {
+                        Ok(wake) => wake.mark(&mut dirty),
+                        Err(_) => break,
                     }
                 }
             }
+            // Re-poll everything in case the watcher missed an update.
+            Err(RecvTimeoutError::Timeout) => dirty.fill(true),
             Err(RecvTimeoutError::Disconnected) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "watcher channel disconnected",
-                ));
+                return Err(io::Error::other("watcher channel disconnected").into());
             }
         }
+        live.clear()?;
+        for (job, _) in jobs
+            .iter_mut()
+            .zip(&dirty)
+            .filter(|(j, &dirty)| dirty && !j.ready)
+        {
+            let (finished, _progress) = job.poll()?;
+            if finished {
+                if let Some(code) = job.failure() {
+                    first_error.get_or_insert(code);
+                }
+            }
+        }
+        live.refresh(jobs)?;
     }
 
-    // Drain any remaining buffered output.
-    for job in jobs.iter_mut() {
-        let _ = job.poll()?;
-    }
-
-    // Emit summary lines.
-    for job in jobs.iter() {
-        let meta_path = JobPaths::new(&job.name)?.meta;
-        emit_summary(&job.name, job.exit_code.unwrap_or(1), &meta_path)?;
+    // Drain any remaining buffered output. Jobs that became ready keep
+    // running; their later output is not ours to replay.
+    live.clear()?;
+    for job in jobs.iter_mut().filter(|j| !j.ready) {
+        job.drain()?;
     }
 
-    Ok(first_error.unwrap_or(0))
+    Ok(overall_code(jobs, first_error))
 }
 
 // -------------------------------------------------------------------------
 // Legacy polling implementation (fallback)
 // -------------------------------------------------------------------------
 
-fn wait_interleaved_polling(jobs: &mut [JobState]) -> io::Result<i32> {
+fn wait_interleaved_polling(jobs: &mut [JobState], live: &mut Live) -> Result<i32> {
     let mut remaining = jobs.len();
     let mut first_error: Option<i32> = None;
 
-    let base_delay = std::time::Duration::from_millis(50);
-    let max_delay = std::time::Duration::from_secs(2);
+    let base_delay = Duration::from_millis(50);
+    let max_delay = Duration::from_secs(2);
     let mut current_delay = base_delay;
 
+    // Workers reachable through their control socket wake us up the moment
+    // they finish, cutting the back-off short.
+    let (wake_tx, wake_rx) = std::sync::mpsc::channel::<Wake>();
+    spawn_exit_subscribers(jobs, &wake_tx);
+
     while remaining > 0 {
         let mut any_progress = false;
 
+        live.clear()?;
         for job in jobs.iter_mut() {
-            if job.exit_code.is_some()
-                && job.log_offset == crate::paths::JobPaths::file_len(&job.log_path)
+            if job.ready
+                || (job.exit_code.is_some()
+                    && job.log_offset == crate::paths::JobPaths::file_len(&job.log_path))
             {
                 continue;
             }
@@ -302,15 +946,16 @@ fn wait_interleaved_polling(jobs: &mut [JobState]) -> io::Result<i32> {
             }
 
             if finished {
-                if let Some(code) = job.exit_code {
-                    if code != 0 && first_error.is_none() {
-                        first_error = Some(code);
-                    }
+                if let Some(code) = job.failure() {
+                    first_error.get_or_insert(code);
                 }
             }
         }
 
-        remaining = jobs.iter().filter(|j| j.exit_code.is_none()).count();
+        remaining = jobs.iter().filter(|j| !j.done()).count();
+        if remaining > 0 {
+            live.refresh(jobs)?;
+        }
 
         if remaining > 0 {
             if any_progress {
@@ -318,60 +963,479 @@ fn wait_interleaved_polling(jobs: &mut [JobState]) -> io::Result<i32> {
             } else {
                 current_delay = std::cmp::min(current_delay * 2, max_delay);
             }
-            std::thread::sleep(current_delay);
+            let _ = wake_rx.recv_timeout(live.tick(current_delay));
         }
     }
 
     // Drain remaining output
-    for job in jobs.iter_mut() {
-        let _ = job.poll()?;
+    live.clear()?;
+    for job in jobs.iter_mut().filter(|j| !j.ready) {
+        job.drain()?;
     }
 
-    for job in jobs.iter() {
-        let meta_path = JobPaths::new(&job.name)?.meta;
-        emit_summary(&job.name, job.exit_code.unwrap_or(1), &meta_path)?;
-    }
-
-    Ok(first_error.unwrap_or(0))
+    Ok(overall_code(jobs, first_error))
 }
 
 // -------------------------------------------------------------------------
 // Shared helpers
 // -------------------------------------------------------------------------
 
-fn emit_summary<P: AsRef<std::path::Path>>(
-    job_name: &str,
-    exit_code: i32,
-    meta_path: P,
-) -> io::Result<()> {
-    let meta_path = meta_path.as_ref();
-
-    let duration_secs = if let Ok(meta_bytes) = fs::read(meta_path) {
-        if let Ok(meta_json) = serde_json::from_slice::<serde_json::Value>(&meta_bytes) {
-            let started = meta_json.get("started").and_then(|v| v.as_str());
-            let ended = meta_json.get("ended").and_then(|v| v.as_str());
-            if let (Some(start), Some(end)) = (started, ended) {
-                let s = chrono::DateTime::parse_from_rfc3339(start).ok();
-                let e = chrono::DateTime::parse_from_rfc3339(end).ok();
-                if let (Some(sdt), Some(edt)) = (s, e) {
-                    edt.signed_duration_since(sdt).num_seconds().max(0)
+/// How long watcher events are collected before the affected jobs are read.
+#[cfg(feature = "notify")]
+const COALESCE: Duration = Duration::from_millis(20);
+
+/// What a watcher event or exit notification asks to look at. Polling
+/// looks at every job regardless.
+#[cfg_attr(not(feature = "notify"), allow(dead_code))]
+enum Wake {
+    /// Artifacts of the job at this index changed.
+    Job(usize),
+    /// Events were lost; every job may have changed.
+    All,
+}
+
+#[cfg(feature = "notify")]
+impl Wake {
+    fn mark(self, dirty: &mut [bool]) {
+        match self {
+            Wake::Job(index) => dirty[index] = true,
+            Wake::All => dirty.fill(true),
+        }
+    }
+}
+
+/// Refresh interval of the progress footer.
+const FOOTER_TICK: Duration = Duration::from_millis(200);
+
+/// Status output shown while waiting, besides the jobs' own output: the
+/// progress footer and `--heartbeat` lines.
+struct Live {
+    footer: Option<Footer>,
+    /// Heartbeat interval and when the next line is due.
+    heartbeat: Option<(Duration, Instant)>,
+}
+
+impl Live {
+    fn new(footer: Option<Footer>, heartbeat: Option<Duration>) -> Self {
+        Self {
+            footer,
+            heartbeat: heartbeat.map(|every| (every, Instant::now() + every)),
+        }
+    }
+
+    /// How long the wait loops may sleep, at most `idle`, before the next
+    /// refresh is due.
+    fn tick(&self, idle: Duration) -> Duration {
+        let mut tick = idle;
+        if self.footer.is_some() {
+            tick = tick.min(FOOTER_TICK);
+        }
+        if let Some((_, due)) = self.heartbeat {
+            tick = tick.min(due.saturating_duration_since(Instant::now()));
+        }
+        tick
+    }
+
+    /// Erase the footer (if drawn) so regular output can take its place.
+    fn clear(&mut self) -> io::Result<()> {
+        match &mut self.footer {
+            Some(footer) => footer.clear(),
+            None => Ok(()),
+        }
+    }
+
+    /// Print a heartbeat line if one is due and redraw the footer.
+    fn refresh(&mut self, jobs: &mut [JobState]) -> io::Result<()> {
+        let elapsed: Vec<Option<i64>> = jobs.iter_mut().map(JobState::elapsed).collect();
+        let progress: Vec<Option<f64>> = jobs
+            .iter()
+            .map(|job| match job.done() {
+                true => None,
+                false => crate::report::read(&job.progress_path),
+            })
+            .collect();
+
+        if let Some((every, due)) = &mut self.heartbeat {
+            if Instant::now() >= *due {
+                *due = Instant::now() + *every;
+                let waiting: Vec<String> = jobs
+                    .iter()
+                    .zip(elapsed.iter().zip(&progress))
+                    .filter(|(job, _)| !job.done())
+                    .map(|(job, (secs, percent))| {
+                        let details: Vec<String> = secs
+                            .map(format_elapsed)
+                            .into_iter()
+                            .chain(percent.map(|p| format!("{p:.0}%")))
+                            .collect();
+                        match details.is_empty() {
+                            true => job.name.clone(),
+                            false => format!("{} ({})", job.name, details.join(", ")),
+                        }
+                    })
+                    .collect();
+                if !waiting.is_empty() {
+                    eprintln!("pend: still waiting: {}", waiting.join(", "));
+                }
+            }
+        }
+
+        let Some(footer) = &mut self.footer else {
+            return Ok(());
+        };
+        let time_left: Vec<Option<i64>> = jobs.iter_mut().map(JobState::time_left).collect();
+        let rows: Vec<Row> = jobs
+            .iter()
+            .zip(elapsed.into_iter().zip(progress).zip(time_left))
+            .map(|(job, ((elapsed, progress), time_left))| {
+                let phase = if job.failure().is_some() {
+                    Phase::Failed
+                } else if job.done() {
+                    Phase::Done
                 } else {
-                    0
+                    Phase::Running
+                };
+                Row {
+                    name: &job.name,
+                    phase,
+                    elapsed,
+                    progress,
+                    time_left,
+                }
+            })
+            .collect();
+        footer.draw(&rows)
+    }
+}
+
+/// `45s`, `12m` or `1h05m`.
+fn format_elapsed(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{s}s"),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h{:02}m", s / 3600, s % 3600 / 60),
+    }
+}
+
+/// Subscribe to the exit notification of every unfinished job through its
+/// worker's control socket. Each notification sends one wake-up on `tx`.
+fn spawn_exit_subscribers(jobs: &[JobState], tx: &std::sync::mpsc::Sender<Wake>) {
+    for (index, job) in jobs
+        .iter()
+        .enumerate()
+        .filter(|(_, j)| j.exit_code.is_none())
+    {
+        let paths = job.paths.clone();
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            if crate::control::subscribe_exit(&paths).is_some() {
+                let _ = tx.send(Wake::Job(index));
+            }
+        });
+    }
+}
+
+/// `pend wait`'s exit code: that of the first job seen failing, unless the
+/// final drain showed it printed the awaited output after all.
+fn overall_code(jobs: &[JobState], first_error: Option<i32>) -> i32 {
+    match first_error {
+        Some(code) if jobs.iter().any(|j| j.failure() == Some(code)) => code,
+        _ => jobs.iter().find_map(JobState::failure).unwrap_or(0),
+    }
+}
+
+/// Delete the artifacts of jobs that finished successfully, given `--clean`
+/// or when submitted with `--ephemeral`. Failed jobs keep theirs for
+/// debugging.
+fn clean_successful(jobs: &[JobState], always: bool) -> io::Result<()> {
+    for job in jobs.iter().filter(|j| j.exit_code == Some(0)) {
+        let ephemeral = read_meta(&job.meta_path)
+            .and_then(|m| m.get("ephemeral")?.as_bool())
+            .unwrap_or(false);
+        if always || ephemeral {
+            tracing::debug!(job = job.name, "removing artifacts of a successful run");
+            job.paths.remove_all();
+        }
+    }
+    Ok(())
+}
+
+/// Print one status line per job once waiting is over.
+fn emit_summaries(config: &Config, jobs: &[JobState], options: &WaitOptions) -> io::Result<()> {
+    // Durations and attempts come from the metadata; say so when it is
+    // broken instead of quietly reporting zeros.
+    for job in jobs.iter().filter(|j| j.exit_code.is_some()) {
+        if let Err(err) = crate::validate::check_meta(&job.paths) {
+            eprintln!("pend: {err}");
+        }
+    }
+    // A job that became ready is still running; tell the caller where its
+    // output stopped so a later wait can pick up from there.
+    for job in jobs.iter().filter(|j| j.ready) {
+        eprintln!(
+            "pend: '{}' stopped at log offset {} (resume with --since-offset {})",
+            job.name, job.log_offset, job.log_offset
+        );
+    }
+    let (jobs, groups) = match options.summary_sort {
+        Some(sort) => (summary_order(jobs, sort, options.failures_first), true),
+        None => (jobs.iter().collect(), false),
+    };
+    let why_failed = |job: &JobState| match options.stderr_tail {
+        Some(lines) if job.failure().is_some() => {
+            emit_stderr_tail(&job.meta_path.with_extension("err"), lines)
+        }
+        _ => Ok(()),
+    };
+    if options.summary_table {
+        emit_summary_table(&jobs, groups);
+        for job in jobs.iter().filter(|j| j.failure().is_some()) {
+            if options.stderr_tail.is_some() {
+                println!("{} {}:", glyphs::fail(config.ascii), job.name);
+            }
+            why_failed(job)?;
+        }
+        return Ok(());
+    }
+    let mut previous_group = None;
+    for job in jobs {
+        // A blank line sets the outcome groups apart.
+        let group = groups.then(|| outcome_group(job, options.failures_first));
+        if previous_group.is_some_and(|previous| Some(previous) != group) {
+            println!();
+        }
+        previous_group = group;
+        match (&job.until, job.ready) {
+            (Until::Output(_), true) => println!(
+                "{} {} {} ready (output matched)",
+                glyphs::ok(config.ascii),
+                job.name,
+                glyphs::dash(config.ascii)
+            ),
+            (_, true) => println!(
+                "{} {} {} ready",
+                glyphs::ok(config.ascii),
+                job.name,
+                glyphs::dash(config.ascii)
+            ),
+            (until, false) => {
+                emit_summary(
+                    config,
+                    &job.name,
+                    job.exit_code.unwrap_or(1),
+                    &job.meta_path,
+                )?;
+                why_failed(job)?;
+                match until {
+                    Until::Finished => {}
+                    Until::Output(_) => eprintln!(
+                        "pend: job '{}' finished without printing the awaited output",
+                        job.name
+                    ),
+                    Until::Ready => {
+                        eprintln!("pend: job '{}' finished without becoming ready", job.name)
+                    }
                 }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rank of the outcome group of `job` for `--summary-sort`: successes,
+/// then jobs that became ready, then failures – or failures first.
+fn outcome_group(job: &JobState, failures_first: bool) -> u8 {
+    match (job.failure().is_some(), job.ready) {
+        (true, _) if failures_first => 0,
+        (true, _) => 3,
+        (false, false) => 1,
+        (false, true) => 2,
+    }
+}
+
+/// `jobs` in the order `--summary-sort` lists them.
+fn summary_order(jobs: &[JobState], sort: SummarySort, failures_first: bool) -> Vec<&JobState> {
+    let mut ordered: Vec<(u8, i64, &JobState)> = jobs
+        .iter()
+        .map(|job| {
+            let duration = match sort {
+                SummarySort::Duration => job_duration(&job.meta_path),
+                _ => 0,
+            };
+            (outcome_group(job, failures_first), duration, job)
+        })
+        .collect();
+    ordered.sort_by(|a, b| {
+        let within = match sort {
+            SummarySort::Status => std::cmp::Ordering::Equal,
+            SummarySort::Duration => b.1.cmp(&a.1),
+            SummarySort::Name => a.2.name.cmp(&b.2.name),
+        };
+        a.0.cmp(&b.0).then(within)
+    });
+    ordered.into_iter().map(|(_, _, job)| job).collect()
+}
+
+/// `--summary-table`: one aligned row per job, failures first, then jobs
+/// still running, then successes; slowest first within each group. With
+/// `sorted` the rows keep the order of `jobs` (`--summary-sort`).
+fn emit_summary_table(jobs: &[&JobState], sorted: bool) {
+    struct Row {
+        rank: u8,
+        cells: [String; 5],
+        duration: i64,
+    }
+
+    let mut rows: Vec<Row> = jobs
+        .iter()
+        .map(|job| {
+            // The one-line record spares parsing the metadata of finished
+            // jobs.
+            let quick = if job.ready {
+                None
+            } else {
+                QuickStatus::read(&job.meta_path.with_extension("result"))
+            };
+            let meta = quick.is_none().then(|| read_meta(&job.meta_path)).flatten();
+            let (rank, status) = if job.ready {
+                (1, "ready")
+            } else if job.failure().is_some() {
+                (0, "failed")
             } else {
-                0
+                (2, "ok")
+            };
+            let duration = match &quick {
+                Some(quick) => quick.wall_secs as i64,
+                None => meta.as_ref().map_or(0, run_duration),
+            };
+            let exit = match job.exit_code {
+                Some(code) if !job.ready => crate::exit_status::describe(code),
+                _ => "-".into(),
+            };
+            let attempts = match quick {
+                Some(quick) => quick.attempts.to_string(),
+                None => meta
+                    .as_ref()
+                    .and_then(|m| m.get("attempt"))
+                    .and_then(|v| v.as_u64())
+                    .map_or_else(|| "-".into(), |a| a.to_string()),
+            };
+            Row {
+                rank,
+                cells: [
+                    job.name.clone(),
+                    status.into(),
+                    format!("{duration}s"),
+                    exit,
+                    attempts,
+                ],
+                duration,
             }
-        } else {
-            0
+        })
+        .collect();
+    if !sorted {
+        rows.sort_by(|a, b| a.rank.cmp(&b.rank).then(b.duration.cmp(&a.duration)));
+    }
+
+    let header = ["JOB", "STATUS", "DURATION", "EXIT", "ATTEMPTS"];
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(&row.cells) {
+            *w = (*w).max(cell.chars().count());
         }
-    } else {
-        0
+    }
+    let print_row = |cells: [&str; 5]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, w)| format!("{cell:<w$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+    print_row(header);
+    for row in &rows {
+        print_row(row.cells.each_ref().map(String::as_str));
+    }
+}
+
+/// How much of the end of `.err` `--stderr-tail` looks at.
+const STDERR_TAIL_BYTES: u64 = 64 * 1024;
+
+/// `--stderr-tail`: the last `lines` lines of the job's stderr, indented
+/// below its summary line – usually enough to tell why it failed.
+fn emit_stderr_tail(err_path: &std::path::Path, lines: usize) -> io::Result<()> {
+    let mut file = match File::open(err_path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(STDERR_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let mut tail: Vec<&str> = text.lines().rev().take(lines).collect();
+    // A line cut by the start of the window is not worth showing.
+    if start > 0 && tail.len() == text.lines().count() {
+        tail.pop();
+    }
+    for line in tail.iter().rev() {
+        println!("    {}", line.trim_end());
+    }
+    Ok(())
+}
+
+fn read_meta(path: &std::path::Path) -> Option<serde_json::Value> {
+    crate::version::parse_meta(&fs::read(path).ok()?).ok()
+}
+
+/// Whole seconds from `started` to `ended` (or to now while running).
+fn run_duration(meta: &serde_json::Value) -> i64 {
+    let time = |key: &str| {
+        meta.get(key)
+            .and_then(|v| v.as_str())
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&chrono::Utc))
     };
+    match (time("started"), time("ended")) {
+        (Some(start), end) => end
+            .unwrap_or_else(chrono::Utc::now)
+            .signed_duration_since(start)
+            .num_seconds()
+            .max(0),
+        (None, _) => 0,
+    }
+}
+
+/// Seconds the job ran, from its `.result` or else its metadata.
+fn job_duration(meta_path: &std::path::Path) -> i64 {
+    match QuickStatus::read(&meta_path.with_extension("result")) {
+        Some(quick) => quick.wall_secs as i64,
+        None => read_meta(meta_path).map_or(0, |m| run_duration(&m)),
+    }
+}
 
-    let symbol = if exit_code == 0 { "✓" } else { "✗" };
+pub(crate) fn emit_summary<P: AsRef<std::path::Path>>(
+    config: &Config,
+    job_name: &str,
+    exit_code: i32,
+    meta_path: P,
+) -> io::Result<()> {
+    let duration_secs = job_duration(meta_path.as_ref());
+
+    let symbol = if exit_code == 0 {
+        glyphs::ok(config.ascii)
+    } else {
+        glyphs::fail(config.ascii)
+    };
     println!(
-        "{} {} ({}s) – exit {}",
-        symbol, job_name, duration_secs, exit_code
+        "{} {} ({}s) {} exit {}",
+        symbol,
+        job_name,
+        duration_secs,
+        glyphs::dash(config.ascii),
+        crate::exit_status::describe(exit_code)
     );
     Ok(())
 }
@@ -0,0 +1,102 @@
+//! Implementation of `pend watch` – a detached `cargo-watch` for any command.
+//!
+//! The command runs as an ordinary job (so `pend wait`, `status` and `kill`
+//! work as usual) and is re-submitted whenever something below the watched
+//! paths changes. Bursts of events – an editor saving several files, a
+//! `git checkout` – are coalesced until the paths have been quiet for the
+//! debounce interval. A run still in progress is killed before the next one
+//! starts. `pend watch` itself stays in the foreground until interrupted.
+use notify::{RecursiveMode, Watcher};
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::error::PendError;
+use crate::status::{job_status, State};
+use crate::worker::JobOptions;
+
+/// How long to wait for a killed run to wind down before giving up.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Entry point for `pend watch <job> [--path P …] <cmd …>`.
+pub(crate) fn watch_job(
+    config: &Config,
+    job_name: &str,
+    cmd: &[OsString],
+    options: &JobOptions,
+    paths: &[PathBuf],
+    debounce: Duration,
+) -> io::Result<()> {
+    // Changes to our own artifacts must not trigger re-runs when the jobs
+    // directory lives below a watched path.
+    let jobs_root = crate::paths::jobs_root(config)?.canonicalize()?;
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if event.paths.iter().any(|p| !p.starts_with(&jobs_root)) {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .map_err(io::Error::other)?;
+    for path in paths {
+        // Canonical paths make event paths comparable with `jobs_root`.
+        let path = path.canonicalize().map_err(|e| {
+            io::Error::new(e.kind(), format!("cannot watch {}: {e}", path.display()))
+        })?;
+        watcher
+            .watch(&path, RecursiveMode::Recursive)
+            .map_err(|e| io::Error::other(format!("cannot watch {}: {e}", path.display())))?;
+    }
+
+    restart(config, job_name, cmd, options)?;
+    eprintln!(
+        "watching for changes {} press Ctrl-C to stop",
+        crate::glyphs::dash(config.ascii)
+    );
+
+    loop {
+        // Block until something changes, then let the burst settle.
+        if rx.recv().is_err() {
+            return Ok(());
+        }
+        while rx.recv_timeout(debounce).is_ok() {}
+
+        eprintln!(
+            "change detected {} restarting '{job_name}'",
+            crate::glyphs::dash(config.ascii)
+        );
+        restart(config, job_name, cmd, options)?;
+    }
+}
+
+/// Kill the current run of `job_name` (if any) and submit a fresh one.
+fn restart(
+    config: &Config,
+    job_name: &str,
+    cmd: &[OsString],
+    options: &JobOptions,
+) -> io::Result<()> {
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    if is_running(config, job_name) {
+        crate::kill::kill_job(config, job_name)?;
+    }
+    loop {
+        // The worker releases its lock shortly *after* writing `.exit`, so
+        // a submission can still bounce off the previous run for a moment.
+        match crate::job::do_job(config, job_name, cmd, options) {
+            Err(PendError::AlreadyRunning(_)) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            other => return other.map(drop).map_err(Into::into),
+        }
+    }
+}
+
+fn is_running(config: &Config, job_name: &str) -> bool {
+    job_status(config, job_name).is_ok_and(|s| s.state == State::Running)
+}
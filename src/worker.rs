@@ -6,36 +6,569 @@
 //! timeout and to retry failed attempts a configurable number of times.
 
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::process::{Command, Stdio};
-use std::sync::mpsc;
 use std::time::Duration;
 use wait_timeout::ChildExt;
 
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::error::PendError;
+use crate::frames::FrameWriter;
+use crate::hooks::Event;
 use crate::paths::JobPaths;
+use crate::rotate::{RotatingFile, RotationPolicy};
 
-/// Metadata written to `<job>.json` once the worker finishes.
-#[derive(Serialize)]
+/// Metadata written to `<job>.json`. A first version is persisted as soon as
+/// the worker starts (and refreshed whenever a new attempt is spawned) so that
+/// `pend status` and `pend kill` can locate the running processes; the final
+/// version written once the worker finishes adds `ended` and `exit_code`.
+#[derive(Serialize, Clone)]
 struct Meta<'a> {
+    /// Layout of this file (see `version.rs`).
+    schema: u32,
+    /// The pend release that wrote it.
+    pend_version: &'static str,
     job: &'a str,
+    /// Tells this run apart from others of the same name (see `job_id.rs`).
+    id: String,
     cmd: Vec<String>,
-    pid: u32,
+    /// PID of the user command of the current (or last) attempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    /// PID of the worker process itself, i.e. the holder of the `.lock`.
+    worker_pid: u32,
+    /// When the worker started (see `process::start_time`), telling it
+    /// apart from a later process that reuses its PID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    worker_started: Option<u64>,
+    /// Host the worker runs on; its PID means nothing elsewhere.
+    worker_host: String,
+    /// Effective user the command runs as (Unix).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    /// The `pend do` invocation that submitted the job (see `submitter.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    submitter: Option<crate::submitter::Submitter>,
+    /// Number of the current (or last) attempt, counting retries and
+    /// restarts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attempt: Option<u32>,
+    started: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ended: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    /// The unsigned 32-bit status as reported by Windows (`exit_code` holds
+    /// the same bits as `i32`, which turns NTSTATUS failures negative).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_exit_status: Option<u32>,
+    /// Resource limit (`memory`, `file-size`, `cpu-time`) that most likely
+    /// killed the last attempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_exceeded: Option<&'static str>,
+    /// Why the job failed (see `failure.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<crate::failure::Failure>,
+    /// Failed runs in a row of a recurring job (see `streak.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failing: Option<crate::streak::Streak>,
+    /// When the current (or last) attempt passed its readiness probe.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ready: Option<String>,
+    /// Seconds each attempt may run before it is killed (`--timeout`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<u64>,
+    /// Rotation threshold every attempt honours (`--max-log-size`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_log_size: Option<u64>,
+    /// Seconds of log output kept (`--log-ttl`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    log_ttl: Option<u64>,
+    /// Hex SHA-256 of everything the command wrote to stdout / stderr
+    /// (all attempts, rotated generations included).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    out_sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    err_sha256: Option<String>,
+    /// The streams read (`--capture`), unless both.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capture: Option<crate::capture::Capture>,
+    /// Bytes of stdout / stderr left out by `--max-out-size` /
+    /// `--max-err-size` (all attempts).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    out_dropped: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    err_dropped: Option<u64>,
+    /// Key under which `pend do --cache` may reuse this run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_key: Option<String>,
+    /// Free-text description (`--note`, `pend annotate`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    /// The submitter's own key/value pairs (`--meta`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    user_meta: BTreeMap<String, String>,
+    /// `pend wait` deletes the artifacts once it has replayed a success.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    ephemeral: bool,
+    /// `--expires-in`, and the time the artifacts expire once the job
+    /// finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_in: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<String>,
+    /// The command's environment, limited to the variables worth
+    /// recording (see `environ.rs`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    /// The unit taken of each `--slot`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    slots: BTreeMap<String, u32>,
+    /// The `--priority` the job queued for its slots with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<i32>,
+    /// Every attempt so far, oldest first, for `pend timeline`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attempts: Vec<AttemptRecord>,
+}
+
+/// One attempt as recorded in the metadata. `ended` and `exit_code` stay
+/// unset while it runs.
+#[derive(Debug, Clone, Serialize)]
+struct AttemptRecord {
     started: String,
-    ended: String,
+    /// When `--timeout` kills the attempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deadline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ready: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ended: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    /// The `--retry-cmd` a retry ran instead of the job's command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cmd: Option<String>,
+    /// Killed because it exceeded `--timeout`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    timed_out: bool,
+    /// Terminated by the `--stall-timeout` watchdog.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stalled: bool,
+}
+
+impl Meta<'_> {
+    fn write(&mut self, paths: &JobPaths) -> io::Result<()> {
+        // `pend annotate` edits the note on disk while we run.
+        if let Some(note) = crate::note::read(paths) {
+            self.note = note;
+        }
+        let json = serde_json::to_vec_pretty(self)?;
+        paths.write_meta(&json)
+    }
+}
+
+/// Per-job settings chosen on `pend do` (or `POST /jobs`) and handed to the
+/// worker.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct JobOptions {
+    /// Kill an attempt after this many seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<u64>,
+    /// Re-run a failed attempt up to this many times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) retries: Option<u32>,
+    /// Shell command that retries run instead of the job's command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) retry_cmd: Option<String>,
+    /// Niceness increment for the command (Unix); any positive value selects
+    /// `BELOW_NORMAL_PRIORITY_CLASS` on Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) nice: Option<i32>,
+    /// I/O scheduling class and level (Linux only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ionice: Option<crate::process::IoPriority>,
+    /// Address-space (Unix) or committed-memory (Windows) limit in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_mem: Option<u64>,
+    /// Largest file the command may write, in bytes (Unix only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_fsize: Option<u64>,
+    /// CPU time budget in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_cpu_time: Option<u64>,
+    /// Run the command inside this container image. Priority and resource
+    /// limits then apply to the engine's client process only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) container: Option<crate::container::Container>,
+    /// Run the command as this user (Unix only; requires privileges).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) user: Option<String>,
+    /// Keep the command running as a service (`--restart`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) restart: Option<RestartPolicy>,
+    /// Shell command that succeeds once the job is ready.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ready_cmd: Option<String>,
+    /// Local TCP port that accepts connections once the job is ready.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ready_port: Option<u16>,
+    /// Rotate `.out`, `.err` and `.log` before they exceed this many bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_log_size: Option<u64>,
+    /// Which streams the worker reads (`--capture`, see `capture.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) capture: Option<crate::capture::Capture>,
+    /// Bytes of stdout / stderr each attempt keeps (`--max-out-size`,
+    /// `--max-err-size`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_out_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_err_size: Option<u64>,
+    /// Rotated generations of `.out`, `.err` and `.log` to keep.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_log_files: Option<u32>,
+    /// Gzip rotated log generations.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) compress_logs: bool,
+    /// Keep only about this many seconds of log output (`--log-ttl`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) log_ttl: Option<u64>,
+    /// Frame `.log` with checksummed records (`--checksum-log`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) checksum_log: bool,
+    /// Mirror every output line to this system logger (`--log-to`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) log_to: Option<crate::system_log::LogTarget>,
+    /// Also write the output to this FIFO (`--mirror-fifo`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) mirror_fifo: Option<std::path::PathBuf>,
+    /// What a slow or absent FIFO reader misses (`--mirror-policy`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) mirror_policy: Option<crate::mirror::MirrorPolicy>,
+    /// Reuse a successful run with the same cache key that finished at most
+    /// this many seconds ago (`--cache`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cache: Option<u64>,
+    /// Environment variables whose values are part of the cache key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) cache_env: Vec<String>,
+    /// Seconds without output after which an attempt counts as stalled
+    /// (`--stall-timeout`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) stall_timeout: Option<u64>,
+    /// What happens to a stalled attempt (`--on-stall`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) on_stall: Option<crate::stall::StallAction>,
+    /// Jobs to wait for before the first attempt starts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) after: Vec<crate::ready::Dependency>,
+    /// Free-text description recorded in the metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) note: Option<String>,
+    /// `--meta` pairs recorded in the metadata.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) user_meta: BTreeMap<String, String>,
+    /// Have `pend wait` delete the artifacts of a successful run.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) ephemeral: bool,
+    /// Seconds after the job finished that its artifacts expire
+    /// (`--expires-in`, see `expire.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) expires_in: Option<u64>,
+    /// Start the command from a minimal environment (see `environ.rs`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) clean_env: bool,
+    /// Variables set for the command (`--env`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) env: Vec<(String, String)>,
+    /// The command is one line for the platform shell (`--shell`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) shell: bool,
+    /// Named slots of which to hold a unit while running (`--slot`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) slots: Vec<crate::slot::Slot>,
+    /// Place in the queue for `slots`, higher first (`--priority`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) priority: Option<i32>,
+    /// Most jobs of the jobs root running at once (`--max-jobs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_jobs: Option<u32>,
+    /// Wait for a place under `max_jobs` instead of failing (`--queue`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) queue: bool,
+}
+
+/// When a supervised command is started again after it exits:
+/// `on-failure[:MAX]` or `always[:MAX]`, `MAX` capping the number of
+/// restarts (unlimited by default).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) struct RestartPolicy {
+    always: bool,
+    max: Option<u32>,
+}
+
+impl RestartPolicy {
+    fn applies_to(&self, exit_code: i32) -> bool {
+        self.always || exit_code != 0
+    }
+}
+
+impl std::str::FromStr for RestartPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, max) = match s.split_once(':') {
+            Some((mode, max)) => (mode, Some(max)),
+            None => (s, None),
+        };
+        let always = match mode {
+            "on-failure" => false,
+            "always" => true,
+            other => {
+                return Err(format!(
+                    "unknown restart policy '{other}' (expected on-failure or always)"
+                ))
+            }
+        };
+        let max = max
+            .map(|m| {
+                m.parse::<u32>()
+                    .map_err(|_| format!("invalid restart count '{m}'"))
+            })
+            .transpose()?;
+        Ok(Self { always, max })
+    }
+}
+
+impl std::fmt::Display for RestartPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.always { "always" } else { "on-failure" })?;
+        match self.max {
+            Some(max) => write!(f, ":{max}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TryFrom<String> for RestartPolicy {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<RestartPolicy> for String {
+    fn from(p: RestartPolicy) -> Self {
+        p.to_string()
+    }
+}
+
+/// Delay before the first restart; doubles with every quick crash.
+const RESTART_BACKOFF_MIN: Duration = Duration::from_secs(1);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// An incarnation that ran at least this long resets the back-off.
+const RESTART_BACKOFF_RESET: Duration = Duration::from_secs(30);
+
+impl JobOptions {
+    /// Environment variable carrying the options from `pend do` to the worker
+    /// as JSON, keeping the hidden `worker` sub-command's arguments stable.
+    const ENV: &'static str = "PEND_JOB_OPTIONS";
+
+    /// The front-end's [`Config`] travels alongside the options, so the
+    /// worker runs with the same settings even when its flags came from the
+    /// command line rather than the environment.
+    fn export(&self, config: &Config, command: &mut Command) -> io::Result<()> {
+        #[derive(Serialize)]
+        struct Handoff<'a> {
+            config: &'a Config,
+            #[serde(flatten)]
+            options: &'a JobOptions,
+        }
+        let handoff = Handoff {
+            config,
+            options: self,
+        };
+        command.env(Self::ENV, serde_json::to_string(&handoff)?);
+        Ok(())
+    }
+
+    fn from_env() -> io::Result<Self> {
+        match std::env::var(Self::ENV) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// The [`Config`] exported by [`JobOptions::export`], if any.
+    fn config_from_env() -> Option<Config> {
+        #[derive(Deserialize)]
+        struct Handoff {
+            config: Option<Config>,
+        }
+        let json = std::env::var(Self::ENV).ok()?;
+        serde_json::from_str::<Handoff>(&json).ok()?.config
+    }
+
+    fn probe(&self) -> crate::ready::Probe {
+        crate::ready::Probe {
+            cmd: self.ready_cmd.clone(),
+            port: self.ready_port,
+        }
+    }
+
+    fn rotation(&self) -> RotationPolicy {
+        RotationPolicy {
+            max_size: self.max_log_size,
+            // Enough generations to cover the TTL.
+            keep: self.max_log_files.unwrap_or(match self.log_ttl {
+                Some(_) => crate::rotate::TTL_SLICES,
+                None => 1,
+            }),
+            compress: self.compress_logs,
+            ttl: self.log_ttl.map(Duration::from_secs),
+        }
+    }
+
+    fn limits(&self) -> crate::process::ResourceLimits {
+        crate::process::ResourceLimits {
+            max_mem: self.max_mem,
+            max_fsize: self.max_fsize,
+            max_cpu_time: self.max_cpu_time,
+        }
+    }
+}
+
+/// Name of the user the command will run as, for the metadata.
+fn command_user(options: &JobOptions) -> Option<String> {
+    #[cfg(unix)]
+    {
+        match &options.user {
+            Some(name) => crate::process::lookup_user(name).ok().map(|u| u.name),
+            None => crate::process::current_user_name(),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = options;
+        None
+    }
+}
+
+/// State of the captured streams carried from one attempt to the next.
+#[derive(Default)]
+struct Output {
+    /// Later attempts append to the artifacts of earlier ones.
+    appending: bool,
+    /// The job's ID, heading every generation of `.idx`.
+    job_id: String,
+    /// `--mirror-fifo`, kept open across attempts.
+    mirror: Option<std::sync::Arc<std::sync::Mutex<crate::mirror::Mirror>>>,
+    /// Running SHA-256 of everything written to `.out` and `.err`.
+    digests: [Sha256; 2],
+    /// Bytes of stdout and stderr the size limits left out.
+    dropped: [u64; 2],
+    /// Clients attached through the control socket (`pend attach`).
+    tap: Option<crate::control::Tap>,
+}
+
+/// Bytes a reader thread takes from a pipe at once. Logs rotate between
+/// reads, so this is also how far a log may overshoot `--max-log-size`.
+const READ_CHUNK: usize = 8 * 1024;
+
+/// The combined `.log` and everything fed along with it. Both reader
+/// threads append to it in turn, so a chunk is written where it was read,
+/// without being copied into a message for a writer thread.
+struct LogSink {
+    log: RotatingFile,
+    /// Stderr ranges of `log`.
+    idx: File,
+    idx_path: std::path::PathBuf,
+    idx_header: String,
+    frames: Option<FrameWriter>,
+    forwarder: Option<crate::system_log::Forwarder>,
+    mirror: Option<std::sync::Arc<std::sync::Mutex<crate::mirror::Mirror>>>,
+    tap: Option<crate::control::Tap>,
+}
+
+impl LogSink {
+    fn append(&mut self, is_stderr: bool, chunk: &[u8]) -> io::Result<()> {
+        if self.log.make_room(chunk.len() as u64)? {
+            self.idx = File::create(&self.idx_path)?;
+            self.idx.write_all(self.idx_header.as_bytes())?;
+            if let Some(frames) = &mut self.frames {
+                frames.restart()?;
+            }
+        }
+        // The index entry goes first: a reader that sees the bytes in the log
+        // must also find out which stream they belong to. The frame goes
+        // last, vouching for bytes already written.
+        let offset = self.log.len();
+        if is_stderr {
+            writeln!(self.idx, "{} {}", offset, chunk.len())?;
+        }
+        self.log.write_all(chunk)?;
+        if let Some(frames) = &mut self.frames {
+            frames.record(offset, chunk)?;
+        }
+        if let Some(forwarder) = &mut self.forwarder {
+            forwarder.feed(is_stderr, chunk);
+        }
+        if let Some(mirror) = &self.mirror {
+            mirror.lock().unwrap().write(chunk);
+        }
+        if let Some(tap) = &self.tap {
+            tap.send(is_stderr, chunk);
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) {
+        if let Some(forwarder) = &mut self.forwarder {
+            forwarder.finish();
+        }
+    }
+}
+
+/// Outcome of a single attempt.
+struct Attempt {
     exit_code: i32,
+    runtime: Duration,
+    ended: chrono::DateTime<Utc>,
+    pid: u32,
+    limit_exceeded: Option<&'static str>,
+    timed_out: bool,
+    /// Signal that killed the command (Unix).
+    signal: Option<i32>,
+    /// The kernel's OOM killer ended the command (Linux).
+    oom_killed: bool,
+    /// The `--stall-timeout` watchdog terminated the attempt.
+    stalled: bool,
+    /// The command could not be started (exit code 127).
+    spawn_failed: bool,
 }
 
 /// Spawn a *detached* background worker process responsible for running the
 /// actual command and recording artifacts. Front-end helper called by
 /// `pend do`.
 pub(crate) fn spawn_worker(
+    config: &Config,
     job_name: &str,
-    cmd: &[String],
-    timeout: Option<u64>,
-    retries: Option<u32>,
-) -> io::Result<()> {
+    cmd: &[OsString],
+    options: &JobOptions,
+    job_id: &str,
+    failing: Option<&crate::streak::Streak>,
+    lock_handoff: Option<&str>,
+) -> io::Result<u32> {
     let exe_path = std::env::current_exe()?;
 
     let mut worker_cmd = Command::new(&exe_path);
@@ -45,224 +578,455 @@ pub(crate) fn spawn_worker(
     // Pass optional runtime configuration via environment variables so the
     // command-line surface of the hidden `worker` sub-command remains
     // stable.
-    if let Some(t) = timeout {
-        worker_cmd.env("PEND_TIMEOUT", t.to_string());
-    }
-    if let Some(r) = retries {
-        worker_cmd.env("PEND_RETRIES", r.to_string());
+    options.export(config, &mut worker_cmd)?;
+    crate::job_id::export(&mut worker_cmd, job_id);
+    crate::submitter::export(&mut worker_cmd);
+    crate::streak::export(&mut worker_cmd, failing);
+    crate::version::export(&mut worker_cmd);
+    config.export(&mut worker_cmd);
+    crate::diag::export(&mut worker_cmd);
+    if let Some(token) = lock_handoff {
+        worker_cmd.env("PEND_LOCK_HANDOFF", token);
     }
 
     // Detach from controlling terminal so that the worker survives even when
     // the parent exits.
-    #[cfg(unix)]
-    {
-        use std::os::unix::process::CommandExt;
-        unsafe {
-            worker_cmd.pre_exec(|| {
-                libc::setsid();
-                Ok(())
-            });
-        }
-    }
+    crate::process::detach(&mut worker_cmd);
 
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
-        worker_cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
-    }
-
-    worker_cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
-    worker_cmd.spawn()?;
-    Ok(())
+    worker_cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    Ok(worker_cmd.spawn()?.id())
 }
 
 /// Entry point executed by the hidden `worker` sub-command. Never called by
 /// end users.
-pub(crate) fn run_worker(job_name: &str, cmd: &[String]) -> io::Result<()> {
+pub(crate) fn run_worker(
+    config: &Config,
+    job_name: &str,
+    cmd: &[OsString],
+) -> crate::error::Result<()> {
+    // Settings of the `pend do` that started us; ours are only a fallback
+    // for a front-end of a release that does not hand them over.
+    let handed_over = match crate::version::frontend_mismatch() {
+        Some(_) => None,
+        None => JobOptions::config_from_env(),
+    };
+    let config = &handed_over.unwrap_or_else(|| config.clone());
+
     // ---------------------------------------------------------------------
     // Resolve paths and obtain an exclusive file lock for the duration of
     // the worker. This guarantees *exactly one* worker per job name.
     // ---------------------------------------------------------------------
-    let paths = JobPaths::new(job_name)?;
-
-    use fs2::FileExt;
-    let lock_file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(false)
-        .open(&paths.lock)?;
-    if let Err(err) = lock_file.try_lock_exclusive() {
-        if err.kind() == io::ErrorKind::WouldBlock {
-            return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!("job '{job_name}' is already running"),
-            ));
-        } else {
-            return Err(err);
+    let paths = JobPaths::new(config, job_name)?;
+    crate::diag::init_worker(&paths);
+
+    // The `pend do` front-end still holds the lock while it spawns us and
+    // only releases it when it exits, so retry for a short while before
+    // concluding that another worker owns the job.
+    let lock_deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let handoff = std::env::var("PEND_LOCK_HANDOFF").ok();
+    let lock_file = loop {
+        if let Some(lock) = crate::lock::JobLock::adopt(&paths.lock, handoff.as_deref())? {
+            break lock;
         }
-    }
+        if std::time::Instant::now() >= lock_deadline {
+            return Err(PendError::AlreadyRunning(job_name.to_string()));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
 
-    // Runtime configuration propagated from the front-end.
-    let timeout_secs = std::env::var("PEND_TIMEOUT").ok().and_then(|v| v.parse::<u64>().ok());
-    let mut retries_left: u32 = std::env::var("PEND_RETRIES")
-        .ok()
-        .and_then(|v| v.parse::<u32>().ok())
-        .unwrap_or(0);
+    // Runtime configuration propagated from the front-end, unless that
+    // front-end is of a release whose options we may not understand.
+    let incompatible = crate::version::frontend_mismatch();
+    let mut options = match incompatible {
+        Some(_) => JobOptions::default(),
+        None => JobOptions::from_env()?,
+    };
+    let mut retries_left: u32 = options.retries.unwrap_or(0);
 
     // ---------------------------------------------------------------------
     // Helper executing *one* attempt of the user command.
     // ---------------------------------------------------------------------
     fn run_once(
-        cmd: &[String],
+        job_name: &str,
+        cmd: &[OsString],
         paths: &JobPaths,
-        timeout_secs: Option<u64>,
-        append: bool,
-    ) -> io::Result<(i32, chrono::DateTime<Utc>, chrono::DateTime<Utc>, u32)> {
+        options: &JobOptions,
+        output: &mut Output,
+        on_spawn: &dyn Fn(u32),
+        on_ready: &(dyn Fn() + Sync),
+    ) -> io::Result<Attempt> {
         // Open per-stream artifact files.
-        let open_mode = |p: &std::path::Path, append: bool| -> io::Result<File> {
-            let mut opts = OpenOptions::new();
-            opts.create(true);
-            if append {
-                opts.append(true);
-            } else {
-                opts.write(true).truncate(true);
-            }
-            opts.open(p)
-        };
-
-        let out_file = open_mode(&paths.out, append)?;
-        let err_file = open_mode(&paths.err, append)?;
+        let append = output.appending;
+        let policy = options.rotation();
+        let out_file = RotatingFile::open(&paths.out, append, policy)?;
+        let err_file = RotatingFile::open(&paths.err, append, policy)?;
 
-        // Combined log file and rotation support.
-        let mut log_file = open_mode(&paths.log, append)?;
+        // Combined log file and its stream index, rotated together. The
+        // frames exist before the log so a reader never mistakes a
+        // checksummed log for a plain one.
+        let mut frames = if options.checksum_log {
+            Some(FrameWriter::open(&paths.frames, append)?)
+        } else {
+            None
+        };
+        let mut log_file = RotatingFile::open(&paths.log, append, policy)?;
         if append {
-            let _ = writeln!(log_file, "\n-- retry --\n");
-        }
-
-        let max_log_size = std::env::var("PEND_MAX_LOG_SIZE")
-            .ok()
-            .and_then(|v| v.parse::<u64>().ok());
-
-        let log_path_clone = paths.log.clone();
-        let (tx, rx) = mpsc::channel::<Vec<u8>>();
-
-        let writer_handle = std::thread::spawn(move || -> io::Result<()> {
-            let mut current_len = log_file.metadata().map(|m| m.len()).unwrap_or(0);
-            while let Ok(chunk) = rx.recv() {
-                if let Some(limit) = max_log_size {
-                    if current_len + chunk.len() as u64 > limit {
-                        let rotated = log_path_clone.with_file_name(format!(
-                            "{}.1",
-                            log_path_clone.file_name().unwrap().to_string_lossy()
-                        ));
-                        let _ = fs::rename(&log_path_clone, &rotated);
-                        log_file = OpenOptions::new()
-                            .create(true)
-                            .write(true)
-                            .truncate(true)
-                            .open(&log_path_clone)?;
-                        current_len = 0;
-                    }
+            let marker = b"\n-- retry --\n\n";
+            let offset = log_file.len();
+            if log_file.write_all(marker).is_ok() {
+                if let Some(frames) = &mut frames {
+                    let _ = frames.record(offset, marker);
                 }
-                log_file.write_all(&chunk)?;
-                current_len += chunk.len() as u64;
             }
-            Ok(())
+        }
+        let idx_file = if append {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&paths.idx)?
+        } else {
+            let mut idx = File::create(&paths.idx)?;
+            idx.write_all(crate::job_id::idx_header(&output.job_id).as_bytes())?;
+            idx
+        };
+
+        let forwarder = options.log_to.and_then(|target| {
+            crate::system_log::Forwarder::open(target, job_name)
+                .inspect_err(|err| tracing::warn!(%err, "cannot reach the system logger"))
+                .ok()
         });
 
-        // Spawn child process.
-        let started = Utc::now();
-        let mut child = Command::new(&cmd[0])
-            .args(&cmd[1..])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
+        if let (None, Some(path)) = (&output.mirror, &options.mirror_fifo) {
+            let policy = options.mirror_policy.unwrap_or_default();
+            output.mirror = Some(std::sync::Arc::new(std::sync::Mutex::new(
+                crate::mirror::Mirror::new(path.clone(), policy),
+            )));
+        }
+        let sink = std::sync::Arc::new(std::sync::Mutex::new(LogSink {
+            log: log_file,
+            idx: idx_file,
+            idx_path: paths.idx.clone(),
+            idx_header: crate::job_id::idx_header(&output.job_id),
+            frames,
+            forwarder,
+            mirror: output.mirror.clone(),
+            tap: output.tap.clone(),
+        }));
 
-        let stdout_pipe = child.stdout.take().ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "failed to capture stdout")
-        })?;
-        let stderr_pipe = child.stderr.take().ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "failed to capture stderr")
-        })?;
+        // Spawn child process – wrapped in `<engine> run` for container jobs.
+        let container = options
+            .container
+            .as_ref()
+            .map(|c| (c, crate::container::container_name(job_name)));
+        let mut command = match (&container, options.shell) {
+            // Containers run Linux images, whatever the host.
+            (Some((c, name)), true) => {
+                let line = ["sh".into(), "-c".into(), cmd.join(OsStr::new(" "))];
+                crate::shell::command(&c.wrap(name, &line)?)
+            }
+            (Some((c, name)), false) => crate::shell::command(&c.wrap(name, cmd)?),
+            (None, true) => crate::shell::shell(&cmd.join(OsStr::new(" "))),
+            (None, false) => crate::shell::command(cmd),
+        };
+        crate::environ::apply(&mut command, options);
+        let capture = options.capture.unwrap_or_default();
+        command
+            .env(crate::report::ENV, &paths.progress)
+            .stdout(match capture.stdout() {
+                true => Stdio::piped(),
+                false => Stdio::null(),
+            })
+            .stderr(match capture.stderr() {
+                true => Stdio::piped(),
+                false => Stdio::null(),
+            });
+        // Progress of an earlier attempt does not carry over.
+        let _ = fs::remove_file(&paths.progress);
+
+        // Place the command in its own process group so `pend kill` can
+        // terminate the whole tree it spawned, not just the direct child.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+
+        // Lower the CPU / I/O priority before the command starts so that not
+        // even its first instructions compete with interactive work.
+        crate::process::set_priority(&mut command, options.nice, options.ionice);
+        let limits = options.limits();
+        #[cfg(unix)]
+        crate::process::set_limits(&mut command, &limits);
+
+        // Dropping privileges must come last (see `set_user`).
+        #[cfg(unix)]
+        if let Some(name) = &options.user {
+            crate::process::set_user(&mut command, &crate::process::lookup_user(name)?);
+        }
+
+        // Windows has no process groups; a Job Object plays that role and
+        // also lets timeouts take down grandchildren. Processes the child
+        // starts before it is assigned escape the job, which is the best we
+        // can do without spawning it suspended.
+        #[cfg(windows)]
+        let job_object = crate::process::JobObject::create(paths, &limits).ok();
+
+        let oom = crate::failure::OomWatch::start();
+        let spawned_at = std::time::Instant::now();
+        let mut child = command.spawn()?;
+        #[cfg(windows)]
+        if let Some(job) = &job_object {
+            let _ = job.assign(&child);
+        }
+        tracing::debug!(job = job_name, pid = child.id(), "attempt started");
+        on_spawn(child.id());
+
+        // A `pend kill` racing with the spawn may have missed the new PID –
+        // honour its request ourselves.
+        if paths.cancel.exists() {
+            crate::process::terminate_process_tree(child.id());
+        }
+
+        // A stream not captured reads as empty.
+        let stdout_pipe: Box<dyn Read + Send> = match capture.stdout() {
+            true => Box::new(
+                child
+                    .stdout
+                    .take()
+                    .ok_or_else(|| io::Error::other("failed to capture stdout"))?,
+            ),
+            false => Box::new(io::empty()),
+        };
+        let stderr_pipe: Box<dyn Read + Send> = match capture.stderr() {
+            true => Box::new(
+                child
+                    .stderr
+                    .take()
+                    .ok_or_else(|| io::Error::other("failed to capture stderr"))?,
+            ),
+            false => Box::new(io::empty()),
+        };
+
+        /// One stream's artifact, its running digest and what an attempt
+        /// may still keep of it.
+        struct Stream {
+            dest: RotatingFile,
+            digest: Sha256,
+            budget: crate::capture::Budget,
+            is_stderr: bool,
+        }
 
         // Reader helper feeding per-stream artifacts *and* combined log.
         fn spawn_reader<R: Read + Send + 'static>(
-            reader: R,
-            mut dest: File,
-            tx: mpsc::Sender<Vec<u8>>,
-        ) -> std::thread::JoinHandle<io::Result<()>> {
+            mut reader: R,
+            mut stream: Stream,
+            sink: std::sync::Arc<std::sync::Mutex<LogSink>>,
+            progress: std::path::PathBuf,
+            activity: std::sync::Arc<crate::stall::Activity>,
+        ) -> std::thread::JoinHandle<io::Result<(Sha256, u64)>> {
             std::thread::spawn(move || {
-                let mut buf = std::io::BufReader::new(reader);
-                let mut chunk = [0u8; 8192];
+                // One buffer for the whole attempt: every chunk is read once
+                // and written from here.
+                let mut chunk = vec![0u8; READ_CHUNK];
+                let mut scanner = crate::report::Scanner::default();
                 loop {
-                    let n = match buf.read(&mut chunk) {
+                    let n = match reader.read(&mut chunk) {
                         Ok(0) => break,
                         Ok(n) => n,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
                         Err(e) => return Err(e),
                     };
-                    dest.write_all(&chunk[..n])?;
-                    let _ = tx.send(chunk[..n].to_vec());
+                    let chunk = &chunk[..n];
+                    activity.touch();
+                    let kept = stream.budget.take(chunk);
+                    if !kept.is_empty() {
+                        stream.dest.make_room(kept.len() as u64)?;
+                        stream.dest.write_all(kept)?;
+                        stream.digest.update(kept);
+                        sink.lock().unwrap().append(stream.is_stderr, kept)?;
+                    }
+                    if let Some(percent) = scanner.feed(chunk) {
+                        let _ = crate::paths::replace_file(
+                            &progress,
+                            format!("{percent}%\n").as_bytes(),
+                        );
+                    }
                 }
-                Ok(())
+                Ok((stream.digest, stream.budget.dropped))
             })
         }
 
-        let r1 = spawn_reader(stdout_pipe, out_file, tx.clone());
-        let r2 = spawn_reader(stderr_pipe, err_file, tx);
+        let [out_digest, err_digest] = output.digests.clone();
+        let activity = crate::stall::Activity::new();
+        let r1 = spawn_reader(
+            stdout_pipe,
+            Stream {
+                dest: out_file,
+                digest: out_digest,
+                budget: crate::capture::Budget::new(options.max_out_size),
+                is_stderr: false,
+            },
+            sink.clone(),
+            paths.progress.clone(),
+            activity.clone(),
+        );
+        let r2 = spawn_reader(
+            stderr_pipe,
+            Stream {
+                dest: err_file,
+                digest: err_digest,
+                budget: crate::capture::Budget::new(options.max_err_size),
+                is_stderr: true,
+            },
+            sink.clone(),
+            paths.progress.clone(),
+            activity.clone(),
+        );
 
-        // Wait with optional timeout.
-        let status = if let Some(secs) = timeout_secs {
-            match child.wait_timeout(Duration::from_secs(secs))? {
-                Some(s) => s,
-                None => {
-                    let _ = child.kill();
-                    child.wait()?
-                }
+        // Also watches the pipes after the command exited: something it
+        // started may keep them open.
+        let watchdog = options.stall_timeout.map(|secs| {
+            let pid = child.id();
+            let engine = container
+                .as_ref()
+                .map(|(c, name)| ((*c).clone(), name.clone()));
+            #[cfg(windows)]
+            let job_paths = paths.clone();
+            crate::stall::Watchdog::start(
+                job_name,
+                paths,
+                Duration::from_secs(secs),
+                options.on_stall.unwrap_or_default(),
+                activity,
+                Box::new(move || {
+                    if let Some((c, name)) = &engine {
+                        c.kill(name);
+                    }
+                    #[cfg(windows)]
+                    if crate::process::JobObject::open(&job_paths)
+                        .is_some_and(|job| job.terminate(1))
+                    {
+                        return;
+                    }
+                    crate::process::terminate_process_tree(pid);
+                }),
+            )
+        });
+
+        // Probe for readiness while the attempt runs; a job without a probe
+        // is ready as soon as it has been spawned.
+        let probe = options.probe();
+        let probe_stop = std::sync::atomic::AtomicBool::new(false);
+        let mut timed_out = false;
+        let status = std::thread::scope(|scope| {
+            if probe.is_empty() {
+                on_ready();
+            } else {
+                scope.spawn(|| {
+                    if probe.wait_until_ready(&probe_stop) {
+                        on_ready();
+                    }
+                });
             }
-        } else {
-            child.wait()?
-        };
+
+            // Wait with optional timeout.
+            let status = if let Some(secs) = options.timeout {
+                match child.wait_timeout(Duration::from_secs(secs)) {
+                    Ok(Some(s)) => Ok(s),
+                    Ok(None) => {
+                        timed_out = true;
+                        if let Some((c, name)) = &container {
+                            c.kill(name);
+                        }
+                        #[cfg(windows)]
+                        if let Some(job) = &job_object {
+                            job.terminate(1);
+                        }
+                        // The command itself is killed outright (exit 137),
+                        // then the rest of its group: grandchildren would
+                        // otherwise keep the pipes open and the readers
+                        // waiting.
+                        let _ = child.kill();
+                        crate::process::terminate_process_tree(child.id());
+                        child.wait()
+                    }
+                    Err(e) => Err(e),
+                }
+            } else {
+                child.wait()
+            };
+            probe_stop.store(true, std::sync::atomic::Ordering::Relaxed);
+            status
+        })?;
 
         // Join helper threads.
-        for h in [r1, r2] {
-            match h.join() {
-                Ok(res) => res?,
-                Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "reader thread panicked")),
+        let joined: Vec<_> = [r1, r2].into_iter().map(|h| h.join()).collect();
+        let stalled = watchdog.is_some_and(|watchdog| watchdog.stop());
+        for (i, res) in joined.into_iter().enumerate() {
+            match res {
+                Ok(res) => {
+                    let (digest, dropped) = res?;
+                    output.digests[i] = digest;
+                    output.dropped[i] += dropped;
+                }
+                Err(_) => return Err(io::Error::other("reader thread panicked")),
             }
         }
+        output.appending = true;
 
-        match writer_handle.join() {
-            Ok(res) => res?,
-            Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "writer thread panicked")),
+        // Both readers are gone, and with them the other handles.
+        if let Some(sink) = std::sync::Arc::into_inner(sink) {
+            sink.into_inner()
+                .map_err(|_| io::Error::other("reader thread panicked"))?
+                .finish();
         }
 
         let ended = Utc::now();
+        tracing::debug!(job = job_name, ?status, "attempt exited");
 
-        #[cfg(unix)]
-        use std::os::unix::process::ExitStatusExt;
+        // Windows always reports a code – the full 32-bit `DWORD`, carried
+        // bit-for-bit in the `i32` (see `exit_status.rs`). Unix reports
+        // either a code or the terminating signal.
+        #[cfg(windows)]
+        let (exit_code, limit_exceeded, signal) = {
+            let code = status.code().unwrap_or(1);
+            let limit = job_object
+                .as_ref()
+                .and_then(|job| job.limit_exceeded(&limits, code));
+            (code, limit, None)
+        };
 
-        let mut exit_code = 1;
         #[cfg(unix)]
-        let mut terminated_signal: Option<i32> = None;
-
-        match status.code() {
-            Some(c) => exit_code = c,
-            None => {
-                #[cfg(unix)]
-                if let Some(sig) = status.signal() {
-                    terminated_signal = Some(sig);
-                    exit_code = 128 + sig;
+        let (exit_code, limit_exceeded, signal) = {
+            use std::os::unix::process::ExitStatusExt;
+
+            match (status.code(), status.signal()) {
+                // Shells report a child killed by a signal as `128 + n`.
+                (Some(c), _) if c > 128 => (c, limits.exceeded_by_signal(c - 128), None),
+                (Some(c), _) => (c, None, None),
+                (None, Some(sig)) => {
+                    let _ = fs::write(&paths.signal, format!("{}\n", sig));
+                    (128 + sig, limits.exceeded_by_signal(sig), Some(sig))
                 }
+                (None, None) => (1, None, None),
             }
-        }
-
-        #[cfg(unix)]
-        if let Some(sig) = terminated_signal {
-            let _ = fs::write(&paths.signal, format!("{}\n", sig));
-        }
+        };
 
-        Ok((exit_code, started, ended, child.id()))
+        Ok(Attempt {
+            exit_code,
+            runtime: spawned_at.elapsed(),
+            ended,
+            pid: child.id(),
+            limit_exceeded,
+            timed_out,
+            signal,
+            oom_killed: oom.killed(signal),
+            stalled,
+            spawn_failed: false,
+        })
     }
 
     // ------------------------------------------------------------------
@@ -275,16 +1039,200 @@ pub(crate) fn run_worker(job_name: &str, cmd: &[String]) -> io::Result<()> {
     // convert *any* error into an artificial non-zero exit code and ensure we
     // persist the usual artifacts so other commands observe a completed job.
 
-    let first_attempt = run_once(cmd, &paths, timeout_secs, false);
+    let worker_pid = std::process::id();
+    let first_started = Utc::now();
+    let previous_streak = crate::streak::from_env();
+    let job_id = crate::job_id::from_env(job_name);
+    let mut meta = Meta {
+        schema: crate::version::SCHEMA,
+        pend_version: crate::version::VERSION,
+        job: job_name,
+        id: job_id.clone(),
+        cmd: cmd
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect(),
+        pid: None,
+        worker_pid,
+        worker_started: crate::process::start_time(worker_pid),
+        worker_host: crate::lock::hostname(),
+        user: command_user(&options),
+        submitter: crate::submitter::from_env(),
+        attempt: None,
+        started: first_started.to_rfc3339(),
+        ended: None,
+        exit_code: None,
+        raw_exit_status: None,
+        limit_exceeded: None,
+        failure: None,
+        failing: None,
+        ready: None,
+        timeout: options.timeout,
+        max_log_size: options.max_log_size,
+        log_ttl: options.log_ttl,
+        out_sha256: None,
+        err_sha256: None,
+        capture: options
+            .capture
+            .filter(|&capture| capture != crate::capture::Capture::Both),
+        out_dropped: None,
+        err_dropped: None,
+        cache_key: match options.cache {
+            Some(_) => Some(crate::cache::cache_key(cmd, &options)?),
+            None => None,
+        },
+        note: options.note.clone(),
+        user_meta: options.user_meta.clone(),
+        ephemeral: options.ephemeral,
+        expires_in: options.expires_in,
+        expires: None,
+        env: crate::environ::recorded(&options),
+        slots: BTreeMap::new(),
+        priority: options.priority,
+        attempts: Vec::new(),
+    };
+    meta.write(&paths)?;
+    let _ = paths.write_status(if options.after.is_empty() {
+        "starting"
+    } else {
+        "waiting for dependencies"
+    });
+
+    // Serve `status` / `kill` / `wait` / `attach` requests over the control
+    // socket while we run (see `control.rs`).
+    let control = crate::control::ControlServer::start(job_name, &paths);
+    let attempt = std::cell::Cell::new(0u32);
 
-    let (
-        mut final_exit_code,
-        first_started,
-        mut last_ended,
-        mut final_pid,
-    ) = match first_attempt {
-        Ok(tuple) => tuple,
+    // Refresh the live metadata whenever a new attempt starts so the
+    // recorded PID always refers to the currently running command. A new
+    // attempt has to pass the readiness probe again.
+    let live = std::sync::Mutex::new(meta.clone());
+    // Attempts a job without restart policy makes at most.
+    let max_attempts = options
+        .retries
+        .filter(|_| options.restart.is_none())
+        .map(|retries| retries + 1);
+    let timeout = options.timeout;
+    // Set while a retry runs `--retry-cmd`.
+    let retry_cmd = options.retry_cmd.clone();
+    let running_retry_cmd = std::cell::Cell::new(false);
+    let record_pid = |pid: u32| {
+        attempt.set(attempt.get() + 1);
+        let _ = paths.write_status(&match (attempt.get(), max_attempts) {
+            (1, None | Some(1)) => "running".to_string(),
+            (n, Some(max)) => format!("running attempt {n}/{max}"),
+            (n, None) => format!("running attempt {n}"),
+        });
+        if let Some(control) = &control {
+            control.set_attempt(attempt.get(), pid);
+        }
+        let mut live = live.lock().unwrap();
+        live.pid = Some(pid);
+        live.attempt = Some(attempt.get());
+        live.ready = None;
+        let now = Utc::now();
+        live.attempts.push(AttemptRecord {
+            started: now.to_rfc3339(),
+            deadline: timeout
+                .map(|secs| (now + chrono::TimeDelta::seconds(secs as i64)).to_rfc3339()),
+            ready: None,
+            ended: None,
+            exit_code: None,
+            cmd: retry_cmd.clone().filter(|_| running_retry_cmd.get()),
+            timed_out: false,
+            stalled: false,
+        });
+        let _ = live.write(&paths);
+    };
+    let has_probe = !options.probe().is_empty();
+    let record_ready = || {
+        tracing::debug!(job = job_name, "readiness probe passed");
+        let mut live = live.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        // Without a probe every attempt is ready at once; not worth a record.
+        if let Some(record) = live.attempts.last_mut().filter(|_| has_probe) {
+            record.ready = Some(now.clone());
+        }
+        live.ready = Some(now);
+        let _ = live.write(&paths);
+    };
+    // An attempt that failed before its command started never reached
+    // `record_pid`.
+    let record_unspawned = || {
+        attempt.set(attempt.get() + 1);
+        let mut live = live.lock().unwrap();
+        live.attempts.push(AttemptRecord {
+            started: Utc::now().to_rfc3339(),
+            deadline: None,
+            ready: None,
+            ended: None,
+            exit_code: None,
+            cmd: retry_cmd.clone().filter(|_| running_retry_cmd.get()),
+            timed_out: false,
+            stalled: false,
+        });
+    };
+    let record_end = |last: &Attempt| {
+        let mut live = live.lock().unwrap();
+        if let Some(record) = live.attempts.last_mut() {
+            record.ended = Some(last.ended.to_rfc3339());
+            record.exit_code = Some(last.exit_code);
+            record.timed_out = last.timed_out;
+            record.stalled = last.stalled;
+        }
+        let _ = live.write(&paths);
+    };
+
+    let hooks = crate::hooks::Hooks::locate(config);
+    let mut output = Output {
+        job_id,
+        tap: control.as_ref().map(|control| control.tap()),
+        ..Output::default()
+    };
+    let mut held_slots = crate::slot::Held::default();
+    let first_attempt = if let Some(reason) = incompatible {
+        Err(io::Error::new(io::ErrorKind::Unsupported, reason))
+    } else if paths.cancel.exists() {
+        Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "job was killed before it started",
+        ))
+    } else {
+        crate::ready::wait_for_dependencies(config, &options.after, &paths).and_then(|()| {
+            held_slots = crate::slot::acquire(
+                config,
+                &options.slots,
+                options.priority.unwrap_or(0),
+                job_name,
+                &paths,
+            )?;
+            if !options.slots.is_empty() {
+                held_slots.assign_device(&mut options);
+                let mut live = live.lock().unwrap();
+                live.slots = held_slots.indices();
+                live.env = crate::environ::recorded(&options);
+                let _ = live.write(&paths);
+            }
+            hooks.run(Event::PreStart, job_name, &paths, 1, None);
+            run_once(
+                job_name,
+                cmd,
+                &paths,
+                &options,
+                &mut output,
+                &record_pid,
+                &record_ready,
+            )
+        })
+    };
+
+    let mut last = match first_attempt {
+        Ok(attempt) => {
+            record_end(&attempt);
+            attempt
+        }
         Err(err) => {
+            tracing::debug!(job = job_name, %err, "job failed to start");
             // Record the failure so that `pend wait` sees the job as
             // finished. We deliberately choose exit code 127 which is widely
             // used for *command not found* on Unix shells and unambiguously
@@ -295,54 +1243,305 @@ pub(crate) fn run_worker(job_name: &str, cmd: &[String]) -> io::Result<()> {
             // Best-effort: write diagnostic message to `.err` / `.log` so
             // users can inspect what went wrong later. Ignore I/O problems
             // here – the crucial piece is the `.exit` file.
-            let _ = std::fs::write(&paths.err, format!("{}\n", err));
-            let _ = std::fs::write(&paths.log, format!("{}\n", err));
+            let _ = paths.write_status("failed to start");
+            let msg = format!("{}\n", err);
+            let _ = std::fs::write(&paths.err, &msg);
+            let _ = std::fs::write(
+                &paths.idx,
+                format!("{}0 {}\n", crate::job_id::idx_header(&meta.id), msg.len()),
+            );
+            let _ = std::fs::write(&paths.log, &msg);
+            if options.checksum_log {
+                let _ = std::fs::write(&paths.frames, crate::frames::line(0, msg.as_bytes()));
+            }
+
+            let ended = Utc::now();
+            meta.ended = Some(ended.to_rfc3339());
+            meta.expires = options
+                .expires_in
+                .map(|secs| crate::expire::expiry(ended, secs));
+            meta.exit_code = Some(code);
+            meta.failure = Some(crate::failure::Failure::spawn_failed());
+            meta.failing =
+                crate::streak::Streak::after(previous_streak, code, paths.cancel.exists(), ended);
+            let _ = meta.write(&paths);
+            let _ = crate::quick::QuickStatus {
+                exit_code: code,
+                wall_secs: wall_secs(first_started, ended),
+                attempts: 0,
+                signal: None,
+                id: Some(meta.id.clone()),
+            }
+            .write(&paths.result);
+            hooks.run(
+                Event::PostFailure,
+                job_name,
+                &paths,
+                attempt.get(),
+                Some(code),
+            );
 
             let _ = std::fs::write(&paths.exit, format!("{}\n", code));
+            if let Some(control) = control {
+                control.finish(code);
+            }
 
             // Remove advisory lock so further commands are not blocked.
             drop(lock_file);
+            let _ = std::fs::remove_file(&paths.cancel);
             let _ = std::fs::remove_file(&paths.lock);
 
             return Ok(());
         }
     };
 
-    let append = true; // subsequent attempts should append to existing log files
-
-    while final_exit_code != 0 && retries_left > 0 {
-        retries_left -= 1;
+    let mut restarts_left = options.restart.and_then(|p| p.max);
+    let mut backoff = RESTART_BACKOFF_MIN;
 
-        let (code, _started, ended, pid) = run_once(cmd, &paths, timeout_secs, append)?;
+    // Retries are spent first; afterwards the restart policy (if any) keeps
+    // the command alive. A job stopped through `pend kill` must be neither
+    // retried nor restarted.
+    while !paths.cancel.exists() {
+        running_retry_cmd.set(false);
+        if last.exit_code != 0 && retries_left > 0 {
+            retries_left -= 1;
+            running_retry_cmd.set(retry_cmd.is_some());
+            let _ = paths.write_status(&match last.timed_out {
+                true => "timed out, retrying".to_string(),
+                false => format!("exited {}, retrying", last.exit_code),
+            });
+            tracing::debug!(
+                job = job_name,
+                exit_code = last.exit_code,
+                retries_left,
+                "retrying"
+            );
+        } else if options
+            .restart
+            .is_some_and(|p| p.applies_to(last.exit_code))
+            && restarts_left != Some(0)
+        {
+            restarts_left = restarts_left.map(|n| n - 1);
+            if last.runtime >= RESTART_BACKOFF_RESET {
+                backoff = RESTART_BACKOFF_MIN;
+            }
+            let _ = paths.write_status(&format!(
+                "exited {}, restarting in {}s",
+                last.exit_code,
+                backoff.as_secs()
+            ));
+            tracing::debug!(
+                job = job_name,
+                exit_code = last.exit_code,
+                ?restarts_left,
+                ?backoff,
+                "restarting"
+            );
+            if !sleep_unless_cancelled(&paths, backoff) {
+                break;
+            }
+            backoff = (backoff * 2).min(RESTART_BACKOFF_MAX);
+        } else {
+            break;
+        }
+        hooks.run(
+            Event::OnRetry,
+            job_name,
+            &paths,
+            attempt.get() + 1,
+            Some(last.exit_code),
+        );
 
         // The first_started timestamp is intentionally preserved from the very
-        // first attempt, but we keep updating the other fields so that the
-        // metadata reflects the details from the last attempt.
-        last_ended = ended;
-        final_pid = pid;
-        final_exit_code = code;
+        // first attempt, but the other fields reflect the last attempt.
+        let spawned_before = attempt.get();
+        let next = match retry_cmd.as_ref().filter(|_| running_retry_cmd.get()) {
+            Some(line) => run_once(
+                job_name,
+                &[OsString::from(line)],
+                &paths,
+                &JobOptions {
+                    shell: true,
+                    ..options.clone()
+                },
+                &mut output,
+                &record_pid,
+                &record_ready,
+            ),
+            None => run_once(
+                job_name,
+                cmd,
+                &paths,
+                &options,
+                &mut output,
+                &record_pid,
+                &record_ready,
+            ),
+        };
+        match next {
+            Ok(next) => {
+                last = next;
+                record_end(&last);
+            }
+            // Like a first attempt that cannot start, but the output of the
+            // earlier attempts stays.
+            Err(err) => {
+                tracing::debug!(job = job_name, %err, "attempt failed to start");
+                if attempt.get() == spawned_before {
+                    record_unspawned();
+                }
+                append_spawn_error(&paths, &options, &mut output, &err);
+                last = Attempt {
+                    exit_code: 127,
+                    runtime: Duration::ZERO,
+                    ended: Utc::now(),
+                    limit_exceeded: None,
+                    timed_out: false,
+                    signal: None,
+                    oom_killed: false,
+                    stalled: false,
+                    spawn_failed: true,
+                    ..last
+                };
+                record_end(&last);
+                break;
+            }
+        }
     }
+    let final_exit_code = last.exit_code;
 
     // ------------------------------------------------------------------
-    // Persist exit code and metadata.
+    // Persist metadata and exit code. The `.exit` marker goes last because
+    // readers treat its presence as "all artifacts are final".
     // ------------------------------------------------------------------
-    fs::write(&paths.exit, format!("{}\n", final_exit_code))?;
-
-    let meta = Meta {
-        job: job_name,
-        cmd: cmd.to_vec(),
-        pid: final_pid,
-        started: first_started.to_rfc3339(),
-        ended: last_ended.to_rfc3339(),
-        exit_code: final_exit_code,
+    meta.pid = Some(last.pid);
+    meta.attempt = Some(attempt.get());
+    meta.ended = Some(last.ended.to_rfc3339());
+    meta.expires = options
+        .expires_in
+        .map(|secs| crate::expire::expiry(last.ended, secs));
+    meta.exit_code = Some(final_exit_code);
+    meta.raw_exit_status = cfg!(windows).then_some(final_exit_code as u32);
+    meta.limit_exceeded = last.limit_exceeded;
+    meta.failure = match last.spawn_failed {
+        true => Some(crate::failure::Failure::spawn_failed()),
+        false => crate::failure::Failure::classify(&crate::failure::Outcome {
+            exit_code: final_exit_code,
+            timed_out: last.timed_out,
+            signal: last.signal,
+            limit_exceeded: last.limit_exceeded,
+            oom_killed: last.oom_killed,
+            stalled: last.stalled,
+        }),
     };
-    let json = serde_json::to_vec_pretty(&meta)?;
-    fs::write(&paths.meta, json)?;
+    meta.failing = crate::streak::Streak::after(
+        previous_streak,
+        final_exit_code,
+        paths.cancel.exists(),
+        last.ended,
+    );
+    {
+        let live = live.lock().unwrap();
+        meta.ready = live.ready.clone();
+        meta.attempts = live.attempts.clone();
+        meta.slots = live.slots.clone();
+        meta.env = live.env.clone();
+    }
+    let [out_digest, err_digest] = output.digests;
+    meta.out_sha256 = Some(format!("{:x}", out_digest.finalize()));
+    meta.err_sha256 = Some(format!("{:x}", err_digest.finalize()));
+    let [out_dropped, err_dropped] = output.dropped;
+    meta.out_dropped = (out_dropped > 0).then_some(out_dropped);
+    meta.err_dropped = (err_dropped > 0).then_some(err_dropped);
+    // Quiet stderr leaves `.log` a copy of `.out`; keep the bytes once.
+    if let Err(err) = crate::dedup::share_identical(&paths) {
+        tracing::debug!(%err, "cannot store .log once with .out");
+    }
+    meta.write(&paths)?;
+    crate::quick::QuickStatus {
+        exit_code: final_exit_code,
+        wall_secs: wall_secs(first_started, last.ended),
+        attempts: attempt.get(),
+        signal: last.signal,
+        id: Some(meta.id.clone()),
+    }
+    .write(&paths.result)?;
+    let _ = paths.write_status(&match (last.timed_out, paths.cancel.exists()) {
+        (true, _) => format!("timed out (exit {final_exit_code})"),
+        (false, true) => format!("killed (exit {final_exit_code})"),
+        (false, false) => format!("finished (exit {final_exit_code})"),
+    });
+    hooks.run(
+        Event::finished(final_exit_code),
+        job_name,
+        &paths,
+        attempt.get(),
+        Some(final_exit_code),
+    );
+
+    fs::write(&paths.exit, format!("{}\n", final_exit_code))?;
+    if let Some(control) = control {
+        control.finish(final_exit_code);
+    }
 
     // All artifacts persisted – drop the advisory lock and delete the file so
     // the presence of a lingering `.lock` does not confuse future commands.
+    drop(held_slots);
     drop(lock_file); // explicit – ensures the exclusive lock is released first
+    let _ = fs::remove_file(&paths.cancel);
     let _ = fs::remove_file(&paths.lock);
 
     Ok(())
 }
+
+/// Append why a later attempt could not start to `.err` and `.log`, as
+/// that attempt's stderr. Best effort, like the record of a first attempt
+/// that cannot start.
+fn append_spawn_error(
+    paths: &JobPaths,
+    options: &JobOptions,
+    output: &mut Output,
+    err: &io::Error,
+) {
+    let msg = format!("{err}\n");
+    let append = |path: &std::path::Path, bytes: &[u8]| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(bytes))
+    };
+    let offset = JobPaths::file_len(&paths.log);
+    if append(&paths.log, msg.as_bytes()).is_ok() {
+        let _ = append(&paths.idx, format!("{offset} {}\n", msg.len()).as_bytes());
+        if options.checksum_log {
+            let _ = append(
+                &paths.frames,
+                crate::frames::line(offset, msg.as_bytes()).as_bytes(),
+            );
+        }
+    }
+    if append(&paths.err, msg.as_bytes()).is_ok() {
+        output.digests[1].update(msg.as_bytes());
+    }
+}
+
+/// Sleep for `delay` unless `pend kill` asks the job to stop first. Returns
+/// `false` when cancelled.
+fn sleep_unless_cancelled(paths: &JobPaths, delay: Duration) -> bool {
+    let deadline = std::time::Instant::now() + delay;
+    while std::time::Instant::now() < deadline {
+        if paths.cancel.exists() {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    !paths.cancel.exists()
+}
+
+/// Seconds from `start` to `end`, for `<job>.result`.
+fn wall_secs(start: chrono::DateTime<Utc>, end: chrono::DateTime<Utc>) -> f64 {
+    end.signed_duration_since(start)
+        .to_std()
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+}
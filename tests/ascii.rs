@@ -0,0 +1,81 @@
+//! `--ascii` (or a non-UTF-8 locale) keeps summaries to plain ASCII.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env_remove("LC_ALL")
+        .env_remove("LC_CTYPE")
+        .env_remove("LANG");
+    cmd
+}
+
+fn wait(cmd: &mut Command, job: &str) -> String {
+    let out = cmd.args(["wait", job]).output().unwrap();
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn ascii_summaries() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "good", "true"]).assert().success();
+    pend(&tmp).args(["do", "bad", "false"]).assert().success();
+
+    assert_eq!(
+        wait(pend(&tmp).arg("--ascii"), "good"),
+        "OK good (0s) - exit 0\n"
+    );
+    assert_eq!(
+        wait(pend(&tmp).arg("--ascii"), "bad"),
+        "FAIL bad (0s) - exit 1\n"
+    );
+    assert_eq!(
+        wait(pend(&tmp).env("LANG", "C"), "good"),
+        "OK good (0s) - exit 0\n"
+    );
+    assert_eq!(
+        wait(pend(&tmp).env("LANG", "en_US.ISO-8859-1"), "bad"),
+        "FAIL bad (0s) - exit 1\n"
+    );
+
+    // UTF-8 locales, and no locale at all, keep the symbols.
+    assert_eq!(
+        wait(pend(&tmp).env("LANG", "C").env("LC_ALL", "C.UTF-8"), "good"),
+        "✓ good (0s) – exit 0\n"
+    );
+    assert_eq!(wait(&mut pend(&tmp), "bad"), "✗ bad (0s) – exit 1\n");
+}
+
+#[test]
+fn ascii_notes_and_folds() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--note",
+            "nightly",
+            "job",
+            "sh",
+            "-c",
+            "echo x; echo x",
+        ])
+        .assert()
+        .success();
+    let out = pend(&tmp)
+        .args(["--ascii", "wait", "--fold-repeats", "job"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .starts_with("x\n... last line repeated once\n"));
+    let out = pend(&tmp)
+        .args(["--ascii", "status", "job"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .ends_with(" - nightly\n"));
+}
@@ -0,0 +1,48 @@
+//! `pend attach` follows a running job's output through its worker's control
+//! socket.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn follows_output_until_the_job_ends() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--shell",
+            "ticker",
+            "sleep 1; for i in 1 2 3 4 5; do echo tick $i; sleep 0.1; done; echo bye >&2; exit 3",
+        ])
+        .assert()
+        .success();
+
+    pend(&tmp)
+        .args(["attach", "ticker"])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .code(3)
+        .stdout("tick 1\ntick 2\ntick 3\ntick 4\ntick 5\n")
+        .stderr("bye\n");
+}
+
+#[test]
+fn refuses_a_finished_job() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "done", "true"]).assert().success();
+    pend(&tmp).args(["wait", "done"]).assert().success();
+    pend(&tmp)
+        .args(["attach", "done"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("job 'done' is not running"));
+    pend(&tmp).args(["attach", "missing"]).assert().code(3);
+}
@@ -0,0 +1,45 @@
+//! `pend bench` runs a command repeatedly and reports its timing.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn reports_statistics_over_runs() {
+    let tmp = TempDir::new().unwrap();
+    let out = pend(&tmp)
+        .args(["bench", "nap", "--runs", "3", "--", "sleep", "0.1"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    for (i, line) in lines[..3].iter().enumerate() {
+        assert!(line.starts_with(&format!("nap-{}  0.1", i + 1)), "{line}");
+    }
+    assert_eq!(lines[4], "runs    3");
+    for stat in ["min", "mean", "median", "stddev", "max"] {
+        assert!(lines.iter().any(|l| l.starts_with(stat)), "{stat} missing");
+    }
+    // Every run is an ordinary job.
+    pend(&tmp).args(["status", "nap-3"]).assert().success();
+}
+
+#[test]
+fn failing_run_stops_the_benchmark() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["bench", "bad", "--runs", "3", "--", "sh", "-c", "exit 6"])
+        .assert()
+        .code(6)
+        .stderr(predicates::str::contains(
+            "run 1 ('bad-1') failed with exit code 6",
+        ));
+    pend(&tmp).args(["status", "bad-2"]).assert().code(3);
+}
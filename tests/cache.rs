@@ -0,0 +1,87 @@
+//! `pend do --cache` reuses a recent successful run of the same command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn run_counted(tmp: &TempDir, counter: &std::path::Path, extra: &[&str]) -> Command {
+    let mut cmd = pend(tmp);
+    cmd.args(["do", "step", "--cache"])
+        .args(extra)
+        .args(["bash", "-c"])
+        .arg(format!("echo run >> '{}'; echo result", counter.display()));
+    cmd
+}
+
+fn runs(counter: &std::path::Path) -> usize {
+    std::fs::read_to_string(counter)
+        .unwrap_or_default()
+        .lines()
+        .count()
+}
+
+#[test]
+fn second_identical_run_is_served_from_cache() {
+    let tmp = TempDir::new().unwrap();
+    let counter = tmp.path().join("counter");
+
+    run_counted(&tmp, &counter, &[]).assert().success();
+    pend(&tmp).args(["wait", "step"]).assert().success();
+    assert_eq!(runs(&counter), 1);
+
+    run_counted(&tmp, &counter, &[])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("cached result"));
+    pend(&tmp)
+        .args(["wait", "step"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("result"));
+    assert_eq!(runs(&counter), 1, "command ran again despite the cache");
+    assert!(!tmp.path().join("step.lock").exists());
+
+    // A cache-relevant environment variable changing invalidates the entry.
+    run_counted(&tmp, &counter, &["--cache-env", "PEND_TEST_FLAVOUR"])
+        .env("PEND_TEST_FLAVOUR", "spicy")
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "step"]).assert().success();
+    assert_eq!(runs(&counter), 2);
+}
+
+#[test]
+fn failures_and_expired_entries_are_not_reused() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["do", "bad", "--cache", "false"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "bad"]).assert().code(1);
+    pend(&tmp)
+        .args(["do", "bad", "--cache", "false"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("cached").not());
+    pend(&tmp).args(["wait", "bad"]).assert().code(1);
+
+    pend(&tmp)
+        .args(["do", "old", "--cache", "true"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "old"]).assert().success();
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    pend(&tmp)
+        .args(["do", "old", "--cache", "--cache-ttl", "0", "true"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("cached").not());
+    pend(&tmp).args(["wait", "old"]).assert().success();
+}
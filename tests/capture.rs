@@ -0,0 +1,112 @@
+//! `pend do --capture` reads only one of the command's streams;
+//! `--max-out-size` / `--max-err-size` keep the head of each.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn run(tmp: &TempDir, args: &[&str]) {
+    pend(tmp).arg("do").args(args).assert().success();
+    let job = args[args.len() - 2];
+    pend(tmp).args(["wait", job]).assert().success();
+}
+
+fn read(tmp: &TempDir, file: &str) -> String {
+    std::fs::read_to_string(tmp.path().join(file)).unwrap()
+}
+
+fn meta(tmp: &TempDir, job: &str) -> serde_json::Value {
+    serde_json::from_str(&read(tmp, &format!("{job}.json"))).unwrap()
+}
+
+#[test]
+fn capture_stderr_discards_stdout() {
+    let tmp = TempDir::new().unwrap();
+    run(
+        &tmp,
+        &[
+            "--capture",
+            "stderr",
+            "--shell",
+            "quiet",
+            "seq 1 100000; echo kept >&2",
+        ],
+    );
+    assert_eq!(read(&tmp, "quiet.out"), "");
+    assert_eq!(read(&tmp, "quiet.err"), "kept\n");
+    assert_eq!(read(&tmp, "quiet.log"), "kept\n");
+    assert_eq!(meta(&tmp, "quiet")["capture"], "stderr");
+    pend(&tmp).args(["validate", "quiet"]).assert().success();
+}
+
+#[test]
+fn keeps_the_head_of_each_stream() {
+    let tmp = TempDir::new().unwrap();
+    run(
+        &tmp,
+        &[
+            "--max-out-size",
+            "10",
+            "--shell",
+            "head",
+            "seq 1 1000; echo done >&2",
+        ],
+    );
+    assert_eq!(read(&tmp, "head.out"), "1\n2\n3\n4\n5\n");
+    assert_eq!(read(&tmp, "head.err"), "done\n");
+    assert!(read(&tmp, "head.log").contains("done\n"));
+    let meta = meta(&tmp, "head");
+    // `seq 1 1000` writes 3893 bytes.
+    assert_eq!(meta["out_dropped"], 3883);
+    assert!(meta.get("err_dropped").is_none());
+    pend(&tmp).args(["validate", "head"]).assert().success();
+}
+
+#[test]
+fn every_attempt_keeps_its_own_head() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--retries",
+            "1",
+            "--max-err-size",
+            "2",
+            "--shell",
+            "flaky",
+            "echo out; echo abcd >&2; exit 1",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "flaky"]).assert().code(1);
+    assert_eq!(read(&tmp, "flaky.out"), "out\nout\n");
+    assert_eq!(read(&tmp, "flaky.err"), "abab");
+    assert_eq!(meta(&tmp, "flaky")["err_dropped"], 6);
+}
+
+#[test]
+fn rejects_a_limit_on_a_stream_not_captured() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--capture",
+            "stdout",
+            "--max-err-size",
+            "1K",
+            "job",
+            "true",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--max-err-size has no effect with --capture stdout",
+        ));
+}
@@ -0,0 +1,68 @@
+//! `pend do --check` validates a submission without starting it.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn no_artifacts(tmp: &TempDir) -> bool {
+    std::fs::read_dir(tmp.path()).unwrap().next().is_none()
+}
+
+#[test]
+fn reports_the_resolved_program() {
+    let tmp = TempDir::new().unwrap();
+    let program = env!("CARGO_BIN_EXE_pend");
+    pend(&tmp)
+        .args(["do", "--check", "build", program, "--version"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("build: ok"));
+    assert!(no_artifacts(&tmp));
+}
+
+#[test]
+fn reports_a_missing_program() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--check", "build", "definitely-not-a-program-xyz"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "'definitely-not-a-program-xyz' was not found on PATH",
+        ));
+    pend(&tmp)
+        .args(["do", "--check", "build", "./no/such/script.sh"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("does not exist"));
+    // A PATH passed with --env is the one searched.
+    let dir = std::path::Path::new(env!("CARGO_BIN_EXE_pend"))
+        .parent()
+        .unwrap();
+    pend(&tmp)
+        .args(["do", "--check", "--env"])
+        .arg(format!("PATH={}", dir.display()))
+        .args(["build", "pend"])
+        .assert()
+        .success();
+    assert!(no_artifacts(&tmp));
+}
+
+#[test]
+fn rejects_invalid_names_and_options() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--check", "../escape", "true"])
+        .assert()
+        .code(2);
+    pend(&tmp)
+        .args(["do", "--check", "--after", "missing", "build", "true"])
+        .assert()
+        .code(3);
+}
@@ -0,0 +1,100 @@
+//! `pend do --checksum-log` frames the log with checksummed records, and
+//! `pend wait` replays only records that verify.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::io::Read;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn frame(offset: usize, bytes: &[u8]) -> String {
+    format!("{offset} {} {:08x}\n", bytes.len(), crc32fast::hash(bytes))
+}
+
+#[test]
+fn checksummed_log_replays_both_streams() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--checksum-log",
+            "framed",
+            "bash",
+            "-c",
+            "echo out; sleep 0.2; echo err >&2; sleep 0.2; echo more",
+        ])
+        .assert()
+        .success();
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "framed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("out\nmore\n"))
+        .stderr(predicate::str::starts_with("err\n"));
+
+    let frames = std::fs::read_to_string(tmp.path().join("framed.frames")).unwrap();
+    assert_eq!(
+        frames,
+        frame(0, b"out\n") + &frame(4, b"err\n") + &frame(8, b"more\n")
+    );
+}
+
+#[test]
+fn torn_record_is_replayed_once_it_completes() {
+    let tmp = TempDir::new().unwrap();
+    let log = tmp.path().join("torn.log");
+    // The second record's bytes have not arrived: the file reads as zeros.
+    std::fs::write(&log, b"hello\n\0\0\0\0\0\0").unwrap();
+    std::fs::write(
+        tmp.path().join("torn.frames"),
+        frame(0, b"hello\n") + &frame(6, b"world\n"),
+    )
+    .unwrap();
+
+    let mut wait = std::process::Command::new(assert_cmd::cargo::cargo_bin("pend"))
+        .env("PEND_DIR", tmp.path())
+        .args(["--no-color", "wait", "torn"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    std::fs::write(&log, b"hello\nworld\n").unwrap();
+    std::fs::write(tmp.path().join("torn.exit"), "0\n").unwrap();
+
+    let mut stdout = String::new();
+    wait.stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+    assert!(wait.wait().unwrap().success());
+    assert!(stdout.starts_with("hello\nworld\n✓"), "{stdout:?}");
+}
+
+#[test]
+fn record_that_never_verifies_is_skipped() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::write(tmp.path().join("bad.log"), b"hello\ngarbage\ntail\n").unwrap();
+    std::fs::write(
+        tmp.path().join("bad.frames"),
+        frame(0, b"hello\n") + &frame(6, b"worlds!\n") + &frame(14, b"tail\n"),
+    )
+    .unwrap();
+    std::fs::write(tmp.path().join("bad.exit"), "0\n").unwrap();
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "bad"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("hello\ntail\n"))
+        .stderr(predicate::str::contains(
+            "skipped 8 bytes of 'bad' output that failed their checksum",
+        ));
+}
@@ -0,0 +1,76 @@
+//! `pend do --clean-env` / `--env` control the command's environment, and
+//! the metadata records it without unrelated variables.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env("SECRET_TOKEN", "hunter2")
+        .env("KEEP_ME", "kept");
+    cmd
+}
+
+fn meta_env(tmp: &TempDir, job: &str) -> serde_json::Value {
+    let meta = std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap();
+    let meta: serde_json::Value = serde_json::from_slice(&meta).unwrap();
+    meta["env"].clone()
+}
+
+#[test]
+fn clean_env_keeps_only_base_and_passed_variables() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--clean-env",
+            "--env",
+            "GREETING=hello",
+            "--env",
+            "KEEP_ME",
+            "--env",
+            "NOT_SET_ANYWHERE",
+            "clean",
+            "env",
+        ])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "clean"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("GREETING=hello\n"))
+        .stdout(predicate::str::contains("KEEP_ME=kept\n"))
+        .stdout(predicate::str::contains("PATH="))
+        .stdout(predicate::str::contains("SECRET_TOKEN").not())
+        .stdout(predicate::str::contains("NOT_SET_ANYWHERE").not())
+        .stdout(predicate::str::contains("PEND_JOB_OPTIONS").not());
+
+    let env = meta_env(&tmp, "clean");
+    assert_eq!(env["GREETING"], "hello");
+    assert_eq!(env["KEEP_ME"], "kept");
+    assert!(env.get("PATH").is_some());
+    assert!(env.get("SECRET_TOKEN").is_none());
+}
+
+#[test]
+fn inherited_environment_is_not_recorded() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--env", "EXTRA=1", "full", "env"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "full"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SECRET_TOKEN=hunter2"))
+        .stdout(predicate::str::contains("EXTRA=1"));
+
+    let env = meta_env(&tmp, "full");
+    assert_eq!(env["EXTRA"], "1");
+    assert!(env.get("SECRET_TOKEN").is_none());
+}
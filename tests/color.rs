@@ -0,0 +1,59 @@
+//! `--color auto|always|never`, `NO_COLOR` and `CLICOLOR_FORCE`.
+#![cfg(feature = "color")]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env_remove("NO_COLOR")
+        .env_remove("CLICOLOR_FORCE");
+    cmd
+}
+
+/// Whether `pend wait` on two finished jobs colours its (piped) stdout.
+fn coloured(tmp: &TempDir, args: &[&str], env: &[(&str, &str)]) -> bool {
+    let mut cmd = pend(tmp);
+    cmd.args(args).args(["wait", "one", "two"]);
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+    let out = cmd.output().unwrap();
+    assert!(out.status.success());
+    out.stdout.contains(&0x1b)
+}
+
+#[test]
+fn color_choice_and_environment() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "one", "echo", "1"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "two", "echo", "2"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "one", "two"]).assert().success();
+
+    // `auto` never colours a pipe …
+    assert!(!coloured(&tmp, &[], &[]));
+    // … unless forced, and `NO_COLOR` beats `CLICOLOR_FORCE`.
+    assert!(coloured(&tmp, &[], &[("CLICOLOR_FORCE", "1")]));
+    assert!(!coloured(&tmp, &[], &[("CLICOLOR_FORCE", "0")]));
+    assert!(!coloured(
+        &tmp,
+        &[],
+        &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]
+    ));
+
+    // Explicit choices override the environment.
+    assert!(coloured(&tmp, &["--color", "always"], &[("NO_COLOR", "1")]));
+    assert!(!coloured(
+        &tmp,
+        &["--color", "never"],
+        &[("CLICOLOR_FORCE", "1")]
+    ));
+    assert!(!coloured(&tmp, &["--no-color"], &[("CLICOLOR_FORCE", "1")]));
+}
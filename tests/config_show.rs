@@ -0,0 +1,135 @@
+//! `pend config show` reports each effective setting with its source, and
+//! `pend config set` edits the config file in place.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_CONFIG", tmp.path().join("config.toml"))
+        .env_remove("PEND_DIR")
+        .env_remove("PEND_PERSISTENT")
+        .env_remove("PEND_PER_USER")
+        .env_remove("PEND_PROFILE");
+    cmd
+}
+
+fn setting(stdout: &[u8], key: &str) -> String {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix(key)
+                .filter(|rest| rest.starts_with(' '))
+                .map(|rest| rest.trim().to_string())
+        })
+        .unwrap_or_else(|| panic!("no {key} line"))
+}
+
+#[test]
+fn show_reports_where_each_value_came_from() {
+    let tmp = TempDir::new().unwrap();
+    let jobs = tmp.path().join("ci-jobs");
+    std::fs::write(
+        tmp.path().join("config.toml"),
+        format!(
+            "[profiles.ci]\ndir = {:?}\ntimeout = 60\ncolor = \"never\"\n",
+            jobs.display().to_string()
+        ),
+    )
+    .unwrap();
+
+    let out = pend(&tmp)
+        .env("PEND_PROFILE", "ci")
+        .args(["config", "show"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert_eq!(setting(&out.stdout, "profile"), "ci  (env PEND_PROFILE)");
+    assert_eq!(
+        setting(&out.stdout, "dir"),
+        format!("{}  (profile ci)", jobs.display())
+    );
+    assert_eq!(setting(&out.stdout, "timeout"), "60s  (profile ci)");
+    assert_eq!(setting(&out.stdout, "color"), "never  (profile ci)");
+    assert_eq!(setting(&out.stdout, "retries"), "0  (default)");
+
+    // The environment beats the file, the command line beats both.
+    let other = tmp.path().join("other");
+    let out = pend(&tmp)
+        .env("PEND_PROFILE", "ci")
+        .env("PEND_DIR", &other)
+        .args(["--color", "always", "config", "show"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        setting(&out.stdout, "dir"),
+        format!("{}  (env PEND_DIR)", other.display())
+    );
+    assert_eq!(setting(&out.stdout, "color"), "always  (flag --color)");
+
+    let out = pend(&tmp)
+        .env("PEND_PROFILE", "ci")
+        .env("PEND_DIR", &other)
+        .args(["--dir", "flagged", "config", "show"])
+        .output()
+        .unwrap();
+    assert_eq!(setting(&out.stdout, "dir"), "flagged  (flag --dir)");
+}
+
+#[test]
+fn set_edits_the_file_and_keeps_comments() {
+    let tmp = TempDir::new().unwrap();
+    let config = tmp.path().join("config.toml");
+    std::fs::write(&config, "# shared templates\n[templates]\nt = \"true\"\n").unwrap();
+
+    pend(&tmp)
+        .args(["--profile", "ci", "config", "set", "timeout", "90"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--profile", "ci", "config", "set", "max_log_size", "10M"])
+        .assert()
+        .success();
+
+    let text = std::fs::read_to_string(&config).unwrap();
+    assert!(text.starts_with("# shared templates\n"), "{text}");
+    assert!(
+        text.contains("[profiles.ci]\ntimeout = 90\nmax_log_size = \"10M\"\n"),
+        "{text}"
+    );
+
+    let out = pend(&tmp)
+        .args(["--profile", "ci", "config", "show"])
+        .output()
+        .unwrap();
+    assert_eq!(setting(&out.stdout, "timeout"), "90s  (profile ci)");
+}
+
+#[test]
+fn set_rejects_bad_values_without_touching_the_file() {
+    let tmp = TempDir::new().unwrap();
+    let config = tmp.path().join("config.toml");
+    std::fs::write(&config, "[profiles.ci]\ntimeout = 5\n").unwrap();
+
+    pend(&tmp)
+        .args(["--profile", "ci", "config", "set", "color", "sometimes"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("invalid value for color"));
+    pend(&tmp)
+        .args(["--profile", "ci", "config", "set", "colour", "never"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown setting 'colour'"));
+    pend(&tmp)
+        .args(["config", "set", "timeout", "5"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--profile"));
+
+    assert_eq!(
+        std::fs::read_to_string(&config).unwrap(),
+        "[profiles.ci]\ntimeout = 5\n"
+    );
+}
@@ -0,0 +1,65 @@
+//! `pend do --docker/--podman`, exercised against a fake engine CLI that just
+//! echoes its arguments.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::TempDir;
+
+fn fake_engine(dir: &TempDir, name: &str) {
+    let path = dir.path().join(name);
+    std::fs::write(&path, "#!/bin/sh\necho \"$0 $*\"\nexit 3\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn docker_wraps_command_and_keeps_exit_code() {
+    let jobs = TempDir::new().unwrap();
+    let bin = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    fake_engine(&bin, "docker");
+
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let pend = || {
+        let mut cmd = Command::cargo_bin("pend").unwrap();
+        cmd.env("PEND_DIR", jobs.path())
+            .env("PATH", &path)
+            .current_dir(work.path());
+        cmd
+    };
+
+    pend()
+        .args(["do", "--docker", "alpine:3", "ctr", "echo", "hi"])
+        .assert()
+        .success();
+
+    let cwd = work.path().canonicalize().unwrap();
+    pend()
+        .args(["--no-color", "wait", "ctr"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(
+            "docker run --rm --init --name pend-ctr-",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "-v {0}:{0} -w {0} alpine:3 echo hi",
+            cwd.display()
+        )));
+}
+
+#[test]
+fn docker_and_podman_conflict() {
+    let jobs = TempDir::new().unwrap();
+    Command::cargo_bin("pend")
+        .unwrap()
+        .env("PEND_DIR", jobs.path())
+        .args(["do", "--docker", "a", "--podman", "b", "ctr", "true"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
@@ -0,0 +1,58 @@
+//! The worker's control socket: live status, kill, and exit notification.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn socket_serves_status_and_kill() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "svc", "sleep", "30"])
+        .assert()
+        .success();
+
+    // Wait for the worker to bind its socket.
+    let sock = tmp.path().join("svc.sock");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !sock.exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert!(sock.exists(), "worker did not create its control socket");
+
+    // The socket is bound just before the first attempt is spawned.
+    let mut json = serde_json::Value::Null;
+    while Instant::now() < deadline {
+        let out = pend(&tmp)
+            .args(["status", "--json", "svc"])
+            .output()
+            .unwrap();
+        json = serde_json::from_slice(&out.stdout).unwrap();
+        if json[0]["attempt"] == 1 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(json[0]["state"], "running");
+    assert_eq!(json[0]["attempt"], 1);
+
+    pend(&tmp).args(["kill", "svc"]).assert().success();
+
+    let start = Instant::now();
+    pend(&tmp)
+        .args(["--no-color", "wait", "svc"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // The worker removes its socket once finished.
+    assert!(!sock.exists());
+}
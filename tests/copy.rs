@@ -0,0 +1,91 @@
+//! `pend copy` exports a job's artifacts with a manifest.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::os::unix::fs::MetadataExt;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path().join("jobs"));
+    cmd
+}
+
+fn finished_job(tmp: &TempDir) {
+    pend(tmp)
+        .args(["do", "build", "sh", "-c", "echo built"])
+        .assert()
+        .success();
+    pend(tmp).args(["wait", "build"]).assert().success();
+}
+
+#[test]
+fn copies_artifacts_with_timestamps_and_manifest() {
+    let tmp = TempDir::new().unwrap();
+    finished_job(&tmp);
+    let dest = tmp.path().join("artifacts");
+
+    let out = pend(&tmp)
+        .args(["copy", "build"])
+        .arg(&dest)
+        .assert()
+        .success();
+    let manifest_path = dest.join("build.manifest.json");
+    assert_eq!(
+        String::from_utf8_lossy(&out.get_output().stdout).trim(),
+        manifest_path.display().to_string()
+    );
+
+    let jobs = tmp.path().join("jobs");
+    assert_eq!(std::fs::read(dest.join("build.log")).unwrap(), b"built\n");
+    assert!(!dest.join("build.lock").exists());
+    let original = std::fs::metadata(jobs.join("build.exit")).unwrap();
+    let copied = std::fs::metadata(dest.join("build.exit")).unwrap();
+    assert_eq!(original.modified().unwrap(), copied.modified().unwrap());
+    assert_ne!(original.ino(), copied.ino());
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+    assert_eq!(manifest["job"], "build");
+    assert_eq!(manifest["state"], "finished");
+    assert_eq!(manifest["exit_code"], 0);
+    let files = manifest["files"].as_array().unwrap();
+    let log = files.iter().find(|f| f["name"] == "build.log").unwrap();
+    assert_eq!(log["bytes"], 6);
+    assert_eq!(log["sha256"].as_str().unwrap().len(), 64);
+    assert!(files.iter().all(|f| f["name"] != "build.lock"));
+}
+
+#[test]
+fn link_shares_the_files() {
+    let tmp = TempDir::new().unwrap();
+    finished_job(&tmp);
+    let dest = tmp.path().join("artifacts");
+
+    pend(&tmp)
+        .args(["copy", "--link", "build"])
+        .arg(&dest)
+        .assert()
+        .success();
+    let original = std::fs::metadata(tmp.path().join("jobs/build.log")).unwrap();
+    let linked = std::fs::metadata(dest.join("build.log")).unwrap();
+    assert_eq!(original.ino(), linked.ino());
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(dest.join("build.manifest.json")).unwrap()).unwrap();
+    assert!(manifest["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|f| f["linked"] == true));
+}
+
+#[test]
+fn unknown_job_is_not_found() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["copy", "ghost"])
+        .arg(tmp.path().join("out"))
+        .assert()
+        .code(3);
+}
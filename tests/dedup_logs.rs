@@ -0,0 +1,68 @@
+//! A job with empty stderr stores `.out` and `.log` once.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let (a, b) = (std::fs::metadata(a).unwrap(), std::fs::metadata(b).unwrap());
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+#[test]
+fn quiet_stderr_shares_the_log() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "quiet", "sh", "-c", "echo one; echo two"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "noisy", "sh", "-c", "echo one; echo two >&2"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "quiet", "noisy"])
+        .assert()
+        .success();
+
+    let file = |name: &str| tmp.path().join(name);
+    assert_eq!(std::fs::read(file("quiet.log")).unwrap(), b"one\ntwo\n");
+    assert_eq!(std::fs::read(file("quiet.out")).unwrap(), b"one\ntwo\n");
+    // A reflink keeps two inodes; only a hard link is visible here, and
+    // /tmp on tmpfs or ext4 supports no reflinks.
+    let reflinks = std::process::Command::new("stat")
+        .args(["-f", "-c", "%T"])
+        .arg(tmp.path())
+        .output()
+        .map(|o| {
+            let fs = String::from_utf8_lossy(&o.stdout);
+            fs.contains("btrfs") || fs.contains("xfs")
+        })
+        .unwrap_or(false);
+    if !reflinks {
+        assert!(same_file(&file("quiet.out"), &file("quiet.log")));
+    }
+    assert!(!file("quiet.log.dedup").exists());
+    assert!(!same_file(&file("noisy.out"), &file("noisy.log")));
+
+    // Replay and a re-run of the job are unaffected.
+    pend(&tmp)
+        .args(["--no-color", "wait", "quiet"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("one\ntwo\n"));
+    pend(&tmp)
+        .args(["do", "quiet", "sh", "-c", "echo three"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "quiet"]).assert().success();
+    assert_eq!(std::fs::read(file("quiet.log")).unwrap(), b"three\n");
+    assert_eq!(std::fs::read(file("quiet.out")).unwrap(), b"three\n");
+}
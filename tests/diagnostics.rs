@@ -0,0 +1,61 @@
+//! Internal diagnostics logging via `-v` and `PEND_LOG`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path()).env_remove("PEND_LOG");
+    cmd
+}
+
+#[test]
+fn quiet_by_default() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["do", "quiet", "echo", "hi"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+    pend(&tmp).args(["wait", "quiet"]).assert().success();
+    assert!(!tmp.path().join("quiet.pend-debug.log").exists());
+}
+
+#[test]
+fn verbose_logs_to_stderr() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["-v", "do", "loud", "echo", "hi"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("DEBUG"));
+}
+
+#[test]
+fn worker_writes_debug_log_and_clean_removes_it() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .env("PEND_LOG", "debug")
+        .args(["do", "traced", "--retries", "1", "sh", "-c", "exit 3"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "traced"]).assert().code(3);
+
+    let log = std::fs::read_to_string(tmp.path().join("traced.pend-debug.log")).unwrap();
+    assert!(log.contains("attempt started"), "{log}");
+    assert!(log.contains("retrying"), "{log}");
+
+    // The debug log is an artifact, not a job of its own.
+    pend(&tmp)
+        .args(["status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pend-debug").not());
+
+    pend(&tmp).args(["clean", "traced"]).assert().success();
+    assert!(!tmp.path().join("traced.pend-debug.log").exists());
+}
@@ -0,0 +1,44 @@
+//! `pend do --json` describes the job it submitted.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn descriptor_lists_artifacts_and_worker() {
+    let tmp = TempDir::new().unwrap();
+
+    let out = pend(&tmp)
+        .args(["do", "--json", "described", "echo", "hello"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let desc: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+
+    assert_eq!(desc["job"], "described");
+    assert_eq!(desc["cached"], false);
+    assert!(desc["worker_pid"].as_u64().is_some());
+    assert!(desc["started"].as_str().is_some_and(|s| !s.is_empty()));
+    let dir = std::path::PathBuf::from(desc["dir"].as_str().unwrap());
+    assert_eq!(dir, tmp.path());
+    let out_path = desc["artifacts"]["out"].as_str().unwrap();
+    assert_eq!(
+        std::path::Path::new(out_path),
+        tmp.path().join("described.out")
+    );
+
+    pend(&tmp).args(["wait", "described"]).assert().success();
+    assert_eq!(std::fs::read_to_string(out_path).unwrap(), "hello\n");
+
+    // Without `--json` nothing is printed.
+    pend(&tmp)
+        .args(["do", "quiet", "true"])
+        .assert()
+        .success()
+        .stdout("");
+}
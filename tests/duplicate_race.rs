@@ -0,0 +1,55 @@
+//! Concurrent `pend do` calls for one job name: exactly one starts a worker,
+//! the others fail with "already running" before returning.
+#![cfg(unix)]
+
+use assert_cmd::cargo::CommandCargoExt;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn only_one_of_simultaneous_submissions_wins() {
+    let tmp = TempDir::new().unwrap();
+    let runs = tmp.path().join("runs.txt");
+
+    let submitters: Vec<_> = (0..8)
+        .map(|_| {
+            let mut cmd = pend(&tmp);
+            cmd.args(["do", "race", "--shell"])
+                .arg(format!("echo run >> '{}'; sleep 2", runs.display()));
+            std::thread::spawn(move || cmd.output().expect("run pend do"))
+        })
+        .collect();
+    let outputs: Vec<_> = submitters.into_iter().map(|t| t.join().unwrap()).collect();
+
+    let won = outputs.iter().filter(|out| out.status.success()).count();
+    assert_eq!(won, 1, "{outputs:?}");
+    for out in outputs.iter().filter(|out| !out.status.success()) {
+        assert_eq!(out.status.code(), Some(4), "{out:?}");
+        assert!(String::from_utf8_lossy(&out.stderr).contains("already running"));
+    }
+
+    let wait = pend(&tmp).args(["wait", "race"]).output().unwrap();
+    assert!(wait.status.success(), "{wait:?}");
+    assert_eq!(std::fs::read_to_string(&runs).unwrap(), "run\n");
+}
+
+#[test]
+fn resubmitting_right_away_is_rejected() {
+    let tmp = TempDir::new().unwrap();
+    let first = pend(&tmp)
+        .args(["do", "nap", "sleep", "2"])
+        .output()
+        .unwrap();
+    assert!(first.status.success(), "{first:?}");
+    // The worker may not have taken over the lock from `pend do` yet.
+    let second = pend(&tmp).args(["do", "nap", "true"]).output().unwrap();
+    assert_eq!(second.status.code(), Some(4), "{second:?}");
+    let wait = pend(&tmp).args(["wait", "nap"]).output().unwrap();
+    assert!(wait.status.success(), "{wait:?}");
+}
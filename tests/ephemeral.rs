@@ -0,0 +1,69 @@
+//! `pend do --ephemeral` / `pend wait --clean` drop the artifacts of
+//! successful jobs after the replay and keep failed ones.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn artifacts(tmp: &TempDir, job: &str) -> Vec<String> {
+    std::fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(&format!("{job}.")))
+        .collect()
+}
+
+#[test]
+fn ephemeral_job_is_removed_after_success() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--ephemeral", "ok", "echo", "hello"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "ok"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("hello\n"));
+    assert_eq!(artifacts(&tmp, "ok"), Vec::<String>::new());
+}
+
+#[test]
+fn ephemeral_job_is_kept_after_failure() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--ephemeral", "bad", "sh", "-c", "echo oops; exit 3"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "bad"]).assert().code(3);
+    assert!(!artifacts(&tmp, "bad").is_empty());
+    pend(&tmp)
+        .args(["wait", "bad"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("oops"));
+}
+
+#[test]
+fn wait_clean_removes_only_successful_jobs() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "good", "true"]).assert().success();
+    pend(&tmp).args(["do", "bad", "false"]).assert().success();
+    pend(&tmp)
+        .args(["wait", "--clean", "good", "bad"])
+        .assert()
+        .code(1);
+    assert_eq!(artifacts(&tmp, "good"), Vec::<String>::new());
+    assert!(!artifacts(&tmp, "bad").is_empty());
+
+    // Without --clean or --ephemeral the artifacts stay.
+    pend(&tmp).args(["do", "kept", "true"]).assert().success();
+    pend(&tmp).args(["wait", "kept"]).assert().success();
+    assert!(!artifacts(&tmp, "kept").is_empty());
+}
@@ -0,0 +1,49 @@
+//! `pend exists <job>` answers through its exit code alone.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn exit_code_tells_the_state() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["exists", "nope"])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr("");
+
+    pend(&tmp)
+        .args(["do", "nap", "sleep", "1"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["exists", "nap"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("");
+
+    pend(&tmp).args(["wait", "nap"]).assert().success();
+    pend(&tmp)
+        .args(["exists", "nap"])
+        .assert()
+        .code(0)
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn failed_job_counts_as_finished() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "bad", "false"]).assert().success();
+    pend(&tmp).args(["wait", "bad"]).assert().code(1);
+    pend(&tmp).args(["exists", "bad"]).assert().code(0);
+}
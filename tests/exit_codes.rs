@@ -0,0 +1,58 @@
+//! Exit codes beyond the 8-bit Unix range, as recorded by Windows workers.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[test]
+fn ntstatus_exit_is_rendered_and_mapped() {
+    let tmp = TempDir::new().unwrap();
+
+    // Artifacts of a job that crashed with an access violation (0xC0000005,
+    // stored bit-for-bit as a signed 32-bit integer).
+    std::fs::write(tmp.path().join("crash.log"), "").unwrap();
+    std::fs::write(tmp.path().join("crash.exit"), "-1073741819\n").unwrap();
+
+    let assert = Command::cargo_bin("pend")
+        .unwrap()
+        .env("PEND_DIR", tmp.path())
+        .args(["--no-color", "wait", "crash"])
+        .assert()
+        .stdout(predicate::str::contains(
+            "exit 0xC0000005 (STATUS_ACCESS_VIOLATION)",
+        ));
+
+    let expected = if cfg!(windows) { -1073741819 } else { 1 };
+    assert.code(expected);
+}
+
+#[test]
+fn ignore_failures_reports_but_exits_zero() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::write(tmp.path().join("bad.log"), "").unwrap();
+    std::fs::write(tmp.path().join("bad.exit"), "3\n").unwrap();
+    std::fs::write(tmp.path().join("good.log"), "").unwrap();
+    std::fs::write(tmp.path().join("good.exit"), "0\n").unwrap();
+
+    let pend = || {
+        let mut cmd = Command::cargo_bin("pend").unwrap();
+        cmd.env("PEND_DIR", tmp.path());
+        cmd
+    };
+    pend()
+        .args(["--no-color", "--ascii", "wait", "bad", "good"])
+        .assert()
+        .code(3);
+    pend()
+        .args([
+            "--no-color",
+            "--ascii",
+            "wait",
+            "--ignore-failures",
+            "bad",
+            "good",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("FAIL bad"));
+}
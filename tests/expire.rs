@@ -0,0 +1,107 @@
+//! `pend do --expires-in` records when a job's artifacts expire;
+//! `pend clean --expired` and `pend serve` delete them.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn run(tmp: &TempDir, args: &[&str]) {
+    pend(tmp).arg("do").args(args).assert().success();
+}
+
+#[test]
+fn clean_expired_deletes_only_expired_jobs() {
+    let tmp = TempDir::new().unwrap();
+    run(&tmp, &["--expires-in", "1s", "throwaway", "true"]);
+    run(&tmp, &["--expires-in", "1h", "later", "true"]);
+    run(&tmp, &["kept", "true"]);
+    run(&tmp, &["--expires-in", "1s", "running", "sleep", "30"]);
+    for job in ["throwaway", "later", "kept"] {
+        pend(&tmp).args(["wait", job]).assert().success();
+    }
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("throwaway.json")).unwrap()).unwrap();
+    assert_eq!(meta["expires_in"], 1);
+    assert!(meta["expires"].is_string());
+    pend(&tmp)
+        .args(["status", "later"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("exit 0, expires in "));
+
+    std::thread::sleep(Duration::from_millis(1500));
+    pend(&tmp)
+        .args(["status", "throwaway"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("exit 0, expired"));
+    pend(&tmp).args(["clean", "--expired"]).assert().success();
+
+    assert!(!tmp.path().join("throwaway.json").exists());
+    assert!(!tmp.path().join("throwaway.log").exists());
+    for job in ["later", "kept", "running"] {
+        assert!(tmp.path().join(format!("{job}.json")).exists(), "{job}");
+    }
+    pend(&tmp).args(["kill", "running"]).assert();
+}
+
+#[test]
+fn serve_sweeps_expired_jobs() {
+    let tmp = TempDir::new().unwrap();
+    run(&tmp, &["--expires-in", "1s", "throwaway", "true"]);
+    pend(&tmp).args(["wait", "throwaway"]).assert().success();
+    std::thread::sleep(Duration::from_millis(1500));
+
+    let mut serve = std::process::Command::new(assert_cmd::cargo::cargo_bin("pend"))
+        .env("PEND_DIR", tmp.path())
+        .args(["serve", "--bind", "127.0.0.1:0", "--token", "s3cret"])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let started = Instant::now();
+    while tmp.path().join("throwaway.json").exists() && started.elapsed() < Duration::from_secs(10)
+    {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let _ = serve.kill();
+    let _ = serve.wait();
+    assert!(!tmp.path().join("throwaway.json").exists());
+}
+
+#[test]
+fn ttl_in_days() {
+    let tmp = TempDir::new().unwrap();
+    run(&tmp, &["--expires-in", "7d", "weekly", "true"]);
+    pend(&tmp).args(["wait", "weekly"]).assert().success();
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("weekly.json")).unwrap()).unwrap();
+    assert_eq!(meta["expires_in"], 7 * 24 * 60 * 60);
+    pend(&tmp)
+        .args(["status", "weekly"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("exit 0, expires in 167h"));
+}
+
+#[test]
+fn rejects_a_zero_ttl() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--expires-in", "0s", "job", "true"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("duration must be positive"));
+    pend(&tmp)
+        .args(["clean", "--expired", "job"])
+        .assert()
+        .code(2);
+}
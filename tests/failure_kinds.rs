@@ -0,0 +1,76 @@
+//! The worker records why a job failed; `pend status --failed --reason`
+//! filters on it.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn failure(tmp: &TempDir, job: &str) -> serde_json::Value {
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap())
+            .unwrap();
+    meta["failure"].clone()
+}
+
+#[test]
+fn classifies_failures() {
+    let tmp = TempDir::new().unwrap();
+    let jobs: [&[&str]; 5] = [
+        &["fine", "true"],
+        &["exit3", "sh", "-c", "exit 3"],
+        &["--timeout", "1", "slow", "sleep", "30"],
+        &["crash", "sh", "-c", "kill -SEGV $$"],
+        &["missing", "/nonexistent/program"],
+    ];
+    for args in jobs {
+        pend(&tmp).arg("do").args(args).assert().success();
+    }
+    for job in ["fine", "exit3", "slow", "crash", "missing"] {
+        pend(&tmp).args(["wait", job]).assert();
+    }
+
+    assert!(failure(&tmp, "fine").is_null());
+    assert_eq!(
+        failure(&tmp, "exit3"),
+        serde_json::json!({"kind": "nonzero_exit"})
+    );
+    assert_eq!(
+        failure(&tmp, "slow"),
+        serde_json::json!({"kind": "timed_out"})
+    );
+    assert_eq!(
+        failure(&tmp, "crash"),
+        serde_json::json!({"kind": "killed_by_signal", "signal": 11})
+    );
+    assert_eq!(
+        failure(&tmp, "missing"),
+        serde_json::json!({"kind": "spawn_failed"})
+    );
+
+    pend(&tmp)
+        .args(["status", "--failed"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("exit3")
+                .and(predicate::str::contains("slow"))
+                .and(predicate::str::contains("killed by signal 11"))
+                .and(predicate::str::contains("fine").not()),
+        );
+    pend(&tmp)
+        .args(["status", "--reason", "timed_out"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("^slow +exit [0-9]+ \\(timed out\\)\n$").unwrap());
+    pend(&tmp)
+        .args(["status", "--reason", "bogus"])
+        .assert()
+        .code(2);
+}
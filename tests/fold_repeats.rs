@@ -0,0 +1,37 @@
+//! `pend wait --fold-repeats` collapses runs of identical lines.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn folds_runs_of_identical_lines() {
+    let tmp = TempDir::new().unwrap();
+    let script = "echo start; for i in $(seq 500); do echo retrying; done; \
+                  echo between; echo between; echo done; printf tail; printf tail";
+    pend(&tmp)
+        .args(["do", "loop", "sh", "-c", script])
+        .assert()
+        .success();
+
+    let out = pend(&tmp)
+        .args(["wait", "--fold-repeats", "loop"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8(out.stdout).unwrap().starts_with(
+        "start\nretrying\n… last line repeated 499 times\nbetween\n\
+         … last line repeated once\ndone\ntailtail"
+    ));
+
+    // Without the flag every line is replayed.
+    let out = pend(&tmp).args(["wait", "loop"]).output().unwrap();
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout.matches("retrying\n").count(), 500);
+}
@@ -0,0 +1,67 @@
+//! `pend wait --heartbeat` keeps printing while jobs are silent.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn silent_jobs_get_still_waiting_lines() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "quiet", "sleep", "3"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "loud", "echo", "hi"])
+        .assert()
+        .success();
+
+    let out = pend(&tmp)
+        .args(["--no-color", "wait", "--heartbeat", "1s", "quiet", "loud"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let beats: Vec<&str> = stderr
+        .lines()
+        .filter(|l| l.starts_with("pend: still waiting: "))
+        .collect();
+    assert!(beats.len() >= 2, "{stderr}");
+    assert!(
+        beats
+            .iter()
+            .all(|l| l.contains("quiet (") && !l.contains("loud")),
+        "{stderr}"
+    );
+    // Heartbeats go to stderr only.
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("still waiting"));
+}
+
+#[test]
+fn no_heartbeat_by_default_and_bad_intervals_rejected() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "brief", "sleep", "1"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "brief"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("still waiting").not());
+
+    pend(&tmp)
+        .args(["wait", "--heartbeat", "0", "brief"])
+        .assert()
+        .code(2);
+    pend(&tmp)
+        .args(["wait", "--heartbeat", "5w", "brief"])
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,84 @@
+//! Hook scripts in `<jobs root>/hooks/` run at lifecycle points.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env("PEND_CONFIG", tmp.path().join("config.toml"));
+    cmd
+}
+
+/// Hooks for every event that append their context to `events.txt`.
+fn install_hooks(dir: &Path, record: &Path) {
+    std::fs::create_dir_all(dir).unwrap();
+    for event in ["pre-start", "on-retry", "post-success", "post-failure.sh"] {
+        let hook = dir.join(event);
+        std::fs::write(
+            &hook,
+            format!(
+                "#!/bin/sh\necho \"$PEND_HOOK $PEND_JOB $PEND_ATTEMPT ${{PEND_EXIT_CODE:--}}\" >> '{}'\n",
+                record.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    // Not executable, so never run.
+    std::fs::write(dir.join("pre-start.sample"), "#!/bin/sh\nexit 1\n").unwrap();
+}
+
+fn events(record: &Path) -> Vec<String> {
+    std::fs::read_to_string(record)
+        .unwrap_or_default()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn hooks_run_at_lifecycle_points() {
+    let tmp = TempDir::new().unwrap();
+    let record = tmp.path().join("events.txt");
+    install_hooks(&tmp.path().join("hooks"), &record);
+
+    pend(&tmp).args(["do", "ok", "true"]).assert().success();
+    pend(&tmp).args(["wait", "ok"]).assert().success();
+    assert_eq!(events(&record), ["pre-start ok 1 -", "post-success ok 1 0"]);
+
+    std::fs::remove_file(&record).unwrap();
+    pend(&tmp)
+        .args(["do", "--retries", "1", "flaky", "sh", "-c", "exit 3"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "flaky"]).assert().code(3);
+    assert_eq!(
+        events(&record),
+        [
+            "pre-start flaky 1 -",
+            "on-retry flaky 2 3",
+            "post-failure flaky 2 3"
+        ]
+    );
+}
+
+#[test]
+fn config_can_move_the_hooks_dir() {
+    let tmp = TempDir::new().unwrap();
+    let record = tmp.path().join("events.txt");
+    let hooks = tmp.path().join("elsewhere");
+    install_hooks(&hooks, &record);
+    std::fs::write(
+        tmp.path().join("config.toml"),
+        format!("hooks_dir = {:?}\n", hooks.display().to_string()),
+    )
+    .unwrap();
+
+    pend(&tmp).args(["do", "ok", "true"]).assert().success();
+    pend(&tmp).args(["wait", "ok"]).assert().success();
+    assert_eq!(events(&record), ["pre-start ok 1 -", "post-success ok 1 0"]);
+}
@@ -0,0 +1,77 @@
+//! Every submission gets an ID, and artifacts of different runs mixed under
+//! one name are reported.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn submit(tmp: &TempDir, job: &str) -> String {
+    let out = pend(tmp)
+        .args(["do", "--json", job, "cargo", "--version"])
+        .output()
+        .unwrap();
+    let submission: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    pend(tmp).args(["wait", job]).assert().success();
+    submission["id"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn ids_are_recorded_and_mixups_warned_about() {
+    let tmp = TempDir::new().unwrap();
+    let id = submit(&tmp, "build");
+    let uuid =
+        regex::Regex::new("^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$")
+            .unwrap();
+    assert!(uuid.is_match(&id), "{id}");
+
+    let file = |ext: &str| tmp.path().join(format!("build.{ext}"));
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(file("json")).unwrap()).unwrap();
+    assert_eq!(meta["id"], id.as_str());
+    let result = std::fs::read_to_string(file("result")).unwrap();
+    assert!(result.contains(&format!(" id={id}")), "{result}");
+    let idx = std::fs::read_to_string(file("idx")).unwrap();
+    assert_eq!(idx, format!("# job {id}\n"));
+
+    pend(&tmp).args(["validate", "build"]).assert().success();
+    pend(&tmp)
+        .args(["status", "build"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+
+    // A re-run gets a new ID.
+    let old_idx = idx;
+    let new_id = submit(&tmp, "build");
+    assert_ne!(new_id, id);
+
+    // Another run's index put in place is noticed, not replayed silently.
+    std::fs::write(file("idx"), old_idx).unwrap();
+    let warning = format!("its log index is from job {id}, not {new_id}");
+    pend(&tmp)
+        .args(["wait", "build"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "mixes artifacts of different runs",
+        ))
+        .stderr(predicate::str::contains(warning.clone()));
+    pend(&tmp)
+        .args(["status", "build"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(warning.clone()));
+    let out = pend(&tmp)
+        .args(["status", "--json", "build"])
+        .output()
+        .unwrap();
+    let status: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(status[0]["id"], new_id.as_str());
+    assert_eq!(status[0]["mixed"], warning.as_str());
+}
@@ -0,0 +1,61 @@
+//! `pend wait @FILE` / `@-` take job names from a list.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn waits_on_names_from_file() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "one", "echo", "first"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "two", "echo", "second"])
+        .assert()
+        .success();
+
+    let list = tmp.path().join("jobs.txt");
+    std::fs::write(
+        &list,
+        "# submitted by the build script\none\n\ntwo  # again\none\n",
+    )
+    .unwrap();
+    pend(&tmp)
+        .args(["--no-color", "wait", "--summary-table"])
+        .arg(format!("@{}", list.display()))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first"))
+        .stdout(predicate::str::contains("second"))
+        .stdout(predicate::str::contains("first").count(1));
+}
+
+#[test]
+fn reads_names_from_stdin() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "a", "true"]).assert().success();
+    pend(&tmp).args(["do", "b", "false"]).assert().success();
+    pend(&tmp)
+        .args(["wait", "@-"])
+        .write_stdin("a\nb\n")
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn missing_list_is_a_usage_error() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["wait", "@does-not-exist.txt"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("does-not-exist.txt"));
+}
@@ -0,0 +1,68 @@
+//! `%last`, `%-` and `%N` refer to the latest jobs of the terminal session,
+//! falling back to the user's.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir, session: &str) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env("PEND_SHELL_PID", session);
+    cmd
+}
+
+fn submit(tmp: &TempDir, session: &str, job: &str) {
+    pend(tmp, session)
+        .args(["do", job, "cargo", "--version"])
+        .assert()
+        .success();
+    pend(tmp, session).args(["wait", job]).assert().success();
+}
+
+fn resolves(tmp: &TempDir, session: &str, reference: &str, job: &str) {
+    pend(tmp, session)
+        .args(["status", reference])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!("{job} ")));
+}
+
+#[test]
+fn references_follow_the_session_history() {
+    let tmp = TempDir::new().unwrap();
+    let me = std::process::id().to_string();
+    for job in ["first", "second", "third"] {
+        submit(&tmp, &me, job);
+    }
+
+    pend(&tmp, &me)
+        .args(["--no-color", "wait", "%last"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("third"));
+    resolves(&tmp, &me, "%%", "third");
+    resolves(&tmp, &me, "%-", "second");
+    resolves(&tmp, &me, "%3", "first");
+    pend(&tmp, &me)
+        .args(["status", "%4"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("fewer than 4"));
+    pend(&tmp, &me)
+        .args(["status", "%later"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown job reference"));
+
+    // A session that submitted nothing sees the user's latest job; once it
+    // submits one, that is its own while this session keeps its history.
+    resolves(&tmp, "1", "%last", "third");
+    submit(&tmp, "1", "other");
+    resolves(&tmp, "1", "%last", "other");
+    resolves(&tmp, &me, "%last", "third");
+
+    // Cleaned jobs drop out of the history.
+    pend(&tmp, &me).args(["clean", "%last"]).assert().success();
+    resolves(&tmp, &me, "%last", "second");
+}
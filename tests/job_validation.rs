@@ -15,7 +15,7 @@ fn rejects_leading_dot() {
     let (_tmp, mut cmd) = pend_with_tmp();
     cmd.args(["do", ".hidden", "echo", "oops"])
         .assert()
-        .failure()
+        .code(2)
         .stderr(predicate::str::contains("job name"));
 }
 
@@ -0,0 +1,77 @@
+//! The worker keeps `<job>.status` up to date at each state transition.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn live_status(tmp: &TempDir, job: &str) -> String {
+    std::fs::read_to_string(tmp.path().join(format!("{job}.status")))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+#[cfg(unix)]
+#[test]
+fn reports_attempts_and_the_final_outcome() {
+    use predicates::prelude::*;
+    use std::time::{Duration, Instant};
+
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "slow",
+            "--timeout",
+            "1",
+            "--retries",
+            "2",
+            "sleep",
+            "5",
+        ])
+        .assert()
+        .success();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while live_status(&tmp, "slow") != "running attempt 2/3" {
+        assert!(Instant::now() < deadline, "never saw the second attempt");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    pend(&tmp)
+        .args(["status", "slow"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("running attempt 2/3 (pid "));
+    pend(&tmp)
+        .args(["status", "--json", "slow"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""activity": "running attempt 2/3""#,
+        ));
+
+    pend(&tmp).args(["wait", "slow"]).assert().code(137);
+    assert_eq!(live_status(&tmp, "slow"), "timed out (exit 137)");
+    // Finished jobs are described by their exit code alone.
+    pend(&tmp)
+        .args(["status", "--json", "slow"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("activity").not());
+}
+
+#[test]
+fn plain_job_runs_and_finishes() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "quick", "true"]).assert().success();
+    pend(&tmp).args(["wait", "quick"]).assert().success();
+    assert_eq!(live_status(&tmp, "quick"), "finished (exit 0)");
+
+    pend(&tmp).args(["clean", "quick"]).assert().success();
+    assert!(!tmp.path().join("quick.status").exists());
+}
@@ -0,0 +1,77 @@
+//! `pend lock` / `pend unlock`: named locks for scripts.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn runs_the_command_under_the_lock() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["lock", "db", "--", "sh", "-c", "echo inside; exit 3"])
+        .assert()
+        .code(3)
+        .stdout("inside\n");
+    // Released again.
+    pend(&tmp)
+        .args(["lock", "--no-wait", "db", "--", "true"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn holds_the_lock_until_unlock() {
+    for strategy in ["flock", "lockfile"] {
+        let tmp = TempDir::new().unwrap();
+        let pend = |tmp: &TempDir| {
+            let mut cmd = pend(tmp);
+            cmd.env("PEND_LOCK", strategy);
+            cmd
+        };
+        pend(&tmp).args(["lock", "deploy"]).assert().success();
+        pend(&tmp)
+            .args(["lock", "--no-wait", "deploy", "--", "true"])
+            .assert()
+            .code(4)
+            .stderr(predicate::str::contains("lock 'deploy' is held elsewhere"));
+
+        // A waiting `pend lock` gets its turn once the lock is released.
+        let waiter = std::process::Command::new(assert_cmd::cargo::cargo_bin("pend"))
+            .env("PEND_DIR", tmp.path())
+            .env("PEND_LOCK", strategy)
+            .args(["lock", "deploy", "--", "true"])
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        let started = Instant::now();
+        pend(&tmp).args(["unlock", "deploy"]).assert().success();
+        let out = waiter.wait_with_output().unwrap();
+        assert!(out.status.success(), "{strategy}");
+        assert!(started.elapsed() < Duration::from_secs(5), "{strategy}");
+
+        pend(&tmp)
+            .args(["unlock", "deploy"])
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("lock 'deploy' is not held"));
+    }
+}
+
+#[test]
+fn rejects_bad_names() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["lock", "../etc", "--", "true"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("invalid lock name"));
+}
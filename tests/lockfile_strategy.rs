@@ -0,0 +1,170 @@
+//! The `O_EXCL` lockfile strategy used on network filesystems, forced via
+//! `PEND_LOCK=lockfile` so it can be exercised on a local disk.
+
+use assert_cmd::cargo::CommandCargoExt;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path()).env("PEND_LOCK", "lockfile");
+    cmd
+}
+
+#[test]
+fn lockfile_rejects_duplicates_and_cleans_up() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["do", "shared", "bash", "-c", "sleep 1 && echo done"])
+        .assert()
+        .success();
+
+    // The lockfile records the owning host and PID. Give the worker a moment
+    // to take it over from the `pend do` front-end.
+    let lock = tmp.path().join("shared.lock");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    let mut owner = String::new();
+    while owner.is_empty() && std::time::Instant::now() < deadline {
+        owner = std::fs::read_to_string(&lock).unwrap_or_default();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert_eq!(owner.trim().split(' ').count(), 2, "owner: {owner:?}");
+
+    pend(&tmp)
+        .args(["do", "shared", "echo", "again"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("already running"));
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "shared"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("done"));
+
+    assert!(!lock.exists(), "lockfile still present after completion");
+}
+
+#[test]
+fn lockfile_of_dead_owner_is_taken_over() {
+    let tmp = TempDir::new().unwrap();
+
+    // Simulate a crashed owner on this host: a PID that cannot be alive.
+    let host = String::from_utf8(
+        std::process::Command::new("hostname")
+            .output()
+            .map(|o| o.stdout)
+            .unwrap_or_default(),
+    )
+    .unwrap();
+    if host.trim().is_empty() {
+        eprintln!("warning: skipping – `hostname` unavailable");
+        return;
+    }
+    std::fs::write(
+        tmp.path().join("stale.lock"),
+        format!("{} 4294967\n", host.trim()),
+    )
+    .unwrap();
+
+    pend(&tmp)
+        .args(["do", "stale", "echo", "recovered"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "stale"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("recovered"));
+}
+
+#[test]
+fn empty_lockfile_goes_by_its_age() {
+    let tmp = TempDir::new().unwrap();
+
+    // An owner that has created the lockfile but not yet written to it.
+    let lock = tmp.path().join("fresh.lock");
+    std::fs::write(&lock, "").unwrap();
+    pend(&tmp)
+        .args(["do", "fresh", "echo", "second"])
+        .assert()
+        .code(4);
+    assert!(lock.exists());
+
+    // Left empty for longer than a heartbeat could explain.
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(120);
+    std::fs::File::options()
+        .write(true)
+        .open(&lock)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+    pend(&tmp)
+        .args(["do", "fresh", "echo", "recovered"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "fresh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("recovered"));
+}
+
+#[test]
+fn only_one_of_simultaneous_submissions_wins() {
+    let tmp = TempDir::new().unwrap();
+
+    // `pend do` exits right after starting its worker, often before the
+    // worker adopted the lockfile: a contender must not find it abandoned
+    // then, start a worker of its own and leave the first one to die.
+    for round in 0..5 {
+        let job = format!("race-{round}");
+        let runs = tmp.path().join(format!("{job}.runs"));
+        // Staggered, so some arrive just as the first `pend do` exits.
+        let submitters: Vec<_> = (0..16)
+            .map(|i| {
+                let mut cmd = std::process::Command::cargo_bin("pend").unwrap();
+                cmd.env("PEND_DIR", tmp.path())
+                    .env("PEND_LOCK", "lockfile")
+                    .args(["do", &job, "--shell"])
+                    .arg(format!("echo run >> '{}'; sleep 1", runs.display()));
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(i));
+                    cmd.output().expect("run pend do")
+                })
+            })
+            .collect();
+        let outputs: Vec<_> = submitters.into_iter().map(|t| t.join().unwrap()).collect();
+
+        let won = outputs.iter().filter(|out| out.status.success()).count();
+        assert_eq!(won, 1, "{outputs:?}");
+        for out in outputs.iter().filter(|out| !out.status.success()) {
+            assert_eq!(out.status.code(), Some(4), "{out:?}");
+        }
+        pend(&tmp).args(["wait", &job]).assert().success();
+        assert_eq!(std::fs::read_to_string(&runs).unwrap(), "run\n");
+    }
+}
+
+#[test]
+fn handed_over_lockfile_names_the_worker() {
+    let tmp = TempDir::new().unwrap();
+    let out = pend(&tmp)
+        .args(["do", "--json", "handed", "sleep", "1"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    let desc: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let worker = desc["worker_pid"].as_u64().unwrap();
+
+    // Whether or not the worker adopted it yet, the lock is the worker's
+    // once `pend do` returned.
+    let owner = std::fs::read_to_string(tmp.path().join("handed.lock")).unwrap();
+    assert!(
+        owner.trim().ends_with(&format!(" {worker}")),
+        "owner: {owner:?}"
+    );
+    pend(&tmp).args(["wait", "handed"]).assert().success();
+}
@@ -0,0 +1,125 @@
+//! `--max-log-size` rotation of all three streams, with `--max-log-files`
+//! generations and optional gzip compression.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+/// Print 400 lines of 100 bytes each to both streams – more than four
+/// full read chunks, so even large reads rotate several times.
+const NOISY: &str =
+    r#"for i in $(seq 1 400); do printf '%099d\n' $i; printf '%099d\n' $i >&2; done"#;
+
+#[test]
+fn keeps_requested_generations_of_every_stream() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args([
+            "do",
+            "noisy",
+            "--max-log-size",
+            "1K",
+            "--max-log-files",
+            "3",
+        ])
+        .args(["bash", "-c", NOISY])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "noisy"]).assert().success();
+
+    for ext in ["out", "err", "log"] {
+        let current = tmp.path().join(format!("noisy.{ext}"));
+        // Rotation happens between reads, so a file may overshoot the limit
+        // by at most one read chunk.
+        assert!(
+            std::fs::metadata(&current).unwrap().len() <= 1024 + 8192,
+            "{ext} too big"
+        );
+        for n in 1..=3 {
+            let rotated = tmp.path().join(format!("noisy.{ext}.{n}"));
+            assert!(rotated.exists(), "missing {}", rotated.display());
+        }
+        assert!(!tmp.path().join(format!("noisy.{ext}.4")).exists());
+    }
+}
+
+#[test]
+fn compresses_rotated_generations() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["do", "gz", "--max-log-size", "1K", "--compress-logs"])
+        .args(["bash", "-c", NOISY])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "gz"]).assert().success();
+
+    let rotated = std::fs::read(tmp.path().join("gz.log.1.gz")).unwrap();
+    assert_eq!(&rotated[..2], &[0x1f, 0x8b], "not a gzip file");
+    assert!(!tmp.path().join("gz.log.1").exists());
+    assert!(!tmp.path().join("gz.log.2.gz").exists());
+
+    // `clean` removes compressed generations too.
+    pend(&tmp).args(["clean", "gz"]).assert().success();
+    assert!(std::fs::read_dir(tmp.path()).unwrap().next().is_none());
+}
+
+#[test]
+fn limit_is_recorded_and_honoured_by_retries() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["do", "flaky", "--max-log-size", "2K", "--retries", "1"])
+        .args(["bash", "-c", &format!("{NOISY}; exit 1")])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "flaky"]).assert().code(1);
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("flaky.json")).unwrap()).unwrap();
+    assert_eq!(meta["max_log_size"], 2048);
+    assert_eq!(meta["attempt"], 2);
+    // The second attempt appends 40 KB; it must have rotated as well.
+    let current = std::fs::metadata(tmp.path().join("flaky.out"))
+        .unwrap()
+        .len();
+    assert!(current <= 2048 + 8192, "second attempt ignored the limit");
+}
+
+#[test]
+fn log_ttl_drops_output_older_than_the_ttl() {
+    let tmp = TempDir::new().unwrap();
+
+    // One numbered line every 50ms for 4s, with a TTL of 2s: the logs rotate
+    // every half second and the first lines expire.
+    pend(&tmp)
+        .args(["do", "svc", "--log-ttl", "2s", "--max-log-files", "50"])
+        .args([
+            "bash",
+            "-c",
+            "for i in $(seq 1 80); do echo line-$i; sleep 0.05; done",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "svc"]).assert().success();
+
+    let kept: String = std::fs::read_dir(tmp.path())
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("svc.log"))
+        .map(|entry| std::fs::read_to_string(entry.path()).unwrap())
+        .collect();
+    assert!(kept.contains("line-80\n"), "{kept}");
+    assert!(!kept.contains("line-1\n"), "{kept}");
+    assert!(tmp.path().join("svc.log.1").exists());
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("svc.json")).unwrap()).unwrap();
+    assert_eq!(meta["log_ttl"], 2);
+}
@@ -0,0 +1,72 @@
+//! `pend map` submits one job per input line with bounded concurrency.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn one_job_per_line_and_a_glob_wait() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["map", "--name", "conv-{}", "--", "echo", "item={}"])
+        .write_stdin("alpha\nimg/beta.jpg\n\ngamma\n")
+        .assert()
+        .success()
+        .stdout("conv-alpha\nconv-img_beta.jpg\nconv-gamma\n");
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "conv-*"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("item=alpha")
+                .and(predicate::str::contains("item=img/beta.jpg"))
+                .and(predicate::str::contains("item=gamma")),
+        );
+}
+
+#[test]
+fn concurrency_is_bounded() {
+    let tmp = TempDir::new().unwrap();
+    let busy = tmp.path().join("busy");
+    let script = format!(
+        "test ! -e '{0}' && touch '{0}' && sleep 0.3 && rm '{0}'",
+        busy.display()
+    );
+
+    pend(&tmp)
+        .args([
+            "map",
+            "--name",
+            "serial-{#}",
+            "-j",
+            "1",
+            "sh",
+            "-c",
+            &script,
+        ])
+        .write_stdin("1\n2\n3\n")
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "serial-?"]).assert().success();
+}
+
+#[test]
+fn colliding_names_are_rejected() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["map", "--name", "same", "true"])
+        .write_stdin("a\nb\n")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("{#}"));
+    pend(&tmp).args(["status"]).assert().success().stdout("");
+}
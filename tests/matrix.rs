@@ -0,0 +1,119 @@
+//! `pend matrix` starts one job per combination of a manifest's parameters.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path().join("jobs"));
+    cmd
+}
+
+fn manifest(tmp: &TempDir, text: &str) -> std::path::PathBuf {
+    let path = tmp.path().join("matrix.toml");
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn starts_a_job_per_combination() {
+    let tmp = TempDir::new().unwrap();
+    let path = manifest(
+        &tmp,
+        r#"
+name = "build-{os}-{opt}"
+cmd = "echo $OS_NAME {opt}"
+exclude = [{ os = "mac", opt = 3 }]
+
+[matrix]
+os = ["linux", "mac"]
+opt = [0, 3]
+
+[env]
+OS_NAME = "os={os}"
+"#,
+    );
+
+    let out = pend(&tmp).arg("matrix").arg(&path).output().unwrap();
+    assert!(out.status.success());
+    let names = String::from_utf8(out.stdout).unwrap();
+    // Parameters in alphabetical order, the last one varying fastest.
+    assert_eq!(names, "build-linux-0\nbuild-mac-0\nbuild-linux-3\n");
+
+    // The printed names are the group to wait on.
+    pend(&tmp)
+        .args(["wait", "@-"])
+        .write_stdin(names)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("os=linux 0\n"))
+        .stdout(predicate::str::contains("os=linux 3\n"))
+        .stdout(predicate::str::contains("os=mac 0\n"));
+    assert!(!tmp.path().join("jobs/build-mac-3.json").exists());
+}
+
+#[test]
+fn dry_run_lists_the_jobs() {
+    let tmp = TempDir::new().unwrap();
+    let path = manifest(
+        &tmp,
+        r#"
+name = "shard"
+cmd = ["./run", "--shard", "{n}/2", "--target", "{target}"]
+
+[matrix]
+target = ["x86_64/linux"]
+n = [1, 2]
+"#,
+    );
+    pend(&tmp)
+        .args(["matrix", "--dry-run"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(
+            "shard-1-x86_64_linux: ./run --shard 1/2 --target x86_64/linux\n\
+             shard-2-x86_64_linux: ./run --shard 2/2 --target x86_64/linux\n",
+        );
+    assert!(!tmp.path().join("jobs").exists());
+}
+
+#[test]
+fn rejects_clashing_names_before_starting_anything() {
+    let tmp = TempDir::new().unwrap();
+    let path = manifest(
+        &tmp,
+        r#"
+name = "job-{a}"
+cmd = "true"
+
+[matrix]
+a = [1, 2]
+b = ["x", "y"]
+"#,
+    );
+    pend(&tmp)
+        .arg("matrix")
+        .arg(&path)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "gives several combinations the job name 'job-1'",
+        ));
+    assert!(!tmp.path().join("jobs/job-1.json").exists());
+
+    let path = manifest(
+        &tmp,
+        "name = \"j\"\ncmd = \"true\"\nexclude = [{ c = 1 }]\n[matrix]\na = [1]\n",
+    );
+    pend(&tmp)
+        .arg("matrix")
+        .arg(&path)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "'c', which is not a matrix parameter",
+        ));
+}
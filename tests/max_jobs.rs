@@ -0,0 +1,82 @@
+//! `pend do --max-jobs N` refuses to start more than `N` jobs at once, or
+//! waits for a place with `--queue`.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn refuses_jobs_beyond_the_cap() {
+    let tmp = TempDir::new().unwrap();
+    for job in ["a", "b"] {
+        pend(&tmp)
+            .args(["do", "--max-jobs", "2", job, "sleep", "30"])
+            .assert()
+            .success();
+    }
+    pend(&tmp)
+        .args(["do", "--max-jobs", "2", "c", "true"])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains(
+            "2 jobs are running, the limit is 2 (--max-jobs)",
+        ));
+    assert!(!tmp.path().join("c.json").exists());
+
+    // Finished jobs do not count.
+    pend(&tmp).args(["kill", "a"]).assert().success();
+    pend(&tmp).args(["wait", "a"]).assert().failure();
+    pend(&tmp)
+        .args(["do", "--max-jobs", "2", "c", "true"])
+        .assert()
+        .success();
+    pend(&tmp).args(["kill", "b"]).assert().success();
+}
+
+#[test]
+fn queue_waits_for_a_running_job() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "first", "sleep", "1"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "--max-jobs", "1", "--queue", "second", "true"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("'second' waits for one to finish"));
+    // `second` only started once `first` had finished.
+    assert!(tmp.path().join("first.exit").exists());
+    pend(&tmp).args(["wait", "second"]).assert().success();
+}
+
+#[test]
+fn profile_sets_the_cap() {
+    let tmp = TempDir::new().unwrap();
+    let config = tmp.path().join("config.toml");
+    std::fs::write(&config, "[profiles.shared]\nmax_jobs = 1\n").unwrap();
+    let with_profile = || {
+        let mut cmd = pend(&tmp);
+        cmd.env("PEND_CONFIG", &config)
+            .args(["--profile", "shared"]);
+        cmd
+    };
+    with_profile()
+        .args(["do", "one", "sleep", "30"])
+        .assert()
+        .success();
+    with_profile().args(["do", "two", "true"]).assert().code(6);
+    // The command line wins over the profile.
+    with_profile()
+        .args(["do", "--max-jobs", "2", "two", "true"])
+        .assert()
+        .success();
+    pend(&tmp).args(["kill", "one"]).assert().success();
+}
@@ -0,0 +1,88 @@
+//! `pend merge` combines job logs into one prefixed file.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn merges_logs_with_prefixes() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "second",
+            "sh",
+            "-c",
+            "echo two; sleep 0.2; echo oops >&2",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "second"]).assert().success();
+    pend(&tmp)
+        .args(["do", "first-job", "printf", "one\\nno newline"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "first-job"]).assert().success();
+
+    let merged = tmp.path().join("merged.txt");
+    pend(&tmp)
+        .args(["merge", "first-job", "second", "--output"])
+        .arg(&merged)
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(&merged).unwrap(),
+        "first-job | one\nfirst-job | no newline\nsecond    | two\nsecond    ! oops\n"
+    );
+
+    let out = pend(&tmp)
+        .args(["merge", "--by-start", "first-job", "second"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .starts_with("second    | two\n"));
+}
+
+#[test]
+fn unknown_job_is_not_found() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["merge", "nope"]).assert().code(3);
+}
+
+#[test]
+fn merges_large_finished_logs() {
+    let tmp = TempDir::new().unwrap();
+    // Over a MiB, so the finished log is mapped where the build allows it.
+    pend(&tmp)
+        .args(["do", "--shell", "big"])
+        .arg("seq 1 200000; sleep 0.2; echo oops >&2; sleep 0.2; seq 1 200000")
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "big"]).assert().success();
+
+    let out = pend(&tmp).args(["merge", "big"]).output().unwrap();
+    assert!(out.status.success());
+    let merged = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(merged.lines().count(), 400_001);
+    assert_eq!(
+        merged.lines().filter(|l| l.starts_with("big ! ")).count(),
+        1
+    );
+    assert!(merged.contains("big | 200000\nbig ! oops\nbig | 1\n"));
+
+    // Truncating afterwards leaves an empty log to merge.
+    pend(&tmp).args(["truncate", "big"]).assert().success();
+    pend(&tmp)
+        .args(["merge", "big"])
+        .assert()
+        .success()
+        .stdout("");
+}
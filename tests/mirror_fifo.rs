@@ -0,0 +1,75 @@
+//! `pend do --mirror-fifo` copies the output to a FIFO without ever making
+//! the job wait for its reader.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::io::Read;
+use std::os::unix::fs::FileTypeExt;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn a_reader_gets_the_output_live() {
+    let tmp = TempDir::new().unwrap();
+    let fifo = tmp.path().join("build.fifo");
+    // An existing FIFO is used as it is.
+    assert!(std::process::Command::new("mkfifo")
+        .arg(&fifo)
+        .status()
+        .unwrap()
+        .success());
+
+    // Blocks until the worker opens its end and ends when the worker closes
+    // it.
+    let reader = {
+        let fifo = fifo.clone();
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            std::fs::File::open(&fifo)
+                .unwrap()
+                .read_to_string(&mut text)
+                .unwrap();
+            text
+        })
+    };
+    pend(&tmp)
+        .args(["do", "--mirror-fifo"])
+        .arg(&fifo)
+        .args([
+            "build",
+            "sh",
+            "-c",
+            "sleep 0.5; echo compiling; echo 'warning: unused' >&2; sleep 0.2; echo done",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "build"]).assert().success();
+    assert_eq!(reader.join().unwrap(), "compiling\nwarning: unused\ndone\n");
+}
+
+#[test]
+fn no_reader_never_blocks_the_job() {
+    let tmp = TempDir::new().unwrap();
+    let fifo = tmp.path().join("nobody.fifo");
+    for policy in ["drop", "buffer"] {
+        let started = Instant::now();
+        pend(&tmp)
+            .args(["do", "--mirror-fifo"])
+            .arg(&fifo)
+            .args(["--mirror-policy", policy, "flood", "seq", "1", "200000"])
+            .assert()
+            .success();
+        pend(&tmp).args(["wait", "flood"]).assert().success();
+        assert!(started.elapsed() < Duration::from_secs(30));
+        // Created by `pend do`.
+        assert!(std::fs::metadata(&fifo).unwrap().file_type().is_fifo());
+        let out = std::fs::read_to_string(tmp.path().join("flood.out")).unwrap();
+        assert_eq!(out.lines().count(), 200000);
+    }
+}
@@ -0,0 +1,40 @@
+//! Command arguments that are not valid UTF-8 reach the job unchanged.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn latin1_file_name_round_trips() {
+    let tmp = TempDir::new().unwrap();
+    let name = OsStr::from_bytes(b"caf\xe9.txt");
+    let file = tmp.path().join(name);
+    std::fs::write(&file, "contents\n").unwrap();
+
+    pend(&tmp)
+        .args(["do", "read", "cat"])
+        .arg(&file)
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "echo", "printf", "%s"])
+        .arg(name)
+        .assert()
+        .success();
+
+    let out = pend(&tmp).args(["wait", "read"]).output().unwrap();
+    assert!(out.status.success());
+    assert!(out.stdout.starts_with(b"contents\n"));
+
+    let out = pend(&tmp).args(["wait", "echo"]).output().unwrap();
+    assert!(out.status.success());
+    assert!(out.stdout.starts_with(b"caf\xe9.txt"));
+}
@@ -0,0 +1,91 @@
+//! `pend do --note` and `pend annotate` describe jobs in their metadata.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn note_in_meta(tmp: &TempDir, job: &str) -> Option<String> {
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap())
+            .unwrap();
+    meta.get("note").and_then(|n| n.as_str()).map(String::from)
+}
+
+#[test]
+fn note_from_submission_and_annotate() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "deploy",
+            "--note",
+            "nightly deploy for release 1.4",
+            "true",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "deploy"]).assert().success();
+    assert_eq!(
+        note_in_meta(&tmp, "deploy").as_deref(),
+        Some("nightly deploy for release 1.4")
+    );
+    pend(&tmp)
+        .args(["status", "deploy"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("– nightly deploy for release 1.4"));
+
+    pend(&tmp)
+        .args(["annotate", "deploy", "rolled back"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["status", "--json", "deploy"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""note": "rolled back""#));
+
+    pend(&tmp)
+        .args(["annotate", "deploy", ""])
+        .assert()
+        .success();
+    assert_eq!(note_in_meta(&tmp, "deploy"), None);
+
+    pend(&tmp)
+        .args(["annotate", "ghost", "hi"])
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn annotation_survives_worker_updates() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "flaky",
+            "--retries",
+            "2",
+            "bash",
+            "-c",
+            "sleep 1; exit 1",
+        ])
+        .assert()
+        .success();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    pend(&tmp)
+        .args(["annotate", "flaky", "investigating"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "flaky"]).assert().code(1);
+    assert_eq!(
+        note_in_meta(&tmp, "flaky").as_deref(),
+        Some("investigating")
+    );
+}
@@ -0,0 +1,31 @@
+//! `pend wait --pager` only pages a terminal.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn redirected_output_is_not_paged() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "job", "echo", "hello"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "job"]).assert().success();
+
+    let out = pend(&tmp)
+        .env("PAGER", "sed s/^/paged:/")
+        .args(["wait", "--pager", "job"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .starts_with("hello\n"));
+}
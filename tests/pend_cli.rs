@@ -101,9 +101,47 @@ fn dir_flag_overrides_env() {
     assert!(!env_dir.path().join("flagjob.out").exists());
 }
 
+#[cfg(unix)]
+#[test]
+fn dir_flag_reaches_the_command() {
+    // Nested `pend` calls inside a job must see the same jobs directory.
+    let env_dir = TempDir::new().unwrap();
+    let flag_dir = TempDir::new().unwrap();
+    let flag = flag_dir.path().to_str().unwrap();
+
+    pend_bin()
+        .env("PEND_DIR", env_dir.path())
+        .args([
+            "--dir",
+            flag,
+            "do",
+            "nested",
+            "sh",
+            "-c",
+            "echo \"$PEND_DIR\"",
+        ])
+        .assert()
+        .success();
+    pend_bin()
+        .env("PEND_DIR", env_dir.path())
+        .args(["--dir", flag, "wait", "nested"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!("{flag}\n")));
+}
+
 /// Spawn two background jobs that finish at different times and wait for both
 /// of them simultaneously. The helper should replay *both* job logs and exit
 /// with the non-zero status code of the first failing job that finishes.
+#[test]
+fn wait_without_jobs_needs_a_terminal() {
+    let (_tmp, mut cmd) = pend_with_tempdir();
+    cmd.arg("wait")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("name the jobs to wait for"));
+}
+
 #[test]
 fn multi_job_interleaved_wait() {
     // Dedicated temporary jobs directory for test isolation.
@@ -151,3 +189,53 @@ fn multi_job_interleaved_wait() {
         .code(2)
         .failure();
 }
+
+#[test]
+fn wait_routes_stderr_to_stderr() {
+    let (tmp, mut cmd) = pend_with_tempdir();
+
+    cmd.args([
+        "do",
+        "streams",
+        "bash",
+        "-c",
+        "echo to-out; echo to-err >&2; echo more-out",
+    ])
+    .assert()
+    .success();
+
+    pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args(["--no-color", "wait", "streams"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("to-out\nmore-out\n"))
+        .stdout(predicate::str::contains("to-err").not())
+        .stderr(predicate::str::contains("to-err\n"));
+}
+
+#[test]
+fn metadata_records_output_checksums() {
+    let (tmp, mut cmd) = pend_with_tempdir();
+
+    cmd.args(["do", "sums", "bash", "-c", "printf hello; printf oops >&2"])
+        .assert()
+        .success();
+    pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args(["wait", "sums"])
+        .assert()
+        .success();
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("sums.json")).unwrap()).unwrap();
+    // sha256("hello") and sha256("oops")
+    assert_eq!(
+        meta["out_sha256"],
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+    assert_eq!(
+        meta["err_sha256"],
+        "d13f2eadd4ed5b027fa773a29520cc0d65ce374365d641112de786f8a029c2fe"
+    );
+}
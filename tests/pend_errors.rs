@@ -0,0 +1,69 @@
+//! Failures of pend itself are reported distinctly from job failures.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn usage_errors_exit_2() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "solo", "--after", "solo", "true"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot run after itself"));
+    pend(&tmp)
+        .args(["clean", "--all"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("no jobs to clean"));
+}
+
+#[cfg(unix)]
+#[test]
+fn vanished_worker_is_detected() {
+    use std::time::{Duration, Instant};
+
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "doomed", "sleep", "30"])
+        .assert()
+        .success();
+
+    // Wait for the worker to record itself, then take it down hard.
+    let meta_path = tmp.path().join("doomed.json");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let meta = loop {
+        let meta = std::fs::read(&meta_path)
+            .ok()
+            .and_then(|b| serde_json::from_slice::<serde_json::Value>(&b).ok())
+            .filter(|m| m.get("pid").is_some_and(|p| !p.is_null()));
+        if let Some(meta) = meta {
+            break meta;
+        }
+        assert!(Instant::now() < deadline, "worker never started");
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    for field in ["worker_pid", "pid"] {
+        let pid = meta[field].as_u64().unwrap().to_string();
+        std::process::Command::new("kill")
+            .args(["-9", &pid])
+            .status()
+            .unwrap();
+    }
+
+    pend(&tmp)
+        .args(["wait", "doomed"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .code(125)
+        .stderr(predicate::str::contains(
+            "died without recording an exit code",
+        ));
+}
@@ -0,0 +1,62 @@
+//! `PEND_PER_USER` namespaces artifacts under `$PEND_DIR/<user>/`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path()).env("PEND_PER_USER", "1");
+    cmd
+}
+
+#[test]
+fn artifacts_live_in_the_user_directory() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "build", "echo", "hi"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "build"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("hi\n"));
+
+    let dirs: Vec<_> = std::fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(dirs.len(), 1, "{dirs:?}");
+    assert!(dirs[0].is_dir());
+    assert!(dirs[0].join("build.exit").exists());
+
+    // Another user's job of the same name does not interfere.
+    let other = tmp.path().join("someone-else");
+    std::fs::create_dir(&other).unwrap();
+    std::fs::write(other.join("build.exit"), "3\n").unwrap();
+    pend(&tmp)
+        .args(["status", "build"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("exit 0"));
+
+    let me = dirs[0].file_name().unwrap().to_string_lossy().into_owned();
+    pend(&tmp)
+        .args(["status", "--all-users"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{me}/build")))
+        .stdout(predicate::str::contains("someone-else/build"));
+}
+
+#[test]
+fn all_users_requires_per_user_layout() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .env_remove("PEND_PER_USER")
+        .args(["status", "--all-users"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("PEND_PER_USER"));
+}
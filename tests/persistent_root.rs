@@ -0,0 +1,42 @@
+//! `--persistent` / `PEND_PERSISTENT` keep artifacts in the XDG state dir.
+#![cfg(all(unix, not(target_os = "macos")))]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(state: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env_remove("PEND_DIR")
+        .env_remove("PEND_PERSISTENT")
+        .env("XDG_STATE_HOME", state.path());
+    cmd
+}
+
+#[test]
+fn persistent_flag_and_env_use_the_state_dir() {
+    let state = TempDir::new().unwrap();
+    pend(&state)
+        .args(["--persistent", "do", "overnight", "true"])
+        .assert()
+        .success();
+    pend(&state)
+        .args(["--persistent", "wait", "overnight"])
+        .assert()
+        .success();
+    assert!(state.path().join("pend/overnight.exit").exists());
+
+    pend(&state)
+        .env("PEND_PERSISTENT", "1")
+        .args(["status", "overnight"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn persistent_conflicts_with_dir() {
+    let state = TempDir::new().unwrap();
+    pend(&state)
+        .args(["--persistent", "--dir", "/tmp/x", "status"])
+        .assert()
+        .code(2);
+}
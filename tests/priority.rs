@@ -0,0 +1,69 @@
+//! `pend do --nice/--ionice`.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn nice_lowers_command_priority() {
+    let tmp = TempDir::new().unwrap();
+    let base: i32 = String::from_utf8(std::process::Command::new("nice").output().unwrap().stdout)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+
+    pend(&tmp)
+        .args(["do", "--nice", "5", "niced", "nice"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "niced"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "{}\n",
+            (base + 5).min(19)
+        )));
+}
+
+#[test]
+fn invalid_ionice_is_rejected() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--ionice", "best-effort:9", "io", "true"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("0-7"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn ionice_idle_is_applied() {
+    if std::process::Command::new("ionice")
+        .arg("-p")
+        .arg("1")
+        .output()
+        .is_err()
+    {
+        eprintln!("warning: skipping – `ionice` unavailable");
+        return;
+    }
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--ionice", "idle", "io", "bash", "-c", "ionice -p $$"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "io"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("idle\n"));
+}
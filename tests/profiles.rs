@@ -0,0 +1,84 @@
+//! `--profile` / `PEND_PROFILE` apply defaults from the config file.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let config = tmp.path().join("config.toml");
+    let jobs = tmp.path().join("ci-jobs");
+    std::fs::write(
+        &config,
+        format!(
+            "[profiles.ci]\ndir = {:?}\ntimeout = 1\ncolor = \"never\"\n\n[profiles.laptop]\n",
+            jobs.display().to_string()
+        ),
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_CONFIG", config)
+        .env_remove("PEND_DIR")
+        .env_remove("PEND_PROFILE");
+    cmd
+}
+
+#[test]
+fn profile_sets_jobs_dir_and_timeout() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["--profile", "ci", "do", "slow", "sleep", "30"])
+        .assert()
+        .success();
+
+    pend(&tmp)
+        .env("PEND_PROFILE", "ci")
+        .args(["wait", "slow"])
+        .assert()
+        .failure();
+    assert!(tmp.path().join("ci-jobs").join("slow.json").exists());
+    pend(&tmp)
+        .env("PEND_PROFILE", "ci")
+        .args(["timeline", "slow"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("killed by timeout"));
+}
+
+#[test]
+fn command_line_wins_over_profile() {
+    let tmp = TempDir::new().unwrap();
+    let own = tmp.path().join("own");
+    pend(&tmp)
+        .arg("--dir")
+        .arg(&own)
+        .args([
+            "--profile",
+            "ci",
+            "do",
+            "--timeout",
+            "60",
+            "quick",
+            "echo",
+            "hi",
+        ])
+        .assert()
+        .success();
+    pend(&tmp)
+        .arg("--dir")
+        .arg(&own)
+        .args(["wait", "quick"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("hi"));
+    assert!(!tmp.path().join("ci-jobs").exists());
+}
+
+#[test]
+fn unknown_profile_is_a_usage_error() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["--profile", "nope", "status"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unknown profile 'nope'"));
+}
@@ -0,0 +1,98 @@
+//! Commands report progress via `::pend::progress` lines or
+//! `$PEND_PROGRESS_FILE`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn await_progress(tmp: &TempDir, job: &str, expected: &str) {
+    let path = tmp.path().join(format!("{job}.progress"));
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while std::fs::read_to_string(&path).unwrap_or_default().trim() != expected {
+        assert!(
+            Instant::now() < deadline,
+            "progress never became {expected}"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn marker_lines_update_status() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "etl",
+            "bash",
+            "-c",
+            "echo '::pend::progress 10%'; sleep 0.2; echo '::pend::progress 3/4' >&2; sleep 30",
+        ])
+        .assert()
+        .success();
+    await_progress(&tmp, "etl", "75%");
+
+    pend(&tmp)
+        .args(["status", "etl"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("75%"));
+    pend(&tmp)
+        .args(["status", "--json", "etl"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""progress": 75.0"#));
+    pend(&tmp)
+        .args([
+            "--no-color",
+            "wait",
+            "--heartbeat",
+            "1s",
+            "--for-output",
+            "nothing",
+            "etl",
+        ])
+        .timeout(Duration::from_secs(3))
+        .assert()
+        .stderr(
+            predicate::str::contains("still waiting: etl (").and(predicate::str::contains("75%)")),
+        );
+    pend(&tmp).args(["kill", "etl"]).assert().success();
+}
+
+#[test]
+fn progress_file_from_environment() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "load",
+            "bash",
+            "-c",
+            r#"echo 42 > "$PEND_PROGRESS_FILE"; sleep 30"#,
+        ])
+        .assert()
+        .success();
+    await_progress(&tmp, "load", "42");
+    pend(&tmp)
+        .args(["status", "load"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("42%"));
+    pend(&tmp).args(["kill", "load"]).assert().success();
+    pend(&tmp).args(["wait", "load"]).assert().failure();
+
+    // Only running jobs report progress.
+    pend(&tmp)
+        .args(["status", "--json", "load"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("progress").not());
+}
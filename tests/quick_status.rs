@@ -0,0 +1,69 @@
+//! `<job>.result` sums up a finished job in one line.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn fields(tmp: &TempDir, job: &str) -> Vec<(String, String)> {
+    std::fs::read_to_string(tmp.path().join(format!("{job}.result")))
+        .unwrap()
+        .split_whitespace()
+        .map(|field| {
+            let (key, value) = field.split_once('=').unwrap();
+            (key.to_string(), value.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn records_exit_code_time_and_attempts() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--retries",
+            "1",
+            "flaky",
+            "sh",
+            "-c",
+            "sleep 0.2; exit 3",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "flaky"]).assert().code(3);
+
+    let fields = fields(&tmp, "flaky");
+    let keys: Vec<&str> = fields.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["exit", "secs", "attempts", "id"]);
+    assert_eq!(fields[0].1, "3");
+    assert!(fields[1].1.parse::<f64>().unwrap() >= 0.4);
+    assert_eq!(fields[2].1, "2");
+
+    let out = pend(&tmp)
+        .args(["status", "--json", "flaky"])
+        .output()
+        .unwrap();
+    let status: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(status[0]["attempt"], 2);
+    assert!(status[0]["wall_secs"].as_f64().unwrap() >= 0.4);
+}
+
+#[test]
+fn records_the_killing_signal() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "doomed", "sh", "-c", "kill -9 $$"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "doomed"]).assert().code(137);
+
+    let fields = fields(&tmp, "doomed");
+    assert_eq!(fields[0], ("exit".into(), "137".into()));
+    assert_eq!(fields[3], ("signal".into(), "9".into()));
+}
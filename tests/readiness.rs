@@ -0,0 +1,142 @@
+//! Readiness probes (`--ready-cmd`, `--ready-port`), `pend wait --ready` and
+//! `--after` dependency ordering.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn meta(tmp: &TempDir, job: &str) -> serde_json::Value {
+    let bytes = std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[test]
+fn wait_ready_returns_once_probe_passes() {
+    let tmp = TempDir::new().unwrap();
+    let flag = tmp.path().join("up.flag");
+
+    pend(&tmp)
+        .args(["do", "svc", "--ready-cmd"])
+        .arg(format!("test -e '{}'", flag.display()))
+        .args(["bash", "-c"])
+        .arg(format!("sleep 1; touch '{}'; sleep 30", flag.display()))
+        .assert()
+        .success();
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "--ready", "svc"])
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓ svc – ready"));
+
+    assert!(!tmp.path().join("svc.exit").exists());
+    assert!(meta(&tmp, "svc")["ready"].is_string());
+    pend(&tmp).args(["kill", "svc"]).assert().success();
+}
+
+#[test]
+fn ready_port_probe() {
+    let tmp = TempDir::new().unwrap();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    pend(&tmp)
+        .args(["do", "db", "--ready-port", &port.to_string(), "sleep", "30"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "--ready", "db"])
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .success();
+    pend(&tmp).args(["kill", "db"]).assert().success();
+}
+
+#[test]
+fn wait_ready_fails_when_job_exits_first() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["do", "never", "--ready-cmd", "false", "true"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "--ready", "never"])
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("without becoming ready"));
+}
+
+#[test]
+fn after_waits_for_readiness_and_completion() {
+    let tmp = TempDir::new().unwrap();
+    let flag = tmp.path().join("up.flag");
+
+    pend(&tmp)
+        .args(["do", "server", "--ready-cmd"])
+        .arg(format!("test -e '{}'", flag.display()))
+        .args(["sleep", "30"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args([
+            "do",
+            "client",
+            "--after",
+            "server:ready",
+            "echo",
+            "connected",
+        ])
+        .assert()
+        .success();
+
+    std::thread::sleep(Duration::from_millis(700));
+    assert!(
+        meta(&tmp, "client").get("pid").is_none(),
+        "client started before its dependency was ready"
+    );
+
+    std::fs::write(&flag, "").unwrap();
+    pend(&tmp)
+        .args(["--no-color", "wait", "client"])
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("connected"));
+    pend(&tmp).args(["kill", "server"]).assert().success();
+
+    // A dependency that fails takes its dependants down with it.
+    pend(&tmp)
+        .args(["do", "broken", "false"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "next", "--after", "broken", "echo", "unreachable"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "next"])
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("dependency 'broken'"));
+}
+
+#[test]
+fn after_unknown_job_is_rejected() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "orphan", "--after", "ghost", "true"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("job 'ghost' not found"));
+}
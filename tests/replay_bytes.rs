@@ -0,0 +1,42 @@
+//! Replayed output must be byte-identical to what the command wrote when
+//! stdout is not a terminal, even for invalid UTF-8.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path()).env_remove("NO_COLOR");
+    cmd
+}
+
+#[test]
+fn multi_job_replay_is_binary_safe_when_piped() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args([
+            "do",
+            "bin",
+            "bash",
+            "-c",
+            r"printf 'a\xff\xfeb\n\xc3'; printf '\xa9\n'",
+        ])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "txt", "bash", "-c", "echo plain"])
+        .assert()
+        .success();
+
+    let out = pend(&tmp).args(["wait", "bin", "txt"]).output().unwrap();
+    assert!(out.status.success());
+
+    let needle: &[u8] = b"a\xff\xfeb\n\xc3\xa9\n";
+    assert!(
+        out.stdout.windows(needle.len()).any(|w| w == needle),
+        "raw bytes mangled: {:?}",
+        String::from_utf8_lossy(&out.stdout)
+    );
+    assert!(!out.stdout.contains(&0x1b), "escape codes in piped output");
+}
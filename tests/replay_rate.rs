@@ -0,0 +1,121 @@
+//! `pend wait --max-replay-rate` paces the replay, and a reader that goes
+//! away early ends the replay quietly.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::io::Read;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+/// Run `seq 1 <lines>` as job `name` to the end; returns its output.
+fn finished_job(tmp: &TempDir, name: &str, lines: u32) -> String {
+    pend(tmp)
+        .args(["do", name, "seq", "1", &lines.to_string()])
+        .assert()
+        .success();
+    pend(tmp).args(["wait", name]).assert().success();
+    (1..=lines).map(|n| format!("{n}\n")).collect()
+}
+
+#[test]
+fn limits_lines_per_second() {
+    let tmp = TempDir::new().unwrap();
+    let expected = finished_job(&tmp, "lines", 30);
+
+    // A second's worth right away, the remaining 20 lines over two more.
+    let started = Instant::now();
+    let out = pend(&tmp)
+        .args(["wait", "--max-replay-rate", "10lines", "lines"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .starts_with(&expected));
+    assert!(started.elapsed() >= Duration::from_millis(1800));
+}
+
+#[test]
+fn limits_bytes_per_second() {
+    let tmp = TempDir::new().unwrap();
+    // 3893 bytes: two seconds' worth at 2K, one of them allowed at once.
+    let expected = finished_job(&tmp, "bytes", 1000);
+
+    let started = Instant::now();
+    let out = pend(&tmp)
+        .args(["wait", "--max-replay-rate", "2K", "bytes"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .starts_with(&expected));
+    assert!(started.elapsed() >= Duration::from_millis(800));
+}
+
+#[test]
+fn rejects_a_zero_rate() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["wait", "--max-replay-rate", "0lines", "x"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("the rate must be above 0"));
+}
+
+/// Start `pend wait <args>` and close its stdout after the first bytes, like
+/// `| head -c 10`.
+fn wait_closing_stdout(tmp: &TempDir, args: &[&str]) -> std::process::Output {
+    let mut wait = std::process::Command::new(assert_cmd::cargo::cargo_bin("pend"))
+        .env("PEND_DIR", tmp.path())
+        .arg("wait")
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut first = [0u8; 10];
+    wait.stdout.take().unwrap().read_exact(&mut first).unwrap();
+    wait.wait_with_output().unwrap()
+}
+
+#[test]
+fn closed_stdout_still_waits_for_the_job() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "big", "seq 1 500000; sleep 1; exit 3"])
+        .assert()
+        .success();
+    let out = wait_closing_stdout(&tmp, &["big"]);
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&out.stderr), "");
+
+    // Successful jobs are still cleaned up.
+    finished_job(&tmp, "ok", 500_000);
+    let out = wait_closing_stdout(&tmp, &["--clean", "ok"]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(!tmp.path().join("ok.log").exists());
+}
+
+#[test]
+fn sigpipe_exits_at_once() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "big", "seq 1 500000; sleep 30"])
+        .assert()
+        .success();
+    let started = Instant::now();
+    let out = wait_closing_stdout(&tmp, &["--sigpipe", "big"]);
+    assert_eq!(out.status.code(), Some(141));
+    assert_eq!(String::from_utf8_lossy(&out.stderr), "");
+    assert!(started.elapsed() < Duration::from_secs(15));
+    pend(&tmp).args(["kill", "big"]).assert();
+}
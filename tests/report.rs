@@ -0,0 +1,107 @@
+//! `pend report --html` renders a standalone summary page.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn html_report_covers_every_job() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "good", "echo", "<b>fine</b>"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args([
+            "do",
+            "--retries",
+            "1",
+            "bad",
+            "sh",
+            "-c",
+            "echo broken; exit 3",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "good"]).assert().success();
+    pend(&tmp).args(["wait", "bad"]).assert().code(3);
+
+    let page = tmp.path().join("report.html");
+    pend(&tmp)
+        .args(["report", "--html", "--output"])
+        .arg(&page)
+        .assert()
+        .success();
+    let page = std::fs::read_to_string(&page).unwrap();
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("2 jobs: 1 succeeded, 1 failed"));
+    assert!(page.contains(r#"<span class="badge succeeded">succeeded</span>"#));
+    assert!(page.contains(r#"<span class="badge failed">failed</span>"#));
+    // Output is escaped, and failed jobs show their log expanded.
+    assert!(page.contains("&lt;b&gt;fine&lt;/b&gt;"));
+    assert!(page
+        .contains("<details open><summary>Log</summary>\n<pre>broken\n\n-- retry --\n\nbroken\n"));
+    assert_eq!(page.matches("<li>exit 3 after").count(), 2);
+
+    let out = pend(&tmp)
+        .args(["report", "--html", "good"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let page = String::from_utf8(out.stdout).unwrap();
+    assert!(page.contains("1 jobs: 1 succeeded"));
+    assert!(!page.contains("broken"));
+}
+
+#[test]
+fn report_needs_a_format() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["report"]).assert().code(2);
+}
+
+#[test]
+fn markdown_report_shows_stderr_of_failures() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "ok", "sh", "-c", "echo quiet >&2"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args([
+            "do",
+            "bad",
+            "sh",
+            "-c",
+            "for i in $(seq 30); do echo e$i >&2; done; exit 2",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "ok"]).assert().success();
+    pend(&tmp).args(["wait", "bad"]).assert().code(2);
+
+    let out = pend(&tmp).args(["report", "--markdown"]).output().unwrap();
+    assert!(out.status.success());
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(text.contains("2 jobs: 1 succeeded, 1 failed"));
+    assert!(text.contains("| ❌ | `bad` | "));
+    assert!(text.contains("| ✅ | `ok` | "));
+    assert!(text.contains("### ❌ `bad` (failed)\n\n```text\ne11\n"));
+    assert!(text.ends_with("e30\n```\n"));
+    assert!(!text.contains("e10\n"));
+    assert!(!text.contains("quiet"));
+}
+
+#[test]
+fn formats_are_exclusive() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["report", "--html", "--markdown"])
+        .assert()
+        .code(2);
+}
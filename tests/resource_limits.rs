@@ -0,0 +1,60 @@
+//! `pend do --max-fsize/--max-cpu-time/--max-mem` and the recorded cause of
+//! death.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+/// Run `bash -c script` as `job` with the given limit flags, wait for it to
+/// fail, and return its metadata.
+fn run_failing(tmp: &TempDir, job: &str, flags: &[&str], script: &str) -> serde_json::Value {
+    pend(tmp)
+        .arg("do")
+        .args(flags)
+        .args([job, "bash", "-c", script])
+        .assert()
+        .success();
+    pend(tmp)
+        .args(["--no-color", "wait", job])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .failure();
+    let meta = std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap();
+    serde_json::from_slice(&meta).unwrap()
+}
+
+#[test]
+fn file_size_limit_kills_and_is_recorded() {
+    let tmp = TempDir::new().unwrap();
+    let out = tmp.path().join("big.bin");
+    let script = format!("head -c 1048576 /dev/zero > {}", out.display());
+    let meta = run_failing(&tmp, "fsz", &["--max-fsize", "64K"], &script);
+    assert_eq!(meta["limit_exceeded"], "file-size");
+    assert!(std::fs::metadata(&out).unwrap().len() <= 64 * 1024);
+}
+
+#[test]
+fn cpu_time_limit_kills_and_is_recorded() {
+    let tmp = TempDir::new().unwrap();
+    let meta = run_failing(
+        &tmp,
+        "spin",
+        &["--max-cpu-time", "1"],
+        "while :; do :; done",
+    );
+    assert_eq!(meta["limit_exceeded"], "cpu-time");
+}
+
+#[test]
+fn plain_failures_record_no_limit() {
+    let tmp = TempDir::new().unwrap();
+    let meta = run_failing(&tmp, "plain", &["--max-mem", "1G"], "exit 3");
+    assert!(meta.get("limit_exceeded").is_none());
+    assert_eq!(meta["exit_code"], 3);
+}
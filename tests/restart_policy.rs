@@ -0,0 +1,122 @@
+//! `pend do --restart`: service-style supervision.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn on_failure_restarts_up_to_max() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--restart",
+            "on-failure:2",
+            "flaky",
+            "bash",
+            "-c",
+            "echo run; exit 4",
+        ])
+        .assert()
+        .success();
+
+    let out = pend(&tmp)
+        .args(["--no-color", "wait", "flaky"])
+        .timeout(Duration::from_secs(30))
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(4));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout).matches("run").count(),
+        3
+    );
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("flaky.json")).unwrap()).unwrap();
+    assert_eq!(meta["attempt"], 3);
+}
+
+#[test]
+fn always_keeps_running_until_killed() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--restart", "always", "svc", "bash", "-c", "echo up"])
+        .assert()
+        .success();
+
+    // Wait for the first restart to show up in the status.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut attempt = 0;
+    while attempt < 2 && Instant::now() < deadline {
+        let out = pend(&tmp)
+            .args(["status", "--json", "svc"])
+            .output()
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(json[0]["state"], "running");
+        attempt = json[0]["attempt"].as_u64().unwrap_or(0);
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(attempt >= 2, "service was not restarted");
+
+    pend(&tmp).args(["kill", "svc"]).assert().success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "svc"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .stdout(predicate::str::contains("up"));
+}
+
+#[test]
+fn invalid_policy_is_rejected() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--restart", "sometimes", "x", "true"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown restart policy"));
+}
+
+#[cfg(unix)]
+#[test]
+fn restart_that_cannot_start_finishes_the_job() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new().unwrap();
+    // The command deletes itself, so the restart has nothing to run.
+    let script = tmp.path().join("once.sh");
+    std::fs::write(&script, "#!/bin/sh\necho ran\nrm \"$0\"\nexit 1\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    pend(&tmp)
+        .args(["do", "--restart", "on-failure:3", "once"])
+        .arg(&script)
+        .assert()
+        .success();
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "once"])
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .code(127)
+        .stdout(predicate::str::contains("ran"))
+        .stderr(predicate::str::contains("No such file"));
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("once.json")).unwrap()).unwrap();
+    assert_eq!(meta["exit_code"], 127);
+    assert_eq!(meta["failure"]["kind"], "spawn_failed");
+    assert_eq!(meta["attempt"], 2);
+    let attempts = meta["attempts"].as_array().unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0]["exit_code"], 1);
+    assert_eq!(attempts[1]["exit_code"], 127);
+    assert!(tmp.path().join("once.result").exists());
+    pend(&tmp).args(["validate", "once"]).assert().success();
+}
@@ -0,0 +1,114 @@
+//! `pend do --retry-cmd` runs a different command for retries and records it
+//! per attempt.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn retries_run_the_retry_command() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--retries",
+            "2",
+            "--retry-cmd",
+            "echo resumed; exit 0",
+        ])
+        .args(["build", "sh", "-c", "echo first; exit 1"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "build"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first").and(predicate::str::contains("resumed")));
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("build.json")).unwrap()).unwrap();
+    let attempts = meta["attempts"].as_array().unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert!(attempts[0].get("cmd").is_none());
+    assert_eq!(attempts[1]["cmd"], "echo resumed; exit 0");
+
+    pend(&tmp)
+        .args(["timeline", "build"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "attempt 2 started: echo resumed; exit 0",
+        ));
+}
+
+#[test]
+fn first_attempt_success_skips_it() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--retries", "1", "--retry-cmd", "echo never"])
+        .args(["ok", "echo", "once"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "ok"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("never").not());
+}
+
+#[test]
+fn rejects_an_empty_retry_command() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--retries", "1", "--retry-cmd", " ", "job", "true"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--retry-cmd cannot be empty"));
+}
+
+#[test]
+fn retry_command_that_cannot_start_fails_the_job() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new().unwrap();
+    // The retry command runs through the `sh` on the job's PATH, which the
+    // first attempt removes.
+    let bin = tmp.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    std::os::unix::fs::symlink("/bin/sh", bin.join("sh")).unwrap();
+    let first = bin.join("first");
+    std::fs::write(
+        &first,
+        "#!/bin/sh\necho first\n/bin/rm \"${0%/*}/sh\"\nexit 1\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&first, std::fs::Permissions::from_mode(0o755)).unwrap();
+    pend(&tmp)
+        .args(["do", "--retries", "2", "--retry-cmd", "echo resumed"])
+        .arg("--env")
+        .arg(format!("PATH={}", bin.display()))
+        .arg("build")
+        .arg(&first)
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "build"])
+        .assert()
+        .code(127)
+        .stdout(predicate::str::contains("first"));
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("build.json")).unwrap()).unwrap();
+    assert_eq!(meta["failure"]["kind"], "spawn_failed");
+    let attempts = meta["attempts"].as_array().unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[1]["cmd"], "echo resumed");
+    assert_eq!(attempts[1]["exit_code"], 127);
+}
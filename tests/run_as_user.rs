@@ -0,0 +1,55 @@
+//! `pend do --user`.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn unknown_user_is_rejected_up_front() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--user", "no-such-user-hopefully", "job", "true"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown user"));
+    assert!(!tmp.path().join("job.exit").exists());
+}
+
+fn meta(tmp: &TempDir, job: &str) -> serde_json::Value {
+    serde_json::from_slice(&std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap()).unwrap()
+}
+
+#[test]
+fn metadata_records_effective_user() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "me", "true"]).assert().success();
+    pend(&tmp).args(["wait", "me"]).assert().success();
+    assert!(meta(&tmp, "me")["user"].is_string());
+}
+
+#[test]
+fn command_runs_as_target_user() {
+    // Safety: `geteuid` cannot fail.
+    if unsafe { libc::geteuid() } != 0 {
+        eprintln!("warning: skipping – switching users needs root");
+        return;
+    }
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--user", "nobody", "as", "id", "-un"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["--no-color", "wait", "as"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("nobody\n"));
+    assert_eq!(meta(&tmp, "as")["user"], "nobody");
+}
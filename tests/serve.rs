@@ -0,0 +1,204 @@
+//! Integration tests for the `pend serve` HTTP API.
+
+use assert_cmd::cargo::CommandCargoExt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use tempfile::TempDir;
+
+const TOKEN: &str = "s3cret";
+
+/// Running `pend serve` instance that is killed on drop.
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn start_server(tmp: &TempDir) -> Server {
+    start_server_in(tmp.path())
+}
+
+fn start_server_in(root: &Path) -> Server {
+    let mut child = Command::cargo_bin("pend")
+        .expect("binary exists")
+        .env("PEND_DIR", root)
+        .args(["serve", "--bind", "127.0.0.1:0", "--token", TOKEN])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn pend serve");
+
+    // First line announces the bound address.
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .expect("read banner");
+    let addr = line
+        .trim()
+        .strip_prefix("listening on http://")
+        .expect("banner")
+        .to_string();
+    Server { child, addr }
+}
+
+/// Send a single request and return `(status code, body)`.
+fn request(addr: &str, method: &str, path: &str, token: &str, body: &str) -> (u16, String) {
+    request_with(addr, method, path, token, body, "")
+}
+
+/// [`request`] with extra header lines, each ending in `\r\n`.
+fn request_with(
+    addr: &str,
+    method: &str,
+    path: &str,
+    token: &str,
+    body: &str,
+    headers: &str,
+) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).expect("connect");
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: test\r\nAuthorization: Bearer {token}\r\n\
+         {headers}Content-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .expect("status code");
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, b)| b.to_string())
+        .unwrap_or_default();
+    (status, body)
+}
+
+#[test]
+fn rejects_missing_token() {
+    let tmp = TempDir::new().unwrap();
+    let server = start_server(&tmp);
+
+    let (status, _) = request(&server.addr, "GET", "/jobs", "wrong", "");
+    assert_eq!(status, 401);
+}
+
+#[test]
+fn submit_stream_and_status() {
+    let tmp = TempDir::new().unwrap();
+    let server = start_server(&tmp);
+
+    let (status, _) = request(
+        &server.addr,
+        "POST",
+        "/jobs",
+        TOKEN,
+        r#"{"name": "web", "cmd": ["bash", "-c", "echo hello-from-api"]}"#,
+    );
+    assert_eq!(status, 201);
+
+    // The log endpoint blocks until the job finishes.
+    let (status, body) = request(&server.addr, "GET", "/jobs/web/log", TOKEN, "");
+    assert_eq!(status, 200);
+    assert!(body.contains("hello-from-api"), "body: {body}");
+
+    let (status, body) = request(&server.addr, "GET", "/jobs/web", TOKEN, "");
+    assert_eq!(status, 200);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["state"], "finished");
+    assert_eq!(json["exit_code"], 0);
+
+    let (status, _) = request(&server.addr, "GET", "/jobs/ghost", TOKEN, "");
+    assert_eq!(status, 404);
+}
+
+#[test]
+fn kill_running_job() {
+    let tmp = TempDir::new().unwrap();
+    let server = start_server(&tmp);
+
+    let (status, _) = request(
+        &server.addr,
+        "POST",
+        "/jobs",
+        TOKEN,
+        r#"{"name": "sleepy", "cmd": ["sleep", "30"], "retries": 3}"#,
+    );
+    assert_eq!(status, 201);
+
+    // Give the worker a moment to spawn the command.
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    let (status, _) = request(&server.addr, "POST", "/jobs/sleepy/kill", TOKEN, "");
+    assert_eq!(status, 202);
+
+    // Killing skips the retries, so the job finishes promptly with a failure.
+    assert_cmd::Command::cargo_bin("pend")
+        .unwrap()
+        .env("PEND_DIR", tmp.path())
+        .args(["--no-color", "wait", "sleepy"])
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .failure();
+}
+
+#[test]
+fn rejects_names_that_leave_the_jobs_directory() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path().join("jobs");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(tmp.path().join("secret.log"), "top secret\n").unwrap();
+    let server = start_server_in(&root);
+
+    let (status, body) = request(&server.addr, "GET", "/jobs/..%2Fsecret/log", TOKEN, "");
+    assert_eq!(status, 400);
+    assert!(!body.contains("top secret"), "body: {body}");
+    let (status, _) = request(&server.addr, "GET", "/jobs/..%2Fsecret", TOKEN, "");
+    assert_eq!(status, 400);
+    let (status, _) = request(&server.addr, "POST", "/jobs/..%2Fx/kill", TOKEN, "");
+    assert_eq!(status, 400);
+    assert!(!tmp.path().join("x.cancel").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn sse_sends_whole_lines() {
+    let tmp = TempDir::new().unwrap();
+    let server = start_server(&tmp);
+
+    // The worker flushes each piece on its own and the server polls the log
+    // every 200ms, so the line and its `é` reach the log in separate reads.
+    let (status, _) = request(
+        &server.addr,
+        "POST",
+        "/jobs",
+        TOKEN,
+        r#"{"name": "split", "cmd": ["sh", "-c", "printf 'h\\303'; sleep 0.5; printf '\\251llo\\nta'; sleep 0.5; printf il"]}"#,
+    );
+    assert_eq!(status, 201);
+
+    let (status, body) = request_with(
+        &server.addr,
+        "GET",
+        "/jobs/split/log",
+        TOKEN,
+        "",
+        "Accept: text/event-stream\r\n",
+    );
+    assert_eq!(status, 200);
+    assert!(body.contains("data: h\u{e9}llo\n\n"), "body: {body}");
+    assert!(body.contains("data: tail\n\n"), "body: {body}");
+    assert!(!body.contains('\u{fffd}'), "body: {body}");
+    assert!(body.contains("event: exit\ndata: 0\n"), "body: {body}");
+}
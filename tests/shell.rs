@@ -0,0 +1,69 @@
+//! `pend do --shell` and command lines that must reach the shell verbatim.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn shell_runs_the_command_line() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "pipeline", "echo one && echo two"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "pipeline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("^one ?\r?\ntwo").unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn shell_line_keeps_quotes() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "quoted", r#"printf '%s|' "a b" 'c"d'"#])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "quoted"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(r#"a b|c"d|"#));
+}
+
+#[cfg(windows)]
+#[test]
+fn cmd_sees_carets_and_quotes_verbatim() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "caret", r#"echo ^<tag^> "quoted""#])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args([
+            "do",
+            "direct",
+            "cmd",
+            "/c",
+            "echo",
+            "^<tag^>",
+            r#""quoted""#,
+        ])
+        .assert()
+        .success();
+    for job in ["caret", "direct"] {
+        pend(&tmp)
+            .args(["wait", job])
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with("<tag> \"quoted\""));
+    }
+}
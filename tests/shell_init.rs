@@ -0,0 +1,108 @@
+//! `pend shell-init` prints shell functions built on `pend do --auto-name`,
+//! `pend last` and `pend status --count`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env("PEND_SHELL_PID", std::process::id().to_string());
+    cmd
+}
+
+#[test]
+fn auto_named_jobs_are_remembered_per_shell() {
+    let tmp = TempDir::new().unwrap();
+    for expected in ["cargo-1\n", "cargo-2\n"] {
+        pend(&tmp)
+            .args(["do", "--auto-name", "--", "cargo", "--version"])
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+    pend(&tmp)
+        .args(["last"])
+        .assert()
+        .success()
+        .stdout("cargo-2\n");
+    pend(&tmp)
+        .args(["--no-color", "wait", "cargo-2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("cargo "));
+
+    // A plain `pend do` counts as well.
+    pend(&tmp)
+        .args(["do", "named", "cargo", "--version"])
+        .assert()
+        .success()
+        .stdout("");
+    pend(&tmp)
+        .args(["last"])
+        .assert()
+        .success()
+        .stdout("named\n");
+
+    // Another shell has submitted nothing, so the user's last job counts.
+    pend(&tmp)
+        .env("PEND_SHELL_PID", "1")
+        .args(["last"])
+        .assert()
+        .success()
+        .stdout("named\n");
+}
+
+#[test]
+fn status_count_reports_running_jobs() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["status", "--count"])
+        .assert()
+        .success()
+        .stdout("0\n");
+    if cfg!(unix) {
+        pend(&tmp)
+            .args(["do", "nap", "sleep", "5"])
+            .assert()
+            .success();
+        pend(&tmp)
+            .args(["status", "--count"])
+            .assert()
+            .success()
+            .stdout("1\n");
+        pend(&tmp).args(["kill", "nap"]).assert().success();
+    }
+}
+
+#[test]
+fn scripts_define_the_helpers() {
+    for shell in ["bash", "zsh", "fish"] {
+        let out = Command::cargo_bin("pend")
+            .unwrap()
+            .args(["shell-init", shell])
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+        let script = String::from_utf8(out.stdout).unwrap();
+        assert!(script.contains("PEND_SHELL_PID"), "{shell}: {script}");
+        for helper in ["pd", "pw", "__pend_prompt"] {
+            assert!(script.contains(helper), "{shell} lacks {helper}");
+        }
+    }
+
+    // The bash script at least parses.
+    let script = Command::cargo_bin("pend")
+        .unwrap()
+        .args(["shell-init", "bash"])
+        .output()
+        .unwrap()
+        .stdout;
+    if let Ok(status) = std::process::Command::new("bash")
+        .args(["-n", "-c", &String::from_utf8(script).unwrap()])
+        .status()
+    {
+        assert!(status.success());
+    }
+}
@@ -0,0 +1,148 @@
+//! `--slot NAME:N` limits how many jobs holding the slot run at once.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn meta(tmp: &TempDir, job: &str) -> serde_json::Value {
+    serde_json::from_slice(&std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap()).unwrap()
+}
+
+fn attempt_time(meta: &serde_json::Value, key: &str) -> chrono::DateTime<chrono::FixedOffset> {
+    chrono::DateTime::parse_from_rfc3339(meta["attempts"][0][key].as_str().unwrap()).unwrap()
+}
+
+#[test]
+fn single_slot_serializes_jobs() {
+    let tmp = TempDir::new().unwrap();
+    for job in ["one", "two"] {
+        pend(&tmp)
+            .args(["do", "--slot", "db", job, "sleep", "0.5"])
+            .assert()
+            .success();
+    }
+    pend(&tmp).args(["wait", "one", "two"]).assert().success();
+
+    let (one, two) = (meta(&tmp, "one"), meta(&tmp, "two"));
+    let (first, second) = if attempt_time(&one, "started") < attempt_time(&two, "started") {
+        (one, two)
+    } else {
+        (two, one)
+    };
+    assert!(attempt_time(&second, "started") >= attempt_time(&first, "ended"));
+    assert_eq!(first["slots"]["db"], 0);
+    assert_eq!(second["slots"]["db"], 0);
+}
+
+#[test]
+fn units_run_side_by_side() {
+    let tmp = TempDir::new().unwrap();
+    for job in ["a", "b"] {
+        pend(&tmp)
+            .args(["do", "--slot", "gpu:2", job, "sleep", "0.5"])
+            .assert()
+            .success();
+    }
+    pend(&tmp).args(["wait", "a", "b"]).assert().success();
+
+    let (a, b) = (meta(&tmp, "a"), meta(&tmp, "b"));
+    assert!(attempt_time(&a, "started") < attempt_time(&b, "ended"));
+    assert!(attempt_time(&b, "started") < attempt_time(&a, "ended"));
+    let mut units = [a["slots"]["gpu"].as_u64(), b["slots"]["gpu"].as_u64()];
+    units.sort();
+    assert_eq!(units, [Some(0), Some(1)]);
+}
+
+#[test]
+fn invalid_slot_is_a_usage_error() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--slot", "gpu:0", "job", "true"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn gpu_units_pick_the_device() {
+    let tmp = TempDir::new().unwrap();
+    for job in ["train-a", "train-b"] {
+        pend(&tmp)
+            .args(["do", "--slot", "gpu:2", job, "sh", "-c"])
+            .arg("sleep 0.3; echo \"device=$CUDA_VISIBLE_DEVICES\"")
+            .assert()
+            .success();
+    }
+    pend(&tmp)
+        .args(["do", "--slot", "gpu:2", "--env", "CUDA_VISIBLE_DEVICES=7"])
+        .args([
+            "pinned",
+            "sh",
+            "-c",
+            "echo \"device=$CUDA_VISIBLE_DEVICES\"",
+        ])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["wait", "train-a", "train-b", "pinned"])
+        .assert()
+        .success();
+
+    let mut devices = Vec::new();
+    for job in ["train-a", "train-b"] {
+        let out = std::fs::read_to_string(tmp.path().join(format!("{job}.out"))).unwrap();
+        let meta = meta(&tmp, job);
+        assert_eq!(
+            out.trim(),
+            format!("device={}", meta["slots"]["gpu"].as_u64().unwrap())
+        );
+        assert_eq!(
+            meta["env"]["CUDA_VISIBLE_DEVICES"].as_str(),
+            out.trim().strip_prefix("device=")
+        );
+        devices.push(out);
+    }
+    assert_ne!(devices[0], devices[1]);
+    let out = std::fs::read_to_string(tmp.path().join("pinned.out")).unwrap();
+    assert_eq!(out, "device=7\n");
+}
+
+#[test]
+fn higher_priority_waiter_goes_first() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--slot", "db", "holder", "sleep", "1.5"])
+        .assert()
+        .success();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    for (job, priority) in [("late", "low"), ("urgent", "high")] {
+        pend(&tmp)
+            .args([
+                "do",
+                "--slot",
+                "db",
+                "--priority",
+                priority,
+                job,
+                "sleep",
+                "0.2",
+            ])
+            .assert()
+            .success();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+    }
+    pend(&tmp)
+        .args(["wait", "holder", "late", "urgent"])
+        .assert()
+        .success();
+
+    let (late, urgent) = (meta(&tmp, "late"), meta(&tmp, "urgent"));
+    assert!(attempt_time(&urgent, "started") < attempt_time(&late, "started"));
+    assert_eq!(urgent["priority"], 1);
+    assert_eq!(late["priority"], -1);
+}
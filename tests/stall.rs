@@ -0,0 +1,81 @@
+//! `pend do --stall-timeout` watches for attempts that stop writing, and for
+//! leftovers keeping a finished command's output open.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn meta(tmp: &TempDir, job: &str) -> serde_json::Value {
+    serde_json::from_slice(&std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap()).unwrap()
+}
+
+#[test]
+fn term_ends_a_silent_attempt() {
+    let tmp = TempDir::new().unwrap();
+    let started = Instant::now();
+    pend(&tmp)
+        .args(["do", "--stall-timeout", "1s", "--on-stall", "term"])
+        .args(["silent", "sh", "-c", "echo hi; sleep 30"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "silent"]).assert().code(143);
+    assert!(started.elapsed() < Duration::from_secs(15));
+
+    let meta = meta(&tmp, "silent");
+    assert_eq!(meta["failure"]["kind"], "stalled");
+    assert_eq!(meta["attempts"][0]["stalled"], true);
+    pend(&tmp)
+        .args(["status", "--reason", "stalled"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("silent"));
+}
+
+#[test]
+fn term_ends_leftovers_holding_the_output() {
+    let tmp = TempDir::new().unwrap();
+    let started = Instant::now();
+    pend(&tmp)
+        .args(["do", "--stall-timeout", "1s", "--on-stall", "term"])
+        .args(["leftover", "sh", "-c", "sleep 30 & echo started"])
+        .assert()
+        .success();
+    // The command itself succeeded.
+    pend(&tmp)
+        .args(["wait", "leftover"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("started"));
+    assert!(started.elapsed() < Duration::from_secs(15));
+    assert!(meta(&tmp, "leftover")["failure"].is_null());
+}
+
+#[test]
+fn warn_marks_the_job_but_lets_it_run() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--stall-timeout", "1s"])
+        .args(["slow", "sh", "-c", "echo a; sleep 3; echo b"])
+        .assert()
+        .success();
+    std::thread::sleep(Duration::from_millis(2000));
+    pend(&tmp)
+        .args(["status", "slow"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("stalled (no output for"));
+    pend(&tmp)
+        .args(["wait", "slow"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("a\nb\n"));
+    assert!(meta(&tmp, "slow")["attempts"][0].get("stalled").is_none());
+}
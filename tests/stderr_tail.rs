@@ -0,0 +1,67 @@
+//! `pend wait --stderr-tail` shows the end of a failed job's stderr below
+//! its summary line.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn failed_jobs_show_their_last_stderr_lines() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "broken",
+            "bash",
+            "-c",
+            "for i in $(seq 1 15); do echo \"problem $i\" >&2; done; exit 2",
+        ])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "fine", "bash", "-c", "echo harmless >&2"])
+        .assert()
+        .success();
+
+    let out = pend(&tmp)
+        .args(["--no-color", "wait", "--stderr-tail", "broken", "fine"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(out).unwrap();
+    let expected: String = (6..=15).map(|i| format!("    problem {i}\n")).collect();
+    assert!(
+        stdout.contains(&format!("✗ broken (0s) – exit 2\n{expected}")),
+        "{stdout}"
+    );
+    // Successful jobs keep their one line.
+    assert!(!stdout.contains("    harmless"), "{stdout}");
+
+    let out = pend(&tmp)
+        .args(["--no-color", "wait", "--stderr-tail=2", "broken"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .ends_with("exit 2\n    problem 14\n    problem 15\n"));
+
+    // Without the flag the summary stays one line.
+    let out = pend(&tmp)
+        .args(["--no-color", "wait", "broken"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    assert!(String::from_utf8(out).unwrap().ends_with("exit 2\n"));
+}
@@ -0,0 +1,80 @@
+//! A job that fails run after run under the same name records its failure
+//! streak; `pend status` and `post-failure` hooks report it.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env("PEND_CONFIG", tmp.path().join("config.toml"));
+    cmd
+}
+
+fn run(tmp: &TempDir, script: &str) {
+    pend(tmp)
+        .args(["do", "--shell", "nightly", script])
+        .assert()
+        .success();
+    pend(tmp).args(["wait", "nightly"]).assert();
+}
+
+fn failing(tmp: &TempDir) -> serde_json::Value {
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("nightly.json")).unwrap()).unwrap();
+    meta["failing"].clone()
+}
+
+#[test]
+fn counts_failed_runs_in_a_row() {
+    let tmp = TempDir::new().unwrap();
+    let hooks = tmp.path().join("hooks");
+    std::fs::create_dir_all(&hooks).unwrap();
+    let record = tmp.path().join("hook.txt");
+    let hook = hooks.join("post-failure");
+    std::fs::write(
+        &hook,
+        format!(
+            "#!/bin/sh\necho \"$PEND_FAILED_RUNS $PEND_FAILING_SINCE\" > '{}'\n",
+            record.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    run(&tmp, "exit 1");
+    assert_eq!(failing(&tmp)["runs"], 1);
+    let since = failing(&tmp)["since"].as_str().unwrap().to_string();
+    // A single failure is nothing to point out.
+    pend(&tmp)
+        .args(["status", "nightly"])
+        .assert()
+        .stdout(predicate::str::contains("in a row").not());
+
+    run(&tmp, "exit 2");
+    run(&tmp, "exit 1");
+    assert_eq!(
+        failing(&tmp),
+        serde_json::json!({"runs": 3, "since": since})
+    );
+    pend(&tmp)
+        .args(["status", "nightly"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "exit 1, failed 3 runs in a row, first failure at ",
+        ));
+    assert_eq!(
+        std::fs::read_to_string(&record).unwrap(),
+        format!("3 {since}\n")
+    );
+
+    // Success ends the streak.
+    run(&tmp, "true");
+    assert!(failing(&tmp).is_null());
+    run(&tmp, "exit 1");
+    assert_eq!(failing(&tmp)["runs"], 1);
+}
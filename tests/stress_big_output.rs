@@ -52,11 +52,67 @@ fn stress_big_output() {
         .assert()
         .success();
 
-    // Wait and ensure both streams were replayed (look for sentinel).
+    // Wait and ensure both streams were replayed (look for the sentinel,
+    // which the script writes to stderr).
     pend_bin()
         .env("PEND_DIR", jobs_dir.path())
         .args(["--no-color", "wait", "bigout"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("DONE"));
+        .stderr(predicate::str::contains("DONE"));
+}
+
+// Many small writes produce a flood of watcher events for one job while
+// another job trickles along.
+const PY_BURSTY: &str = r#"import sys
+for i in range(50_000):
+    sys.stdout.write(f'line {i}\n')
+    sys.stdout.flush()
+sys.stdout.write('NOISY DONE\n')
+"#;
+
+const PY_QUIET: &str = r#"import time
+for i in range(3):
+    print(f'quiet {i}', flush=True)
+    time.sleep(0.2)
+"#;
+
+#[test]
+fn stress_bursty_multi_job_wait() {
+    let jobs_dir = TempDir::new().expect("create jobs dir");
+    let script_dir = TempDir::new().expect("create script dir");
+    let python = ["python3", "python"]
+        .iter()
+        .find(|prog| Command::new(prog).arg("--version").output().is_ok())
+        .cloned();
+    let Some(python) = python else {
+        eprintln!("warning: skipping stress_bursty_multi_job_wait test – no python interpreter found in PATH");
+        return;
+    };
+
+    for (job, script) in [("noisy", PY_BURSTY), ("quiet", PY_QUIET)] {
+        let script_path = script_dir.path().join(format!("{job}.py"));
+        std::fs::write(&script_path, script).expect("write helper script");
+        pend_bin()
+            .env("PEND_DIR", jobs_dir.path())
+            .args(["do", job, python, script_path.to_str().unwrap()])
+            .assert()
+            .success();
+    }
+
+    let output = pend_bin()
+        .env("PEND_DIR", jobs_dir.path())
+        .args(["--no-color", "wait", "noisy", "quiet"])
+        .output()
+        .expect("run pend wait");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().filter(|l| l.starts_with("line ")).count(),
+        50_000
+    );
+    assert!(stdout.contains("NOISY DONE"));
+    for i in 0..3 {
+        assert!(stdout.contains(&format!("quiet {i}")));
+    }
 }
@@ -0,0 +1,75 @@
+//! Jobs record who submitted them and from where; `pend status --who`
+//! shows it.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path())
+        .env_remove("SSH_CONNECTION")
+        .env_remove("GITHUB_ACTIONS")
+        .env_remove("CI");
+    cmd
+}
+
+#[test]
+fn records_the_submitting_invocation() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .env("GITLAB_CI", "true")
+        .env("CI_JOB_URL", "https://gitlab.example/jobs/42")
+        .env("SSH_CONNECTION", "10.1.2.3 50000 10.0.0.1 22")
+        .args(["do", "--shell", "greet", "echo hi"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "greet"]).assert().success();
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("greet.json")).unwrap()).unwrap();
+    let submitter = &meta["submitter"];
+    let argv: Vec<&str> = submitter["argv"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    assert_eq!(argv[1..], ["do", "--shell", "greet", "echo hi"]);
+    assert_eq!(submitter["ssh_client"], "10.1.2.3");
+    assert_eq!(submitter["ci"], "gitlab");
+    assert_eq!(submitter["ci_url"], "https://gitlab.example/jobs/42");
+    assert!(submitter["host"].as_str().is_some_and(|h| !h.is_empty()));
+
+    pend(&tmp)
+        .args(["status", "--who", "greet"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("over SSH from 10.1.2.3")
+                .and(predicate::str::contains(
+                    "in gitlab (https://gitlab.example/jobs/42)",
+                ))
+                .and(predicate::str::contains("do --shell greet echo hi")),
+        );
+    pend(&tmp)
+        .args(["status", "--json", "greet"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"ssh_client\": \"10.1.2.3\""));
+}
+
+#[test]
+fn plain_status_leaves_it_out() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "quiet", "echo hi"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "quiet"]).assert().success();
+    pend(&tmp)
+        .args(["status", "quiet"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("submitted by").not());
+}
@@ -0,0 +1,98 @@
+//! `pend wait --summary-sort` groups the summary lines by outcome.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+/// The summary lines (those starting with a glyph, and blank lines) of a
+/// wait on `jobs`, reduced to their glyph and job name.
+fn summary(tmp: &TempDir, flags: &[&str], jobs: &[&str]) -> Vec<String> {
+    let out = pend(tmp)
+        .args(["--no-color", "wait"])
+        .args(flags)
+        .args(jobs)
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .filter(|line| line.is_empty() || line.starts_with(['✓', '✗']))
+        .map(|line| line.split(" (").next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn summaries_are_grouped_by_outcome() {
+    let tmp = TempDir::new().unwrap();
+    for (job, script) in [
+        ("delta", "exit 1"),
+        ("alpha", "sleep 1.2"),
+        ("charlie", "sleep 1.2; exit 1"),
+        ("bravo", "true"),
+    ] {
+        pend(&tmp)
+            .args(["do", job, "bash", "-c", script])
+            .assert()
+            .success();
+    }
+    let jobs = ["delta", "alpha", "charlie", "bravo"];
+
+    // Without the flag the order is the one given.
+    assert_eq!(
+        summary(&tmp, &[], &jobs),
+        ["✗ delta", "✓ alpha", "✗ charlie", "✓ bravo"]
+    );
+    assert_eq!(
+        summary(&tmp, &["--summary-sort", "status"], &jobs),
+        ["✓ alpha", "✓ bravo", "", "✗ delta", "✗ charlie"]
+    );
+    assert_eq!(
+        summary(&tmp, &["--summary-sort", "name"], &jobs),
+        ["✓ alpha", "✓ bravo", "", "✗ charlie", "✗ delta"]
+    );
+    assert_eq!(
+        summary(
+            &tmp,
+            &["--summary-sort=duration", "--failures-first"],
+            &jobs
+        ),
+        ["✗ charlie", "✗ delta", "", "✓ alpha", "✓ bravo"]
+    );
+
+    // The table follows the requested order too.
+    let out = pend(&tmp)
+        .args([
+            "--no-color",
+            "wait",
+            "--summary-table",
+            "--summary-sort",
+            "name",
+        ])
+        .args(jobs)
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(out).unwrap();
+    let rows: Vec<&str> = out
+        .lines()
+        .skip_while(|line| !line.starts_with("JOB "))
+        .skip(1)
+        .map(|line| line.split(' ').next().unwrap())
+        .collect();
+    assert_eq!(rows, ["alpha", "bravo", "charlie", "delta"], "{out}");
+
+    pend(&tmp)
+        .args(["wait", "--failures-first", "alpha"])
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,49 @@
+//! `pend wait --summary-table` ends with one aligned row per job.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn failures_come_first_in_an_aligned_table() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp).args(["do", "fine", "true"]).assert().success();
+    pend(&tmp)
+        .args(["do", "a-much-longer-name", "bash", "-c", "exit 3"])
+        .assert()
+        .success();
+
+    let out = pend(&tmp)
+        .args([
+            "--no-color",
+            "wait",
+            "--summary-table",
+            "fine",
+            "a-much-longer-name",
+        ])
+        .assert()
+        .code(3)
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().rev().take(3).collect();
+    let [ok, failed, header] = lines[..] else {
+        panic!("unexpected output: {out}");
+    };
+
+    assert!(header.starts_with("JOB "), "{out}");
+    assert!(failed.starts_with("a-much-longer-name  failed"), "{out}");
+    assert!(ok.starts_with("fine                ok"), "{out}");
+    // Columns line up.
+    let col = header.find("EXIT").unwrap();
+    assert_eq!(&failed[col..col + 1], "3", "{out}");
+    assert_eq!(&ok[col..col + 1], "0", "{out}");
+    assert!(!out.contains('✓'), "loose summary lines printed: {out}");
+}
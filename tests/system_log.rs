@@ -0,0 +1,99 @@
+//! `pend do --log-to` mirrors every line of output to the system logger.
+#![cfg(target_os = "linux")]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+/// Run a job logging to `logger` through a socket of our own and collect
+/// the records it receives.
+fn records(logger: &str) -> Vec<String> {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("logger.sock");
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    pend(&tmp)
+        .env("PEND_LOG_SOCKET", &path)
+        .args([
+            "do",
+            "--log-to",
+            logger,
+            "build",
+            "sh",
+            "-c",
+            "echo compiling; echo 'warning: unused' >&2; printf done",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "build"]).assert().success();
+
+    let mut records = Vec::new();
+    let mut buf = vec![0u8; 65536];
+    while records.len() < 3 {
+        let n = socket.recv(&mut buf).expect("a record arrives");
+        records.push(String::from_utf8_lossy(&buf[..n]).into_owned());
+    }
+    records.sort();
+    records
+}
+
+#[test]
+fn lines_go_to_syslog() {
+    let records = records("syslog");
+    let tagged =
+        regex::Regex::new(r"^<(\d+)>\w{3} [ \d]\d \d\d:\d\d:\d\d pend/build\[\d+\]: (.*)$")
+            .unwrap();
+    let mut lines: Vec<(String, String)> = records
+        .iter()
+        .map(|record| {
+            let caps = tagged
+                .captures(record)
+                .unwrap_or_else(|| panic!("{record:?}"));
+            (caps[2].to_string(), caps[1].to_string())
+        })
+        .collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            ("compiling".to_string(), "14".to_string()),
+            ("done".to_string(), "14".to_string()),
+            ("warning: unused".to_string(), "11".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn lines_go_to_journald_with_fields() {
+    let records = records("journald");
+    let stderr = records
+        .iter()
+        .find(|r| r.contains("MESSAGE=warning: unused\n"))
+        .expect("stderr line forwarded");
+    assert!(stderr.contains("PEND_JOB=build\n"), "{stderr}");
+    assert!(stderr.contains("PEND_STREAM=stderr\n"), "{stderr}");
+    assert!(stderr.contains("PRIORITY=3\n"), "{stderr}");
+    assert!(records
+        .iter()
+        .any(|r| r.contains("PEND_STREAM=stdout\n") && r.contains("MESSAGE=done\n")));
+}
+
+#[test]
+fn other_platforms_loggers_are_refused() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--log-to", "eventlog", "build", "true"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("only available on Windows"));
+}
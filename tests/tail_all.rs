@@ -0,0 +1,72 @@
+//! `pend tail-all` follows every running job, prefixing each line with the
+//! job's name.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::process::Stdio;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path()).env("NO_COLOR", "1");
+    cmd
+}
+
+#[test]
+fn follows_running_and_new_jobs() {
+    let tmp = TempDir::new().unwrap();
+    for job in ["alpha", "beta"] {
+        pend(&tmp)
+            .args(["do", "--shell", job])
+            .arg(format!("echo early-{job}; sleep 2; echo late-{job}"))
+            .assert()
+            .success();
+    }
+    std::thread::sleep(Duration::from_millis(500));
+
+    let tail = std::process::Command::new(assert_cmd::cargo::cargo_bin("pend"))
+        .env("PEND_DIR", tmp.path())
+        .env("NO_COLOR", "1")
+        .args(["tail-all", "--until-idle"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    pend(&tmp)
+        .args(["do", "--shell", "gamma", "echo from-gamma >&2; sleep 1"])
+        .assert()
+        .success();
+
+    let out = tail.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+
+    // Running jobs are followed from where they are now ...
+    assert!(stdout.contains("[alpha] late-alpha\n"), "{stdout}");
+    assert!(stdout.contains("[beta] late-beta\n"), "{stdout}");
+    assert!(!stdout.contains("early-"), "{stdout}");
+    // ... jobs started later from their beginning.
+    assert!(stderr.contains("[gamma] from-gamma\n"), "{stderr}");
+    for job in ["alpha", "beta", "gamma"] {
+        assert!(stdout.contains(&format!(" {job} (")), "{stdout}");
+    }
+}
+
+#[test]
+fn until_idle_without_jobs() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "done", "true"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "done"]).assert().success();
+    pend(&tmp)
+        .args(["tail-all", "--until-idle"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("no jobs are running"));
+}
@@ -0,0 +1,87 @@
+//! `pend do --template` runs command templates from the config file.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const CONFIG: &str = r#"
+[templates]
+greet = "echo hello {who}"
+
+[templates.bench]
+command = "echo bench {target} {mode}"
+defaults = { mode = "quick" }
+"#;
+
+fn pend(tmp: &TempDir) -> Command {
+    let config = tmp.path().join("config.toml");
+    std::fs::write(&config, CONFIG).unwrap();
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path()).env("PEND_CONFIG", config);
+    cmd
+}
+
+#[test]
+fn fills_in_parameters_and_defaults() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "hi", "--template", "greet", "who=world"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "mybench", "--template", "bench", "target=io"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args([
+            "do",
+            "full",
+            "--template",
+            "bench",
+            "target=io",
+            "mode=full",
+        ])
+        .assert()
+        .success();
+
+    pend(&tmp)
+        .args(["wait", "hi"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("hello world"));
+    pend(&tmp)
+        .args(["wait", "mybench"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("bench io quick"));
+    pend(&tmp)
+        .args(["wait", "full"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("bench io full"));
+}
+
+#[test]
+fn rejects_bad_template_invocations() {
+    let tmp = TempDir::new().unwrap();
+    for args in [
+        &["do", "a", "--template", "nope"][..],
+        &["do", "b", "--template", "greet"],
+        &["do", "c", "--template", "greet", "who=x", "extra=1"],
+        &["do", "d", "--template", "greet", "world"],
+    ] {
+        pend(&tmp).args(args).assert().code(2);
+    }
+    assert!(!tmp.path().join("a.json").exists());
+}
+
+#[test]
+fn explicit_config_must_exist() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .env("PEND_CONFIG", tmp.path().join("missing.toml"))
+        .args(["do", "a", "--template", "greet", "who=x"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("missing.toml"));
+}
@@ -0,0 +1,67 @@
+//! `pend timeline` lists the lifecycle events of a job.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[cfg(unix)]
+#[test]
+fn shows_every_attempt_and_timeout_kills() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "flaky",
+            "--timeout",
+            "1",
+            "--retries",
+            "1",
+            "sleep",
+            "5",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "flaky"]).assert().code(137);
+
+    let out = pend(&tmp).args(["timeline", "flaky"]).output().unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let events: Vec<&str> = stdout
+        .lines()
+        .map(|l| l.trim_start().split_once("  ").unwrap().1)
+        .collect();
+    assert_eq!(events[0], "submitted");
+    assert_eq!(events[1], "attempt 1 started");
+    assert!(
+        events[2].starts_with("attempt 1 killed by timeout after 1."),
+        "{stdout}"
+    );
+    assert_eq!(events[3], "attempt 2 started");
+    assert!(
+        events[4].starts_with("attempt 2 killed by timeout"),
+        "{stdout}"
+    );
+    assert_eq!(events[5], "finished with exit code 137");
+    assert!(stdout.lines().all(|l| l.trim_start().starts_with('+')));
+}
+
+#[test]
+fn running_and_unknown_jobs() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "slow", "sleep", "5"])
+        .assert()
+        .success();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let out = pend(&tmp).args(["timeline", "slow"]).output().unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).ends_with("still running\n"));
+    pend(&tmp).args(["kill", "slow"]).assert().success();
+
+    pend(&tmp).args(["timeline", "nope"]).assert().code(3);
+}
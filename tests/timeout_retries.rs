@@ -73,6 +73,38 @@ fn timeout_enforced() {
     );
 }
 
+/// A timeout kills the command's children too: a background process left
+/// running would hold the output pipes open and keep the job from finishing.
+#[cfg(unix)]
+#[test]
+fn timeout_kills_the_process_group() {
+    let (tmp, mut pend_cmd) = pend_with_tmpdir();
+    pend_cmd
+        .args([
+            "do",
+            "forked",
+            "--timeout",
+            "1",
+            "sh",
+            "-c",
+            "sleep 30 & sleep 30",
+        ])
+        .assert()
+        .success();
+
+    let start = Instant::now();
+    pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args(["--no-color", "wait", "forked"])
+        .assert()
+        .failure();
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed < Duration::from_secs(5),
+        "expected the timeout to end the job early (elapsed = {elapsed:?})"
+    );
+}
+
 /// Verify that the `--retries` flag re-runs a failing command and ultimately
 /// reports success when one of the attempts exits with status 0.
 #[test]
@@ -139,3 +171,66 @@ else:
         .success()
         .stdout(predicate::str::contains("second run – success"));
 }
+
+/// A running job with `--timeout` shows when it will be killed.
+#[test]
+fn status_counts_down_to_the_timeout() {
+    let (tmp, mut pend_cmd) = pend_with_tmpdir();
+    pend_cmd
+        .args(["do", "slow", "--timeout", "60", "cargo", "--version"])
+        .assert()
+        .success();
+    pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args(["wait", "slow"])
+        .assert()
+        .success();
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("slow.json")).unwrap()).unwrap();
+    assert_eq!(meta["timeout"], 60);
+    let started =
+        chrono::DateTime::parse_from_rfc3339(meta["attempts"][0]["started"].as_str().unwrap())
+            .unwrap();
+    let deadline =
+        chrono::DateTime::parse_from_rfc3339(meta["attempts"][0]["deadline"].as_str().unwrap())
+            .unwrap();
+    assert_eq!((deadline - started).num_seconds(), 60);
+
+    // A finished job has nothing left to count down.
+    pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args(["status", "slow"])
+        .assert()
+        .stdout(predicate::str::contains("timeout in").not());
+
+    if cfg!(unix) {
+        pend_bin()
+            .env("PEND_DIR", tmp.path())
+            .args(["do", "nap", "--timeout", "60", "sleep", "5"])
+            .assert()
+            .success();
+        // The deadline is known once the worker has started the command.
+        let countdown = regex::Regex::new(r"nap +running \(.*timeout in [0-9]+\.[0-9]s\)").unwrap();
+        let give_up = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        loop {
+            let out = pend_bin()
+                .env("PEND_DIR", tmp.path())
+                .args(["status", "nap"])
+                .output()
+                .unwrap();
+            if countdown.is_match(&String::from_utf8_lossy(&out.stdout)) {
+                break;
+            }
+            assert!(
+                std::time::Instant::now() < give_up,
+                "no countdown in status"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        pend_bin()
+            .env("PEND_DIR", tmp.path())
+            .args(["kill", "nap"])
+            .assert()
+            .success();
+    }
+}
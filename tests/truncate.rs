@@ -0,0 +1,96 @@
+//! `pend truncate` empties the output of finished jobs but keeps their
+//! status.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn truncated_jobs_keep_their_status() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "--max-log-size",
+            "1K",
+            "--max-log-files",
+            "2",
+            "chatty",
+            "bash",
+            "-c",
+            "for i in $(seq 1 300); do echo \"line $i\"; done; echo oops >&2; exit 3",
+        ])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "chatty"]).assert().code(3);
+    let file = |name: &str| tmp.path().join(name);
+    assert!(file("chatty.log.1").exists());
+
+    pend(&tmp)
+        .args(["truncate", "chatty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("^truncated 'chatty': freed [0-9]+ bytes\n$").unwrap());
+
+    for ext in ["out", "err", "log"] {
+        assert_eq!(
+            std::fs::metadata(file(&format!("chatty.{ext}")))
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+    assert!(!file("chatty.log.1").exists());
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(file("chatty.json")).unwrap()).unwrap();
+    assert_eq!(meta["exit_code"], 3);
+    // 300 lines of "line N\n" plus "oops\n".
+    let out_bytes: u64 = (1..=300).map(|i| format!("line {i}\n").len() as u64).sum();
+    assert_eq!(meta["truncated"]["out"], out_bytes);
+    assert_eq!(meta["truncated"]["err"], 5);
+    assert_eq!(meta["truncated"]["log"], out_bytes + 5);
+
+    pend(&tmp)
+        .args(["status", "chatty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("exit 3 (output truncated)"));
+    pend(&tmp)
+        .args(["--no-color", "wait", "chatty"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("output of 'chatty' was discarded"));
+    pend(&tmp).args(["validate", "chatty"]).assert().success();
+
+    // Sizes add up over repeated truncations; the second frees nothing.
+    pend(&tmp)
+        .args(["truncate", "chatty"])
+        .assert()
+        .success()
+        .stdout("truncated 'chatty': freed 0 bytes\n");
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(file("chatty.json")).unwrap()).unwrap();
+    assert_eq!(meta["truncated"]["out"], out_bytes);
+}
+
+#[test]
+fn running_and_unknown_jobs_are_refused() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["truncate", "ghost"]).assert().code(3);
+    pend(&tmp)
+        .args(["do", "slow", "sleep", "5"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["truncate", "slow"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("has not finished"));
+    pend(&tmp).args(["kill", "slow"]).assert();
+}
@@ -0,0 +1,71 @@
+//! `pend do --meta KEY=VALUE` records the submitter's own pairs;
+//! `pend status --meta` filters on them.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn submit(tmp: &TempDir, job: &str, meta: &[&str]) {
+    let mut cmd = pend(tmp);
+    cmd.arg("do");
+    for pair in meta {
+        cmd.args(["--meta", pair]);
+    }
+    cmd.args([job, "true"]).assert().success();
+    pend(tmp).args(["wait", job]).assert().success();
+}
+
+fn listed(tmp: &TempDir, args: &[&str]) -> Vec<String> {
+    let out = pend(tmp)
+        .args(["status", "--json"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let statuses: Vec<serde_json::Value> = serde_json::from_slice(&out.stdout).unwrap();
+    statuses
+        .iter()
+        .map(|st| st["job"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn records_and_filters_on_pairs() {
+    let tmp = TempDir::new().unwrap();
+    submit(&tmp, "a", &["commit=abc123", "ticket=OPS-42"]);
+    submit(&tmp, "b", &["commit=abc123", "url=https://x/?q=1"]);
+    submit(&tmp, "c", &[]);
+
+    let meta: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(tmp.path().join("b.json")).unwrap()).unwrap();
+    assert_eq!(
+        meta["user_meta"],
+        serde_json::json!({ "commit": "abc123", "url": "https://x/?q=1" })
+    );
+
+    assert_eq!(listed(&tmp, &["--meta", "commit=abc123"]), ["a", "b"]);
+    assert_eq!(
+        listed(
+            &tmp,
+            &["--meta", "commit=abc123", "--meta", "ticket=OPS-42"]
+        ),
+        ["a"]
+    );
+    assert!(listed(&tmp, &["--meta", "commit=def456"]).is_empty());
+}
+
+#[test]
+fn rejects_a_pair_without_a_key() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--meta", "=x", "job", "true"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("expected KEY=VALUE"));
+}
@@ -0,0 +1,77 @@
+//! Corrupt artifacts are reported instead of silently defaulting.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn finished_job(tmp: &TempDir, name: &str) {
+    pend(tmp)
+        .args(["do", name, "sh", "-c", "echo out; echo err >&2"])
+        .assert()
+        .success();
+    pend(tmp).args(["wait", name]).assert().success();
+}
+
+#[test]
+fn intact_jobs_validate() {
+    let tmp = TempDir::new().unwrap();
+    finished_job(&tmp, "fine");
+
+    pend(&tmp)
+        .args(["validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fine").and(predicate::str::contains("ok")));
+}
+
+#[test]
+fn tampered_output_and_metadata_are_detected() {
+    let tmp = TempDir::new().unwrap();
+    finished_job(&tmp, "tampered");
+    std::fs::write(tmp.path().join("tampered.out"), "something else\n").unwrap();
+
+    pend(&tmp)
+        .args(["validate", "tampered"])
+        .assert()
+        .code(5)
+        .stdout(predicate::str::contains("does not match out_sha256"));
+
+    std::fs::write(tmp.path().join("tampered.json"), "{ truncated").unwrap();
+    pend(&tmp)
+        .args(["--no-color", "wait", "tampered"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("corrupt artifact"));
+    pend(&tmp)
+        .args(["status", "tampered"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("corrupt (corrupt artifact"));
+}
+
+#[test]
+fn unparsable_exit_marker_fails_wait_and_status() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::write(tmp.path().join("garbled.log"), "").unwrap();
+    std::fs::write(tmp.path().join("garbled.exit"), "not a number\n").unwrap();
+
+    pend(&tmp)
+        .args(["wait", "garbled"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("expected an exit code"));
+    pend(&tmp)
+        .args(["status", "--json", "garbled"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"corrupt\""));
+    pend(&tmp).args(["validate"]).assert().code(5);
+}
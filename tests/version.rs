@@ -0,0 +1,76 @@
+//! Metadata records its schema, the worker refuses jobs from an incompatible
+//! front-end, and readers skip fields of newer schemas.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn meta(tmp: &TempDir, job: &str) -> serde_json::Value {
+    serde_json::from_slice(&std::fs::read(tmp.path().join(format!("{job}.json"))).unwrap()).unwrap()
+}
+
+#[test]
+fn metadata_records_version_and_schema() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "job", "true"]).assert().success();
+    pend(&tmp).args(["wait", "job"]).assert().success();
+
+    let meta = meta(&tmp, "job");
+    assert_eq!(meta["schema"], 1);
+    assert_eq!(meta["pend_version"], env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn worker_refuses_incompatible_frontend() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .env("PEND_FRONTEND_VERSION", "99.0.0")
+        .args(["worker", "job", "--", "touch", "ran"])
+        .current_dir(tmp.path())
+        .assert()
+        .success();
+
+    assert!(!tmp.path().join("ran").exists());
+    pend(&tmp)
+        .args(["wait", "job"])
+        .assert()
+        .code(127)
+        .stderr(predicate::str::contains(
+            "cannot run a job submitted by pend 99.0.0",
+        ));
+}
+
+#[test]
+fn newer_schema_fields_are_not_misread() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "job", "true"]).assert().success();
+    pend(&tmp).args(["wait", "job"]).assert().success();
+
+    // A later schema may store `ready` differently; this one would not pass
+    // as a timestamp.
+    let mut meta = meta(&tmp, "job");
+    meta["ready"] = serde_json::json!({ "at": 3 });
+    let path = tmp.path().join("job.json");
+    std::fs::write(&path, serde_json::to_vec(&meta).unwrap()).unwrap();
+    pend(&tmp)
+        .args(["wait", "job"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("invalid 'ready' timestamp"));
+
+    meta["schema"] = serde_json::json!(99);
+    std::fs::write(&path, serde_json::to_vec(&meta).unwrap()).unwrap();
+    pend(&tmp)
+        .args(["wait", "job"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("corrupt").not());
+    pend(&tmp).args(["status", "job"]).assert().success();
+}
@@ -0,0 +1,70 @@
+//! `pend wait --all` waits for every job in the jobs directory.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn waits_for_every_job() {
+    let tmp = TempDir::new().unwrap();
+    for (job, text) in [("build-a", "alpha"), ("build-b", "beta"), ("lint", "gamma")] {
+        pend(&tmp)
+            .args(["do", "--shell", job, &format!("echo {text}")])
+            .assert()
+            .success();
+    }
+    pend(&tmp)
+        .args(["wait", "--all"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("alpha")
+                .and(predicate::str::contains("beta"))
+                .and(predicate::str::contains("gamma")),
+        );
+
+    // Names given with --all select among the jobs.
+    pend(&tmp)
+        .args(["wait", "--all", "build-*"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("alpha")
+                .and(predicate::str::contains("beta"))
+                .and(predicate::str::contains("gamma").not()),
+        );
+}
+
+#[test]
+fn reports_failures_of_any_job() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "--shell", "ok", "exit 0"])
+        .assert()
+        .success();
+    pend(&tmp)
+        .args(["do", "--shell", "broken", "exit 7"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "--all"]).assert().code(7);
+}
+
+#[test]
+fn nothing_to_wait_for_is_not_an_error() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["wait", "--all"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("no jobs to wait for"));
+    pend(&tmp)
+        .args(["wait", "--all", "missing-*"])
+        .assert()
+        .success();
+}
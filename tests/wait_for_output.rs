@@ -0,0 +1,65 @@
+//! `pend wait --for-output` returns once the job prints a matching line.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn returns_when_pattern_appears() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args([
+            "do",
+            "server",
+            "bash",
+            "-c",
+            "echo booting; echo 'Listening on port 8080'; sleep 30",
+        ])
+        .assert()
+        .success();
+
+    let start = Instant::now();
+    pend(&tmp)
+        .args([
+            "--no-color",
+            "wait",
+            "--for-output",
+            r"Listening on port \d+",
+            "server",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Listening on port 8080"))
+        .stdout(predicate::str::contains("ready"));
+    assert!(start.elapsed() < Duration::from_secs(20), "waited for exit");
+
+    // The job is still running.
+    assert!(!tmp.path().join("server.exit").exists());
+    pend(&tmp).args(["kill", "server"]).assert().success();
+}
+
+#[test]
+fn fails_when_job_exits_without_match() {
+    let tmp = TempDir::new().unwrap();
+
+    pend(&tmp)
+        .args(["do", "quiet", "echo", "nothing to see"])
+        .assert()
+        .success();
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "--for-output", "Listening", "quiet"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "without printing the awaited output",
+        ));
+}
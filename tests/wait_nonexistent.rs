@@ -15,6 +15,47 @@ fn wait_on_non_existing_job_exits_fast() {
     cmd.env("PEND_DIR", tmp.path())
         .args(["wait", "ghost"])
         .assert()
-        .failure()
+        .code(3)
         .stderr(contains("not found"));
 }
+
+#[test]
+fn allow_pending_waits_for_the_job_to_be_submitted() {
+    let tmp = TempDir::new().expect("tmp");
+    let waiter = pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args(["wait", "--allow-pending", "late"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("spawn waiter");
+
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args(["do", "late", "--", "cargo", "--version"])
+        .assert()
+        .success();
+
+    let out = waiter.wait_with_output().expect("waiter finishes");
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).starts_with("cargo "));
+}
+
+#[test]
+fn pending_timeout_gives_up() {
+    let tmp = TempDir::new().expect("tmp");
+    let started = std::time::Instant::now();
+    pend_bin()
+        .env("PEND_DIR", tmp.path())
+        .args([
+            "wait",
+            "--allow-pending",
+            "--pending-timeout",
+            "1s",
+            "ghost",
+        ])
+        .assert()
+        .code(3)
+        .stderr(contains("not found"));
+    assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+}
@@ -0,0 +1,91 @@
+//! `pend wait --no-backlog` / `--since-offset` resume streaming mid-log.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+#[test]
+fn resumes_where_previous_wait_stopped() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args([
+            "do",
+            "server",
+            "bash",
+            "-c",
+            "echo booting; echo ready; sleep 2; echo later",
+        ])
+        .assert()
+        .success();
+
+    let out = pend(&tmp)
+        .args(["--no-color", "wait", "--for-output", "^ready", "server"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let offset: u64 = stderr
+        .split("--since-offset ")
+        .nth(1)
+        .and_then(|rest| rest.trim_end().trim_end_matches(')').parse().ok())
+        .unwrap_or_else(|| panic!("no resume offset in {stderr:?}"));
+    assert_eq!(offset, "booting\nready\n".len() as u64);
+
+    pend(&tmp)
+        .args([
+            "--no-color",
+            "wait",
+            "--since-offset",
+            &offset.to_string(),
+            "server",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("later"))
+        .stdout(predicate::str::contains("booting").not())
+        .stdout(predicate::str::contains("ready\n").not());
+}
+
+#[test]
+fn no_backlog_skips_existing_output() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "done", "echo", "old output"])
+        .assert()
+        .success();
+    pend(&tmp).args(["wait", "done"]).assert().success();
+
+    pend(&tmp)
+        .args(["--no-color", "wait", "--no-backlog", "done"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old output").not());
+
+    // An offset past the end (the log was rotated since) replays everything.
+    pend(&tmp)
+        .args(["--no-color", "wait", "--since-offset", "100000", "done"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old output"));
+}
+
+#[test]
+fn since_offset_needs_exactly_one_job() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp).args(["do", "a", "true"]).assert().success();
+    pend(&tmp).args(["do", "b", "true"]).assert().success();
+    pend(&tmp)
+        .args(["wait", "--since-offset", "0", "a", "b"])
+        .assert()
+        .code(2);
+    pend(&tmp)
+        .args(["wait", "--since-offset", "0", "--no-backlog", "a"])
+        .assert()
+        .code(2);
+}
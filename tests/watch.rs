@@ -0,0 +1,56 @@
+//! `pend watch` re-submits its job when a watched file changes.
+#![cfg(feature = "notify")]
+
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Kills the foreground `pend watch` process when the test ends.
+struct Watcher(Child);
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn wait_for(what: &str, mut cond: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !cond() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn rerun_on_change() {
+    let jobs = TempDir::new().unwrap();
+    let src = TempDir::new().unwrap();
+    let input = src.path().join("input.txt");
+    std::fs::write(&input, "one\n").unwrap();
+
+    let _watcher = Watcher(
+        Command::new(assert_cmd::cargo::cargo_bin("pend"))
+            .env("PEND_DIR", jobs.path())
+            .args(["watch", "--debounce", "100", "--path"])
+            .arg(src.path())
+            .args(["w", "cat"])
+            .arg(&input)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    let log = jobs.path().join("w.log");
+    let exit = jobs.path().join("w.exit");
+    wait_for("first run", || {
+        exit.exists() && std::fs::read_to_string(&log).unwrap_or_default() == "one\n"
+    });
+
+    std::fs::write(&input, "two\n").unwrap();
+    wait_for("re-run", || {
+        exit.exists() && std::fs::read_to_string(&log).unwrap_or_default() == "two\n"
+    });
+}
@@ -0,0 +1,98 @@
+//! A dead worker is recognised by `status` and `clean`, even when its PID
+//! has been handed to another process since.
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn pend(tmp: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("pend").expect("binary exists");
+    cmd.env("PEND_DIR", tmp.path());
+    cmd
+}
+
+fn started_meta(tmp: &TempDir, job: &str) -> serde_json::Value {
+    let meta_path = tmp.path().join(format!("{job}.json"));
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let meta = std::fs::read(&meta_path)
+            .ok()
+            .and_then(|b| serde_json::from_slice::<serde_json::Value>(&b).ok())
+            .filter(|m| m.get("pid").is_some_and(|p| !p.is_null()));
+        if let Some(meta) = meta {
+            return meta;
+        }
+        assert!(Instant::now() < deadline, "worker never started");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn killed_worker_shows_as_died() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "doomed", "sleep", "30"])
+        .assert()
+        .success();
+    let meta = started_meta(&tmp, "doomed");
+    for field in ["worker_pid", "pid"] {
+        let pid = meta[field].as_u64().unwrap().to_string();
+        std::process::Command::new("kill")
+            .args(["-9", &pid])
+            .status()
+            .unwrap();
+    }
+    std::thread::sleep(Duration::from_millis(200));
+
+    pend(&tmp)
+        .args(["status", "doomed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("died"));
+    pend(&tmp).args(["kill", "doomed"]).assert().code(2);
+    pend(&tmp).args(["clean", "doomed"]).assert().success();
+    assert!(!tmp.path().join("doomed.json").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn reused_worker_pid_is_not_mistaken_for_the_worker() {
+    let tmp = TempDir::new().unwrap();
+    pend(&tmp)
+        .args(["do", "ghost", "sleep", "30"])
+        .assert()
+        .success();
+    let mut meta = started_meta(&tmp, "ghost");
+    let child_pid = meta["pid"].as_u64().unwrap().to_string();
+
+    // Pretend the worker died and its PID now belongs to this test process.
+    std::process::Command::new("kill")
+        .args(["-9", &meta["worker_pid"].to_string(), &child_pid])
+        .status()
+        .unwrap();
+    meta["worker_pid"] = std::process::id().into();
+    std::fs::write(
+        tmp.path().join("ghost.json"),
+        serde_json::to_vec(&meta).unwrap(),
+    )
+    .unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+
+    pend(&tmp)
+        .args(["status", "--json", "ghost"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""state": "died""#));
+    pend(&tmp)
+        .args(["wait", "ghost"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .code(125);
+    pend(&tmp)
+        .args(["clean", "ghost"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("appears to be running").not());
+}